@@ -2,40 +2,82 @@
 pub const MINIMUM_FEE_BPS: u64 = 1; // 0.01%
 pub const MINIMUM_FEE: u64 = 1; // Minimum fee in lamports
 pub const MAX_EARLY_TRADE_FEE_BPS: u64 = 1000; // 10% maximum fee for early trades
-pub const MAXIMUM_FEE_BPS: u64 = 1000; // 10%
+pub const MAX_EARLY_TRADE_DURATION: u64 = 604800; // 7 days of launch-phase fees at most
+pub const MAXIMUM_FEE_BPS: u64 = 1000; // 10% cap on the total fee, surcharges included
 
 // Cooldowns and timelocks
 pub const EMERGENCY_TIMELOCK_SECONDS: u64 = 3600; // 1 hour emergency action delay
+pub const EMERGENCY_PAUSE_TIMELOCK_SECONDS: u64 = 300; // 5 minute default delay before a pause
 pub const PARAMETER_UPDATE_TIMELOCK: u64 = 86400; // 24 hours
 pub const ADMIN_UPDATE_COOLDOWN: u64 = 86400; // 24 hours
+pub const MIN_TRADE_TIMELOCK: u64 = 21600; // 6 hours
+pub const MIN_PROTECTION_TIMELOCK: u64 = 43200; // 12 hours
+pub const MIN_FEE_TIMELOCK: u64 = 86400; // 24 hours
+pub const MIN_STATE_TIMELOCK: u64 = 3600; // 1 hour
+pub const MIN_EMERGENCY_PAUSE_TIMELOCK: u64 = 0; // Pausing may take effect immediately
+pub const MIN_EMERGENCY_RESUME_TIMELOCK: u64 = 3600; // 1 hour
+pub const MAX_PARAMETER_UPDATE_TIMELOCK: u64 = 2592000; // 30 days
+
+// Pool state layout; bump on any change other than appending a field, including
+// fields added inside nested settings, and add the step to `legacy`
+pub const POOL_STATE_VERSION: u8 = 3;
+pub const MAX_TOKEN_DECIMALS: u8 = 18; // Highest mint precision the u64 amount math is sized for
 
 // Pool state seeds
 pub const POOL_ID_SEED: &[u8] = b"pool_authority";
 pub const REENTRANCY_GUARD_SEED: &[u8] = b"reentrancy_guard";
 pub const PENDING_UPDATE_SEED: &[u8] = b"pending_update";
+pub const TRADER_STATE_SEED: &[u8] = b"trader_state";
+pub const REFERRAL_STATE_SEED: &[u8] = b"referral_state";
+pub const TRADER_VOLUME_SEED: &[u8] = b"trader_vol";
 
 // Fee mode constants for tracking fee application
+// Values match the `FeeMode` variant order
 pub const FEE_MODE_NONE: u8 = 0;
 pub const FEE_MODE_EARLY_TRADE: u8 = 1;
-pub const FEE_MODE_TIER_BASED: u8 = 1;
-pub const FEE_MODE_VOLUME_BASED: u8 = 2;
-pub const FEE_MODE_CIRCUIT_BREAKER: u8 = 3;
+pub const FEE_MODE_TIER_BASED: u8 = 2;
+pub const FEE_MODE_VOLUME_BASED: u8 = 3;
+pub const FEE_MODE_CIRCUIT_BREAKER: u8 = 4;
+pub const NO_FEE_TIER: u8 = u8::MAX; // Trade event tier index when no tier priced the trade
+
+// Reason codes reported by `TradeRejected`
+pub const TRADE_REJECT_OTHER: u8 = 0;
+pub const TRADE_REJECT_PAUSED: u8 = 1; // Paused, retired, frozen or in the post-resume cooldown
+pub const TRADE_REJECT_BLACKLISTED: u8 = 2;
+pub const TRADE_REJECT_RATE_LIMIT: u8 = 3; // Trade count and cooldown limits
+pub const TRADE_REJECT_VOLUME: u8 = 4; // Hourly and daily volume caps
+pub const TRADE_REJECT_CIRCUIT_BREAKER: u8 = 5;
+pub const TRADE_REJECT_TRADE_SIZE: u8 = 6;
+pub const TRADE_REJECT_PRICE_IMPACT: u8 = 7;
+pub const TRADE_REJECT_SLIPPAGE: u8 = 8;
+pub const TRADE_REJECT_LIQUIDITY: u8 = 9; // Liquidity floor and drain guard
 
 // --- Limits ---
 pub const MAX_FEE_TIERS: usize = 100;
+pub const DEFAULT_MAX_FEE_TIERS: u16 = 8; // Per-pool cap, raisable up to MAX_FEE_TIERS
 pub const MAX_BLACKLIST_SIZE: usize = 1000;
 pub const MAX_PENDING_UPDATE_SIZE: usize = 100;
-pub const BATCH_BLACKLIST_MAX_SIZE: usize = 50;
-pub const MIN_FEE_TIER_SPACING_BPS: u64 = 10; // 0.1%
+pub const MAX_BLACKLIST_BATCH: u16 = 200; // Hard cap on blacklist entries per call, bounded by compute
+pub const DEFAULT_MAX_BLACKLIST_BATCH: u16 = 50; // Per-pool cap, raisable up to MAX_BLACKLIST_BATCH
+pub const MAX_BATCH_TRADE_LEGS: usize = 10;
+pub const MAX_PENDING_DEPOSITS: usize = 8; // Deposits tracked inside the liquidity grace period
+pub const MAX_LIQUIDITY_LOCKS: usize = 8; // Unexpired liquidity locks held at once
+pub const BLACKLIST_PAGE_MAX_SIZE: u32 = 30; // Bounded by the 1 KiB return data limit
+pub const MIN_FEE_TIER_SPACING_BPS: u64 = 10; // 0.1% minimum fee difference between consecutive tiers
 
 // --- Circuit Breaker Settings ---
 pub const MAX_PRICE_IMPACT_BPS: u64 = 1000; // 10% maximum price impact
+pub const CIRCUIT_BREAKER_HIGH_WATER_BPS: u64 = 8000; // Default utilization that emits an approaching warning
+pub const HEALTH_NEAR_CAP_BPS: u64 = 8000; // 80% utilization reports a limit as near its cap
+pub const DEFAULT_LARGE_WITHDRAWAL_BPS: u64 = 2000; // Liquidity removals above 20% of reserves are guarded
 pub const MAX_DAILY_VOLUME_BPS: u64 = 10000; // 100% of max_daily_volume
 pub const MAX_TRADE_SIZE_BPS: u64 = 1000; // 10% of max_daily_volume
 pub const MAX_HOURLY_TRADES: u32 = 100; // Maximum trades per hour
 pub const MAX_HOURLY_VOLUME_BPS: u64 = 1000; // 10% of max_daily_volume
 
 // --- Volume and Trade Limits ---
+pub const VOLUME_BUCKET_COUNT: usize = 24; // Hourly buckets in the rolling 24h window
+pub const VOLUME_BUCKET_SECONDS: u64 = 3600; // 1 hour per bucket
 pub const MAX_TRADE_SIZE: u64 = 1000000; // Maximum trade size in token units
 pub const MAX_HOURLY_VOLUME: u64 = 10000000; // Maximum hourly volume in token units
 pub const MAX_DAILY_VOLUME_LIMIT: u64 = 100000000; // Maximum daily volume in token units
@@ -67,6 +109,7 @@ pub const MAX_HOURLY_TRADES_DECAY: u64 = 100; // 1% decay per hour
 pub const MAX_HOURLY_VOLUME_DECAY: u64 = 100; // 1% decay per hour
 pub const MAX_DAILY_VOLUME_DECAY: u64 = 100; // 1% decay per hour
 pub const MAX_PRICE_IMPACT_DECAY: u64 = 100; // 1% decay per hour
+pub const CIRCUIT_BREAKER_DECAY: u64 = 100; // 1% of the threshold per hour
 
 // --- Decay Windows ---
 pub const MAX_TRADE_SIZE_DECAY_WINDOW: u64 = 3600; // 1 hour decay window
@@ -74,10 +117,62 @@ pub const MAX_HOURLY_TRADES_DECAY_WINDOW: u64 = 3600; // 1 hour decay window
 pub const MAX_HOURLY_VOLUME_DECAY_WINDOW: u64 = 3600; // 1 hour decay window
 pub const MAX_DAILY_VOLUME_DECAY_WINDOW: u64 = 86400; // 24 hour decay window
 pub const MAX_PRICE_IMPACT_DECAY_WINDOW: u64 = 3600; // 1 hour decay window
+pub const CIRCUIT_BREAKER_DECAY_WINDOW: u64 = 3600; // 1 hour decay window
 
 // --- Decay Reset Periods ---
 pub const MAX_TRADE_SIZE_DECAY_RESET: u64 = 3600; // 1 hour decay reset
 pub const MAX_HOURLY_TRADES_DECAY_RESET: u64 = 3600; // 1 hour decay reset
 pub const MAX_HOURLY_VOLUME_DECAY_RESET: u64 = 3600; // 1 hour decay reset
 pub const MAX_DAILY_VOLUME_DECAY_RESET: u64 = 86400; // 24 hour decay reset
-pub const MAX_PRICE_IMPACT_DECAY_RESET: u64 = 3600; // 1 hour decay reset 
\ No newline at end of file
+pub const MAX_PRICE_IMPACT_DECAY_RESET: u64 = 3600; // 1 hour decay reset 
+
+// Referrals
+pub const MAX_REFERRAL_SHARE_BPS: u64 = 5000; // Referrers get at most half of a trade fee
+
+// Protocol fee
+pub const MAX_PROTOCOL_FEE_BPS: u64 = 500; // 5% protocol cut on top of the LP fee
+
+// CPI caller allow-list
+pub const MAX_ALLOWED_CALLERS: usize = 10;
+
+// Paused operation bits
+pub const PAUSE_OP_TRADE: u8 = 1 << 0;
+pub const PAUSE_OP_ADD_LIQUIDITY: u8 = 1 << 1;
+pub const PAUSE_OP_REMOVE_LIQUIDITY: u8 = 1 << 2;
+pub const PAUSE_OP_WITHDRAW: u8 = 1 << 3; // Fee, insurance and referral withdrawals
+pub const PAUSE_OP_ALL: u8 = PAUSE_OP_TRADE | PAUSE_OP_ADD_LIQUIDITY | PAUSE_OP_REMOVE_LIQUIDITY | PAUSE_OP_WITHDRAW;
+
+// Volatility-adaptive price impact
+pub const VOLATILITY_PRICE_SCALE: u128 = 1_000_000_000; // Fixed-point scale for execution prices
+pub const VOLATILITY_EWMA_WEIGHT: u64 = 4; // Each new price move carries 1/4 of the average
+pub const MIN_VOLATILITY_SAMPLES: u32 = 3; // Price moves needed before the cap adapts
+
+// Post-resume cooldown
+pub const MAX_POST_RESUME_COOLDOWN_SECONDS: i64 = 86400; // 24 hours
+
+// Fee withdrawal cooldown
+pub const MAX_FEE_WITHDRAW_COOLDOWN_SECONDS: i64 = 604800; // 7 days
+
+// Governance token fee discounts
+pub const MAX_GOVERNANCE_DISCOUNT_TIERS: usize = 8;
+pub const MAX_GOVERNANCE_DISCOUNT_BPS: u64 = 5000; // Holders pay at least half the LP fee
+
+// Sandwich detection
+pub const MAX_SANDWICH_WINDOW_SLOTS: u64 = 150; // About a minute of slots
+pub const MAX_SANDWICH_SURCHARGE_BPS: u64 = 500; // 5%
+
+// Low reserve mode
+pub const MAX_LOW_RESERVE_SURCHARGE_BPS: u64 = 500; // 5%
+
+// Drain guard
+pub const MAX_OUTPUT_FRACTION_BPS: u64 = 10000; // 100% of reserves, which disables the guard
+
+// Size-tiered price impact caps
+pub const MAX_PRICE_IMPACT_TIERS: usize = 8;
+
+// Fee distribution
+pub const MAX_FEE_RECIPIENTS: usize = 8;
+pub const FEE_RECIPIENT_TOTAL_BPS: u64 = 10000; // Recipient shares must add up to exactly 100%
+
+// Trade history
+pub const RECENT_TRADES_CAPACITY: usize = 8; // Kept small; every slot is paid for in pool account rent
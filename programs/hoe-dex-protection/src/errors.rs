@@ -84,4 +84,152 @@ pub enum ErrorCode {
     DuplicateFeeTierThreshold,
     #[msg("Circuit breaker cooldown")]
     CircuitBreakerCooldown,
+    #[msg("Slippage exceeded: output below minimum or above pool slippage ceiling")]
+    SlippageExceeded,
+    #[msg("Invalid trader address: default pubkey or protected admin key")]
+    InvalidTraderAddress,
+    #[msg("Blacklist batch too large")]
+    BlacklistBatchTooLarge,
+    #[msg("Blacklist is full")]
+    BlacklistFull,
+    #[msg("Trade cooldown active for this trader")]
+    TradeCooldownActive,
+    #[msg("No fees available to withdraw")]
+    NoFeesAvailable,
+    #[msg("No pending update available")]
+    NoPendingUpdate,
+    #[msg("Too many updates in a single scheduled batch")]
+    TooManyPendingUpdates,
+    #[msg("Invalid parameter relationship")]
+    InvalidParameterRelationship,
+    #[msg("Timelock duration outside allowed range")]
+    InvalidTimelockDuration,
+    #[msg("Invalid guardian")]
+    InvalidGuardian,
+    #[msg("Fee withdrawal would dip into liquidity reserves")]
+    InsufficientFeeReserves,
+    #[msg("Max fee tiers outside allowed range")]
+    InvalidMaxFeeTiers,
+    #[msg("Pool is fully paused; only unpausing is allowed")]
+    PoolFrozen,
+    #[msg("Removal would leave liquidity below the configured floor")]
+    BelowLiquidityFloor,
+    #[msg("Too many legs in a single trade batch")]
+    TradeBatchTooLarge,
+    #[msg("Pool state version is not supported by this program")]
+    UnsupportedPoolVersion,
+    #[msg("Trader is blacklisted")]
+    TraderBlacklisted,
+    #[msg("Blacklist expiry must be zero or in the future")]
+    InvalidBlacklistExpiry,
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    #[msg("Referral share exceeds the maximum")]
+    InvalidReferralShare,
+    #[msg("Calling program is not on the pool's allow-list")]
+    CallerNotAllowed,
+    #[msg("Too many allowed callers")]
+    TooManyAllowedCallers,
+    #[msg("Too many trades against this pool in one transaction")]
+    TooManyTradesPerTx,
+    #[msg("Token mint has a freeze authority")]
+    MintHasFreezeAuthority,
+    #[msg("No pending emergency action available")]
+    NoPendingEmergency,
+    #[msg("Scheduled emergency action does not match")]
+    EmergencyActionMismatch,
+    #[msg("Protocol fee exceeds the maximum")]
+    InvalidProtocolFee,
+    #[msg("Operation is paused")]
+    OperationPaused,
+    #[msg("Paused operations contain unknown bits")]
+    InvalidPausedOps,
+    #[msg("Pool has been retired")]
+    PoolRetired,
+    #[msg("Max blacklist batch outside allowed range")]
+    InvalidMaxBlacklistBatch,
+    #[msg("Pool balance does not cover its obligations")]
+    PoolInsolvent,
+    #[msg("Invalid volatility settings")]
+    InvalidVolatilitySettings,
+    #[msg("Trading is cooling down after an emergency resume")]
+    PostResumeCooldown,
+    #[msg("Post-resume cooldown outside allowed range")]
+    InvalidPostResumeCooldown,
+    #[msg("Invalid governance discount schedule")]
+    InvalidGovernanceDiscount,
+    #[msg("Governance token account does not match the pool's governance mint or the trader")]
+    InvalidGovernanceTokenAccount,
+    #[msg("Hourly volume limit exceeded")]
+    HourlyVolumeLimitExceeded,
+    #[msg("Invalid max hourly volume")]
+    InvalidMaxHourlyVolume,
+    #[msg("Hourly trade limit exceeded")]
+    HourlyTradeLimitExceeded,
+    #[msg("Invalid max hourly trades")]
+    InvalidMaxHourlyTrades,
+    #[msg("Token account is not owned by the trader")]
+    OutputAccountOwnerMismatch,
+    #[msg("Invalid sandwich detection settings")]
+    InvalidSandwichSettings,
+    #[msg("Invalid launch schedule")]
+    InvalidLaunchSchedule,
+    #[msg("Pool has already traded; the launch can no longer be scheduled")]
+    LaunchAlreadyStarted,
+    #[msg("Trade output exceeds the allowed fraction of reserves")]
+    OutputExceedsReserveFraction,
+    #[msg("Invalid max output fraction")]
+    InvalidMaxOutputFraction,
+    #[msg("Invalid fee recipients")]
+    InvalidFeeRecipients,
+    #[msg("Token mint has a permanent delegate or close authority")]
+    RiskyMintAuthority,
+    #[msg("Invalid slippage tolerance")]
+    InvalidSlippageTolerance,
+    #[msg("Accrued fees are at the high-water mark; withdraw fees before trading")]
+    FeeWithdrawalRequired,
+    #[msg("Pool has not been updated within its staleness window; an admin refresh is required")]
+    PoolStale,
+    #[msg("Invalid max staleness")]
+    InvalidMaxStaleness,
+    #[msg("Trader daily volume limit exceeded")]
+    TraderDailyVolumeExceeded,
+    #[msg("Pool has no fee tiers configured")]
+    FeeTiersEmpty,
+    #[msg("Trade would pay in and receive nothing")]
+    ZeroOutput,
+    #[msg("Pool still holds liquidity or fees")]
+    PoolNotEmpty,
+    #[msg("Fee withdrawal cooldown has not elapsed")]
+    FeeWithdrawCooldown,
+    #[msg("Invalid fee withdrawal cooldown")]
+    InvalidFeeWithdrawCooldown,
+    #[msg("Reentrant call: the pool's reentrancy lock is held")]
+    ReentrancyLocked,
+    #[msg("Trade exceeds the size allowed while reserves are low")]
+    LowReserveTradeTooLarge,
+    #[msg("Invalid low reserve settings")]
+    InvalidLowReserveSettings,
+    #[msg("Liquidity is locked until its unlock time")]
+    LiquidityLocked,
+    #[msg("Invalid liquidity lock")]
+    InvalidLiquidityLock,
+    #[msg("Too many liquidity locks")]
+    TooManyLiquidityLocks,
+    #[msg("Invalid price impact tiers")]
+    InvalidPriceImpactTiers,
+    #[msg("A fee tier unlock is already scheduled")]
+    FeeTiersUnlockPending,
+    #[msg("No fee tier unlock is scheduled")]
+    NoPendingFeeTiersUnlock,
+    #[msg("Destination is not the pool's recovery account")]
+    InvalidRecoveryAccount,
+    #[msg("Fee tiers are not locked")]
+    FeeTiersNotLocked,
+    #[msg("Insufficient liquidity: the pool does not hold enough liquidity")]
+    InsufficientLiquidity,
+    #[msg("Invalid fee authority")]
+    InvalidFeeAuthority,
+    #[msg("No fee authority transfer is pending")]
+    NoPendingFeeAuthority,
 } 
\ No newline at end of file
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::types::{TradeSettingsUpdate, ProtectionSettingsUpdate, FeeSettingsUpdate, StateSettingsUpdate};
+use crate::types::{
+    FailureStats, FeeOverflowMode, FeeRecipient, FeeRounding, GovernanceDiscountTier, LowReserveSettings,
+    ParameterUpdate, PauseLevel, PriceImpactTier, SandwichSettingsUpdate, TradeDirection, VolatilitySettingsUpdate,
+};
 
 #[event]
 pub struct PoolInitialized {
@@ -13,6 +16,7 @@ pub struct LiquidityAdded {
     pub pool: Pubkey,
     pub admin_pubkey: Pubkey,
     pub amount: u64,
+    pub event_seq: u64,
     pub ts: i64,
 }
 
@@ -21,6 +25,7 @@ pub struct LiquidityRemoved {
     pub pool: Pubkey,
     pub admin_pubkey: Pubkey,
     pub amount: u64,
+    pub event_seq: u64,
     pub ts: i64,
 }
 
@@ -32,8 +37,12 @@ pub struct TradeExecuted {
     pub amount_out: u64,
     pub fee_amount: u64,
     pub fee_mode: u8,
+    pub event_seq: u64,
     pub ts: i64,
     pub token_mint: Pubkey,
+    /// Fee tier that priced the trade, or `NO_FEE_TIER`
+    pub fee_tier_index: u8,
+    pub direction: TradeDirection,
 }
 
 #[event]
@@ -52,10 +61,20 @@ pub struct CircuitBreakerTriggered {
     pub ts: i64,
 }
 
+#[event]
+pub struct CircuitBreakerApproaching {
+    pub pool: Pubkey,
+    pub current_amount: u64,
+    pub max_amount: u64,
+    pub ts: i64,
+}
+
 #[event]
 pub struct TraderBlacklisted {
     pub pool: Pubkey,
     pub trader_pubkey: Pubkey,
+    pub reason_code: u8,
+    pub expires_at: i64,
     pub ts: i64,
 }
 
@@ -79,6 +98,26 @@ pub struct FeesWithdrawn {
     pub pool: Pubkey,
     pub admin_pubkey: Pubkey,
     pub amount: u64,
+    pub event_seq: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct InsuranceFundConfigured {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub insurance_fee_share_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct InsuranceFundWithdrawn {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub insurance_fund: Pubkey,
+    pub amount: u64,
+    pub event_seq: u64,
     pub ts: i64,
 }
 
@@ -101,6 +140,7 @@ pub struct ParameterUpdateScheduled {
     pub pool: Pubkey,
     pub admin_pubkey: Pubkey,
     pub scheduled_time: i64,
+    pub update_count: u32,
 }
 
 #[event]
@@ -108,10 +148,33 @@ pub struct ParameterUpdateCancelled {
     pub pool: Pubkey,
     pub admin_pubkey: Pubkey,
     pub ts: i64,
-    pub trade_settings: Option<TradeSettingsUpdate>,
-    pub protection_settings: Option<ProtectionSettingsUpdate>,
-    pub fee_settings: Option<FeeSettingsUpdate>,
-    pub state_settings: Option<StateSettingsUpdate>,
+    pub updates: Vec<ParameterUpdate>,
+}
+
+#[event]
+pub struct ParameterUpdateVetoed {
+    pub pool: Pubkey,
+    pub guardian_pubkey: Pubkey,
+    pub scheduled_time: i64,
+    pub updates: Vec<ParameterUpdate>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxFeeTiersUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_fee_tiers: u16,
+    pub new_max_fee_tiers: u16,
+    pub ts: i64,
+}
+
+#[event]
+pub struct GuardianUpdated {
+    pub pool: Pubkey,
+    pub old_guardian_pubkey: Option<Pubkey>,
+    pub new_guardian_pubkey: Option<Pubkey>,
+    pub ts: i64,
 }
 
 #[event]
@@ -121,6 +184,83 @@ pub struct ParametersUpdated {
     pub ts: i64,
 }
 
+#[event]
+pub struct TradeSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub early_trade_fee_bps: u64,
+    pub early_trade_window_seconds: u64,
+    pub max_trade_size_bps: u64,
+    pub min_trade_size: u64,
+    pub cooldown_seconds: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ProtectionSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub max_daily_volume: u64,
+    pub max_price_impact_bps: u64,
+    pub max_slippage_bps: u64,
+    pub blacklist_enabled: bool,
+    pub min_trade_interval: i64,
+    pub min_liquidity_floor: u64,
+    pub large_withdrawal_bps: u64,
+    pub circuit_breaker_threshold: u64,
+    pub circuit_breaker_window: u64,
+    pub circuit_breaker_cooldown: u64,
+    pub circuit_breaker_decay_bps: u64,
+    pub rate_limit_window: u64,
+    pub rate_limit_max: u32,
+    pub max_hourly_volume: u64,
+    pub max_output_fraction_bps: u64,
+    pub max_daily_volume_per_trader: u64,
+    pub low_reserve: LowReserveSettings,
+    pub fee_withdraw_cooldown: i64,
+    pub sandwich: SandwichSettingsUpdate,
+    pub max_hourly_trades: u32,
+    pub max_trades_per_tx: u16,
+    pub volatility: VolatilitySettingsUpdate,
+    pub post_resume_cooldown: i64,
+    pub allowed_callers: Vec<Pubkey>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub fee_tiers_count: u32,
+    pub fee_tiers_locked: bool,
+    pub fee_rounding: FeeRounding,
+    pub protocol_fee_bps: u64,
+    pub fee_free_threshold: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct StateSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub pause_level: PauseLevel,
+    pub is_emergency_paused: bool,
+    pub ts: i64,
+}
+
+#[event]
+pub struct TimelockSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub trade_seconds: u64,
+    pub protection_seconds: u64,
+    pub fee_seconds: u64,
+    pub state_seconds: u64,
+    pub emergency_pause_seconds: u64,
+    pub emergency_resume_seconds: u64,
+    pub ts: i64,
+}
+
 #[event]
 pub struct EmergencyPauseScheduled {
     pub pool: Pubkey,
@@ -149,6 +289,16 @@ pub struct EmergencyResumed {
     pub ts: i64,
 }
 
+#[event]
+pub struct EmergencyWithdraw {
+    pub pool: Pubkey,
+    pub emergency_admin_pubkey: Pubkey,
+    pub recovery_account: Pubkey,
+    pub amount: u64,
+    pub event_seq: u64,
+    pub ts: i64,
+}
+
 #[event]
 pub struct CircuitBreakerReset {
     pub pool: Pubkey,
@@ -164,6 +314,14 @@ pub struct AdminUpdated {
     pub ts: i64,
 }
 
+#[event]
+pub struct EmergencyAdminUpdated {
+    pub pool: Pubkey,
+    pub old_emergency_admin_pubkey: Pubkey,
+    pub new_emergency_admin_pubkey: Pubkey,
+    pub ts: i64,
+}
+
 #[event]
 pub struct OperationFailed {
     pub pool: Pubkey,
@@ -183,6 +341,7 @@ pub struct FreezeAuthorityWarning {
 pub struct PoolPaused {
     pub pool: Pubkey,
     pub admin_pubkey: Pubkey,
+    pub pause_level: PauseLevel,
     pub ts: i64,
 }
 
@@ -237,4 +396,498 @@ pub struct AdminOperationFailed {
     pub operation: String,
     pub reason: String,
     pub ts: i64,
-} 
\ No newline at end of file
+} 
+
+#[event]
+pub struct ReservesSynced {
+    pub pool: Pubkey,
+    pub token_balance: u64,
+    pub old_liquidity: u64,
+    pub new_liquidity: u64,
+    pub delta: i64,
+    pub event_seq: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct LargeWithdrawalFlagged {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub amount: u64,
+    pub total_liquidity: u64,
+    pub withdrawal_bps: u64,
+    pub threshold_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PoolStateMigrated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ReferralShareUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_share_bps: u64,
+    pub new_share_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ReferrerRegistered {
+    pub pool: Pubkey,
+    pub referrer: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ReferralFeeAccrued {
+    pub pool: Pubkey,
+    pub referrer: Pubkey,
+    pub trader: Pubkey,
+    pub amount: u64,
+    pub accrued: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ReferralFeesClaimed {
+    pub pool: Pubkey,
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub event_seq: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct AllowedCallersUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub allowed_callers: Vec<Pubkey>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxTradesPerTxUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_trades_per_tx: u16,
+    pub new_max_trades_per_tx: u16,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ProtocolFeesWithdrawn {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub amount: u64,
+    pub event_seq: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PausedOpsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_paused_ops: u8,
+    pub new_paused_ops: u8,
+    pub ts: i64,
+}
+
+#[event]
+pub struct ParameterUpdateRescheduled {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_scheduled_time: i64,
+    pub scheduled_time: i64,
+    pub update_count: u32,
+    /// Whether the replacement kept the original scheduled time
+    pub schedule_kept: bool,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PoolMigrated {
+    pub pool: Pubkey,
+    pub new_pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub liquidity: u64,
+    /// Treasury, insurance and protocol fees moved alongside the liquidity
+    pub fees: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxBlacklistBatchUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_blacklist_batch: u16,
+    pub new_max_blacklist_batch: u16,
+    pub ts: i64,
+}
+
+#[event]
+pub struct SolvencyChecked {
+    pub pool: Pubkey,
+    pub token_balance: u64,
+    /// Liquidity plus every fee balance held in the pool token account
+    pub obligations: u64,
+    pub surplus: u64,
+    pub deficit: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct VolatilitySettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub enabled: bool,
+    pub floor_impact_bps: u64,
+    pub max_volatility_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PostResumeCooldownUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_cooldown: i64,
+    pub new_cooldown: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct GovernanceDiscountUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub governance_mint: Option<Pubkey>,
+    pub tiers: Vec<GovernanceDiscountTier>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxHourlyVolumeUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_hourly: u64,
+    pub new_max_hourly: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxHourlyTradesUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_hourly_trades: u32,
+    pub new_max_hourly_trades: u32,
+    pub ts: i64,
+}
+
+#[event]
+pub struct OutputOwnerEnforcementUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub enforce_output_owner: bool,
+    pub ts: i64,
+}
+
+#[event]
+pub struct SandwichSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub enabled: bool,
+    pub window_slots: u64,
+    pub large_trade_bps: u64,
+    pub similarity_bps: u64,
+    pub surcharge_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct SandwichSuspected {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub direction: TradeDirection,
+    pub amount_in: u64,
+    pub front_run_amount: u64,
+    pub surcharge: u64,
+    pub slot: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PeriodClosed {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub period_index: u64,
+    pub period_volume: u64,
+    pub period_fees: u64,
+    pub started_at: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct LaunchScheduled {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub launch_ts: i64,
+    pub early_trade_duration: u64,
+    pub early_trade_fee_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxOutputFractionUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_output_fraction_bps: u64,
+    pub new_max_output_fraction_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct TradeRejected {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub amount_in: u64,
+    pub reason_code: u8,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeRecipientsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub recipients: Vec<FeeRecipient>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeesDistributed {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub amount: u64,
+    pub recipients: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub event_seq: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct RiskyMintAllowed {
+    pub pool: Pubkey,
+    pub token_mint: Pubkey,
+    pub permanent_delegate: Option<Pubkey>,
+    pub close_authority: Option<Pubkey>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct VolumeLimitTightened {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_daily: u64,
+    pub new_max_daily: u64,
+    pub ts: i64,
+}
+
+
+#[event]
+pub struct FeeOverflowBehaviorUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub fee_high_water_mark: u64,
+    pub fee_overflow_mode: FeeOverflowMode,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeAccrualCapped {
+    pub pool: Pubkey,
+    pub total_fees_collected: u64,
+    pub dropped_amount: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxStalenessUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_staleness: i64,
+    pub new_max_staleness: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PoolStale {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub last_update: u64,
+    pub max_staleness: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PendingUpdateForceExpired {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_scheduled_time: i64,
+    pub scheduled_time: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct MaxDailyVolumePerTraderUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_max_daily_volume_per_trader: u64,
+    pub new_max_daily_volume_per_trader: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PoolTicked {
+    pub pool: Pubkey,
+    pub caller: Pubkey,
+    pub volume_before: u64,
+    pub volume_after: u64,
+    pub rate_limit_rolled: bool,
+    pub circuit_breaker_before: u64,
+    pub circuit_breaker_after: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PoolClosed {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct VolumeLimitHit {
+    pub pool: Pubkey,
+    pub attempted_volume: u64,
+    pub max_daily: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct CircuitBreakerWarnThresholdUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_warn_threshold_bps: u64,
+    pub new_warn_threshold_bps: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeWithdrawCooldownUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_cooldown: i64,
+    pub new_cooldown: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PartialFill {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub requested_amount_in: u64,
+    pub filled_amount_in: u64,
+    pub amount_out: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FailureStatsReset {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_stats: FailureStats,
+    pub ts: i64,
+}
+
+#[event]
+pub struct LowReserveSettingsUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub threshold: u64,
+    pub surcharge_bps: u64,
+    pub max_trade_size: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct LowReserveMode {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub reserves: u64,
+    pub threshold: u64,
+    pub amount_in: u64,
+    pub surcharge: u64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct LiquidityLockCreated {
+    pub pool: Pubkey,
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub unlock_ts: i64,
+    pub ts: i64,
+}
+
+#[event]
+pub struct PriceImpactTiersUpdated {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub tiers: Vec<PriceImpactTier>,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeTiersUnlocked {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct RecoveryAccountSet {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub old_recovery_account: Pubkey,
+    pub new_recovery_account: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeAuthorityProposed {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub fee_authority: Pubkey,
+    pub pending_fee_authority: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeAuthorityTransferred {
+    pub pool: Pubkey,
+    pub old_fee_authority: Pubkey,
+    pub new_fee_authority: Pubkey,
+    pub ts: i64,
+}
+
+#[event]
+pub struct FeeAuthorityTransferCancelled {
+    pub pool: Pubkey,
+    pub admin_pubkey: Pubkey,
+    pub pending_fee_authority: Pubkey,
+    pub ts: i64,
+}
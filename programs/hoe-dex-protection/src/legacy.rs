@@ -0,0 +1,599 @@
+//! Pool state layouts written by earlier program versions
+//!
+//! The current `PoolState` cannot deserialize these, so `migrate_pool_state`
+//! decodes the raw account data with the matching layout here and converts it
+//! one version step at a time. Each layout is frozen: never edit one, add the
+//! next version instead.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::*;
+
+/// Offset of the first field after the account discriminator
+const FIELDS_OFFSET: usize = 8;
+
+/// Layout version of a pool state account, read from its raw data
+///
+/// Version 1 accounts predate the version field and start with the admin key;
+/// every later layout starts with the version byte followed by the admin key.
+/// `admin` tells the two apart, so it must be the pool's admin. Returns `None`
+/// when the data is not a pool state account administered by `admin`.
+pub fn detect_version(data: &[u8], admin: &Pubkey) -> Option<u8> {
+    if data.len() < FIELDS_OFFSET + 1 + 32 || &data[..FIELDS_OFFSET] != crate::PoolState::DISCRIMINATOR {
+        return None;
+    }
+
+    let admin = admin.as_ref();
+    if &data[FIELDS_OFFSET + 1..FIELDS_OFFSET + 1 + 32] == admin {
+        Some(data[FIELDS_OFFSET])
+    } else if &data[FIELDS_OFFSET..FIELDS_OFFSET + 32] == admin {
+        Some(1)
+    } else {
+        None
+    }
+}
+
+/// Decodes a pool of any supported layout and migrates it to the current one
+///
+/// Pending parameter updates and scheduled emergency actions are dropped: their
+/// layouts changed along with the pool's, and the admin can schedule them again.
+pub fn migrate_to_current(data: &[u8], version: u8, current_time: u64) -> Result<crate::PoolState> {
+    let mut fields = &data[FIELDS_OFFSET..];
+    match version {
+        1 => Ok(v1::PoolState::deserialize(&mut fields)?.migrate(current_time).migrate(current_time)),
+        2 => Ok(v2::PoolState::deserialize(&mut fields)?.migrate(current_time)),
+        version => {
+            msg!("No migration path from version {}", version);
+            Err(crate::ErrorCode::UnsupportedPoolVersion.into())
+        }
+    }
+}
+
+/// The original layout, before the version field existed
+pub mod v1 {
+    use super::*;
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct TradeSettings {
+        pub max_size_bps: u64,
+        pub min_size: u64,
+        pub cooldown_seconds: u64,
+        pub last_trade_time: u64,
+        pub early_trade_fee_bps: u64,
+        pub early_trade_window_seconds: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct ProtectionSettings {
+        pub enabled: bool,
+        pub snipe_protection_seconds: u64,
+        pub max_price_impact_bps: u64,
+        pub max_slippage: u64,
+        pub blacklist_enabled: bool,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct TradeSettingsUpdate {
+        pub early_trade_fee_bps: u64,
+        pub early_trade_window_seconds: u64,
+        pub max_trade_size_bps: u64,
+        pub min_trade_size: u64,
+        pub cooldown_seconds: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct ProtectionSettingsUpdate {
+        pub max_daily_volume: u64,
+        pub max_price_impact_bps: u64,
+        pub circuit_breaker_threshold: u64,
+        pub circuit_breaker_window: u64,
+        pub circuit_breaker_cooldown: u64,
+        pub rate_limit_window: u64,
+        pub rate_limit_max: u32,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct FeeSettingsUpdate {
+        pub fee_tiers: Vec<FeeTier>,
+        pub fee_tiers_locked: bool,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct StateSettingsUpdate {
+        pub is_paused: bool,
+        pub is_emergency_paused: bool,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct FeeTier {
+        pub volume_threshold: u64,
+        pub fee_bps: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct RateLimitSettings {
+        pub window_seconds: u64,
+        pub count: u32,
+        pub max_calls: u32,
+        pub last_reset: u64,
+        pub window_size: u64,
+        pub max_per_window: u64,
+        pub current_window: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct CircuitBreakerSettings {
+        pub enabled: bool,
+        pub threshold: u64,
+        pub window: u64,
+        pub cooldown: u64,
+        pub last_trigger: u64,
+        pub cooldown_period: u64,
+        pub max_amount: u64,
+        pub current_amount: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct VolumeSettings {
+        pub volume_24h: u64,
+        pub last_update: u64,
+        pub last_decay: u64,
+        pub max_daily: u64,
+        pub current_volume: u64,
+        pub last_reset: u64,
+        pub decay_period: u64,
+        /// This layout declared `current_volume` twice; this is the second copy
+        pub current_volume_dup: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct PendingUpdate {
+        pub scheduled_time: u64,
+        pub trade_settings: Option<TradeSettingsUpdate>,
+        pub protection_settings: Option<ProtectionSettingsUpdate>,
+        pub fee_settings: Option<FeeSettingsUpdate>,
+        pub state_settings: Option<StateSettingsUpdate>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct PoolState {
+        pub admin: Pubkey,
+        pub emergency_admin: Pubkey,
+        pub token_mint: Pubkey,
+        pub token_decimals: u8,
+        pub total_liquidity: u64,
+        pub total_fees_collected: u64,
+        pub is_initialized: bool,
+        pub is_paused: bool,
+        pub is_finalized: bool,
+        pub pool_start_time: u64,
+        pub last_update: u64,
+        pub fee_tiers: Vec<FeeTier>,
+        pub fee_tiers_locked: bool,
+        pub default_fee_bps: Option<u16>,
+        pub volume: VolumeSettings,
+        pub rate_limit: RateLimitSettings,
+        pub circuit_breaker: CircuitBreakerSettings,
+        pub protection: ProtectionSettings,
+        pub trade_settings: TradeSettings,
+        pub pending_update: Option<PendingUpdate>,
+        pub emergency_action_scheduled_time: u64,
+        pub trader_blacklist: Vec<Pubkey>,
+        pub whitelist: Vec<Pubkey>,
+        pub is_emergency_paused: bool,
+        pub bump: u8,
+        pub pool_id: [u8; 32],
+    }
+
+    impl PoolState {
+        /// Carries every setting and balance over to the version 2 layout
+        ///
+        /// Settings this layout lacks start from what `initialize_pool` gave
+        /// them at version 2. Tracked volume has no hourly breakdown, so it
+        /// lands in the bucket of its last update and ages out from there.
+        pub fn migrate(self, current_time: u64) -> v2::PoolState {
+            let volume = self.volume.current_volume.max(self.volume.current_volume_dup);
+            let bucket_hour = self.volume.last_update / VOLUME_BUCKET_SECONDS;
+            let mut hourly_buckets = [0; v2::VOLUME_BUCKET_COUNT];
+            hourly_buckets[(bucket_hour % v2::VOLUME_BUCKET_COUNT as u64) as usize] = volume;
+
+            v2::PoolState {
+                version: 2,
+                admin: self.admin,
+                emergency_admin: self.emergency_admin,
+                token_mint: self.token_mint,
+                token_decimals: self.token_decimals,
+                total_liquidity: self.total_liquidity,
+                total_fees_collected: self.total_fees_collected,
+                is_initialized: self.is_initialized,
+                pause_level: if self.is_paused { v2::PauseLevel::TradingOnly } else { v2::PauseLevel::None },
+                is_finalized: self.is_finalized,
+                pool_start_time: self.pool_start_time,
+                last_update: self.last_update,
+                max_fee_tiers: DEFAULT_MAX_FEE_TIERS.max(self.fee_tiers.len() as u16),
+                fee_tiers: self.fee_tiers
+                    .into_iter()
+                    .map(|tier| v2::FeeTier { volume_threshold: tier.volume_threshold, fee_bps: tier.fee_bps })
+                    .collect(),
+                fee_tiers_locked: self.fee_tiers_locked,
+                default_fee_bps: self.default_fee_bps,
+                volume: v2::VolumeSettings {
+                    volume_24h: volume,
+                    last_update: self.volume.last_update,
+                    last_decay: self.volume.last_decay,
+                    max_daily: self.volume.max_daily,
+                    current_volume: volume,
+                    last_reset: self.volume.last_reset,
+                    decay_period: self.volume.decay_period,
+                    hourly_buckets,
+                    bucket_hour,
+                },
+                rate_limit: v2::RateLimitSettings {
+                    window_seconds: self.rate_limit.window_seconds,
+                    count: self.rate_limit.count,
+                    max_calls: self.rate_limit.max_calls,
+                    last_reset: self.rate_limit.last_reset,
+                    window_size: self.rate_limit.window_size,
+                    max_per_window: self.rate_limit.max_per_window,
+                    current_window: self.rate_limit.current_window,
+                },
+                circuit_breaker: v2::CircuitBreakerSettings {
+                    enabled: self.circuit_breaker.enabled,
+                    threshold: self.circuit_breaker.threshold,
+                    window: self.circuit_breaker.window,
+                    cooldown: self.circuit_breaker.cooldown,
+                    last_trigger: self.circuit_breaker.last_trigger,
+                    cooldown_period: self.circuit_breaker.cooldown_period,
+                    max_amount: self.circuit_breaker.max_amount,
+                    current_amount: self.circuit_breaker.current_amount,
+                    ..Default::default()
+                },
+                protection: v2::ProtectionSettings {
+                    enabled: self.protection.enabled,
+                    snipe_protection_seconds: self.protection.snipe_protection_seconds,
+                    max_price_impact_bps: self.protection.max_price_impact_bps,
+                    max_slippage_bps: self.protection.max_slippage,
+                    blacklist_enabled: self.protection.blacklist_enabled,
+                    min_trade_interval: 0,
+                    large_withdrawal_bps: DEFAULT_LARGE_WITHDRAWAL_BPS,
+                },
+                trade_settings: v2::TradeSettings {
+                    max_size_bps: self.trade_settings.max_size_bps,
+                    min_size: self.trade_settings.min_size,
+                    cooldown_seconds: self.trade_settings.cooldown_seconds,
+                    last_trade_time: self.trade_settings.last_trade_time,
+                    early_trade_fee_bps: self.trade_settings.early_trade_fee_bps,
+                    early_trade_window_seconds: self.trade_settings.early_trade_window_seconds,
+                },
+                timelocks: v2::TimelockSettings {
+                    trade_seconds: PARAMETER_UPDATE_TIMELOCK,
+                    protection_seconds: PARAMETER_UPDATE_TIMELOCK,
+                    fee_seconds: PARAMETER_UPDATE_TIMELOCK,
+                    state_seconds: PARAMETER_UPDATE_TIMELOCK,
+                },
+                // The pause time was not recorded; start the withdraw timelock now
+                emergency_paused_at: if self.is_emergency_paused { current_time } else { 0 },
+                trader_blacklist: self.trader_blacklist,
+                whitelist: self.whitelist,
+                is_emergency_paused: self.is_emergency_paused,
+                bump: self.bump,
+                pool_id: self.pool_id,
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// The first versioned layout
+pub mod v2 {
+    use super::*;
+
+    /// Hourly buckets in the rolling 24h window, as of this layout
+    pub const VOLUME_BUCKET_COUNT: usize = 24;
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct TradeSettings {
+        pub max_size_bps: u64,
+        pub min_size: u64,
+        pub cooldown_seconds: u64,
+        pub last_trade_time: u64,
+        pub early_trade_fee_bps: u64,
+        pub early_trade_window_seconds: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct ProtectionSettings {
+        pub enabled: bool,
+        pub snipe_protection_seconds: u64,
+        pub max_price_impact_bps: u64,
+        pub max_slippage_bps: u64,
+        pub blacklist_enabled: bool,
+        pub min_trade_interval: i64,
+        pub large_withdrawal_bps: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct TradeSettingsUpdate {
+        pub early_trade_fee_bps: u64,
+        pub early_trade_window_seconds: u64,
+        pub max_trade_size_bps: u64,
+        pub min_trade_size: u64,
+        pub cooldown_seconds: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct ProtectionSettingsUpdate {
+        pub max_daily_volume: u64,
+        pub max_price_impact_bps: u64,
+        pub max_slippage_bps: u64,
+        pub blacklist_enabled: bool,
+        pub min_trade_interval: i64,
+        pub min_liquidity_floor: u64,
+        pub large_withdrawal_bps: u64,
+        pub circuit_breaker_threshold: u64,
+        pub circuit_breaker_window: u64,
+        pub circuit_breaker_cooldown: u64,
+        pub rate_limit_window: u64,
+        pub rate_limit_max: u32,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct FeeSettingsUpdate {
+        pub fee_tiers: Vec<FeeTier>,
+        pub fee_tiers_locked: bool,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct StateSettingsUpdate {
+        pub pause_level: PauseLevel,
+        pub is_emergency_paused: bool,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum PauseLevel {
+        #[default]
+        None,
+        TradingOnly,
+        Full,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct FeeTier {
+        pub volume_threshold: u64,
+        pub fee_bps: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct RateLimitSettings {
+        pub window_seconds: u64,
+        pub count: u32,
+        pub max_calls: u32,
+        pub last_reset: u64,
+        pub window_size: u64,
+        pub max_per_window: u64,
+        pub current_window: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct CircuitBreakerSettings {
+        pub enabled: bool,
+        pub threshold: u64,
+        pub window: u64,
+        pub cooldown: u64,
+        pub last_trigger: u64,
+        pub cooldown_period: u64,
+        pub max_amount: u64,
+        pub current_amount: u64,
+        pub auto_pause_on_breaker: bool,
+        pub paused_by_breaker: bool,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct VolumeSettings {
+        pub volume_24h: u64,
+        pub last_update: u64,
+        pub last_decay: u64,
+        pub max_daily: u64,
+        pub current_volume: u64,
+        pub last_reset: u64,
+        pub decay_period: u64,
+        pub hourly_buckets: [u64; VOLUME_BUCKET_COUNT],
+        pub bucket_hour: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct LiquidityDeposit {
+        pub amount: u64,
+        pub deposited_at: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct TimelockSettings {
+        pub trade_seconds: u64,
+        pub protection_seconds: u64,
+        pub fee_seconds: u64,
+        pub state_seconds: u64,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub enum ParameterUpdate {
+        Trade(TradeSettingsUpdate),
+        Protection(ProtectionSettingsUpdate),
+        Fee(FeeSettingsUpdate),
+        State(StateSettingsUpdate),
+        Timelocks(TimelockSettings),
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+    pub struct PendingUpdate {
+        pub scheduled_time: u64,
+        pub updates: Vec<ParameterUpdate>,
+    }
+
+    #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+    pub struct PoolState {
+        pub version: u8,
+        pub admin: Pubkey,
+        pub emergency_admin: Pubkey,
+        pub guardian: Option<Pubkey>,
+        pub token_mint: Pubkey,
+        pub token_decimals: u8,
+        pub total_liquidity: u64,
+        pub min_liquidity_floor: u64,
+        pub liquidity_grace_period: u64,
+        pub pending_deposits: Vec<LiquidityDeposit>,
+        pub total_fees_collected: u64,
+        pub insurance_fund: Pubkey,
+        pub insurance_fee_share_bps: u64,
+        pub insurance_collected: u64,
+        pub is_initialized: bool,
+        pub pause_level: PauseLevel,
+        pub is_finalized: bool,
+        pub pool_start_time: u64,
+        pub last_update: u64,
+        pub event_seq: u64,
+        pub fee_tiers: Vec<FeeTier>,
+        pub fee_tiers_locked: bool,
+        pub max_fee_tiers: u16,
+        pub default_fee_bps: Option<u16>,
+        pub volume: VolumeSettings,
+        pub rate_limit: RateLimitSettings,
+        pub circuit_breaker: CircuitBreakerSettings,
+        pub protection: ProtectionSettings,
+        pub trade_settings: TradeSettings,
+        pub pending_update: Option<PendingUpdate>,
+        pub timelocks: TimelockSettings,
+        pub emergency_action_scheduled_time: u64,
+        pub emergency_paused_at: u64,
+        pub trader_blacklist: Vec<Pubkey>,
+        pub whitelist: Vec<Pubkey>,
+        pub is_emergency_paused: bool,
+        pub bump: u8,
+        pub pool_id: [u8; 32],
+    }
+
+    impl PoolState {
+        /// Carries every setting and balance over to the version 3 layout
+        ///
+        /// Settings this layout lacks start from what `initialize_pool` gives
+        /// them; blacklist entries carry no reason and never expire.
+        pub fn migrate(self, current_time: u64) -> crate::PoolState {
+            let mut state = crate::PoolState {
+                version: 3,
+                admin: self.admin,
+                emergency_admin: self.emergency_admin,
+                guardian: self.guardian,
+                token_mint: self.token_mint,
+                token_decimals: self.token_decimals,
+                total_liquidity: self.total_liquidity,
+                min_liquidity_floor: self.min_liquidity_floor,
+                liquidity_grace_period: self.liquidity_grace_period,
+                pending_deposits: self.pending_deposits
+                    .into_iter()
+                    .map(|deposit| crate::LiquidityDeposit { amount: deposit.amount, deposited_at: deposit.deposited_at })
+                    .collect(),
+                total_fees_collected: self.total_fees_collected,
+                insurance_fund: self.insurance_fund,
+                insurance_fee_share_bps: self.insurance_fee_share_bps,
+                insurance_collected: self.insurance_collected,
+                is_initialized: self.is_initialized,
+                pause_level: match self.pause_level {
+                    PauseLevel::None => crate::PauseLevel::None,
+                    PauseLevel::TradingOnly => crate::PauseLevel::TradingOnly,
+                    PauseLevel::Full => crate::PauseLevel::Full,
+                },
+                is_finalized: self.is_finalized,
+                pool_start_time: self.pool_start_time,
+                last_update: self.last_update,
+                event_seq: self.event_seq,
+                fee_tiers: self.fee_tiers
+                    .into_iter()
+                    .map(|tier| crate::FeeTier {
+                        volume_threshold: tier.volume_threshold,
+                        fee_bps: tier.fee_bps,
+                        liquidity_threshold: None,
+                    })
+                    .collect(),
+                fee_tiers_locked: self.fee_tiers_locked,
+                max_fee_tiers: self.max_fee_tiers,
+                default_fee_bps: self.default_fee_bps,
+                volume: crate::VolumeSettings {
+                    volume_24h: self.volume.volume_24h,
+                    last_update: self.volume.last_update,
+                    last_decay: self.volume.last_decay,
+                    max_daily: self.volume.max_daily,
+                    current_volume: self.volume.current_volume,
+                    last_reset: self.volume.last_reset,
+                    decay_period: self.volume.decay_period,
+                    hourly_buckets: self.volume.hourly_buckets,
+                    bucket_hour: self.volume.bucket_hour,
+                    max_hourly: MAX_HOURLY_VOLUME,
+                    ..Default::default()
+                },
+                rate_limit: crate::RateLimitSettings {
+                    window_seconds: self.rate_limit.window_seconds,
+                    count: self.rate_limit.count,
+                    max_calls: self.rate_limit.max_calls,
+                    last_reset: self.rate_limit.last_reset,
+                    window_size: self.rate_limit.window_size,
+                    max_per_window: self.rate_limit.max_per_window,
+                    current_window: self.rate_limit.current_window,
+                },
+                circuit_breaker: crate::CircuitBreakerSettings {
+                    enabled: self.circuit_breaker.enabled,
+                    threshold: self.circuit_breaker.threshold,
+                    window: self.circuit_breaker.window,
+                    cooldown: self.circuit_breaker.cooldown,
+                    last_trigger: self.circuit_breaker.last_trigger,
+                    cooldown_period: self.circuit_breaker.cooldown_period,
+                    max_amount: self.circuit_breaker.max_amount,
+                    current_amount: self.circuit_breaker.current_amount,
+                    auto_pause_on_breaker: self.circuit_breaker.auto_pause_on_breaker,
+                    paused_by_breaker: self.circuit_breaker.paused_by_breaker,
+                    last_decay: current_time,
+                    ..Default::default()
+                },
+                protection: crate::ProtectionSettings {
+                    enabled: self.protection.enabled,
+                    snipe_protection_seconds: self.protection.snipe_protection_seconds,
+                    max_price_impact_bps: self.protection.max_price_impact_bps,
+                    max_slippage_bps: self.protection.max_slippage_bps,
+                    blacklist_enabled: self.protection.blacklist_enabled,
+                    min_trade_interval: self.protection.min_trade_interval,
+                    large_withdrawal_bps: self.protection.large_withdrawal_bps,
+                    ..Default::default()
+                },
+                trade_settings: crate::TradeSettings {
+                    max_size_bps: self.trade_settings.max_size_bps,
+                    min_size: self.trade_settings.min_size,
+                    cooldown_seconds: self.trade_settings.cooldown_seconds,
+                    last_trade_time: self.trade_settings.last_trade_time,
+                    early_trade_fee_bps: self.trade_settings.early_trade_fee_bps,
+                    early_trade_window_seconds: self.trade_settings.early_trade_window_seconds,
+                },
+                timelocks: crate::TimelockSettings {
+                    trade_seconds: self.timelocks.trade_seconds,
+                    protection_seconds: self.timelocks.protection_seconds,
+                    fee_seconds: self.timelocks.fee_seconds,
+                    state_seconds: self.timelocks.state_seconds,
+                    ..Default::default()
+                },
+                emergency_paused_at: self.emergency_paused_at,
+                trader_blacklist: self.trader_blacklist
+                    .into_iter()
+                    .map(|trader| BlacklistEntry { trader, reason_code: 0, expires_at: 0 })
+                    .collect(),
+                whitelist: self.whitelist,
+                is_emergency_paused: self.is_emergency_paused,
+                bump: self.bump,
+                pool_id: self.pool_id,
+                max_blacklist_batch: DEFAULT_MAX_BLACKLIST_BATCH,
+                max_hourly_trades: MAX_HOURLY_TRADES,
+                max_output_fraction_bps: MAX_OUTPUT_FRACTION_BPS,
+                ..Default::default()
+            };
+            state.accounting_period.started_at = current_time as i64;
+            state
+        }
+    }
+}
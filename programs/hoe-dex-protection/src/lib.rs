@@ -1,14 +1,20 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use std::collections::HashSet;
 
 // Module declarations
 pub mod constants;
+pub mod errors;
 pub mod events;
+pub mod legacy;
 pub mod types;
+pub mod utils;
+pub mod validation;
 pub use constants::*;
+pub use errors::ErrorCode;
 pub use events::*;
 pub use types::*;
+pub use utils::BlacklistOperation;
 
 // Program ID (replace with actual ID after deployment)
 declare_id!("11111111111111111111111111111111"); // Temporary placeholder
@@ -22,85 +28,160 @@ pub mod hoe_dex_protection {
     /// This function sets up the initial state of the pool with all necessary
     /// protection mechanisms and parameters. It performs extensive validation
     /// to ensure the pool starts in a safe state.
-    pub fn initialize_pool(
-        ctx: Context<contexts::InitializePool>,
-        early_trade_fee_bps: u64,
-        early_trade_window_seconds: u64,
-        max_trade_size_bps: u64,
-        min_trade_size: u64,
-        cooldown_seconds: u64,
-        max_daily_volume: u64,
-        max_price_impact_bps: u64,
-        circuit_breaker_threshold: u64,
-        circuit_breaker_window: u64,
-        circuit_breaker_cooldown: u64,
-        rate_limit_window: u64,
-        rate_limit_max: u32,
-        fee_tiers: Vec<FeeTier>,
-        snipe_protection_seconds: u64,
-    ) -> Result<()> {
+    /// - Validates: token mint and its decimals, `config` through the parameter update validators
+    /// - A Token-2022 mint with a permanent delegate or close authority needs
+    ///   `config.allow_risky_mint`, and emits `RiskyMintAllowed`; one with a
+    ///   transfer fee or transfer hook is always rejected
+    /// - `InitialConfig::default()` gives the standard starting settings
+    /// - A `config.preset` other than `Custom` replaces `config.protection`
+    pub fn initialize_pool(ctx: Context<contexts::InitializePool>, mut config: InitialConfig) -> Result<()> {
         let pool_state = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp as u64;
 
-        // Validate fee tiers
-        pool_state.validate_fee_tiers(&fee_tiers)?;
+        // Validate the mint cannot freeze pool or trader token accounts
+        validate_condition!(
+            ctx.accounts.token_mint.freeze_authority.is_none(),
+            crate::ErrorCode::MintHasFreezeAuthority,
+            "Token mint {} has a freeze authority",
+            ctx.accounts.token_mint.key()
+        );
+
+        // Validate the mint cannot move or close pool tokens, unless allowed,
+        // and that transfers deliver the full amount
+        let mint_info = ctx.accounts.token_mint.to_account_info();
+        let mint_data = mint_info.try_borrow_data()?;
+        let mint_authorities = utils::mint_authorities(&mint_data)?;
+        mint_authorities.check(config.allow_risky_mint)?;
+        utils::check_mint_transfer_extensions(&mint_data)?;
+
+        // Validate the mint decimals before they are captured for later checks
+        validate_condition!(
+            ctx.accounts.token_mint.decimals <= MAX_TOKEN_DECIMALS,
+            crate::ErrorCode::InvalidTokenDecimals,
+            "Token mint decimals {} exceed maximum {}",
+            ctx.accounts.token_mint.decimals,
+            MAX_TOKEN_DECIMALS
+        );
+
+        // Apply the protection preset, if any, so it is validated like custom settings
+        if let Some(protection) = config.preset.protection_settings() {
+            config.protection = protection;
+        }
+
+        // Validate the config as if it were scheduled, with fee tiers checked
+        // against the default per-pool cap
+        pool_state.max_fee_tiers = DEFAULT_MAX_FEE_TIERS;
+        pool_state.max_blacklist_batch = DEFAULT_MAX_BLACKLIST_BATCH;
+        pool_state.validate_fee_tiers(&config.fee.fee_tiers)?;
+        validation::validate_protocol_fee(config.fee.protocol_fee_bps)?;
+        pool_state.validate_parameter_update(&ParameterUpdate::Trade(config.trade.clone()))?;
+        pool_state.validate_parameter_update(&ParameterUpdate::Protection(config.protection.clone()))?;
+        pool_state.validate_parameter_update(&ParameterUpdate::Timelocks(config.timelocks.clone()))?;
+        let InitialConfig { trade, protection, fee, timelocks, .. } = config;
 
         // Initialize pool state
-        pool_state.version = 1;
-        pool_state.migration_flag = false;
+        pool_state.version = POOL_STATE_VERSION;
         pool_state.is_initialized = true;
         pool_state.admin = ctx.accounts.admin.key();
         pool_state.emergency_admin = ctx.accounts.admin.key(); // Initially same as admin
         pool_state.token_mint = ctx.accounts.token_mint.key();
         pool_state.token_decimals = ctx.accounts.token_mint.decimals;
+        pool_state.allow_risky_mint = config.allow_risky_mint;
         pool_state.total_fees_collected = 0;
+        pool_state.guardian = None;
+        pool_state.insurance_fund = Pubkey::default();
+        pool_state.recovery_account = Pubkey::default();
+        pool_state.fee_authority = ctx.accounts.admin.key();
+        pool_state.pending_fee_authority = None;
+        pool_state.insurance_fee_share_bps = 0;
+        pool_state.insurance_collected = 0;
         pool_state.total_liquidity = 0;
-        pool_state.is_paused = false;
+        pool_state.min_liquidity_floor = protection.min_liquidity_floor;
+        pool_state.max_output_fraction_bps = protection.max_output_fraction_bps;
+        pool_state.low_reserve = protection.low_reserve.clone();
+        pool_state.fee_withdraw_cooldown = protection.fee_withdraw_cooldown;
+        pool_state.sandwich.configure(&protection.sandwich);
+        pool_state.max_hourly_trades = protection.max_hourly_trades;
+        pool_state.max_trades_per_tx = protection.max_trades_per_tx;
+        pool_state.volatility.configure(&protection.volatility);
+        pool_state.post_resume_cooldown = protection.post_resume_cooldown;
+        pool_state.allowed_callers = protection.allowed_callers.clone();
+        pool_state.liquidity_grace_period = config.liquidity_grace_period;
+        pool_state.pending_deposits = Vec::new();
+        pool_state.pause_level = PauseLevel::None;
         pool_state.is_emergency_paused = false;
         pool_state.is_finalized = false;
         pool_state.pool_start_time = current_time;
         pool_state.last_update = current_time;
+        pool_state.event_seq = 0;
+        pool_state.accounting_period = AccountingPeriod { started_at: current_time as i64, ..Default::default() };
         pool_state.last_admin_update = current_time;
-        pool_state.emergency_action_scheduled_time = 0;
+        pool_state.pending_emergency = None;
+        pool_state.emergency_paused_at = 0;
         pool_state.pending_update = None;
+        pool_state.timelocks = timelocks;
         pool_state.trade_settings = TradeSettings {
-            max_size_bps: max_trade_size_bps,
-            min_size: min_trade_size,
-            cooldown_seconds,
+            max_size_bps: trade.max_trade_size_bps,
+            min_size: trade.min_trade_size,
+            cooldown_seconds: trade.cooldown_seconds,
             last_trade_time: 0,
-            early_trade_fee_bps,
-            early_trade_window_seconds,
+            early_trade_fee_bps: trade.early_trade_fee_bps,
+            early_trade_window_seconds: trade.early_trade_window_seconds,
         };
         pool_state.rate_limit = RateLimitSettings {
-            window_seconds: rate_limit_window,
+            window_seconds: protection.rate_limit_window,
             count: 0,
-            max_calls: rate_limit_max,
+            max_calls: protection.rate_limit_max,
             last_reset: current_time,
         };
         pool_state.circuit_breaker = CircuitBreakerSettings {
             enabled: true,
-            threshold: circuit_breaker_threshold,
-            window: circuit_breaker_window,
-            cooldown: circuit_breaker_cooldown,
+            threshold: protection.circuit_breaker_threshold,
+            window: protection.circuit_breaker_window,
+            cooldown: protection.circuit_breaker_cooldown,
             last_trigger: 0,
+            cooldown_period: protection.circuit_breaker_cooldown,
+            max_amount: protection.circuit_breaker_threshold,
+            current_amount: 0,
+            auto_pause_on_breaker: config.auto_pause_on_breaker,
+            paused_by_breaker: false,
+            decay_bps: protection.circuit_breaker_decay_bps,
+            last_decay: current_time,
+            warn_threshold_bps: 0,
         };
         pool_state.volume = VolumeSettings {
             volume_24h: 0,
             last_update: current_time,
             last_decay: current_time,
-            max_daily: max_daily_volume,
+            max_daily: protection.max_daily_volume,
             current_volume: 0,
             last_reset: current_time,
+            decay_period: MAX_DAILY_VOLUME_DECAY_WINDOW,
+            hourly_buckets: [0; VOLUME_BUCKET_COUNT],
+            bucket_hour: current_time / VOLUME_BUCKET_SECONDS,
+            max_hourly: protection.max_hourly_volume,
+            hourly_volume: 0,
+            hourly_window_start: 0,
+            sell_buckets: [0; VOLUME_BUCKET_COUNT],
+            buy_volume_24h: 0,
+            sell_volume_24h: 0,
         };
         pool_state.protection = ProtectionSettings {
             enabled: true,
-            snipe_protection_seconds,
-            max_price_impact_bps,
-            max_slippage: 100, // 1% default slippage
-            blacklist_enabled: false,
+            snipe_protection_seconds: config.snipe_protection_seconds,
+            max_price_impact_bps: protection.max_price_impact_bps,
+            max_slippage_bps: protection.max_slippage_bps,
+            blacklist_enabled: protection.blacklist_enabled,
+            min_trade_interval: protection.min_trade_interval,
+            large_withdrawal_bps: protection.large_withdrawal_bps,
+            max_daily_volume_per_trader: protection.max_daily_volume_per_trader,
+            price_impact_tiers: Vec::new(),
         };
-        pool_state.fee_tiers = fee_tiers;
-        pool_state.fee_tiers_locked = false;
+        pool_state.fee_tiers = fee.fee_tiers;
+        pool_state.fee_tiers_locked = fee.fee_tiers_locked;
+        pool_state.fee_rounding = fee.fee_rounding;
+        pool_state.protocol_fee_bps = fee.protocol_fee_bps;
+        pool_state.fee_free_threshold = fee.fee_free_threshold;
         pool_state.default_fee_bps = None;
         pool_state.trader_blacklist = Vec::new();
 
@@ -109,6 +190,15 @@ pub mod hoe_dex_protection {
             admin_pubkey: pool_state.admin,
             ts: current_time as i64,
         });
+        if mint_authorities.is_risky() {
+            emit!(RiskyMintAllowed {
+                pool: pool_state.key(),
+                token_mint: pool_state.token_mint,
+                permanent_delegate: mint_authorities.permanent_delegate,
+                close_authority: mint_authorities.close_authority,
+                ts: current_time as i64,
+            });
+        }
 
         Ok(())
     }
@@ -116,7 +206,7 @@ pub mod hoe_dex_protection {
     /// Add liquidity to the pool
     /// 
     /// This function allows the admin to add liquidity to the pool before it starts.
-    /// - Validates: token program, amount, pool state, token accounts
+    /// - Validates: token program, amount, pool state, paused operations, retirement, token mint and decimals, token accounts
     /// - Transfers: tokens from admin to pool
     /// - Updates: pool state with new liquidity and timestamps
     pub fn add_liquidity(ctx: Context<contexts::AddLiquidity>, amount: u64) -> Result<()> {
@@ -125,6 +215,8 @@ pub mod hoe_dex_protection {
 
         // Validate admin action
         validation::validate_admin_action(&ctx.accounts.pool_state, &ctx.accounts.admin.key(), current_time)?;
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_ADD_LIQUIDITY)?;
+        validate_condition!(!ctx.accounts.pool_state.retired, crate::ErrorCode::PoolRetired);
 
         // Validate amount
         if amount == 0 {
@@ -132,7 +224,8 @@ pub mod hoe_dex_protection {
             return Err(crate::ErrorCode::InvalidAmount.into());
         }
 
-        // Check token accounts
+        // Check the mint and token accounts
+        ctx.accounts.pool_state.check_token_mint(&ctx.accounts.token_mint)?;
         ctx.accounts.pool_state.check_token_account(
             &ctx.accounts.admin_token_account,
             &ctx.accounts.pool_state.token_mint,
@@ -142,37 +235,33 @@ pub mod hoe_dex_protection {
             &ctx.accounts.pool_state.token_mint,
         )?;
 
-        // Transfer tokens
-        let transfer_ctx = with_pool_signer(
-            ctx.program_id,
-            &ctx.accounts.pool_state,
-            &[ctx.accounts.pool_authority.to_account_info()],
-        )?;
+        // Update pool state before the transfer
+        ctx.accounts.pool_state.record_deposit(amount, current_time);
+        ctx.accounts.pool_state.total_liquidity = ctx.accounts.pool_state.total_liquidity
+            .checked_add(amount)
+            .ok_or_else(|| {
+                msg!("Liquidity overflow: {} + {}", ctx.accounts.pool_state.total_liquidity, amount);
+                error!(crate::ErrorCode::Overflow)
+            })?;
+
+        ctx.accounts.pool_state.last_update = current_time;
+        ctx.accounts.pool_state.last_admin_update = current_time;
 
         // Transfer from admin to pool
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                TokenTransfer {
+                TransferChecked {
             from: ctx.accounts.admin_token_account.to_account_info(),
+            mint: ctx.accounts.token_mint.to_account_info(),
             to: ctx.accounts.pool_token_account.to_account_info(),
             authority: ctx.accounts.admin.to_account_info(),
                 },
             ),
             amount,
+            ctx.accounts.token_mint.decimals,
         )?;
 
-        // Update pool state
-        ctx.accounts.pool_state.total_liquidity = ctx.accounts.pool_state.total_liquidity
-            .checked_add(amount)
-            .ok_or_else(|| {
-                msg!("Liquidity overflow: {} + {}", ctx.accounts.pool_state.total_liquidity, amount);
-                error!(crate::ErrorCode::Overflow)
-            })?;
-
-        ctx.accounts.pool_state.last_update = current_time;
-        ctx.accounts.pool_state.last_admin_update = current_time;
-
         // Emit event
         ctx.accounts.pool_state.emit_liquidity_added(
             &ctx.accounts.admin.key(),
@@ -183,10 +272,38 @@ pub mod hoe_dex_protection {
         Ok(())
     }
 
+    /// Add liquidity to the pool and lock it until `unlock_ts`
+    ///
+    /// A trust signal for launches: the locked amount cannot be removed before
+    /// `unlock_ts`. The deposit runs through `add_liquidity`, so a failed check
+    /// there reverts the lock with it.
+    /// - Validates: unlock time in the future, lock capacity, then as `add_liquidity`
+    /// - Updates: liquidity locks, then as `add_liquidity`
+    pub fn add_liquidity_locked(ctx: Context<contexts::AddLiquidity>, amount: u64, unlock_ts: i64) -> Result<()> {
+        let current_time = current_unix_ts()?;
+        let provider = ctx.accounts.admin.key();
+
+        // Record the lock; the deposit below validates the admin and amount
+        ctx.accounts.pool_state.lock_liquidity(&provider, amount, unlock_ts, current_time as i64)?;
+
+        // Emit event
+        emit!(LiquidityLockCreated {
+            pool: ctx.accounts.pool_state.key(),
+            provider,
+            amount,
+            unlock_ts,
+            ts: current_time as i64,
+        });
+
+        add_liquidity(ctx, amount)
+    }
+
     /// Remove liquidity from the pool
     /// 
     /// This function allows the admin to withdraw liquidity from the pool.
-    /// - Validates: token program, admin, pool state, token accounts, amount
+    /// - Validates: token program, admin, pool state, paused operations, token accounts, amount,
+    ///   unexpired liquidity locks, liquidity floor
+    /// - Guards: removals above `large_withdrawal_bps` of reserves count against the circuit breaker
     /// - Transfers: tokens from pool to admin
     /// - Updates: pool state with reduced liquidity and timestamps
     pub fn remove_liquidity(ctx: Context<contexts::AdminAction>, amount: u64) -> Result<()> {
@@ -195,6 +312,7 @@ pub mod hoe_dex_protection {
 
         // Validate admin action
         validation::validate_admin_action(&ctx.accounts.pool_state, &ctx.accounts.admin.key(), current_time)?;
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_REMOVE_LIQUIDITY)?;
 
         // Validate amount
         if amount == 0 {
@@ -211,7 +329,47 @@ pub mod hoe_dex_protection {
             return Err(crate::ErrorCode::InsufficientLiquidity.into());
         }
 
+        // Check the removal leaves locked liquidity in place
+        ctx.accounts.pool_state.release_expired_liquidity_locks(current_time as i64);
+        ctx.accounts.pool_state.check_liquidity_unlocked(amount, current_time as i64)?;
+
+        // Check the removal keeps liquidity at or above the floor
+        let remaining_liquidity = ctx.accounts.pool_state.total_liquidity - amount;
+        validate_condition!(
+            remaining_liquidity >= ctx.accounts.pool_state.min_liquidity_floor,
+            crate::ErrorCode::BelowLiquidityFloor,
+            "Removal would leave {} below liquidity floor {}",
+            remaining_liquidity,
+            ctx.accounts.pool_state.min_liquidity_floor
+        );
+
+        // Guard large withdrawals through the circuit breaker, as trades are
+        let withdrawal_bps = ctx.accounts.pool_state.withdrawal_bps(amount)?;
+        if withdrawal_bps > ctx.accounts.pool_state.protection.large_withdrawal_bps {
+            emit!(LargeWithdrawalFlagged {
+                pool: ctx.accounts.pool_state.key(),
+                admin_pubkey: ctx.accounts.admin.key(),
+                amount,
+                total_liquidity: ctx.accounts.pool_state.total_liquidity,
+                withdrawal_bps,
+                threshold_bps: ctx.accounts.pool_state.protection.large_withdrawal_bps,
+                ts: current_time as i64,
+            });
+
+            // An auto-pausing breach commits the pause and skips the removal
+            let breaker_tripped = ctx.accounts.pool_state.update_circuit_breaker(amount, current_time)?;
+            if breaker_tripped {
+                if ctx.accounts.pool_state.pause_level.halts_trading() {
+                    msg!("Circuit breaker tripped: pool auto-paused, removal skipped");
+                    return Ok(());
+                }
+                msg!("Circuit breaker tripped: removal {} exceeds remaining headroom", amount);
+                return Err(crate::ErrorCode::CircuitBreakerTriggered.into());
+            }
+        }
+
         // Update pool state
+        ctx.accounts.pool_state.consume_pending_deposits(amount);
         ctx.accounts.pool_state.total_liquidity = ctx.accounts.pool_state.total_liquidity
             .checked_sub(amount)
             .ok_or_else(|| {
@@ -241,108 +399,195 @@ pub mod hoe_dex_protection {
     /// - Price impact checks
     /// - Fee calculation
     /// - Volume tracking
+    ///
+    /// `max_slippage_bps` can only tighten the pool's `protection.max_slippage_bps`
+    /// ceiling; a looser value from the caller is clamped to the pool setting.
+    ///
+    /// With a `referrer`, the referrer's registered `referral_state` must be passed
+    /// and receives `referral_share_bps` of the fee, claimable later.
+    ///
+    /// With `allow_partial_fill`, a trade that would exceed the volume caps or the
+    /// circuit breaker's headroom fills only what the limits still admit, with
+    /// `minimum_amount_out` scaled down in proportion, and emits `PartialFill`.
+    /// The returned outcome carries the filled amounts.
     pub fn execute_trade(
         ctx: Context<contexts::ExecuteTrade>,
         amount_in: u64,
         minimum_amount_out: u64,
+        max_slippage_bps: u64,
+        referrer: Option<Pubkey>,
+        allow_partial_fill: bool,
     ) -> Result<TradeOutcome> {
         let current_time = current_unix_ts()?;
-        msg!("Executing trade: amount_in={}, minimum_amount_out={}", amount_in, minimum_amount_out);
-
-        // Validate trade parameters
-        validation::validate_trade_parameters(&ctx.accounts.pool_state, amount_in, current_time)?;
-
-        // Calculate fee and amount out
-        let (fee_amount, fee_mode) = ctx.accounts.pool_state.calculate_fee(amount_in, current_time as i64)?;
-        let amount_after_fee = amount_in.checked_sub(fee_amount).ok_or_else(|| {
-            msg!("Fee calculation overflow: {} - {}", amount_in, fee_amount);
-            error!(crate::ErrorCode::Overflow)
-        })?;
-
-        // Calculate price impact
-        let price_impact = ctx.accounts.pool_state.calculate_price_impact(amount_after_fee, ctx.accounts.pool_state.total_liquidity)?;
-        if price_impact > ctx.accounts.pool_state.protection.max_price_impact {
-            msg!("Price impact too high: {} > {}", price_impact, ctx.accounts.pool_state.protection.max_price_impact);
-            return Err(crate::ErrorCode::PriceImpactTooHigh.into());
-        }
+        msg!("Executing trade: amount_in={}, minimum_amount_out={}, max_slippage_bps={}", amount_in, minimum_amount_out, max_slippage_bps);
 
-        // Calculate amount out
-        let amount_out = amount_after_fee.checked_mul(ctx.accounts.pool_state.total_liquidity)
-            .ok_or_else(|| {
-                msg!("Amount calculation overflow: {} * {}", amount_after_fee, ctx.accounts.pool_state.total_liquidity);
-                error!(crate::ErrorCode::Overflow)
-            })?
-            .checked_div(ctx.accounts.pool_state.total_liquidity.checked_add(amount_after_fee)
-                .ok_or_else(|| {
-                    msg!("Pool balance overflow: {} + {}", ctx.accounts.pool_state.total_liquidity, amount_after_fee);
-                    error!(crate::ErrorCode::Overflow)
-                })?)
-            .ok_or_else(|| {
-                msg!("Division by zero in amount calculation");
-                error!(crate::ErrorCode::Overflow)
-            })?;
+        // Size the trade down to the remaining headroom when partial fills are allowed
+        let requested_amount_in = amount_in;
+        let (amount_in, minimum_amount_out) = if allow_partial_fill {
+            utils::partial_fill(&ctx.accounts.pool_state, amount_in, minimum_amount_out, current_time)?
+        } else {
+            (amount_in, minimum_amount_out)
+        };
 
-        // Check slippage
-        if amount_out < minimum_amount_out {
-            msg!("Slippage exceeded: got {} < minimum {}", amount_out, minimum_amount_out);
-            return Err(crate::ErrorCode::SlippageExceeded.into());
+        let pool = ctx.accounts.pool_state.key();
+        utils::acquire_reentrancy_lock(&mut ctx.accounts.reentrancy_guard, pool, ctx.bumps.reentrancy_guard)?;
+        let outcome = utils::process_trade(
+            ctx.accounts,
+            ctx.bumps.trader_state,
+            ctx.bumps.trader_volume,
+            amount_in,
+            minimum_amount_out,
+            max_slippage_bps,
+            referrer,
+            current_time,
+        )?;
+        ctx.accounts.reentrancy_guard.release();
+
+        if amount_in < requested_amount_in {
+            emit!(PartialFill {
+                pool: ctx.accounts.pool_state.key(),
+                trader: ctx.accounts.buyer.key(),
+                requested_amount_in,
+                filled_amount_in: outcome.amount_in,
+                amount_out: outcome.amount_out,
+                ts: current_time as i64,
+            });
         }
+        Ok(outcome)
+    }
 
-        // Transfer tokens
-        let transfer_ctx = with_pool_signer(
-            ctx.program_id,
-            &ctx.accounts.pool_state,
-            &[ctx.accounts.pool_authority.to_account_info()],
-        )?;
+    /// Execute a trade whose minimum output is a tolerance off a quoted amount
+    ///
+    /// Same as `execute_trade` without a referrer, with `minimum_amount_out`
+    /// derived as `expected_out * (10000 - tolerance_bps) / 10000`. The pool's
+    /// slippage ceiling still applies on top.
+    /// - Validates: `expected_out` is non-zero; `tolerance_bps` is at most 10000
+    pub fn execute_trade_with_tolerance(
+        ctx: Context<contexts::ExecuteTrade>,
+        amount_in: u64,
+        expected_out: u64,
+        tolerance_bps: u64,
+    ) -> Result<TradeOutcome> {
+        let current_time = current_unix_ts()?;
+        let minimum_amount_out = utils::min_amount_out_from_tolerance(expected_out, tolerance_bps)?;
+        msg!(
+            "Executing trade: amount_in={}, expected_out={}, tolerance_bps={}, minimum_amount_out={}",
+            amount_in,
+            expected_out,
+            tolerance_bps,
+            minimum_amount_out
+        );
 
-        // Transfer from buyer to pool
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                TokenTransfer {
-                    from: ctx.accounts.buyer_token_account.to_account_info(),
-                    to: ctx.accounts.pool_token_account.to_account_info(),
-                    authority: ctx.accounts.buyer.to_account_info(),
-                },
-            ),
+        let max_slippage_bps = ctx.accounts.pool_state.protection.max_slippage_bps;
+        let pool = ctx.accounts.pool_state.key();
+        utils::acquire_reentrancy_lock(&mut ctx.accounts.reentrancy_guard, pool, ctx.bumps.reentrancy_guard)?;
+        let outcome = utils::process_trade(
+            ctx.accounts,
+            ctx.bumps.trader_state,
+            ctx.bumps.trader_volume,
             amount_in,
+            minimum_amount_out,
+            max_slippage_bps,
+            None,
+            current_time,
         )?;
+        ctx.accounts.reentrancy_guard.release();
+        Ok(outcome)
+    }
 
-        // Update pool state
-        ctx.accounts.pool_state.total_liquidity = ctx.accounts.pool_state.total_liquidity
-            .checked_add(amount_in)
-            .ok_or_else(|| {
-                msg!("Liquidity overflow: {} + {}", ctx.accounts.pool_state.total_liquidity, amount_in);
-                error!(crate::ErrorCode::Overflow)
-            })?;
-
-        ctx.accounts.pool_state.total_fees_collected = ctx.accounts.pool_state.total_fees_collected
-            .checked_add(fee_amount)
-            .ok_or_else(|| {
-                msg!("Fee collection overflow: {} + {}", ctx.accounts.pool_state.total_fees_collected, fee_amount);
-                error!(crate::ErrorCode::Overflow)
-            })?;
-
-        ctx.accounts.pool_state.trade_settings.last_trade_time = current_time;
-        ctx.accounts.pool_state.last_update = current_time;
+    /// Execute several trades from the same buyer in one transaction
+    ///
+    /// Each leg runs through the same checks as `execute_trade`. A leg that fails
+    /// validation is rolled back, reported through `TradeExecutionFailed` and a
+    /// `TradeRejected` reason code, counted in `failed_trade_count`, and the
+    /// remaining legs still run. Errors
+    /// outside leg validation revert as usual. A leg over the daily volume cap
+    /// also emits `VolumeLimitHit`, since a reverted trade cannot. A leg that
+    /// trips an auto-pausing circuit breaker commits the pause but moves
+    /// nothing, so it counts as skipped rather than executed.
+    /// - Validates: batch size; every leg as in `execute_trade`
+    /// - Returns: counts of executed, failed and skipped legs
+    pub fn execute_trades_batch(
+        ctx: Context<contexts::ExecuteTrade>,
+        legs: Vec<TradeLeg>,
+    ) -> Result<BatchTradeSummary> {
+        let current_time = current_unix_ts()?;
 
-        // Emit trade event
-        ctx.accounts.pool_state.emit_trade_executed(
-            &ctx.accounts.buyer.key(),
-            amount_in,
-            amount_out,
-            fee_amount,
-            fee_mode as u8,
-            current_time as i64,
+        // Validate batch size
+        validate_condition!(!legs.is_empty(), crate::ErrorCode::InvalidAmount);
+        validate_condition!(
+            legs.len() <= MAX_BATCH_TRADE_LEGS,
+            crate::ErrorCode::TradeBatchTooLarge,
+            "Invalid trade batch size: {} (max: {})",
+            legs.len(),
+            MAX_BATCH_TRADE_LEGS
         );
 
-        Ok(TradeOutcome {
-            amount_out,
-            fee_amount,
-            fee_mode: FeeMode::from_u8(fee_mode).unwrap_or(FeeMode::None),
-            price_impact,
-            timestamp: current_time as i64,
-        })
+        // Hold the reentrancy lock across every leg
+        let pool = ctx.accounts.pool_state.key();
+        utils::acquire_reentrancy_lock(&mut ctx.accounts.reentrancy_guard, pool, ctx.bumps.reentrancy_guard)?;
+
+        let mut summary = BatchTradeSummary::default();
+        for leg in legs {
+            // Snapshot state so a failed leg leaves no partial updates behind
+            let pool_snapshot = (*ctx.accounts.pool_state).clone();
+            let trader_snapshot = (*ctx.accounts.trader_state).clone();
+            let trader_volume_snapshot = (*ctx.accounts.trader_volume).clone();
+
+            match utils::process_trade(
+                ctx.accounts,
+                ctx.bumps.trader_state,
+                ctx.bumps.trader_volume,
+                leg.amount_in,
+                leg.minimum_amount_out,
+                leg.max_slippage_bps,
+                None,
+                current_time,
+            ) {
+                Ok(outcome) if outcome.amount_out == 0 => {
+                    summary.skipped += 1;
+                }
+                Ok(outcome) => {
+                    summary.executed += 1;
+                    summary.total_amount_out = summary.total_amount_out
+                        .checked_add(outcome.amount_out)
+                        .ok_or(crate::ErrorCode::Overflow)?;
+                }
+                Err(err) => {
+                    *ctx.accounts.pool_state = pool_snapshot;
+                    *ctx.accounts.trader_state = trader_snapshot;
+                    *ctx.accounts.trader_volume = trader_volume_snapshot;
+                    ctx.accounts.pool_state.record_failure(FailureCategory::Trade);
+                    summary.failed += 1;
+
+                    emit!(TradeExecutionFailed {
+                        pool: ctx.accounts.pool_state.key(),
+                        buyer: ctx.accounts.buyer.key(),
+                        amount_in: leg.amount_in,
+                        reason: err.to_string(),
+                        ts: current_time as i64,
+                    });
+                    emit!(TradeRejected {
+                        pool: ctx.accounts.pool_state.key(),
+                        trader: ctx.accounts.buyer.key(),
+                        amount_in: leg.amount_in,
+                        reason_code: utils::trade_reject_reason(&err),
+                        ts: current_time as i64,
+                    });
+                    if err == crate::ErrorCode::VolumeLimitExceeded.into() {
+                        emit!(VolumeLimitHit {
+                            pool: ctx.accounts.pool_state.key(),
+                            attempted_volume: ctx.accounts.pool_state.attempted_daily_volume(leg.amount_in, current_time),
+                            max_daily: ctx.accounts.pool_state.volume.max_daily,
+                            ts: current_time as i64,
+                        });
+                    }
+                }
+            }
+        }
+
+        ctx.accounts.reentrancy_guard.release();
+        Ok(summary)
     }
 
     /// Calculate fee for a trade
@@ -356,7 +601,13 @@ pub mod hoe_dex_protection {
     /// - Admin must be a signer
     /// - Trader cannot be already blacklisted
     /// - Trader cannot be the admin or emergency admin
-    pub fn blacklist_trader(ctx: Context<contexts::ManageBlacklist>, trader: Pubkey) -> Result<()> {
+    /// - `expires_at` must be in the future, or 0 for a permanent ban
+    pub fn blacklist_trader(
+        ctx: Context<contexts::ManageBlacklist>,
+        trader: Pubkey,
+        reason_code: u8,
+        expires_at: i64,
+    ) -> Result<()> {
         let pool_state = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp as u64;
         
@@ -366,7 +617,7 @@ pub mod hoe_dex_protection {
         utils::process_blacklist_operations(
             pool_state,
             vec![trader],
-            BlacklistOperation::Add,
+            BlacklistOperation::Add { reason_code, expires_at },
             current_time,
         )
     }
@@ -396,16 +647,53 @@ pub mod hoe_dex_protection {
     /// This function allows the admin to blacklist multiple traders in a single transaction with:
     /// - Admin must be a signer
     /// - Maximum of 50 traders per batch
-    /// - Each trader must not be already blacklisted
+    /// - Duplicate entries are collapsed and already-blacklisted traders are skipped
+    /// - The batch is rejected if it contains the default pubkey
     /// - No trader can be the admin or emergency admin
-    pub fn batch_blacklist_traders(ctx: Context<contexts::ManageBlacklist>, traders: Vec<Pubkey>) -> Result<()> {
+    /// - Every trader gets the same reason code and expiry
+    pub fn batch_blacklist_traders(
+        ctx: Context<contexts::ManageBlacklist>,
+        traders: Vec<Pubkey>,
+        reason_code: u8,
+        expires_at: i64,
+    ) -> Result<()> {
         let pool_state = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp as u64;
         
         utils::process_blacklist_operations(
             pool_state,
             traders,
-            BlacklistOperation::Add,
+            BlacklistOperation::Add { reason_code, expires_at },
+            current_time,
+        )
+    }
+
+    /// Blacklist traders as the emergency admin
+    ///
+    /// For incident response when the admin is compromised or unavailable:
+    /// responders can freeze attacker addresses at once, including while the
+    /// pool is emergency paused. Bans are permanent until the admin lifts them;
+    /// the emergency admin cannot unblacklist.
+    /// - Validates: emergency admin, then as `batch_blacklist_traders`
+    /// - Updates: trader blacklist
+    pub fn emergency_blacklist(
+        ctx: Context<contexts::EmergencyAction>,
+        traders: Vec<Pubkey>,
+        reason_code: u8,
+    ) -> Result<()> {
+        let pool_state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate emergency admin
+        validate_condition!(
+            ctx.accounts.emergency_admin.key() == pool_state.emergency_admin,
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+
+        utils::process_blacklist_operations(
+            pool_state,
+            traders,
+            BlacklistOperation::Add { reason_code, expires_at: 0 },
             current_time,
         )
     }
@@ -415,7 +703,7 @@ pub mod hoe_dex_protection {
     /// NEW: Added to allow the admin to remove multiple traders from the blacklist in a single transaction.
     /// - Admin must be a signer
     /// - Maximum of 50 traders per batch
-    /// - Each trader must be currently blacklisted
+    /// - Duplicate entries are collapsed and traders not on the blacklist are skipped
     pub fn batch_unblacklist_traders(ctx: Context<contexts::ManageBlacklist>, traders: Vec<Pubkey>) -> Result<()> {
         let pool_state = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp as u64;
@@ -428,578 +716,2990 @@ pub mod hoe_dex_protection {
         )
     }
 
+    /// Read a page of the trader blacklist
+    ///
+    /// This is a read-only view for enumerating the blacklist.
+    /// - `limit` is capped at `BLACKLIST_PAGE_MAX_SIZE`
+    /// - Returns an empty page when `offset` is past the end
+    pub fn get_blacklist_page(ctx: Context<contexts::ViewPool>, offset: u32, limit: u32) -> Result<BlacklistPage> {
+        let blacklist = &ctx.accounts.pool_state.trader_blacklist;
+        let limit = limit.min(BLACKLIST_PAGE_MAX_SIZE) as usize;
+        let start = (offset as usize).min(blacklist.len());
+        let end = start.saturating_add(limit).min(blacklist.len());
+
+        Ok(BlacklistPage {
+            entries: blacklist[start..end].to_vec(),
+            total_count: blacklist.len() as u32,
+        })
+    }
+
+    /// Check whether a trader is blacklisted
+    ///
+    /// This is a read-only view so front-ends can avoid submitting doomed trades.
+    /// Expired entries that have not been pruned yet report as not blacklisted.
+    pub fn check_blacklist_status(ctx: Context<contexts::ViewPool>, trader: Pubkey) -> Result<bool> {
+        let current_time = Clock::get()?.unix_timestamp;
+        Ok(ctx.accounts.pool_state.is_blacklisted(&trader, current_time))
+    }
+
     /// Withdraw collected fees from the pool
     ///
-    /// This function allows the admin to withdraw collected fees with:
-    /// - Admin must be a signer
+    /// This function allows the fee authority to withdraw collected fees with:
+    /// - Fee authority must be a signer
     /// - Fees must be available to withdraw
+    /// - Withdrawals must not be paused
+    /// - Withdrawal is capped to the balance held above liquidity and insurance
+    /// - `fee_withdraw_cooldown` must have passed since the last withdrawal
     /// - Reentrancy protection
     pub fn withdraw_fees(ctx: Context<contexts::WithdrawFees>) -> Result<()> {
-        let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        let current_time = current_unix_ts()?;
 
-        // Validate admin and check cooldown
-        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        // Validate fee authority and check cooldown
+        validation::validate_fee_authority_action(&ctx.accounts.pool_state, &ctx.accounts.fee_authority.key())?;
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_WITHDRAW)?;
+        ctx.accounts.pool_state.check_fee_withdraw_cooldown(current_time as i64)?;
 
         // Validate token accounts
-        state.check_token_mint(&ctx.accounts.token_mint)?;
-        state.check_token_account(&ctx.accounts.admin_token_account, &state.token_mint)?;
-        state.check_token_account(&ctx.accounts.pool_token_account, &state.token_mint)?;
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.fee_authority_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
 
         // Validate fees available
-        validate_condition!(state.total_fees_collected > 0, crate::ErrorCode::NoFeesAvailable);
+        let amount = ctx.accounts.pool_state.total_fees_collected;
+        validate_condition!(amount > 0, crate::ErrorCode::NoFeesAvailable);
 
-        // Transfer fees from pool to admin
-        let cpi_ctx = with_pool_signer(
-            &crate::ID,
-            state,
-            &[ctx.accounts.pool_token_account.to_account_info(), ctx.accounts.admin_token_account.to_account_info()],
-        )?;
+        // Validate the withdrawal cannot touch liquidity or insurance reserves
+        let fee_reserve = ctx.accounts.pool_state.fee_reserve(ctx.accounts.pool_token_account.amount);
+        validate_condition!(
+            amount <= fee_reserve.saturating_sub(ctx.accounts.pool_state.insurance_collected),
+            crate::ErrorCode::InsufficientFeeReserves,
+            "Fees {} exceed reserve {} net of insurance {}",
+            amount,
+            fee_reserve,
+            ctx.accounts.pool_state.insurance_collected
+        );
 
-        token::transfer(
-            cpi_ctx,
-            state.total_fees_collected,
-        )?;
+        // Hold the reentrancy lock through the transfer
+        let pool = ctx.accounts.pool_state.key();
+        utils::acquire_reentrancy_lock(&mut ctx.accounts.reentrancy_guard, pool, ctx.bumps.reentrancy_guard)?;
 
-        // Update pool state
-        state.total_fees_collected = 0;
-        state.last_update = current_time;
+        // Update pool state before the transfer
+        ctx.accounts.pool_state.total_fees_collected = 0;
+        ctx.accounts.pool_state.last_fee_withdraw_ts = current_time as i64;
+        ctx.accounts.pool_state.last_update = current_time;
+
+        // Transfer fees from pool to fee authority
+        let cpi_ctx = with_pool_signer(
+            ctx.program_id,
+            &ctx.accounts.pool_state,
+            &[
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.pool_token_account.to_account_info(),
+                ctx.accounts.token_mint.to_account_info(),
+                ctx.accounts.fee_authority_token_account.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+            ],
+        )?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
 
         // Emit event
+        let state = &mut ctx.accounts.pool_state;
         emit!(FeesWithdrawn {
             pool: state.key(),
-            admin_pubkey: state.admin,
-            amount: state.total_fees_collected,
+            admin_pubkey: ctx.accounts.fee_authority.key(),
+            amount,
+            event_seq: state.next_event_seq(),
             ts: current_time as i64,
         });
-        
+
+        ctx.accounts.reentrancy_guard.release();
         Ok(())
     }
 
-    /// Lock fee tiers to prevent further changes
+    /// Split the accrued LP fees among the configured fee recipients
     ///
-    /// This function allows the admin to lock fee tiers with:
-    /// - Admin must be a signer
-    /// - Fee tiers must not be already locked
-    pub fn lock_fee_tiers(ctx: Context<contexts::LockFeeTiers>) -> Result<()> {
-        let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+    /// Pays out the same balance as `withdraw_fees`, but to each recipient's
+    /// token account in proportion to its `share_bps`. The token accounts are
+    /// passed as remaining accounts in the order of `fee_recipients`. Rounding
+    /// dust goes to the first recipient.
+    /// - Validates: fee authority, paused operations, fee withdrawal cooldown, token accounts,
+    ///   recipient accounts, accrued balance
+    /// - Transfers: accrued fees from pool to every recipient
+    pub fn distribute_fees<'info>(ctx: Context<'_, '_, 'info, 'info, contexts::DistributeFees<'info>>) -> Result<()> {
+        let current_time = current_unix_ts()?;
 
-        // Validate admin and check cooldown
-        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        // Validate fee authority
+        validation::validate_fee_authority_action(&ctx.accounts.pool_state, &ctx.accounts.fee_authority.key())?;
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_WITHDRAW)?;
+        ctx.accounts.pool_state.check_fee_withdraw_cooldown(current_time as i64)?;
 
-        // Validate fee tiers not already locked
-        validate_condition!(!state.fee_tiers_locked, crate::ErrorCode::FeeTiersLocked);
+        // Validate token accounts
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+        let recipients = ctx.accounts.pool_state.fee_recipients.clone();
+        validate_condition!(
+            !recipients.is_empty(),
+            crate::ErrorCode::InvalidFeeRecipients,
+            "No fee recipients configured"
+        );
+        validate_condition!(
+            ctx.remaining_accounts.len() == recipients.len(),
+            crate::ErrorCode::InvalidFeeRecipients,
+            "Expected {} recipient token accounts, got {}",
+            recipients.len(),
+            ctx.remaining_accounts.len()
+        );
 
-        // Update pool state
-        state.fee_tiers_locked = true;
-        state.last_update = current_time;
+        // Validate fees available
+        let amount = ctx.accounts.pool_state.total_fees_collected;
+        validate_condition!(amount > 0, crate::ErrorCode::NoFeesAvailable);
+
+        // Validate the payout cannot touch liquidity or insurance reserves
+        let fee_reserve = ctx.accounts.pool_state.fee_reserve(ctx.accounts.pool_token_account.amount);
+        validate_condition!(
+            amount <= fee_reserve.saturating_sub(ctx.accounts.pool_state.insurance_collected),
+            crate::ErrorCode::InsufficientFeeReserves,
+            "Fees {} exceed reserve {} net of insurance {}",
+            amount,
+            fee_reserve,
+            ctx.accounts.pool_state.insurance_collected
+        );
+
+        // Update pool state before the transfers
+        let amounts = ctx.accounts.pool_state.split_among_fee_recipients(amount);
+        ctx.accounts.pool_state.total_fees_collected = 0;
+        ctx.accounts.pool_state.last_fee_withdraw_ts = current_time as i64;
+        ctx.accounts.pool_state.last_update = current_time;
+
+        // Transfer each recipient's share from pool to recipient
+        for ((recipient, recipient_info), share) in recipients.iter().zip(ctx.remaining_accounts).zip(&amounts) {
+            validate_condition!(
+                recipient_info.key() == recipient.pubkey,
+                crate::ErrorCode::InvalidFeeRecipients,
+                "Recipient account {} does not match configured recipient {}",
+                recipient_info.key(),
+                recipient.pubkey
+            );
+            let recipient_account = InterfaceAccount::<TokenAccount>::try_from(recipient_info)?;
+            validate_condition!(
+                recipient_account.mint == ctx.accounts.pool_state.token_mint,
+                crate::ErrorCode::InvalidTokenAccount,
+                "Recipient account {} has mint {}",
+                recipient.pubkey,
+                recipient_account.mint
+            );
+            if *share == 0 {
+                continue;
+            }
+
+            let cpi_ctx = with_pool_signer(
+                ctx.program_id,
+                &ctx.accounts.pool_state,
+                &[
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.pool_token_account.to_account_info(),
+                    ctx.accounts.token_mint.to_account_info(),
+                    recipient_info.clone(),
+                    ctx.accounts.pool_authority.to_account_info(),
+                ],
+            )?;
+            token_interface::transfer_checked(cpi_ctx, *share, ctx.accounts.token_mint.decimals)?;
+        }
 
         // Emit event
-        emit!(FeeTiersLocked {
+        let state = &mut ctx.accounts.pool_state;
+        emit!(FeesDistributed {
             pool: state.key(),
-            admin_pubkey: state.admin,
+            admin_pubkey: ctx.accounts.fee_authority.key(),
+            amount,
+            recipients: recipients.iter().map(|recipient| recipient.pubkey).collect(),
+            amounts,
+            event_seq: state.next_event_seq(),
             ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    /// Unlock fee tiers to allow future changes
+    /// Withdraw accrued protocol fees from the pool
     ///
-    /// NEW: Added to allow the admin to unlock fee tiers with a 24-hour timelock.
-    /// - Admin must be a signer
-    /// - Fee tiers must be currently locked
-    /// - Schedules unlock via pending_update for delayed execution
-    pub fn unlock_fee_tiers(ctx: Context<contexts::AdminAction>) -> Result<()> {
-        let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+    /// This is separate from `withdraw_fees`: it only moves `protocol_fees`, the
+    /// cut charged on top of the LP fee, and leaves the LP-side counters alone.
+    /// - Validates: fee authority, paused operations, token accounts, accrued balance
+    /// - Transfers: accrued protocol fees from pool to fee authority
+    pub fn withdraw_protocol_fees(ctx: Context<contexts::WithdrawFees>) -> Result<()> {
+        let current_time = current_unix_ts()?;
 
-        // Validate admin and check cooldown
-        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        // Validate fee authority
+        validation::validate_fee_authority_action(&ctx.accounts.pool_state, &ctx.accounts.fee_authority.key())?;
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_WITHDRAW)?;
 
-        // Validate fee tiers are locked
-        validate_condition!(state.fee_tiers_locked, crate::ErrorCode::FeeTiersNotLocked);
+        // Validate token accounts
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.fee_authority_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
 
-        // Update pool state
-        state.fee_tiers_locked = false;
-        state.last_update = current_time;
+        // Validate protocol fees available
+        let amount = ctx.accounts.pool_state.protocol_fees;
+        validate_condition!(amount > 0, crate::ErrorCode::NoFeesAvailable);
+
+        // Validate the withdrawal cannot touch liquidity or other fee balances
+        let state = &ctx.accounts.pool_state;
+        let protocol_reserve = ctx.accounts.pool_token_account.amount
+            .saturating_sub(state.total_liquidity)
+            .saturating_sub(state.referral_fees_owed)
+            .saturating_sub(state.total_fees_collected)
+            .saturating_sub(state.insurance_collected);
+        validate_condition!(
+            amount <= protocol_reserve,
+            crate::ErrorCode::InsufficientFeeReserves,
+            "Protocol fees {} exceed reserve {}",
+            amount,
+            protocol_reserve
+        );
 
-        // Emit event
-        emit!(FeeTiersUnlockScheduled {
-            pool: state.key(),
-            admin_pubkey: state.admin,
-            scheduled_time: current_time as i64,
-        });
+        // Hold the reentrancy lock through the transfer
+        let pool = ctx.accounts.pool_state.key();
+        utils::acquire_reentrancy_lock(&mut ctx.accounts.reentrancy_guard, pool, ctx.bumps.reentrancy_guard)?;
 
-        Ok(())
-    }
+        // Update pool state before the transfer
+        ctx.accounts.pool_state.protocol_fees = 0;
+        ctx.accounts.pool_state.last_update = current_time;
 
-    /// Schedule a parameter update with a 24-hour timelock
-    ///
-    /// This function allows the admin to schedule changes to pool parameters.
-    /// - Validates: admin, new fee tiers, parameter relationships
-    /// - Stores: pending update with scheduled execution time
-    pub fn schedule_parameter_update(
-        ctx: Context<contexts::AdminAction>,
-        trade_settings: Option<TradeSettingsUpdate>,
-        protection_settings: Option<ProtectionSettingsUpdate>,
-        fee_settings: Option<FeeSettingsUpdate>,
-        state_settings: Option<StateSettingsUpdate>,
-    ) -> Result<()> {
-        let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
-
-        // Validate admin and check cooldown
-        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
-
-        // Validate new settings if provided
-        if let Some(settings) = &trade_settings {
-            validate_parameter!(
-                settings.max_trade_size_bps,
-                settings.min_trade_size,
-                u64::MAX,
-                crate::ErrorCode::InvalidParameterRelationship
-            );
-        }
-
-        if let Some(settings) = &protection_settings {
-            validate_parameter!(
-                settings.max_price_impact_bps,
-                0,
-                10000,
-                crate::ErrorCode::PriceImpactTooHigh
-            );
-        }
-
-        if let Some(settings) = &fee_settings {
-            if let Some(fee_tiers) = &settings.fee_tiers {
-                validation::validate_fee_parameters(state, fee_tiers)?;
-            }
-        }
-
-        // Create pending update
-        state.pending_update = Some(PendingUpdate {
-            scheduled_time: current_time + 86400, // 24 hour timelock
-            trade_settings,
-            protection_settings,
-            fee_settings,
-            state_settings,
-        });
+        // Transfer protocol fees from pool to fee authority
+        let cpi_ctx = with_pool_signer(
+            ctx.program_id,
+            &ctx.accounts.pool_state,
+            &[
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.pool_token_account.to_account_info(),
+                ctx.accounts.token_mint.to_account_info(),
+                ctx.accounts.fee_authority_token_account.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+            ],
+        )?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
 
-        emit!(ParameterUpdateScheduled {
+        // Emit event
+        let state = &mut ctx.accounts.pool_state;
+        emit!(ProtocolFeesWithdrawn {
             pool: state.key(),
-            admin_pubkey: state.admin,
-            scheduled_time: current_time + 86400,
+            admin_pubkey: ctx.accounts.fee_authority.key(),
+            amount,
+            event_seq: state.next_event_seq(),
+            ts: current_time as i64,
         });
 
+        ctx.accounts.reentrancy_guard.release();
         Ok(())
     }
 
-    /// Cancel a scheduled parameter update
+    /// Propose a new fee authority
     ///
-    /// This function allows the admin to cancel a pending parameter update before the timelock expires.
-    /// - Validates: admin, presence of pending update
-    /// - Clears: pending update
-    pub fn cancel_parameter_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
+    /// The fee authority signs `withdraw_fees`, `withdraw_protocol_fees` and
+    /// `distribute_fees`. It only changes once the proposed key accepts, so a
+    /// mistyped key cannot lock the fees away.
+    /// - Validates: admin, proposed key is neither the default pubkey nor the current fee authority
+    /// - Updates: pending fee authority, replacing any earlier proposal
+    pub fn propose_fee_authority(ctx: Context<contexts::AdminAction>, new_fee_authority: Pubkey) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
         let current_time = current_unix_ts()?;
 
         // Validate admin
         validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
 
-        // Take the pending update
-        let pending_update = state.pending_update.take().ok_or_else(|| {
-            error!(crate::ErrorCode::NoPendingUpdate, "No pending update available")
-        })?;
+        // Record the proposal
+        state.propose_fee_authority(new_fee_authority)?;
+        state.last_update = current_time;
 
-        // Emit detailed cancellation event
-        emit!(ParameterUpdateCancelled {
+        // Emit event
+        emit!(FeeAuthorityProposed {
             pool: state.key(),
             admin_pubkey: ctx.accounts.admin.key(),
+            fee_authority: state.fee_authority(),
+            pending_fee_authority: new_fee_authority,
             ts: current_time as i64,
-            trade_settings: pending_update.trade_settings,
-            protection_settings: pending_update.protection_settings,
-            fee_settings: pending_update.fee_settings,
-            state_settings: pending_update.state_settings,
         });
 
-        state.last_update = current_time;
         Ok(())
     }
 
-    /// Apply a scheduled parameter update
+    /// Accept a proposed fee authority transfer
     ///
-    /// This function applies a pending parameter update after the timelock expires.
-    /// - Validates: admin, timelock, parameter relationships
-    /// - Updates: pool state with new parameters
-    pub fn apply_parameter_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
+    /// - Validates: signer is the pending fee authority
+    /// - Updates: fee authority, clears the pending one
+    pub fn accept_fee_authority(ctx: Context<contexts::AcceptFeeAuthority>) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
         let current_time = current_unix_ts()?;
 
-        // Validate admin and timelock
-        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
-        validate_condition!(
-            state.pending_update.is_some(),
-            crate::ErrorCode::NoPendingUpdate,
-            "No pending update available"
-        );
-
-        let pending_update = state.pending_update.as_ref().unwrap();
-        validate_condition!(
-            current_time >= pending_update.scheduled_time,
-            crate::ErrorCode::TimelockNotExpired,
-            "Timelock not yet expired"
-        );
-
-        // Apply updates and emit events
-        if let Some(trade_settings) = &pending_update.trade_settings {
-            state.trade_settings.early_trade_fee_bps = trade_settings.early_trade_fee_bps;
-            state.trade_settings.early_trade_window_seconds = trade_settings.early_trade_window_seconds;
-            state.trade_settings.max_size_bps = trade_settings.max_trade_size_bps;
-            state.trade_settings.min_size = trade_settings.min_trade_size;
-            state.trade_settings.cooldown_seconds = trade_settings.cooldown_seconds;
-
-            emit!(TradeSettingsUpdated {
-                pool: state.key(),
-                admin_pubkey: ctx.accounts.admin.key(),
-                early_trade_fee_bps: trade_settings.early_trade_fee_bps,
-                early_trade_window_seconds: trade_settings.early_trade_window_seconds,
-                max_trade_size_bps: trade_settings.max_trade_size_bps,
-                min_trade_size: trade_settings.min_trade_size,
-                cooldown_seconds: trade_settings.cooldown_seconds,
-                ts: current_time as i64,
-            });
-        }
-
-        if let Some(protection_settings) = &pending_update.protection_settings {
-            state.volume.daily_limit = protection_settings.max_daily_volume;
-            state.protection.max_price_impact = protection_settings.max_price_impact_bps;
-            state.protection.max_slippage = protection_settings.max_slippage;
-            state.protection.blacklist_enabled = protection_settings.blacklist_enabled;
-            state.circuit_breaker.threshold = protection_settings.circuit_breaker_threshold;
-            state.circuit_breaker.window = protection_settings.circuit_breaker_window;
-            state.circuit_breaker.cooldown = protection_settings.circuit_breaker_cooldown;
-            state.rate_limit.window_seconds = protection_settings.rate_limit_window;
-            state.rate_limit.max_calls = protection_settings.rate_limit_max as u64;
-
-            emit!(ProtectionSettingsUpdated {
-                pool: state.key(),
-                admin_pubkey: ctx.accounts.admin.key(),
-                max_daily_volume: protection_settings.max_daily_volume,
-                max_price_impact_bps: protection_settings.max_price_impact_bps,
-                max_slippage: protection_settings.max_slippage,
-                blacklist_enabled: protection_settings.blacklist_enabled,
-                circuit_breaker_threshold: protection_settings.circuit_breaker_threshold,
-                circuit_breaker_window: protection_settings.circuit_breaker_window,
-                circuit_breaker_cooldown: protection_settings.circuit_breaker_cooldown,
-                rate_limit_window: protection_settings.rate_limit_window,
-                rate_limit_max: protection_settings.rate_limit_max,
-                ts: current_time as i64,
-            });
-        }
-
-        if let Some(fee_settings) = &pending_update.fee_settings {
-            if !fee_settings.fee_tiers.is_empty() {
-                state.fee_tiers = fee_settings.fee_tiers.clone();
-            }
-            state.fee_tiers_locked = fee_settings.fee_tiers_locked;
-
-            emit!(FeeSettingsUpdated {
-                pool: state.key(),
-                admin_pubkey: ctx.accounts.admin.key(),
-                fee_tiers_count: state.fee_tiers.len(),
-                fee_tiers_locked: state.fee_tiers_locked,
-                ts: current_time as i64,
-            });
-        }
-
-        if let Some(state_settings) = &pending_update.state_settings {
-            state.is_paused = state_settings.is_paused;
-            state.is_emergency_paused = state_settings.is_emergency_paused;
-
-            emit!(StateSettingsUpdated {
-                pool: state.key(),
-                admin_pubkey: ctx.accounts.admin.key(),
-                is_paused: state_settings.is_paused,
-                is_emergency_paused: state_settings.is_emergency_paused,
-                ts: current_time as i64,
-            });
-        }
-
-        // Clear pending update
-        state.pending_update = None;
+        // Hand over the fees
+        let old_fee_authority = state.accept_fee_authority(&ctx.accounts.new_fee_authority.key())?;
         state.last_update = current_time;
 
-        emit!(ParametersUpdated {
+        // Emit event
+        emit!(FeeAuthorityTransferred {
             pool: state.key(),
-            admin_pubkey: ctx.accounts.admin.key(),
+            old_fee_authority,
+            new_fee_authority: state.fee_authority(),
             ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    /// Schedule an emergency pause with a 1-hour timelock
+    /// Cancel a pending fee authority transfer
     ///
-    /// This function allows the emergency admin to schedule a pool pause.
-    /// - Validates: emergency admin
-    /// - Sets: scheduled pause time
-    pub fn schedule_emergency_pause(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+    /// - Validates: admin, presence of a pending fee authority
+    /// - Clears: pending fee authority
+    pub fn cancel_fee_authority_transfer(ctx: Context<contexts::AdminAction>) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
-
-        // Validate emergency admin
-        validate_condition!(
-            ctx.accounts.emergency_admin.key() == state.emergency_admin,
-            crate::ErrorCode::InvalidEmergencyAdmin
-        );
+        let current_time = current_unix_ts()?;
 
-        // Validate not already paused
-        validate_condition!(!state.is_emergency_paused, crate::ErrorCode::EmergencyPaused);
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
 
-        // Schedule emergency pause
-        state.emergency_action_scheduled_time = current_time + 3600; // 1 hour timelock
+        // Drop the proposal
+        let pending_fee_authority = state.cancel_fee_authority_transfer()?;
+        state.last_update = current_time;
 
         // Emit event
-        emit!(EmergencyPauseScheduled {
+        emit!(FeeAuthorityTransferCancelled {
             pool: state.key(),
-            emergency_admin_pubkey: state.emergency_admin,
-            scheduled_time: current_time + 3600,
+            admin_pubkey: ctx.accounts.admin.key(),
+            pending_fee_authority,
+            ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    /// Apply a scheduled emergency pause
+    /// Reconcile tracked liquidity with the pool's actual token balance
     ///
-    /// This function applies a scheduled pause after the timelock expires.
-    /// - Validates: emergency admin, timelock
-    /// - Sets: pool to emergency paused state
-    pub fn apply_emergency_pause(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+    /// `total_liquidity` is maintained incrementally and can drift from the real
+    /// balance, e.g. after a direct transfer into the pool token account. Collected
+    /// but unwithdrawn fees share that account, so they are excluded from liquidity.
+    /// Synced-in liquidity is recorded as a deposit, so it waits out the grace
+    /// period like any other before it counts toward the trade checks.
+    /// - Validates: admin, pool token account mint and owner
+    /// - Updates: total liquidity to the fee-adjusted token balance, pending deposits
+    pub fn sync_reserves(ctx: Context<contexts::SyncReserves>) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        let current_time = current_unix_ts()?;
 
-        // Validate emergency admin
-        validate_condition!(
-            ctx.accounts.emergency_admin.key() == state.emergency_admin,
-            crate::ErrorCode::InvalidEmergencyAdmin
-        );
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
 
-        // Validate timelock has expired
-        validate_condition!(
-            current_time >= state.emergency_action_scheduled_time,
-            crate::ErrorCode::TimelockNotExpired
-        );
+        // Compute liquidity from the actual balance, net of fees owed
+        let balance = ctx.accounts.pool_token_account.amount;
+        let fees_owed = state.fees_owed()?;
+        let synced_liquidity = balance.saturating_sub(fees_owed);
 
-        // Apply emergency pause
-        state.is_emergency_paused = true;
+        let old_liquidity = state.total_liquidity;
+        let delta = (synced_liquidity as i128 - old_liquidity as i128)
+            .clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+
+        // Update pool state; gains enter as a pending deposit, losses consume them
+        if synced_liquidity > old_liquidity {
+            state.record_deposit(synced_liquidity - old_liquidity, current_time);
+        } else {
+            state.consume_pending_deposits(old_liquidity - synced_liquidity);
+        }
+        state.total_liquidity = synced_liquidity;
         state.last_update = current_time;
 
         // Emit event
-        emit!(EmergencyPaused {
+        emit!(ReservesSynced {
             pool: state.key(),
-            emergency_admin_pubkey: state.emergency_admin,
+            token_balance: balance,
+            old_liquidity,
+            new_liquidity: synced_liquidity,
+            delta,
+            event_seq: state.next_event_seq(),
             ts: current_time as i64,
         });
-        
+
         Ok(())
     }
 
-    /// Schedule an emergency resume with a 1-hour timelock
+    /// Check that the pool's token balance covers everything it owes
     ///
-    /// This function allows the emergency admin to schedule a pool resume.
-    /// - Validates: emergency admin
-    /// - Sets: scheduled resume time
-    pub fn schedule_emergency_resume(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
-        let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
-
-        // Validate emergency admin
-        validate_condition!(
-            ctx.accounts.emergency_admin.key() == state.emergency_admin,
-            crate::ErrorCode::InvalidEmergencyAdmin
-        );
-        
-        // Validate is paused
-        validate_condition!(state.is_emergency_paused, crate::ErrorCode::PoolNotPaused);
+    /// Permissionless, so anyone can audit solvency on chain. Compares the pool
+    /// token account balance against liquidity plus every fee balance held for
+    /// later withdrawal, and reports the surplus or deficit. Only a deficit is
+    /// an error; a surplus, e.g. from direct transfers, is reported and left
+    /// for `sync_reserves`.
+    /// - Validates: pool token account mint and owner
+    /// - Emits: solvency check result
+    pub fn verify_solvency(ctx: Context<contexts::VerifySolvency>) -> Result<()> {
+        let state = &ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
 
-        // Schedule emergency resume
-        state.emergency_action_scheduled_time = current_time + 3600; // 1 hour timelock
+        // Compare the balance against tracked obligations
+        let token_balance = ctx.accounts.pool_token_account.amount;
+        let obligations = state.obligations()?;
+        let surplus = token_balance.saturating_sub(obligations);
+        let deficit = obligations.saturating_sub(token_balance);
 
         // Emit event
-        emit!(EmergencyResumeScheduled {
+        emit!(SolvencyChecked {
             pool: state.key(),
-            emergency_admin_pubkey: state.emergency_admin,
-            scheduled_time: current_time + 3600,
+            token_balance,
+            obligations,
+            surplus,
+            deficit,
+            ts: current_time as i64,
         });
-        
+
+        validate_condition!(
+            deficit == 0,
+            crate::ErrorCode::PoolInsolvent,
+            "Pool balance {} is {} short of obligations {}",
+            token_balance,
+            deficit,
+            obligations
+        );
         Ok(())
     }
 
-    /// Apply a scheduled emergency resume
+    /// Advance volume, rate-limit and circuit-breaker decay to now
     ///
-    /// This function applies a scheduled resume after the timelock expires.
-    /// - Validates: emergency admin, timelock
-    /// - Sets: pool to non-emergency paused state
-    pub fn apply_emergency_resume(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+    /// Permissionless, so keepers can bring the protection counters up to date
+    /// before large operations instead of waiting for the next trade. Safe to
+    /// call repeatedly; with nothing to decay it only emits the summary.
+    /// - Updates: rolling volume, rate-limit window, circuit-breaker accumulator
+    /// - Emits: before and after values of each counter
+    pub fn tick(ctx: Context<contexts::Tick>) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
-
-        // Validate emergency admin
-        validate_condition!(
-            ctx.accounts.emergency_admin.key() == state.emergency_admin,
-            crate::ErrorCode::InvalidEmergencyAdmin
-        );
-        
-        // Validate timelock has expired
-        validate_condition!(
-            current_time >= state.emergency_action_scheduled_time,
-            crate::ErrorCode::TimelockNotExpired
-        );
+        let current_time = current_unix_ts()?;
 
-        // Apply emergency resume
-        state.is_emergency_paused = false;
-        state.last_update = current_time;
+        // Update pool state
+        let volume_before = state.volume.current_volume;
+        let rate_limit_reset_before = state.rate_limit.last_reset;
+        let circuit_breaker_before = state.circuit_breaker.current_amount;
+        state.tick(current_time)?;
 
         // Emit event
-        emit!(EmergencyResumed {
+        emit!(PoolTicked {
             pool: state.key(),
-            emergency_admin_pubkey: state.emergency_admin,
+            caller: ctx.accounts.caller.key(),
+            volume_before,
+            volume_after: state.volume.current_volume,
+            rate_limit_rolled: state.rate_limit.last_reset != rate_limit_reset_before,
+            circuit_breaker_before,
+            circuit_breaker_after: state.circuit_breaker.current_amount,
             ts: current_time as i64,
         });
-        
+
         Ok(())
     }
 
-    /// Reset the circuit breaker
+    /// Close the current accounting period and start the next one
     ///
-    /// This function allows the admin to reset the circuit breaker after its cooldown.
-    /// - Validates: admin, timestamp, cooldown
-    /// - Resets: circuit breaker and 24h volume
-    pub fn reset_circuit_breaker(ctx: Context<contexts::AdminAction>) -> Result<()> {
+    /// Reports the period's volume and fees for treasury and analytics, then
+    /// zeroes the period totals. Protection counters such as the rolling
+    /// volume and the circuit breaker are untouched.
+    /// - Validates: admin
+    /// - Updates: accounting period
+    pub fn close_accounting_period(ctx: Context<contexts::AdminAction>) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        let current_time = current_unix_ts()?;
 
-        // Validate admin and check cooldown
+        // Validate admin
         validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
 
-        // Validate cooldown has expired
-        validate_condition!(
-            current_time >= state.circuit_breaker.last_trigger + state.circuit_breaker.cooldown,
-            crate::ErrorCode::CircuitBreakerCooldown
-        );
-
-        // Reset circuit breaker
-        state.circuit_breaker.last_trigger = 0;
+        // Update pool state
+        let closed = state.accounting_period.close(current_time as i64);
         state.last_update = current_time;
 
         // Emit event
-        emit!(CircuitBreakerReset {
+        emit!(PeriodClosed {
             pool: state.key(),
-            admin_pubkey: state.admin,
+            admin_pubkey: ctx.accounts.admin.key(),
+            period_index: closed.index,
+            period_volume: closed.volume,
+            period_fees: closed.fees,
+            started_at: closed.started_at,
             ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    /// Update the pool admin with cooldown protection
+    /// Configure the insurance fund and its share of trading fees
     ///
-    /// This function allows changing the pool admin with the following protections:
-    /// - 24-hour cooldown between admin changes
-    /// - New admin must be different from current and emergency admin
-    /// - Current admin must be a signer
-    pub fn update_admin(ctx: Context<contexts::AdminAction>, new_admin: Pubkey) -> Result<()> {
+    /// This function allows the admin to route a portion of each trade fee to an
+    /// insurance fund. It changes only how collected fees are split, not what
+    /// traders pay, so it takes effect immediately.
+    /// - Validates: admin, share within 0-10000 bps, insurance fund token mint
+    /// - Updates: insurance fund account and fee share
+    pub fn configure_insurance_fund(
+        ctx: Context<contexts::ConfigureInsuranceFund>,
+        insurance_fee_share_bps: u64,
+    ) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+        let current_time = current_unix_ts()?;
 
-        // Validate admin and check cooldown
+        // Validate admin
         validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
 
-        // Validate new admin
+        // Validate share and fund account
         validate_condition!(
-            new_admin != state.admin && new_admin != state.emergency_admin,
-            crate::ErrorCode::InvalidNewAdmin
+            insurance_fee_share_bps <= 10000,
+            crate::ErrorCode::InvalidFeeSettings,
+            "Insurance fee share {} bps exceeds 10000",
+            insurance_fee_share_bps
         );
+        state.check_token_account(&ctx.accounts.insurance_fund, &state.token_mint)?;
 
-        // Update admin
-        let old_admin = state.admin;
-        state.admin = new_admin;
-        state.last_admin_update = current_time;
+        // Update pool state
+        state.insurance_fund = ctx.accounts.insurance_fund.key();
+        state.insurance_fee_share_bps = insurance_fee_share_bps;
         state.last_update = current_time;
 
-        // Emit event
-        emit!(AdminUpdated {
+        emit!(InsuranceFundConfigured {
             pool: state.key(),
-            old_admin_pubkey: old_admin,
-            new_admin_pubkey: new_admin,
+            admin_pubkey: ctx.accounts.admin.key(),
+            insurance_fund: state.insurance_fund,
+            insurance_fee_share_bps,
             ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    /// Reset the pending update
-    pub fn reset_pending_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
-        let state = &mut ctx.accounts.pool_state;
-        let current_time = Clock::get()?.unix_timestamp as u64;
+    /// Withdraw accrued insurance fees to the configured insurance fund
+    ///
+    /// This is separate from `withdraw_fees`: it only moves `insurance_collected`,
+    /// and only to the insurance fund account recorded on the pool.
+    /// - Validates: admin, paused operations, insurance fund account, accrued balance
+    /// - Transfers: accrued insurance fees from pool to insurance fund
+    pub fn withdraw_insurance_fund(ctx: Context<contexts::WithdrawInsuranceFund>) -> Result<()> {
+        let current_time = current_unix_ts()?;
 
-        // Validate admin and check cooldown
-        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        // Validate admin
+        validation::validate_admin_action(&ctx.accounts.pool_state, &ctx.accounts.admin.key(), current_time)?;
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_WITHDRAW)?;
 
-        // Validate pending update exists
-        validate_condition!(state.pending_update.is_some(), crate::ErrorCode::NoPendingUpdate);
+        // Validate token accounts
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
 
-        // Reset pending update
-        state.pending_update = None;
-        state.last_update = current_time;
+        // Validate insurance funds available
+        let amount = ctx.accounts.pool_state.insurance_collected;
+        validate_condition!(amount > 0, crate::ErrorCode::NoFeesAvailable);
 
-        // Emit event
-        emit!(ParameterUpdateCancelled {
-            pool: state.key(),
-            admin_pubkey: state.admin,
-            scheduled_time: current_time as i64,
-            trade_settings: state.pending_update.as_ref().and_then(|u| u.trade_settings.clone()),
-            protection_settings: state.pending_update.as_ref().and_then(|u| u.protection_settings.clone()),
-            fee_settings: state.pending_update.as_ref().and_then(|u| u.fee_settings.clone()),
-            state_settings: state.pending_update.as_ref().and_then(|u| u.state_settings.clone()),
+        // Validate the withdrawal cannot touch liquidity or treasury fees
+        let fee_reserve = ctx.accounts.pool_state.fee_reserve(ctx.accounts.pool_token_account.amount);
+        validate_condition!(
+            amount <= fee_reserve.saturating_sub(ctx.accounts.pool_state.total_fees_collected),
+            crate::ErrorCode::InsufficientFeeReserves,
+            "Insurance {} exceeds reserve {} net of treasury fees {}",
+            amount,
+            fee_reserve,
+            ctx.accounts.pool_state.total_fees_collected
+        );
+
+        // Update pool state before the transfer
+        ctx.accounts.pool_state.insurance_collected = 0;
+        ctx.accounts.pool_state.last_update = current_time;
+
+        // Transfer insurance fees from pool to insurance fund
+        let cpi_ctx = with_pool_signer(
+            ctx.program_id,
+            &ctx.accounts.pool_state,
+            &[
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.pool_token_account.to_account_info(),
+                ctx.accounts.token_mint.to_account_info(),
+                ctx.accounts.insurance_fund.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+            ],
+        )?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        let state = &mut ctx.accounts.pool_state;
+        emit!(InsuranceFundWithdrawn {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            insurance_fund: state.insurance_fund,
+            amount,
+            event_seq: state.next_event_seq(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the share of each trade fee credited to the trade's referrer
+    ///
+    /// Like the insurance share, this only changes how collected fees are split,
+    /// not what traders pay, so it takes effect immediately. A zero share turns
+    /// referral accrual off.
+    /// - Validates: admin, share within 0..=MAX_REFERRAL_SHARE_BPS
+    /// - Updates: referral share
+    pub fn set_referral_share_bps(ctx: Context<contexts::AdminAction>, referral_share_bps: u64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate share
+        validate_condition!(
+            referral_share_bps <= MAX_REFERRAL_SHARE_BPS,
+            crate::ErrorCode::InvalidReferralShare,
+            "Referral share {} bps exceeds {}",
+            referral_share_bps,
+            MAX_REFERRAL_SHARE_BPS
+        );
+
+        // Update pool state
+        let old_share_bps = state.referral_share_bps;
+        state.referral_share_bps = referral_share_bps;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(ReferralShareUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_share_bps,
+            new_share_bps: referral_share_bps,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Register the signer as a referrer on the pool
+    ///
+    /// Creates the referrer's `referral_state` PDA, which trades naming them as
+    /// referrer accrue into.
+    /// - Validates: referrer is not the default pubkey
+    /// - Creates: referral state for the pool and referrer
+    pub fn register_referrer(ctx: Context<contexts::RegisterReferrer>) -> Result<()> {
+        let current_time = current_unix_ts()?;
+        let referrer = ctx.accounts.referrer.key();
+
+        let referral_state = &mut ctx.accounts.referral_state;
+        referral_state.pool = ctx.accounts.pool_state.key();
+        referral_state.referrer = referrer;
+        referral_state.bump = ctx.bumps.referral_state;
+
+        emit!(ReferrerRegistered {
+            pool: referral_state.pool,
+            referrer,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Claim referral fees accrued to the signer
+    ///
+    /// Referral fees stay in the pool token account until claimed, and are
+    /// excluded from liquidity and from the treasury's fee reserve meanwhile.
+    /// - Validates: paused operations, referrer, token accounts, accrued balance
+    /// - Transfers: accrued referral fees from pool to referrer
+    pub fn claim_referral_fees(ctx: Context<contexts::ClaimReferralFees>) -> Result<()> {
+        let current_time = current_unix_ts()?;
+
+        // Validate withdrawals are not paused
+        validation::validate_operation_not_paused(&ctx.accounts.pool_state, PAUSE_OP_WITHDRAW)?;
+
+        // Validate token accounts
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.referrer_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+
+        // Validate referral fees available
+        let amount = ctx.accounts.referral_state.accrued;
+        validate_condition!(amount > 0, crate::ErrorCode::NoFeesAvailable);
+
+        // Validate the claim cannot touch liquidity, treasury or insurance fees
+        let state = &ctx.accounts.pool_state;
+        let referral_reserve = ctx.accounts.pool_token_account.amount
+            .saturating_sub(state.total_liquidity)
+            .saturating_sub(state.total_fees_collected)
+            .saturating_sub(state.insurance_collected)
+            .saturating_sub(state.protocol_fees);
+        validate_condition!(
+            amount <= referral_reserve,
+            crate::ErrorCode::InsufficientFeeReserves,
+            "Referral fees {} exceed reserve {}",
+            amount,
+            referral_reserve
+        );
+
+        // Update referral and pool state before the transfer
+        let referral_state = &mut ctx.accounts.referral_state;
+        referral_state.accrued = 0;
+        referral_state.total_claimed = referral_state.total_claimed.saturating_add(amount);
+
+        let state = &mut ctx.accounts.pool_state;
+        state.referral_fees_owed = state.referral_fees_owed.saturating_sub(amount);
+        state.last_update = current_time;
+
+        // Transfer referral fees from pool to referrer
+        let cpi_ctx = with_pool_signer(
+            ctx.program_id,
+            &ctx.accounts.pool_state,
+            &[
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.pool_token_account.to_account_info(),
+                ctx.accounts.token_mint.to_account_info(),
+                ctx.accounts.referrer_token_account.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+            ],
+        )?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        // Emit event
+        let state = &mut ctx.accounts.pool_state;
+        emit!(ReferralFeesClaimed {
+            pool: state.key(),
+            referrer: ctx.accounts.referrer.key(),
+            amount,
+            event_seq: state.next_event_seq(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lock fee tiers to prevent further changes
+    ///
+    /// This function allows the admin to lock fee tiers with:
+    /// - Admin must be a signer
+    /// - Fee tiers must not be already locked
+    pub fn lock_fee_tiers(ctx: Context<contexts::LockFeeTiers>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate admin and check cooldown
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate fee tiers not already locked
+        validate_condition!(!state.fee_tiers_locked, crate::ErrorCode::FeeTiersLocked);
+
+        // Update pool state
+        state.fee_tiers_locked = true;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FeeTiersLocked {
+            pool: state.key(),
+            admin_pubkey: state.admin,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule unlocking the fee tiers
+    ///
+    /// The tiers stay locked until `apply_fee_tiers_unlock` runs after
+    /// PARAMETER_UPDATE_TIMELOCK, so a lock cannot be lifted for an instant fee change.
+    /// - Admin must be a signer
+    /// - Fee tiers must be currently locked, with no unlock already scheduled
+    /// - Updates: scheduled unlock time
+    pub fn unlock_fee_tiers(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate admin and check cooldown
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Schedule the unlock
+        let scheduled_time = state.schedule_fee_tiers_unlock(current_time)?;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FeeTiersUnlockScheduled {
+            pool: state.key(),
+            admin_pubkey: state.admin,
+            scheduled_time: scheduled_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a scheduled fee tier unlock
+    ///
+    /// - Validates: admin, an unlock is scheduled and its timelock has expired
+    /// - Updates: fee tier lock, scheduled unlock time
+    pub fn apply_fee_tiers_unlock(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Update pool state
+        state.apply_fee_tiers_unlock(current_time)?;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FeeTiersUnlocked {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule a parameter update with a per-category timelock
+    ///
+    /// This function allows the admin to schedule changes to pool parameters.
+    /// Each provided settings group becomes one entry of a batched update.
+    /// - Validates: admin, new fee tiers, parameter relationships
+    /// - Stores: pending update with scheduled execution time
+    pub fn schedule_parameter_update(
+        ctx: Context<contexts::AdminAction>,
+        trade_settings: Option<TradeSettingsUpdate>,
+        protection_settings: Option<ProtectionSettingsUpdate>,
+        fee_settings: Option<FeeSettingsUpdate>,
+        state_settings: Option<StateSettingsUpdate>,
+    ) -> Result<()> {
+        let updates: Vec<ParameterUpdate> = [
+            trade_settings.map(ParameterUpdate::Trade),
+            protection_settings.map(ParameterUpdate::Protection),
+            fee_settings.map(ParameterUpdate::Fee),
+            state_settings.map(ParameterUpdate::State),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
+        schedule_parameter_updates(ctx, updates)
+    }
+
+    /// Schedule a batch of parameter updates with a per-category timelock
+    ///
+    /// This function allows the admin to schedule several parameter changes that
+    /// are applied together, so the pool never lands in a half-updated state.
+    /// The batch waits for the longest timelock of the categories it contains.
+    /// - Validates: admin, batch size, every update in the batch
+    /// - Stores: pending update with scheduled execution time
+    pub fn schedule_parameter_updates(
+        ctx: Context<contexts::AdminAction>,
+        updates: Vec<ParameterUpdate>,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate admin and check cooldown
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate the batch before anything is stored
+        let timelock = state.validate_update_batch(&updates)?;
+
+        // Create pending update, timelocked by the slowest category in the batch
+        let scheduled_time = utils::scheduled_time_after(current_time, timelock)?;
+        let update_count = updates.len() as u32;
+        state.pending_update = Some(PendingUpdate {
+            scheduled_time,
+            updates,
+        });
+
+        emit!(ParameterUpdateScheduled {
+            pool: state.key(),
+            admin_pubkey: state.admin,
+            scheduled_time: scheduled_time as i64,
+            update_count,
+        });
+
+        Ok(())
+    }
+
+    /// Replace the pending parameter update
+    ///
+    /// Equivalent to cancelling and scheduling again, except that a batch no
+    /// looser than the one it replaces keeps the original scheduled time: the
+    /// waiting period already covered anything it can do. See
+    /// `PendingUpdate::keeps_schedule_for` for what counts as looser. Any
+    /// other batch restarts the timelock from now.
+    /// - Validates: admin, presence of pending update, batch size, every update in the batch
+    /// - Stores: replacement pending update
+    pub fn reschedule_parameter_update(
+        ctx: Context<contexts::AdminAction>,
+        updates: Vec<ParameterUpdate>,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate there is something to replace
+        let pending_update = state.pending_update.as_ref().ok_or_else(|| {
+            error!(crate::ErrorCode::NoPendingUpdate, "No pending update available")
+        })?;
+        let old_scheduled_time = pending_update.scheduled_time;
+        let schedule_kept = pending_update.keeps_schedule_for(&updates);
+
+        // Validate the batch before anything is stored
+        let timelock = state.validate_update_batch(&updates)?;
+
+        // Replace pending update, restarting the clock unless it only tightens
+        let scheduled_time = if schedule_kept {
+            old_scheduled_time
+        } else {
+            utils::scheduled_time_after(current_time, timelock)?
+        };
+        let update_count = updates.len() as u32;
+        state.pending_update = Some(PendingUpdate {
+            scheduled_time,
+            updates,
+        });
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(ParameterUpdateRescheduled {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_scheduled_time: old_scheduled_time as i64,
+            scheduled_time: scheduled_time as i64,
+            update_count,
+            schedule_kept,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Restart the pending update's timelock from now
+    ///
+    /// For a schedule stuck far in the future, e.g. by a clock anomaly when it
+    /// was set. Unlike cancelling, the batch stays pending and becomes
+    /// applicable once its category timelock has elapsed from now. A schedule
+    /// already due sooner is left as it is.
+    /// - Validates: admin, presence of pending update
+    /// - Updates: pending update scheduled time
+    pub fn force_expire_pending_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate there is something to re-anchor
+        let pending_update = state.pending_update.as_ref().ok_or_else(|| {
+            error!(crate::ErrorCode::NoPendingUpdate, "No pending update available")
+        })?;
+        let old_scheduled_time = pending_update.scheduled_time;
+        let timelock = state.update_batch_timelock(&pending_update.updates);
+        let scheduled_time = pending_update.re_anchored_time(current_time, timelock);
+
+        // Update pool state
+        if let Some(pending_update) = state.pending_update.as_mut() {
+            pending_update.scheduled_time = scheduled_time;
+        }
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(PendingUpdateForceExpired {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_scheduled_time: old_scheduled_time as i64,
+            scheduled_time: scheduled_time as i64,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a scheduled parameter update
+    ///
+    /// This function allows the admin to cancel a pending parameter update before the timelock expires.
+    /// - Validates: admin, presence of pending update
+    /// - Clears: pending update
+    pub fn cancel_parameter_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Take the pending update
+        let pending_update = state.pending_update.take().ok_or_else(|| {
+            error!(crate::ErrorCode::NoPendingUpdate, "No pending update available")
+        })?;
+
+        // Emit detailed cancellation event
+        emit!(ParameterUpdateCancelled {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            ts: current_time as i64,
+            updates: pending_update.updates,
+        });
+
+        state.last_update = current_time;
+        Ok(())
+    }
+
+    /// Apply a scheduled parameter update
+    ///
+    /// This function applies every update in the pending batch after the timelock expires.
+    /// - Validates: admin, timelock, parameter relationships
+    /// - Updates: pool state with new parameters, last parameter update record
+    pub fn apply_parameter_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin and timelock
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        let pending_update = state.pending_update.take().ok_or_else(|| {
+            error!(crate::ErrorCode::NoPendingUpdate, "No pending update available")
+        })?;
+        validate_condition!(
+            current_time >= pending_update.scheduled_time,
+            crate::ErrorCode::TimelockNotExpired,
+            "Timelock not yet expired"
+        );
+
+        // Apply updates and emit events
+        let admin = ctx.accounts.admin.key();
+        for update in &pending_update.updates {
+            state.apply_update(update, &admin, current_time)?;
+        }
+
+        state.record_parameter_update(&admin, current_time);
+        state.last_update = current_time;
+
+        emit!(ParametersUpdated {
+            pool: state.key(),
+            admin_pubkey: admin,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule an emergency pause behind the pool's pause timelock
+    ///
+    /// This function allows the emergency admin to schedule a pool pause.
+    /// - Validates: emergency admin
+    /// - Sets: scheduled pause time
+    pub fn schedule_emergency_pause(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate emergency admin
+        validate_condition!(
+            ctx.accounts.emergency_admin.key() == state.emergency_admin,
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+
+        // Validate not already paused
+        validate_condition!(!state.is_emergency_paused, crate::ErrorCode::EmergencyPaused);
+
+        // Schedule emergency pause
+        let scheduled_time = state.schedule_emergency_pause(current_time)?;
+
+        // Emit event
+        emit!(EmergencyPauseScheduled {
+            pool: state.key(),
+            emergency_admin_pubkey: state.emergency_admin,
+            scheduled_time: scheduled_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Apply a scheduled emergency pause
+    ///
+    /// This function applies a scheduled pause after the timelock expires.
+    /// - Validates: emergency admin, timelock
+    /// - Sets: pool to emergency paused state
+    pub fn apply_emergency_pause(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate emergency admin
+        validate_condition!(
+            ctx.accounts.emergency_admin.key() == state.emergency_admin,
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+
+        // Validate a pause is scheduled and its timelock has expired
+        state.take_pending_emergency(EmergencyActionKind::Pause, current_time)?;
+
+        // Apply emergency pause
+        state.is_emergency_paused = true;
+        state.emergency_paused_at = current_time;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(EmergencyPaused {
+            pool: state.key(),
+            emergency_admin_pubkey: state.emergency_admin,
+            ts: current_time as i64,
+        });
+        
+        Ok(())
+    }
+
+    /// Schedule an emergency resume behind the pool's resume timelock
+    ///
+    /// This function allows the emergency admin to schedule a pool resume.
+    /// - Validates: emergency admin
+    /// - Sets: scheduled resume time
+    pub fn schedule_emergency_resume(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate emergency admin
+        validate_condition!(
+            ctx.accounts.emergency_admin.key() == state.emergency_admin,
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+        
+        // Validate is paused
+        validate_condition!(state.is_emergency_paused, crate::ErrorCode::PoolNotPaused);
+
+        // Schedule emergency resume
+        let scheduled_time = state.schedule_emergency_resume(current_time)?;
+
+        // Emit event
+        emit!(EmergencyResumeScheduled {
+            pool: state.key(),
+            emergency_admin_pubkey: state.emergency_admin,
+            scheduled_time: scheduled_time as i64,
+        });
+        
+        Ok(())
+    }
+
+    /// Apply a scheduled emergency resume
+    ///
+    /// This function applies a scheduled resume after the timelock expires.
+    /// - Validates: emergency admin, timelock
+    /// - Sets: pool to non-emergency paused state
+    pub fn apply_emergency_resume(ctx: Context<contexts::EmergencyAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate emergency admin
+        validate_condition!(
+            ctx.accounts.emergency_admin.key() == state.emergency_admin,
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+        
+        // Validate a resume is scheduled and its timelock has expired
+        state.take_pending_emergency(EmergencyActionKind::Resume, current_time)?;
+
+        // Apply emergency resume, starting the post-resume cooldown
+        state.is_emergency_paused = false;
+        state.resumed_at = current_time as i64;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(EmergencyResumed {
+            pool: state.key(),
+            emergency_admin_pubkey: state.emergency_admin,
+            ts: current_time as i64,
+        });
+        
+        Ok(())
+    }
+
+    /// Designate the token account `emergency_withdraw` sends reserves to
+    ///
+    /// The admin picks the destination and the emergency admin can only send
+    /// reserves there. It cannot change while the pool is emergency paused, so
+    /// the destination is fixed before a rescue can start.
+    /// - Validates: admin, pool not emergency paused, recovery account token mint
+    /// - Updates: recovery account
+    pub fn set_recovery_account(ctx: Context<contexts::SetRecoveryAccount>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate pool state and recovery account
+        validate_condition!(
+            !state.is_emergency_paused,
+            crate::ErrorCode::EmergencyPaused,
+            "The recovery account cannot change while the pool is emergency paused"
+        );
+        state.check_token_account(&ctx.accounts.recovery_account, &state.token_mint)?;
+
+        // Update pool state
+        let old_recovery_account = state.recovery_account;
+        state.recovery_account = ctx.accounts.recovery_account.key();
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(RecoveryAccountSet {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_recovery_account,
+            new_recovery_account: state.recovery_account,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw all pool reserves to the recovery account while emergency paused
+    ///
+    /// This function lets the emergency admin rescue funds from a frozen pool.
+    /// Unclaimed referral fees stay behind, as referral accounts remain
+    /// claimable against this pool.
+    /// - Validates: emergency admin, pool is emergency paused, timelock since the pause,
+    ///   destination is the designated recovery account
+    /// - Transfers: the pool token balance less referral fees owed to the recovery token account
+    /// - Updates: zeroes tracked liquidity and fees, clears liquidity locks
+    pub fn emergency_withdraw(ctx: Context<contexts::EmergencyWithdraw>) -> Result<()> {
+        let current_time = current_unix_ts()?;
+
+        // Validate emergency admin
+        validate_condition!(
+            ctx.accounts.emergency_admin.key() == ctx.accounts.pool_state.emergency_admin,
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+
+        // Validate pool is emergency paused
+        validate_condition!(ctx.accounts.pool_state.is_emergency_paused, crate::ErrorCode::PoolNotPaused);
+
+        // Validate timelock has expired since the pause took effect
+        let unlock_time = ctx.accounts.pool_state.emergency_paused_at
+            .checked_add(EMERGENCY_TIMELOCK_SECONDS)
+            .ok_or(crate::ErrorCode::Overflow)?;
+        validate_condition!(
+            current_time >= unlock_time,
+            crate::ErrorCode::TimelockNotExpired,
+            "Emergency withdraw available at {}",
+            unlock_time
+        );
+
+        // Validate token accounts
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.pool_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+        ctx.accounts.pool_state.check_token_account(
+            &ctx.accounts.recovery_token_account,
+            &ctx.accounts.pool_state.token_mint,
+        )?;
+
+        let amount = ctx.accounts.pool_state.rescuable_amount(ctx.accounts.pool_token_account.amount);
+        validate_condition!(amount > 0, crate::ErrorCode::InvalidAmount);
+
+        // Update pool state before the transfer
+        let state = &mut ctx.accounts.pool_state;
+        state.clear_rescued_balances();
+        state.last_update = current_time;
+
+        // Transfer reserves from pool to recovery account
+        let cpi_ctx = with_pool_signer(
+            ctx.program_id,
+            &ctx.accounts.pool_state,
+            &[
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.pool_token_account.to_account_info(),
+                ctx.accounts.token_mint.to_account_info(),
+                ctx.accounts.recovery_token_account.to_account_info(),
+                ctx.accounts.pool_authority.to_account_info(),
+            ],
+        )?;
+        token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+
+        let state = &mut ctx.accounts.pool_state;
+        emit!(EmergencyWithdraw {
+            pool: state.key(),
+            emergency_admin_pubkey: ctx.accounts.emergency_admin.key(),
+            recovery_account: ctx.accounts.recovery_token_account.key(),
+            amount,
+            event_seq: state.next_event_seq(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Reset the circuit breaker
+    ///
+    /// This function allows the admin to reset the circuit breaker after its cooldown.
+    /// - Validates: admin, timestamp, cooldown
+    /// - Resets: circuit breaker accumulator
+    /// - Resumes: the pool if it was auto-paused by the breaker
+    pub fn reset_circuit_breaker(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate admin and check cooldown
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate cooldown has expired
+        validate_condition!(
+            current_time >= state.circuit_breaker.last_trigger + state.circuit_breaker.cooldown,
+            crate::ErrorCode::CircuitBreakerCooldown
+        );
+
+        // Reset circuit breaker
+        state.circuit_breaker.last_trigger = 0;
+        state.circuit_breaker.current_amount = 0;
+        state.circuit_breaker.last_decay = current_time;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(CircuitBreakerReset {
+            pool: state.key(),
+            admin_pubkey: state.admin,
+            ts: current_time as i64,
+        });
+
+        // Clear a pause that the breaker itself engaged
+        if state.circuit_breaker.paused_by_breaker {
+            state.circuit_breaker.paused_by_breaker = false;
+            state.pause_level = PauseLevel::None;
+
+            emit!(PoolResumed {
+                pool: state.key(),
+                admin_pubkey: state.admin,
+                ts: current_time as i64,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Set the utilization at which the circuit breaker warns it is approaching
+    ///
+    /// Crossing the threshold emits `CircuitBreakerApproaching`; the breaker
+    /// still trips only past `max_amount`. Zero restores the default.
+    /// - Validates: admin, threshold within 10000 bps
+    /// - Updates: circuit breaker warning threshold
+    pub fn set_circuit_breaker_warn_threshold(
+        ctx: Context<contexts::AdminAction>,
+        warn_threshold_bps: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin and threshold
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        validate_condition!(
+            warn_threshold_bps <= 10000,
+            crate::ErrorCode::InvalidCircuitBreakerSettings,
+            "Circuit breaker warning threshold {} bps exceeds 10000",
+            warn_threshold_bps
+        );
+
+        // Update pool state
+        let old_warn_threshold_bps = state.circuit_breaker.warn_threshold_bps;
+        state.circuit_breaker.warn_threshold_bps = warn_threshold_bps;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(CircuitBreakerWarnThresholdUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_warn_threshold_bps,
+            new_warn_threshold_bps: warn_threshold_bps,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Update the pool admin with cooldown protection
+    ///
+    /// This function allows changing the pool admin with the following protections:
+    /// - 24-hour cooldown between admin changes
+    /// - New admin must be different from current and emergency admin
+    /// - Current admin must be a signer
+    pub fn update_admin(ctx: Context<contexts::AdminAction>, new_admin: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate admin and check cooldown
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new admin
+        validate_condition!(
+            new_admin != state.admin && new_admin != state.emergency_admin,
+            crate::ErrorCode::InvalidNewAdmin
+        );
+
+        // Update admin
+        let old_admin = state.admin;
+        state.admin = new_admin;
+        state.last_admin_update = current_time;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(AdminUpdated {
+            pool: state.key(),
+            old_admin_pubkey: old_admin,
+            new_admin_pubkey: new_admin,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Rotate the emergency admin key
+    ///
+    /// This function allows replacing a lost or compromised emergency admin.
+    /// It is deliberately not timelocked: rotation is most needed during an incident.
+    /// - Validates: admin, new emergency admin is not the default pubkey
+    /// - Updates: emergency admin and admin update timestamps
+    pub fn set_emergency_admin(ctx: Context<contexts::AdminAction>, new_emergency_admin: Pubkey) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new emergency admin
+        validate_condition!(
+            new_emergency_admin != Pubkey::default(),
+            crate::ErrorCode::InvalidEmergencyAdmin
+        );
+
+        // Update emergency admin
+        let old_emergency_admin = state.emergency_admin;
+        state.emergency_admin = new_emergency_admin;
+        state.last_admin_update = current_time;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(EmergencyAdminUpdated {
+            pool: state.key(),
+            old_emergency_admin_pubkey: old_emergency_admin,
+            new_emergency_admin_pubkey: new_emergency_admin,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the per-pool maximum number of fee tiers
+    ///
+    /// Fee tiers are evaluated on every trade, so pools default to a small cap.
+    /// This only bounds future fee tier updates and takes effect immediately.
+    /// - Validates: admin, cap within 1..=MAX_FEE_TIERS and not below the current tier count
+    /// - Updates: max fee tiers
+    pub fn set_max_fee_tiers(ctx: Context<contexts::AdminAction>, max_fee_tiers: u16) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cap
+        validate_condition!(
+            max_fee_tiers >= 1
+                && max_fee_tiers as usize <= MAX_FEE_TIERS
+                && max_fee_tiers as usize >= state.fee_tiers.len(),
+            crate::ErrorCode::InvalidMaxFeeTiers,
+            "Invalid max fee tiers: {} (current tiers: {}, hard limit: {})",
+            max_fee_tiers,
+            state.fee_tiers.len(),
+            MAX_FEE_TIERS
+        );
+
+        // Update pool state
+        let old_max_fee_tiers = state.max_fee_tiers;
+        state.max_fee_tiers = max_fee_tiers;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxFeeTiersUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_fee_tiers,
+            new_max_fee_tiers: max_fee_tiers,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Configure the volatility-adaptive price impact cap
+    ///
+    /// When enabled, the price impact cap slides from `max_price_impact_bps`
+    /// down to `floor_impact_bps` as the average price move between trades
+    /// approaches `max_volatility_bps`. Recorded volatility is kept. Takes
+    /// effect immediately, so once enabled it only narrows the cap: turning it
+    /// off, raising the floor or raising the volatility scale goes through a
+    /// timelocked `ParameterUpdate::Protection`.
+    /// - Validates: admin, thresholds (see `PoolState::validate_volatility_settings`),
+    ///   settings no looser than the current ones
+    /// - Updates: volatility settings
+    pub fn set_volatility_settings(
+        ctx: Context<contexts::AdminAction>,
+        enabled: bool,
+        floor_impact_bps: u64,
+        max_volatility_bps: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate settings and that they only tighten the cap
+        let volatility = VolatilitySettingsUpdate { enabled, floor_impact_bps, max_volatility_bps };
+        PoolState::validate_volatility_settings(&volatility, state.protection.max_price_impact_bps)?;
+        validate_condition!(
+            volatility.is_no_looser_than(&state.volatility.settings()),
+            crate::ErrorCode::Unauthorized,
+            "Volatility settings loosen the price impact cap; apply them through the timelocked path"
+        );
+
+        // Update pool state
+        state.volatility.configure(&volatility);
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(VolatilitySettingsUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            enabled,
+            floor_impact_bps,
+            max_volatility_bps,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the governance token and the fee discounts its holders get
+    ///
+    /// Traders pass their governance token account to `execute_trade`, and the
+    /// highest holding tier their balance reaches discounts the LP fee. Takes
+    /// effect immediately since it can only lower fees. A `None` mint turns
+    /// discounts off and clears the schedule.
+    /// - Validates: admin, tier count, increasing balances, discounts within 1..=MAX_GOVERNANCE_DISCOUNT_BPS
+    /// - Updates: governance mint, discount tiers
+    pub fn set_governance_discount(
+        ctx: Context<contexts::AdminAction>,
+        governance_mint: Option<Pubkey>,
+        tiers: Vec<GovernanceDiscountTier>,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate discount schedule
+        validate_condition!(
+            governance_mint != Some(Pubkey::default()),
+            crate::ErrorCode::InvalidGovernanceDiscount,
+            "Governance mint cannot be the default pubkey"
+        );
+        PoolState::validate_governance_discount_tiers(&tiers)?;
+
+        // Update pool state
+        state.governance_mint = governance_mint;
+        state.governance_discount_tiers = if governance_mint.is_some() { tiers } else { Vec::new() };
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(GovernanceDiscountUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            governance_mint: state.governance_mint,
+            tiers: state.governance_discount_tiers.clone(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Schedule the launch that starts the early-trade phase
+    ///
+    /// Trades before `launch_ts + early_trade_window_seconds` pay the
+    /// early-trade fee. The fee and the window both stay with the timelocked
+    /// trade settings. Trades before the launch count as early too, so the
+    /// launch may be at most `MAX_PARAMETER_UPDATE_TIMELOCK` out. Only allowed
+    /// before the pool's first trade, so traders never see the phase move under them.
+    /// - Validates: admin, no trades yet, launch between now and now + MAX_PARAMETER_UPDATE_TIMELOCK
+    /// - Updates: launch time
+    pub fn schedule_launch(ctx: Context<contexts::AdminAction>, launch_ts: i64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate schedule
+        validate_condition!(state.trade_settings.last_trade_time == 0, crate::ErrorCode::LaunchAlreadyStarted);
+        validate_condition!(
+            launch_ts >= current_time as i64,
+            crate::ErrorCode::InvalidLaunchSchedule,
+            "Launch {} is in the past (now: {})",
+            launch_ts,
+            current_time
+        );
+        validate_condition!(
+            launch_ts <= current_time.saturating_add(MAX_PARAMETER_UPDATE_TIMELOCK) as i64,
+            crate::ErrorCode::InvalidLaunchSchedule,
+            "Launch {} is more than {}s out (now: {})",
+            launch_ts,
+            MAX_PARAMETER_UPDATE_TIMELOCK,
+            current_time
+        );
+
+        // Update pool state
+        state.launch_ts = launch_ts;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(LaunchScheduled {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            launch_ts,
+            early_trade_duration: state.trade_settings.early_trade_window_seconds,
+            early_trade_fee_bps: state.trade_settings.early_trade_fee_bps,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set how long trading stays blocked after an emergency resume
+    ///
+    /// Gives operators time to check the pool before traders return. Applies
+    /// to the next resume, and to one still cooling down. Takes effect
+    /// immediately, so it only lengthens the cooldown; shortening or disabling
+    /// it goes through a timelocked `ParameterUpdate::Protection`, which keeps
+    /// a resume from skipping its cooldown.
+    /// - Validates: admin, cooldown within 0..=MAX_POST_RESUME_COOLDOWN_SECONDS and no shorter than the current one
+    /// - Updates: post-resume cooldown
+    pub fn set_post_resume_cooldown(ctx: Context<contexts::AdminAction>, post_resume_cooldown: i64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cooldown
+        validate_condition!(
+            (0..=MAX_POST_RESUME_COOLDOWN_SECONDS).contains(&post_resume_cooldown),
+            crate::ErrorCode::InvalidPostResumeCooldown,
+            "Invalid post-resume cooldown: {} (max: {})",
+            post_resume_cooldown,
+            MAX_POST_RESUME_COOLDOWN_SECONDS
+        );
+        let old_cooldown = state.post_resume_cooldown;
+        validate_condition!(
+            post_resume_cooldown >= old_cooldown,
+            crate::ErrorCode::Unauthorized,
+            "New post-resume cooldown {} is shorter than {}; shorten it through the timelocked path",
+            post_resume_cooldown,
+            old_cooldown
+        );
+
+        // Update pool state
+        state.post_resume_cooldown = post_resume_cooldown;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(PostResumeCooldownUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_cooldown,
+            new_cooldown: post_resume_cooldown,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the minimum time between fee withdrawals
+    ///
+    /// Slows a compromised admin key draining accrued fees: `withdraw_fees` and
+    /// `distribute_fees` share one cooldown. Zero disables it. Takes effect
+    /// immediately, so it only lengthens the cooldown; shortening or disabling
+    /// it goes through a timelocked `ParameterUpdate::Protection`.
+    /// - Validates: admin, cooldown within 0..=MAX_FEE_WITHDRAW_COOLDOWN_SECONDS and no shorter than the current one
+    /// - Updates: fee withdrawal cooldown
+    pub fn set_fee_withdraw_cooldown(ctx: Context<contexts::AdminAction>, fee_withdraw_cooldown: i64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cooldown
+        validate_condition!(
+            (0..=MAX_FEE_WITHDRAW_COOLDOWN_SECONDS).contains(&fee_withdraw_cooldown),
+            crate::ErrorCode::InvalidFeeWithdrawCooldown,
+            "Invalid fee withdrawal cooldown: {} (max: {})",
+            fee_withdraw_cooldown,
+            MAX_FEE_WITHDRAW_COOLDOWN_SECONDS
+        );
+        let old_cooldown = state.fee_withdraw_cooldown;
+        validate_condition!(
+            fee_withdraw_cooldown >= old_cooldown,
+            crate::ErrorCode::Unauthorized,
+            "New fee withdrawal cooldown {} is shorter than {}; shorten it through the timelocked path",
+            fee_withdraw_cooldown,
+            old_cooldown
+        );
+
+        // Update pool state
+        state.fee_withdraw_cooldown = fee_withdraw_cooldown;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FeeWithdrawCooldownUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_cooldown,
+            new_cooldown: fee_withdraw_cooldown,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the per-pool maximum blacklist batch size
+    ///
+    /// Larger batches onboard long lists in fewer calls at the cost of compute
+    /// per call. Takes effect immediately; it only bounds future batches.
+    /// - Validates: admin, cap within 1..=MAX_BLACKLIST_BATCH
+    /// - Updates: max blacklist batch
+    pub fn set_max_blacklist_batch(ctx: Context<contexts::AdminAction>, max_blacklist_batch: u16) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cap
+        validate_condition!(
+            max_blacklist_batch >= 1 && max_blacklist_batch <= MAX_BLACKLIST_BATCH,
+            crate::ErrorCode::InvalidMaxBlacklistBatch,
+            "Invalid max blacklist batch: {} (hard limit: {})",
+            max_blacklist_batch,
+            MAX_BLACKLIST_BATCH
+        );
+
+        // Update pool state
+        let old_max_blacklist_batch = state.blacklist_batch_limit() as u16;
+        state.max_blacklist_batch = max_blacklist_batch;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxBlacklistBatchUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_blacklist_batch,
+            new_max_blacklist_batch: max_blacklist_batch,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the programs allowed to invoke trades on the pool via CPI
+    ///
+    /// Restricts which wrapper programs may trade against the pool, so trades
+    /// cannot be routed through unmonitored programs. Direct calls are always
+    /// allowed, and an empty list lifts the restriction. Takes effect
+    /// immediately, so it only narrows the list: it can restrict an
+    /// unrestricted pool or drop callers, while adding a caller or clearing
+    /// the list goes through a timelocked `ParameterUpdate::Protection`.
+    /// - Validates: admin, list size, no default pubkey, no caller added to a non-empty list
+    /// - Updates: allowed callers, deduplicated in input order
+    pub fn set_allowed_callers(ctx: Context<contexts::AdminAction>, allowed_callers: Vec<Pubkey>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate caller list
+        let mut seen = HashSet::with_capacity(allowed_callers.len());
+        let allowed_callers: Vec<Pubkey> = allowed_callers
+            .into_iter()
+            .filter(|caller| seen.insert(*caller))
+            .collect();
+        PoolState::validate_allowed_callers(&allowed_callers)?;
+        validate_condition!(
+            ProtectionSettingsUpdate::allowed_callers_no_looser_than(&allowed_callers, &state.allowed_callers),
+            crate::ErrorCode::Unauthorized,
+            "New allowed callers add to or clear the current list; loosen it through the timelocked path"
+        );
+
+        // Update pool state
+        state.allowed_callers = allowed_callers;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(AllowedCallersUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            allowed_callers: state.allowed_callers.clone(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the cap on volume traded within one hourly window
+    ///
+    /// Catches bursts that stay well under the 24h cap. The window starts at
+    /// the first trade after the last one lapsed and runs for an hour. Takes
+    /// effect immediately, so like `tighten_volume_limit` it only tightens;
+    /// raising the cap goes through a timelocked `ParameterUpdate::Protection`.
+    /// - Validates: admin, nonzero cap no higher than the current one
+    /// - Updates: max hourly volume
+    pub fn set_max_hourly_volume(ctx: Context<contexts::AdminAction>, max_hourly: u64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cap only tightens
+        validate_condition!(max_hourly > 0, crate::ErrorCode::InvalidMaxHourlyVolume);
+        let old_max_hourly = state.volume.hourly_limit();
+        validate_condition!(
+            max_hourly <= old_max_hourly,
+            crate::ErrorCode::Unauthorized,
+            "New hourly volume cap {} is above {}; raise it through the timelocked path",
+            max_hourly,
+            old_max_hourly
+        );
+
+        // Update pool state
+        state.volume.max_hourly = max_hourly;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxHourlyVolumeUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_hourly,
+            new_max_hourly: max_hourly,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the cap on how much one address may trade per daily window
+    ///
+    /// Curbs a single trader concentrating volume while the pool-wide cap still
+    /// has room. Each trader's window starts at their first trade after the
+    /// last one lapsed. Zero disables it. Takes effect immediately, so it only
+    /// tightens; raising the cap or disabling it goes through a timelocked
+    /// `ParameterUpdate::Protection`.
+    /// - Validates: admin, nonzero cap no higher than the current one
+    /// - Updates: max daily volume per trader
+    pub fn set_max_daily_volume_per_trader(
+        ctx: Context<contexts::AdminAction>,
+        max_daily_volume_per_trader: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cap only tightens; zero disables the cap, so it is the loosest
+        let old_max_daily_volume_per_trader = state.protection.max_daily_volume_per_trader;
+        validate_condition!(
+            max_daily_volume_per_trader > 0
+                && (old_max_daily_volume_per_trader == 0 || max_daily_volume_per_trader <= old_max_daily_volume_per_trader),
+            crate::ErrorCode::Unauthorized,
+            "New per-trader cap {} is looser than {}; raise it through the timelocked path",
+            max_daily_volume_per_trader,
+            old_max_daily_volume_per_trader
+        );
+
+        // Update pool state
+        state.protection.max_daily_volume_per_trader = max_daily_volume_per_trader;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxDailyVolumePerTraderUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_daily_volume_per_trader,
+            new_max_daily_volume_per_trader: max_daily_volume_per_trader,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the cap on trades executed within one hourly window
+    ///
+    /// Limits how often the pool can be hit regardless of trade size. The
+    /// window starts at the first trade after the last one lapsed. Takes
+    /// effect immediately, so it only tightens; raising the cap goes through a
+    /// timelocked `ParameterUpdate::Protection`.
+    /// - Validates: admin, nonzero cap no higher than the current one
+    /// - Updates: max hourly trades
+    pub fn set_max_hourly_trades(ctx: Context<contexts::AdminAction>, max_hourly_trades: u32) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new cap only tightens
+        validate_condition!(max_hourly_trades > 0, crate::ErrorCode::InvalidMaxHourlyTrades);
+        let old_max_hourly_trades = state.hourly_trade_limit();
+        validate_condition!(
+            max_hourly_trades <= old_max_hourly_trades,
+            crate::ErrorCode::Unauthorized,
+            "New hourly trade cap {} is above {}; raise it through the timelocked path",
+            max_hourly_trades,
+            old_max_hourly_trades
+        );
+
+        // Update pool state
+        state.max_hourly_trades = max_hourly_trades;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxHourlyTradesUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_hourly_trades,
+            new_max_hourly_trades: max_hourly_trades,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the daily volume cap with immediate effect
+    ///
+    /// Raising the cap loosens protection and must go through the timelocked
+    /// update path; this only ever tightens it, for use during an incident.
+    /// - Validates: admin, new cap nonzero and below the current one
+    /// - Updates: max daily volume
+    pub fn tighten_volume_limit(ctx: Context<contexts::AdminAction>, new_max_daily: u64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate the cap only tightens
+        let old_max_daily = state.volume.max_daily;
+        validate_condition!(
+            new_max_daily < old_max_daily,
+            crate::ErrorCode::Unauthorized,
+            "New daily volume cap {} is not below {}; raise it through the timelocked path",
+            new_max_daily,
+            old_max_daily
+        );
+        validate_condition!(new_max_daily > 0, crate::ErrorCode::InvalidVolumeSettings);
+
+        // Update pool state
+        state.volume.max_daily = new_max_daily;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(VolumeLimitTightened {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_daily,
+            new_max_daily,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Apply protection settings with immediate effect if they only tighten
+    ///
+    /// The immediate counterpart to a timelocked `ParameterUpdate::Protection`:
+    /// every limit must be at or inside its live value, as in
+    /// `ProtectionSettingsUpdate::is_no_looser_than`, or it fails with `Unauthorized`.
+    /// - Validates: admin, settings no looser than the live ones, protection bounds
+    /// - Updates: protection settings, last parameter update record
+    pub fn tighten_protection_settings(
+        ctx: Context<contexts::AdminAction>,
+        settings: ProtectionSettingsUpdate,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate the settings only tighten
+        state.validate_protection_tightening(&settings)?;
+
+        // Update pool state and emit event
+        state.apply_update(&ParameterUpdate::Protection(settings), &ctx.accounts.admin.key(), current_time)?;
+        state.record_parameter_update(&ctx.accounts.admin.key(), current_time);
+        state.last_update = current_time;
+
+        Ok(())
+    }
+
+    /// Set what happens when accrued fees reach a high-water mark
+    ///
+    /// `total_fees_collected` only drops on withdrawal, so without a mark it
+    /// would eventually overflow and fail every trade. Past the mark, trades
+    /// either fail with `FeeWithdrawalRequired` or stop accruing, per `mode`.
+    /// A mark of 0 means `u64::MAX`.
+    /// - Validates: admin
+    /// - Updates: fee high-water mark and overflow mode
+    pub fn set_fee_overflow_behavior(
+        ctx: Context<contexts::AdminAction>,
+        fee_high_water_mark: u64,
+        fee_overflow_mode: FeeOverflowMode,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Update pool state
+        state.fee_high_water_mark = fee_high_water_mark;
+        state.fee_overflow_mode = fee_overflow_mode;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FeeOverflowBehaviorUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            fee_high_water_mark,
+            fee_overflow_mode,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the largest share of reserves a single trade may take out
+    ///
+    /// A direct drain guard on `amount_out`, checked on top of slippage and
+    /// price impact. 10000 bps (100%) disables it. Takes effect immediately,
+    /// so it only tightens; raising the fraction goes through a timelocked
+    /// `ParameterUpdate::Protection`.
+    /// - Validates: admin, fraction within 1..=MAX_OUTPUT_FRACTION_BPS and no higher than the current one
+    /// - Updates: max output fraction
+    pub fn set_max_output_fraction(ctx: Context<contexts::AdminAction>, max_output_fraction_bps: u64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new fraction only tightens
+        validate_parameter!(max_output_fraction_bps, 1, MAX_OUTPUT_FRACTION_BPS, crate::ErrorCode::InvalidMaxOutputFraction);
+        let old_max_output_fraction_bps = state.output_fraction_limit_bps();
+        validate_condition!(
+            max_output_fraction_bps <= old_max_output_fraction_bps,
+            crate::ErrorCode::Unauthorized,
+            "New output fraction {} bps is above {}; raise it through the timelocked path",
+            max_output_fraction_bps,
+            old_max_output_fraction_bps
+        );
+
+        // Update pool state
+        state.max_output_fraction_bps = max_output_fraction_bps;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxOutputFractionUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_output_fraction_bps,
+            new_max_output_fraction_bps: max_output_fraction_bps,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the fee recipients `distribute_fees` pays out to
+    ///
+    /// Shares must add up to exactly FEE_RECIPIENT_TOTAL_BPS. An empty list
+    /// removes every recipient, leaving `withdraw_fees` as the only payout.
+    /// - Validates: admin, recipient count, nonzero shares summing to 10000, distinct accounts
+    /// - Updates: fee recipients
+    pub fn set_fee_recipients(ctx: Context<contexts::AdminAction>, recipients: Vec<FeeRecipient>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate recipients
+        PoolState::validate_fee_recipients(&recipients)?;
+
+        // Update pool state
+        state.fee_recipients = recipients;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FeeRecipientsUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            recipients: state.fee_recipients.clone(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set price impact caps by trade size
+    ///
+    /// A trade uses the cap of the first tier whose `size_threshold` it does
+    /// not exceed, so small and large trades can be held to different limits.
+    /// A tier cap above `max_price_impact_bps` is clamped to it. Larger trades,
+    /// or all trades when the list is empty, use `max_price_impact_bps`.
+    /// Volatility narrowing applies on top.
+    /// - Validates: admin, tier count, strictly increasing thresholds, caps within 1..=10000
+    /// - Updates: price impact tiers
+    pub fn set_price_impact_tiers(ctx: Context<contexts::AdminAction>, tiers: Vec<PriceImpactTier>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate tiers
+        PoolState::validate_price_impact_tiers(&tiers)?;
+
+        // Update pool state
+        state.protection.price_impact_tiers = tiers;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(PriceImpactTiersUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            tiers: state.protection.price_impact_tiers.clone(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Require traders to own the token account they trade through
+    ///
+    /// `execute_trade` settles against `buyer_token_account`; the token program
+    /// accepts a delegate as well as the owner, so with this set a trade from
+    /// a delegated account fails instead of moving someone else's tokens.
+    /// - Validates: admin
+    /// - Updates: owner enforcement flag
+    pub fn set_enforce_output_owner(ctx: Context<contexts::AdminAction>, enforce_output_owner: bool) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Update pool state
+        state.enforce_output_owner = enforce_output_owner;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(OutputOwnerEnforcementUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            enforce_output_owner,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Configure sandwich detection
+    ///
+    /// A trade opposite to a large trade within `window_slots`, and within
+    /// `similarity_bps` of its size, emits `SandwichSuspected` and pays
+    /// `surcharge_bps` on top of its fee. Flagged trades are never rejected.
+    /// Changing the settings forgets the last large trade. Takes effect
+    /// immediately, so it only strengthens detection without raising what
+    /// traders pay; anything looser goes through a timelocked
+    /// `ParameterUpdate::Protection`.
+    /// - Validates: admin, thresholds (see `PoolState::validate_sandwich_settings`),
+    ///   settings no looser than the current ones
+    /// - Updates: sandwich settings
+    pub fn set_sandwich_settings(
+        ctx: Context<contexts::AdminAction>,
+        enabled: bool,
+        window_slots: u64,
+        large_trade_bps: u64,
+        similarity_bps: u64,
+        surcharge_bps: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate thresholds and that the settings only tighten
+        let sandwich = SandwichSettingsUpdate { enabled, window_slots, large_trade_bps, similarity_bps, surcharge_bps };
+        PoolState::validate_sandwich_settings(&sandwich)?;
+        validate_condition!(
+            sandwich.is_no_looser_than(&state.sandwich.settings()),
+            crate::ErrorCode::Unauthorized,
+            "Sandwich settings weaken detection or raise the surcharge; apply them through the timelocked path"
+        );
+
+        // Update pool state
+        state.sandwich.configure(&sandwich);
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(SandwichSettingsUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            enabled,
+            window_slots,
+            large_trade_bps,
+            similarity_bps,
+            surcharge_bps,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Configure low reserve mode
+    ///
+    /// While liquidity is below `threshold`, trades pay `surcharge_bps` on top of
+    /// their fee, trades above `max_trade_size` are rejected, and each trade
+    /// emits `LowReserveMode`. A zero threshold disables it. Takes effect
+    /// immediately, so it may only tighten the mode without raising the
+    /// surcharge, as in `LowReserveSettings::is_no_looser_than`; anything else
+    /// goes through a timelocked `ParameterUpdate::Protection`.
+    /// - Validates: admin, surcharge at most MAX_LOW_RESERVE_SURCHARGE_BPS, settings no looser than the live ones
+    /// - Updates: low reserve settings
+    pub fn set_low_reserve_settings(
+        ctx: Context<contexts::AdminAction>,
+        threshold: u64,
+        surcharge_bps: u64,
+        max_trade_size: u64,
+    ) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate surcharge and that the settings only tighten
+        validate_parameter!(surcharge_bps, 0, MAX_LOW_RESERVE_SURCHARGE_BPS, crate::ErrorCode::InvalidLowReserveSettings);
+        let low_reserve = LowReserveSettings { threshold, surcharge_bps, max_trade_size };
+        validate_condition!(
+            low_reserve.is_no_looser_than(&state.low_reserve),
+            crate::ErrorCode::Unauthorized,
+            "Low reserve settings loosen the mode or raise the surcharge; apply them through the timelocked path"
+        );
+
+        // Update pool state
+        state.low_reserve = low_reserve;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(LowReserveSettingsUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            threshold,
+            surcharge_bps,
+            max_trade_size,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Lower the maximum number of trades against the pool in one transaction
+    ///
+    /// Limits atomic multi-trade bundles; zero means unlimited. Takes effect
+    /// immediately, so it only narrows what a single transaction may do;
+    /// raising the limit or lifting it goes through a timelocked
+    /// `ParameterUpdate::Protection`.
+    /// - Validates: admin, nonzero limit no higher than the current one
+    /// - Updates: max trades per transaction
+    pub fn set_max_trades_per_tx(ctx: Context<contexts::AdminAction>, max_trades_per_tx: u16) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new limit only tightens; zero lifts the limit, so it is the loosest
+        let old_max_trades_per_tx = state.max_trades_per_tx;
+        validate_condition!(
+            max_trades_per_tx > 0 && (old_max_trades_per_tx == 0 || max_trades_per_tx <= old_max_trades_per_tx),
+            crate::ErrorCode::Unauthorized,
+            "New per-transaction trade limit {} is looser than {}; raise it through the timelocked path",
+            max_trades_per_tx,
+            old_max_trades_per_tx
+        );
+
+        // Update pool state
+        state.max_trades_per_tx = max_trades_per_tx;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxTradesPerTxUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_trades_per_tx,
+            new_max_trades_per_tx: max_trades_per_tx,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Pause or resume individual operations
+    ///
+    /// Each bit halts one operation, so an operator can stop deposits while
+    /// trades and withdrawals continue. The pause level still applies on top:
+    /// a trading pause sets the trade bit and a full pause sets every bit.
+    /// - Validates: admin, no bits outside `PAUSE_OP_ALL`
+    /// - Updates: paused operations
+    pub fn set_paused_ops(ctx: Context<contexts::AdminAction>, paused_ops: u8) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate bits
+        validate_condition!(
+            paused_ops & !PAUSE_OP_ALL == 0,
+            crate::ErrorCode::InvalidPausedOps,
+            "Unknown paused operation bits: {:#04x}",
+            paused_ops & !PAUSE_OP_ALL
+        );
+
+        // Update pool state
+        let old_paused_ops = state.paused_ops;
+        state.paused_ops = paused_ops;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(PausedOpsUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_paused_ops,
+            new_paused_ops: paused_ops,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set the longest gap since the last pool update that trading accepts
+    ///
+    /// For pools expected to see regular activity: once nothing has traded or
+    /// updated the pool for `max_staleness` seconds, trades fail with `PoolStale`
+    /// until the admin calls `refresh_pool`. Zero disables the check.
+    /// - Validates: admin, non-negative staleness
+    /// - Updates: max staleness
+    pub fn set_max_staleness(ctx: Context<contexts::AdminAction>, max_staleness: i64) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin and staleness
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+        validate_condition!(
+            max_staleness >= 0,
+            crate::ErrorCode::InvalidMaxStaleness,
+            "Invalid max staleness: {}",
+            max_staleness
+        );
+
+        // Update pool state
+        let old_max_staleness = state.max_staleness;
+        state.max_staleness = max_staleness;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(MaxStalenessUpdated {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_max_staleness,
+            new_max_staleness: max_staleness,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Mark a stale pool as reviewed so trading can resume
+    ///
+    /// Only bumps `last_update`. Refreshing a pool that had gone stale emits
+    /// `PoolStale`, leaving a record of the gap that trades could not.
+    /// - Validates: admin
+    /// - Updates: last update time
+    pub fn refresh_pool(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Emit event
+        if state.is_stale(current_time) {
+            emit!(PoolStale {
+                pool: state.key(),
+                admin_pubkey: ctx.accounts.admin.key(),
+                last_update: state.last_update,
+                max_staleness: state.max_staleness,
+                ts: current_time as i64,
+            });
+        }
+
+        // Update pool state
+        state.last_update = current_time;
+
+        Ok(())
+    }
+
+    /// Reset the rate limiter
+    ///
+    /// Clears the call counter and restarts the window without a full parameter
+    /// update, for when the limiter is stuck.
+    /// - Validates: admin
+    /// - Updates: rate limit count and window start
+    pub fn reset_rate_limit(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Reset counter and window; emits RateLimitReset
+        state.reset_rate_limit(current_time)?;
+        state.last_update = current_time;
+
+        Ok(())
+    }
+
+    /// Reset the soft-failure counters
+    ///
+    /// Lets operators start a fresh monitoring window after an anomaly has been
+    /// investigated.
+    /// - Validates: admin
+    /// - Updates: failed trade, liquidity and admin counts
+    pub fn reset_failure_stats(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Update pool state
+        let old_stats = state.reset_failure_stats();
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(FailureStatsReset {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            old_stats,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Migrate the pool state to the current layout version
+    ///
+    /// Pools created under an older layout are upgraded in place. The current
+    /// `PoolState` cannot read an older layout, so the raw account data is
+    /// decoded with the layout it was written in, converted one version at a
+    /// time, and written back after the account is reallocated to the current
+    /// size. Running it on a current pool is a no-op.
+    /// - Validates: admin, stored version is not newer than this program
+    /// - Updates: every field, layout version
+    pub fn migrate_pool_state(ctx: Context<contexts::MigratePoolState>) -> Result<()> {
+        let current_time = current_unix_ts()?;
+        let admin = ctx.accounts.admin.key();
+        let pool_info = ctx.accounts.pool_state.to_account_info();
+
+        // Validate admin; the admin key also locates the version byte
+        let from_version = legacy::detect_version(&pool_info.try_borrow_data()?, &admin)
+            .ok_or(crate::ErrorCode::Unauthorized)?;
+
+        // Refuse to downgrade a pool written by a newer program
+        validate_condition!(
+            from_version <= POOL_STATE_VERSION,
+            crate::ErrorCode::UnsupportedPoolVersion,
+            "Pool version {} is newer than supported version {}",
+            from_version,
+            POOL_STATE_VERSION
+        );
+
+        // Already current
+        if from_version == POOL_STATE_VERSION {
+            msg!("Pool state already at version {}", POOL_STATE_VERSION);
+            return Ok(());
+        }
+
+        // Decode the legacy layout and apply each version step in order
+        let mut state = legacy::migrate_to_current(&pool_info.try_borrow_data()?, from_version, current_time)?;
+        validation::validate_admin_action(&state, &admin, current_time)?;
+        state.last_update = current_time;
+
+        // Grow the account to the current size, topping up rent from the admin
+        let space = PoolState::calculate_space();
+        let rent_shortfall = Rent::get()?.minimum_balance(space).saturating_sub(pool_info.lamports());
+        if rent_shortfall > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.admin.to_account_info(),
+                        to: pool_info.clone(),
+                    },
+                ),
+                rent_shortfall,
+            )?;
+        }
+        if pool_info.data_len() < space {
+            pool_info.realloc(space, true)?;
+        }
+
+        // Update pool state
+        state.try_serialize(&mut &mut pool_info.try_borrow_mut_data()?[..])?;
+
+        // Emit event
+        emit!(PoolStateMigrated {
+            pool: pool_info.key(),
+            admin_pubkey: admin,
+            from_version,
+            to_version: state.version,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Move the pool's funds and configuration to a successor pool
+    ///
+    /// For layout upgrades that need a fresh account. The source must be paused
+    /// and the signer must be admin of both pools, so hand the successor's admin
+    /// to the same key first. Liquidity, treasury, insurance and protocol fees
+    /// move to the successor's token account; unclaimed referral fees stay, as
+    /// referral accounts are tied to this pool and remain claimable here.
+    /// Unexpired liquidity locks follow the liquidity. The source is then
+    /// retired and accepts no further trades or deposits.
+    /// - Validates: admin of both pools, source paused, neither pool retired, same mint,
+    ///   successor token account owned by the successor's authority
+    /// - Transfers: tracked balances from the source pool to the successor
+    /// - Updates: successor configuration and balances, source retirement
+    pub fn migrate_to_pool(ctx: Context<contexts::MigrateToPool>) -> Result<()> {
+        let current_time = current_unix_ts()?;
+        let admin = ctx.accounts.admin.key();
+
+        // Validate admin of both pools; the source may be fully paused
+        validation::validate_admin_signer(&ctx.accounts.pool_state, &admin, current_time)?;
+        validation::validate_admin_action(&ctx.accounts.new_pool_state, &admin, current_time)?;
+
+        // Validate pool states
+        let source = &ctx.accounts.pool_state;
+        validate_condition!(
+            source.pause_level.halts_trading() || source.is_emergency_paused,
+            crate::ErrorCode::PoolNotPaused,
+            "Pause the pool before migrating it"
+        );
+        validate_condition!(!source.retired, crate::ErrorCode::PoolRetired);
+        validate_condition!(!ctx.accounts.new_pool_state.retired, crate::ErrorCode::PoolRetired);
+        validate_condition!(
+            ctx.accounts.new_pool_state.token_mint == source.token_mint,
+            crate::ErrorCode::InvalidTokenMint,
+            "Successor mint {} does not match {}",
+            ctx.accounts.new_pool_state.token_mint,
+            source.token_mint
+        );
+
+        // Validate token accounts
+        source.check_token_account(&ctx.accounts.pool_token_account, &source.token_mint)?;
+        source.check_token_account(&ctx.accounts.new_pool_token_account, &source.token_mint)?;
+        let (new_pool_authority, _) = derive_pool_authority(&ctx.accounts.new_pool_state.key(), ctx.program_id)?;
+        validate_condition!(
+            ctx.accounts.new_pool_token_account.owner == new_pool_authority,
+            crate::ErrorCode::InvalidTokenAccount,
+            "Successor token account is not owned by its pool authority"
+        );
+
+        // Validate the balance covers everything being moved
+        let liquidity = source.total_liquidity;
+        let fees = source.total_fees_collected;
+        let insurance = source.insurance_collected;
+        let protocol_fees = source.protocol_fees;
+        let amount = liquidity
+            .checked_add(fees)
+            .and_then(|total| total.checked_add(insurance))
+            .and_then(|total| total.checked_add(protocol_fees))
+            .ok_or(crate::ErrorCode::Overflow)?;
+        validate_condition!(
+            amount <= ctx.accounts.pool_token_account.amount,
+            crate::ErrorCode::PoolInsolvent,
+            "Tracked balances {} exceed pool balance {}",
+            amount,
+            ctx.accounts.pool_token_account.amount
+        );
+
+        // Update the successor
+        let new_state = &mut ctx.accounts.new_pool_state;
+        new_state.copy_config_from(&ctx.accounts.pool_state);
+        new_state.total_liquidity = new_state.total_liquidity.checked_add(liquidity).ok_or(crate::ErrorCode::Overflow)?;
+        new_state.total_fees_collected = new_state.total_fees_collected.checked_add(fees).ok_or(crate::ErrorCode::Overflow)?;
+        new_state.insurance_collected = new_state.insurance_collected.checked_add(insurance).ok_or(crate::ErrorCode::Overflow)?;
+        new_state.protocol_fees = new_state.protocol_fees.checked_add(protocol_fees).ok_or(crate::ErrorCode::Overflow)?;
+        new_state.release_expired_liquidity_locks(current_time as i64);
+        new_state.liquidity_locks.extend(
+            ctx.accounts.pool_state.liquidity_locks.iter().filter(|lock| !lock.is_expired(current_time as i64)).cloned(),
+        );
+        validate_condition!(
+            new_state.liquidity_locks.len() <= MAX_LIQUIDITY_LOCKS,
+            crate::ErrorCode::TooManyLiquidityLocks,
+            "Successor would hold {} liquidity locks (max: {})",
+            new_state.liquidity_locks.len(),
+            MAX_LIQUIDITY_LOCKS
+        );
+        new_state.last_update = current_time;
+
+        // Retire the source before the transfer
+        let state = &mut ctx.accounts.pool_state;
+        state.total_liquidity = 0;
+        state.total_fees_collected = 0;
+        state.insurance_collected = 0;
+        state.protocol_fees = 0;
+        state.pending_deposits.clear();
+        state.liquidity_locks.clear();
+        state.retired = true;
+        state.last_update = current_time;
+
+        // Transfer from the source pool to the successor
+        if amount > 0 {
+            let cpi_ctx = with_pool_signer(
+                ctx.program_id,
+                &ctx.accounts.pool_state,
+                &[
+                    ctx.accounts.token_program.to_account_info(),
+                    ctx.accounts.pool_token_account.to_account_info(),
+                    ctx.accounts.token_mint.to_account_info(),
+                    ctx.accounts.new_pool_token_account.to_account_info(),
+                    ctx.accounts.pool_authority.to_account_info(),
+                ],
+            )?;
+            token_interface::transfer_checked(cpi_ctx, amount, ctx.accounts.token_mint.decimals)?;
+        }
+
+        // Emit event
+        emit!(PoolMigrated {
+            pool: ctx.accounts.pool_state.key(),
+            new_pool: ctx.accounts.new_pool_state.key(),
+            admin_pubkey: admin,
+            liquidity,
+            fees: amount - liquidity,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Close an emptied pool and return its rent to the admin
+    ///
+    /// The pool must be paused and hold no liquidity or fees of any kind, so
+    /// withdraw everything (or migrate to a successor) first. The token account
+    /// must be empty too, since tokens sent to it outside the pool's accounting
+    /// could not be recovered once the pool state is gone. The pool state
+    /// account is closed by Anchor once this returns.
+    /// - Validates: admin, pool paused, liquidity and all fee balances withdrawn, empty pool token account
+    /// - Updates: closes the pool state account
+    pub fn close_pool(ctx: Context<contexts::ClosePool>) -> Result<()> {
+        let state = &ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin; a fully paused pool may still be closed
+        validation::validate_admin_signer(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate pool state
+        validate_condition!(
+            state.pause_level.halts_trading() || state.is_emergency_paused,
+            crate::ErrorCode::PoolNotPaused,
+            "Pause the pool before closing it"
+        );
+        let outstanding = state.obligations()?;
+        validate_condition!(
+            outstanding == 0,
+            crate::ErrorCode::PoolNotEmpty,
+            "Pool still holds {} in liquidity and fees",
+            outstanding
+        );
+        let balance = ctx.accounts.pool_token_account.amount;
+        validate_condition!(
+            balance == 0,
+            crate::ErrorCode::PoolNotEmpty,
+            "Pool token account still holds {}",
+            balance
+        );
+
+        // Emit event
+        emit!(PoolClosed {
+            pool: state.key(),
+            admin_pubkey: ctx.accounts.admin.key(),
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Set or clear the guardian
+    ///
+    /// The guardian is an independent key that can veto a pending parameter update
+    /// during its timelock, but can never schedule or apply updates itself.
+    /// - Validates: admin, guardian is neither the default pubkey nor the admin
+    /// - Updates: guardian
+    pub fn set_guardian(ctx: Context<contexts::AdminAction>, new_guardian: Option<Pubkey>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate admin
+        validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
+
+        // Validate new guardian
+        if let Some(guardian) = new_guardian {
+            validate_condition!(
+                guardian != Pubkey::default() && guardian != state.admin,
+                crate::ErrorCode::InvalidGuardian
+            );
+        }
+
+        // Update guardian
+        let old_guardian = state.guardian;
+        state.guardian = new_guardian;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(GuardianUpdated {
+            pool: state.key(),
+            old_guardian_pubkey: old_guardian,
+            new_guardian_pubkey: new_guardian,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Veto a pending parameter update
+    ///
+    /// This function allows the guardian to discard a scheduled update before it is applied.
+    /// - Validates: guardian, presence of pending update
+    /// - Clears: pending update
+    pub fn veto_pending_update(ctx: Context<contexts::GuardianAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        // Validate guardian
+        validate_condition!(
+            state.guardian == Some(ctx.accounts.guardian.key()),
+            crate::ErrorCode::InvalidGuardian
+        );
+
+        // Take the pending update
+        let pending_update = state.pending_update.take().ok_or(crate::ErrorCode::NoPendingUpdate)?;
+        state.last_update = current_time;
+
+        // Emit event
+        emit!(ParameterUpdateVetoed {
+            pool: state.key(),
+            guardian_pubkey: ctx.accounts.guardian.key(),
+            scheduled_time: pending_update.scheduled_time as i64,
+            updates: pending_update.updates,
             ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    /// Toggle the pool pause state
-    pub fn toggle_pause(ctx: Context<contexts::AdminAction>) -> Result<()> {
+    /// Reset the pending update
+    pub fn reset_pending_update(ctx: Context<contexts::AdminAction>) -> Result<()> {
         let state = &mut ctx.accounts.pool_state;
         let current_time = Clock::get()?.unix_timestamp as u64;
 
         // Validate admin and check cooldown
         validation::validate_admin_action(state, &ctx.accounts.admin.key(), current_time)?;
 
-        // Toggle pause state
-        state.is_paused = !state.is_paused;
+        // Reset pending update
+        let pending_update = state.pending_update.take().ok_or(crate::ErrorCode::NoPendingUpdate)?;
         state.last_update = current_time;
 
         // Emit event
-        if state.is_paused {
-            emit!(PoolPaused {
-                pool: state.key(),
-                admin_pubkey: state.admin,
-                ts: current_time,
-            });
-        } else {
-            emit!(PoolResumed {
-                pool: state.key(),
-                admin_pubkey: state.admin,
-                ts: current_time,
-            });
-        }
+        emit!(ParameterUpdateCancelled {
+            pool: state.key(),
+            admin_pubkey: state.admin,
+            ts: current_time as i64,
+            updates: pending_update.updates,
+        });
 
         Ok(())
     }
 
+    /// Get the pool's most recent trades, oldest first
+    ///
+    /// This is a read-only view for audits and disputes. Only the last
+    /// `RECENT_TRADES_CAPACITY` trades are kept.
+    /// - Returns: trader, amounts, fee and timestamp of each retained trade
+    pub fn get_recent_trades(ctx: Context<contexts::ViewPool>) -> Result<Vec<TradeRecord>> {
+        Ok(ctx.accounts.pool_state.recent_trades.to_vec())
+    }
+
+    /// Get a snapshot of the full protection configuration
+    ///
+    /// This is a read-only view for operators and dashboards.
+    /// - Returns: rate limit, circuit breaker, decayed volume, protection settings and fee tiers
+    pub fn get_pool_config(ctx: Context<contexts::ViewPool>) -> Result<PoolConfig> {
+        let state = &ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        Ok(PoolConfig {
+            rate_limit: state.rate_limit.clone(),
+            circuit_breaker: state.circuit_breaker.clone(),
+            volume: state.decayed_volume(current_time)?,
+            protection: state.protection.clone(),
+            fee_tiers: state.fee_tiers.clone(),
+            fee_tiers_locked: state.fee_tiers_locked,
+            fee_rounding: state.fee_rounding,
+            protocol_fee_bps: state.protocol_fee_bps,
+            referral_share_bps: state.referral_share_bps,
+            max_fee_tiers: state.max_fee_tiers,
+            max_blacklist_batch: state.blacklist_batch_limit() as u16,
+            timelocks: state.timelocks.clone(),
+        })
+    }
+
+    /// Report the state of every protection subsystem in one call
+    ///
+    /// This is a read-only view for dashboards. Volume and circuit breaker
+    /// decay are applied to copies, so the numbers are current without a trade.
+    /// - Returns: pause flags, breaker state, and headroom on each limit
+    pub fn get_health(ctx: Context<contexts::ViewPool>) -> Result<PoolHealth> {
+        let current_time = current_unix_ts()?;
+        ctx.accounts.pool_state.health(current_time)
+    }
+
+    /// Price impact cap the next trade will face
+    ///
+    /// Read-only, so clients can size trades against the adaptive cap.
+    pub fn get_effective_max_price_impact(ctx: Context<contexts::ViewPool>) -> Result<u64> {
+        Ok(ctx.accounts.pool_state.effective_max_price_impact_bps())
+    }
+
+    /// Seconds until the pending parameter update can be applied
+    ///
+    /// This is a read-only view so clients can wait out the timelock instead of
+    /// racing `apply_parameter_update` into `TimelockNotExpired`.
+    /// - Returns: remaining seconds, or zero once the timelock has elapsed
+    /// - Fails with `NoPendingUpdate` when nothing is scheduled
+    pub fn get_timelock_remaining(ctx: Context<contexts::ViewPool>) -> Result<u64> {
+        let current_time = current_unix_ts()?;
+        ctx.accounts.pool_state.timelock_remaining(current_time)
+    }
+
+    /// Current 24h volume with decay applied
+    ///
+    /// This is a read-only view; stored volume only decays when a trade runs, so
+    /// reading the account directly can overstate it after a quiet period.
+    /// - Returns: volume still inside the 24h window
+    pub fn get_current_volume(ctx: Context<contexts::ViewPool>) -> Result<u64> {
+        let current_time = current_unix_ts()?;
+        Ok(ctx.accounts.pool_state.decayed_volume(current_time)?.current_volume)
+    }
+
+    /// When parameter settings were last applied, and by which admin
+    ///
+    /// This is a read-only view for audits; `ParametersUpdated` is only in the
+    /// logs, while this record persists on the pool.
+    /// - Returns: the last update record, or `None` if settings were never applied
+    pub fn get_last_parameter_update(ctx: Context<contexts::ViewPool>) -> Result<Option<ParameterUpdateRecord>> {
+        Ok(ctx.accounts.pool_state.last_parameter_update())
+    }
+
+    /// Soft-failure counters per operation category
+    ///
+    /// This is a read-only view for monitoring; a jump in a counter flags an
+    /// anomaly without scraping the `*Failed` events from logs.
+    /// - Returns: failed trade, liquidity and admin operation counts since the last reset
+    pub fn get_failure_stats(ctx: Context<contexts::ViewPool>) -> Result<FailureStats> {
+        Ok(ctx.accounts.pool_state.failure_stats())
+    }
+
+    /// Preview which fee tier applies at a hypothetical volume level and the current liquidity
+    ///
+    /// This is a read-only view for routers; it uses the same tier selection as
+    /// `calculate_fee` but ignores the early trade window.
+    /// - Returns: the tier's `fee_bps` and index, or the fallback fee with no index
+    pub fn preview_fee_tier(ctx: Context<contexts::ViewPool>, volume: u64) -> Result<FeeTierPreview> {
+        let state = &ctx.accounts.pool_state;
+
+        Ok(FeeTierPreview {
+            fee_bps: state.fee_bps_for_volume(volume),
+            tier_index: state.fee_tier_index(volume, state.total_liquidity).map(|index| index as u8),
+        })
+    }
+
+    /// Toggle the pool pause state
+    ///
+    /// Pauses trading when the pool is running, and lifts any pause level otherwise.
+    /// Lifting a full pause is the one admin action a full pause still allows.
+    pub fn toggle_pause(ctx: Context<contexts::AdminAction>) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = Clock::get()?.unix_timestamp as u64;
+
+        let new_level = if state.pause_level.halts_trading() {
+            PauseLevel::None
+        } else {
+            PauseLevel::TradingOnly
+        };
+
+        state.apply_pause_level(&ctx.accounts.admin.key(), new_level, current_time)
+    }
+
+    /// Set the pool pause level
+    ///
+    /// This function allows the admin to halt trading only, or to freeze the pool
+    /// entirely so that admin actions are blocked too until the pause is lifted.
+    /// - Validates: admin; a full pause only allows moving to `None`
+    /// - Updates: pause level
+    pub fn set_pause_level(ctx: Context<contexts::AdminAction>, pause_level: PauseLevel) -> Result<()> {
+        let state = &mut ctx.accounts.pool_state;
+        let current_time = current_unix_ts()?;
+
+        state.apply_pause_level(&ctx.accounts.admin.key(), pause_level, current_time)
+    }
+
     pub fn initialize_default(&mut self) -> Result<()> {
         let current_time = current_unix_ts()?;
         self.pool_start_time = current_time;
@@ -1012,6 +3712,9 @@ pub mod hoe_dex_protection {
 
     pub fn toggle_emergency_pause(&mut self, current_time: u64) -> Result<()> {
         self.is_emergency_paused = !self.is_emergency_paused;
+        if self.is_emergency_paused {
+            self.emergency_paused_at = current_time;
+        }
         self.last_update = current_time;
 
         if self.is_emergency_paused {
@@ -1160,9 +3863,9 @@ mod contexts {
     /// # Accounts
     /// * `pool_state` - The pool state account to initialize
     /// * `admin` - The admin account that will own the pool
-    /// * `token_mint` - The token mint for the pool
+    /// * `token_mint` - The token mint for the pool, owned by SPL Token or Token-2022
     /// * `system_program` - Required for account creation
-    /// * `token_program` - Required for token operations
+    /// * `token_program` - The mint's token program
     /// * `rent` - Required for account creation
 #[derive(Accounts)]
     pub struct InitializePool<'info> {
@@ -1177,9 +3880,9 @@ mod contexts {
     #[account(mut)]
     pub admin: Signer<'info>,
         #[account(mut)]
-    pub token_mint: Account<'info, Mint>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub rent: Sysvar<'info, Rent>,
 }
 
@@ -1191,6 +3894,7 @@ mod contexts {
     /// * `admin_token_account` - The admin's token account
     /// * `pool_token_account` - The pool's token account
     /// * `pool_authority` - The pool's authority PDA
+    /// * `token_mint` - The pool's token mint, checked against the captured decimals
     /// * `token_program` - Required for token operations
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
@@ -1203,15 +3907,19 @@ pub struct AddLiquidity<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
     #[account(mut)]
-    pub admin_token_account: Account<'info, TokenAccount>,
+    pub admin_token_account: InterfaceAccount<'info, TokenAccount>,
         #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         seeds = [b"pool_authority", pool_state.key().as_ref()],
             bump = pool_state.bump
     )]
     pub pool_authority: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
+    #[account(
+        constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(Accounts)]
@@ -1225,19 +3933,52 @@ pub struct ExecuteTrade<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
         #[account(mut)]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
         #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(
         seeds = [b"pool_authority", pool_state.key().as_ref()],
             bump = pool_state.bump
     )]
     pub pool_authority: AccountInfo<'info>,
         #[account(
-            constraint = token_mint.key() == pool_state.token_mint
+            constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+        )]
+        pub token_mint: InterfaceAccount<'info, Mint>,
+        #[account(
+            init_if_needed,
+            payer = buyer,
+            space = 8 + TraderState::INIT_SPACE,
+            seeds = [TRADER_STATE_SEED, pool_state.key().as_ref(), buyer.key().as_ref()],
+            bump
+        )]
+        pub trader_state: Account<'info, TraderState>,
+        #[account(
+            init_if_needed,
+            payer = buyer,
+            space = 8 + TraderVolume::INIT_SPACE,
+            seeds = [TRADER_VOLUME_SEED, pool_state.key().as_ref(), buyer.key().as_ref()],
+            bump
+        )]
+        pub trader_volume: Account<'info, TraderVolume>,
+        #[account(
+            init_if_needed,
+            payer = buyer,
+            space = 8 + ReentrancyLock::INIT_SPACE,
+            seeds = [REENTRANCY_GUARD_SEED, pool_state.key().as_ref()],
+            bump
         )]
-        pub token_mint: Account<'info, Mint>,
-    pub token_program: Program<'info, Token>,
+        pub reentrancy_guard: Account<'info, ReentrancyLock>,
+        /// Required only when the trade names a referrer
+        #[account(mut)]
+        pub referral_state: Option<Account<'info, ReferralState>>,
+        /// CHECK: Instructions sysvar, read to identify the calling program
+        #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+        pub instructions: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+        pub system_program: Program<'info, System>,
+        /// Trader's governance token account, passed to claim a holder discount
+        pub governance_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 }
 
 #[derive(Accounts)]
@@ -1272,33 +4013,300 @@ pub struct ExecuteTrade<'info> {
 
 #[derive(Accounts)]
 pub struct WithdrawFees<'info> {
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    #[account(mut)]
+    pub fee_authority: Signer<'info>,
+    #[account(mut)]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(mut)]
+        pub fee_authority_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(
+        init_if_needed,
+        payer = fee_authority,
+        space = 8 + ReentrancyLock::INIT_SPACE,
+        seeds = [REENTRANCY_GUARD_SEED, pool_state.key().as_ref()],
+        bump
+    )]
+    pub reentrancy_guard: Account<'info, ReentrancyLock>,
+    #[account(
+        constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DistributeFees<'info> {
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
+    #[account(mut)]
+    pub fee_authority: Signer<'info>,
+    #[account(mut)]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: Pool authority PDA, only used to sign the transfers
+    #[account(
+        seeds = [b"pool_authority", pool_state.key().as_ref()],
+        bump
+    )]
+    pub pool_authority: AccountInfo<'info>,
+    #[account(
+        constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+    pub struct ConfigureInsuranceFund<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        pub insurance_fund: InterfaceAccount<'info, TokenAccount>,
+    }
+
+#[derive(Accounts)]
+    pub struct WithdrawInsuranceFund<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        #[account(mut)]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            mut,
+            address = pool_state.insurance_fund
+        )]
+        pub insurance_fund: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+        #[account(
+            constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+        )]
+        pub token_mint: InterfaceAccount<'info, Mint>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+#[derive(Accounts)]
+    pub struct RegisterReferrer<'info> {
+        pub pool_state: Account<'info, PoolState>,
+        #[account(
+            init,
+            payer = referrer,
+            space = 8 + ReferralState::INIT_SPACE,
+            seeds = [REFERRAL_STATE_SEED, pool_state.key().as_ref(), referrer.key().as_ref()],
+            bump
+        )]
+        pub referral_state: Account<'info, ReferralState>,
+        #[account(mut)]
+        pub referrer: Signer<'info>,
+        pub system_program: Program<'info, System>,
+    }
+
+#[derive(Accounts)]
+    pub struct ClaimReferralFees<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(
+            mut,
+            seeds = [REFERRAL_STATE_SEED, pool_state.key().as_ref(), referrer.key().as_ref()],
+            bump = referral_state.bump,
+            has_one = referrer
+        )]
+        pub referral_state: Account<'info, ReferralState>,
+        pub referrer: Signer<'info>,
+        #[account(mut)]
+        pub referrer_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(mut)]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+        #[account(
+            constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+        )]
+        pub token_mint: InterfaceAccount<'info, Mint>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+#[derive(Accounts)]
+    pub struct LockFeeTiers<'info> {
     #[account(mut)]
     pub pool_state: Account<'info, PoolState>,
     #[account(mut)]
     pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+    pub struct EmergencyAction<'info> {
+    #[account(mut)]
+    pub pool_state: Account<'info, PoolState>,
     #[account(mut)]
-    pub pool_token_account: Account<'info, TokenAccount>,
+    pub emergency_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+    pub struct EmergencyWithdraw<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(mut)]
+        pub emergency_admin: Signer<'info>,
+        #[account(mut)]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            mut,
+            address = pool_state.recovery_account @ crate::ErrorCode::InvalidRecoveryAccount
+        )]
+        pub recovery_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+        #[account(
+            constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+        )]
+        pub token_mint: InterfaceAccount<'info, Mint>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+#[derive(Accounts)]
+    pub struct SetRecoveryAccount<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        pub recovery_account: InterfaceAccount<'info, TokenAccount>,
+    }
+
+#[derive(Accounts)]
+    pub struct SyncReserves<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        pub admin: Signer<'info>,
+        #[account(
+            constraint = pool_token_account.mint == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint,
+            constraint = pool_token_account.owner == pool_authority.key() @ crate::ErrorCode::InvalidTokenAccount
+        )]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        /// CHECK: PDA signer for the pool token account
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+    }
+
+#[derive(Accounts)]
+    pub struct MigratePoolState<'info> {
+        /// CHECK: Decoded by `migrate_pool_state` from whichever layout it was written in;
+        /// the current `PoolState` cannot read older layouts
+        #[account(mut, owner = crate::ID)]
+        pub pool_state: UncheckedAccount<'info>,
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        pub system_program: Program<'info, System>,
+    }
+
+#[derive(Accounts)]
+    pub struct MigrateToPool<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(
+            mut,
+            constraint = new_pool_state.key() != pool_state.key()
+        )]
+        pub new_pool_state: Account<'info, PoolState>,
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        #[account(mut)]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(mut)]
+        pub new_pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+        #[account(
+            constraint = token_mint.key() == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint
+        )]
+        pub token_mint: InterfaceAccount<'info, Mint>,
+        pub token_program: Interface<'info, TokenInterface>,
+    }
+
+#[derive(Accounts)]
+    pub struct ClosePool<'info> {
+        #[account(mut, close = admin)]
+        pub pool_state: Account<'info, PoolState>,
+        #[account(mut)]
+        pub admin: Signer<'info>,
+        #[account(
+            constraint = pool_token_account.mint == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint,
+            constraint = pool_token_account.owner == pool_authority.key() @ crate::ErrorCode::InvalidTokenAccount
+        )]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        /// CHECK: PDA that owns the pool token account
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+    }
+
+#[derive(Accounts)]
+    pub struct AcceptFeeAuthority<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        pub new_fee_authority: Signer<'info>,
+    }
+
+#[derive(Accounts)]
+    pub struct GuardianAction<'info> {
         #[account(mut)]
-        pub admin_token_account: Account<'info, TokenAccount>,
-    pub pool_authority: AccountInfo<'info>,
-    pub token_program: Program<'info, Token>,
-}
+        pub pool_state: Account<'info, PoolState>,
+        pub guardian: Signer<'info>,
+    }
 
 #[derive(Accounts)]
-    pub struct LockFeeTiers<'info> {
-    #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
-    #[account(mut)]
-    pub admin: Signer<'info>,
-}
+    pub struct VerifySolvency<'info> {
+        pub pool_state: Account<'info, PoolState>,
+        #[account(
+            constraint = pool_token_account.mint == pool_state.token_mint @ crate::ErrorCode::InvalidTokenMint,
+            constraint = pool_token_account.owner == pool_authority.key() @ crate::ErrorCode::InvalidTokenAccount
+        )]
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
+        /// CHECK: PDA that owns the pool token account
+        #[account(
+            seeds = [b"pool_authority", pool_state.key().as_ref()],
+            bump
+        )]
+        pub pool_authority: AccountInfo<'info>,
+    }
 
 #[derive(Accounts)]
-    pub struct EmergencyAction<'info> {
-    #[account(mut)]
-    pub pool_state: Account<'info, PoolState>,
-    #[account(mut)]
-    pub emergency_admin: Signer<'info>,
-}
+    pub struct ViewPool<'info> {
+        pub pool_state: Account<'info, PoolState>,
+    }
+
+#[derive(Accounts)]
+    pub struct Tick<'info> {
+        #[account(mut)]
+        pub pool_state: Account<'info, PoolState>,
+        pub caller: Signer<'info>,
+    }
 
 #[derive(Accounts)]
     pub struct SimulateTrade<'info> {
@@ -1312,15 +4320,15 @@ pub struct WithdrawFees<'info> {
         #[account(mut)]
         pub admin: Signer<'info>,
         #[account(mut)]
-        pub pool_token_account: Account<'info, TokenAccount>,
+        pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
         #[account(mut)]
-        pub admin_token_account: Account<'info, TokenAccount>,
+        pub admin_token_account: InterfaceAccount<'info, TokenAccount>,
         #[account(
             seeds = [b"pool_authority", pool_state.key().as_ref()],
             bump
         )]
         pub pool_authority: AccountInfo<'info>,
-        pub token_program: Program<'info, Token>,
+        pub token_program: Interface<'info, TokenInterface>,
     }
 }
 
@@ -1333,11 +4341,17 @@ pub fn derive_pool_authority(pool_state: &Pubkey, program_id: &Pubkey) -> Result
 }
 
 // Add helper function for CPI context with proper error handling
+/// Builds a pool-authority-signed transfer from `remaining_accounts`
+///
+/// The accounts are the token program, source, mint, destination and pool
+/// authority, in `TransferChecked` order. Callers update pool state before
+/// the transfer and only emit events after it, so the state the protections
+/// read never lags the tokens that moved.
 pub fn with_pool_signer<'info>(
     program_id: &Pubkey,
     pool_state: &Account<'info, PoolState>,
     remaining_accounts: &[AccountInfo<'info>],
-) -> Result<CpiContext<'info, 'info, 'info, 'info, Transfer<'info>>> {
+) -> Result<CpiContext<'info, 'info, 'info, 'info, TransferChecked<'info>>> {
     let (pool_authority, bump) = derive_pool_authority(&pool_state.key(), program_id)?;
     let seeds: &[&[&[u8]]] = &[&[
         b"pool_authority".as_ref(),
@@ -1346,10 +4360,11 @@ pub fn with_pool_signer<'info>(
     ]];
     Ok(CpiContext::new_with_signer(
         remaining_accounts[0].clone(), // token_program
-        Transfer {
+        TransferChecked {
             from: remaining_accounts[1].clone(),
-            to: remaining_accounts[2].clone(),
-            authority: remaining_accounts[3].clone(), // pool_authority as AccountInfo
+            mint: remaining_accounts[2].clone(),
+            to: remaining_accounts[3].clone(),
+            authority: remaining_accounts[4].clone(), // pool_authority as AccountInfo
         },
         seeds,
     ))
@@ -1358,19 +4373,32 @@ pub fn with_pool_signer<'info>(
 #[account]
 #[derive(Default)]
 pub struct PoolState {
+    /// Layout version; kept first so it sits at a fixed offset in every version.
+    /// New fields are appended at the end of the struct; any other layout
+    /// change bumps `POOL_STATE_VERSION` and freezes the old layout in `legacy`.
+    pub version: u8,
     pub admin: Pubkey,
     pub emergency_admin: Pubkey,
+    pub guardian: Option<Pubkey>,
     pub token_mint: Pubkey,
     pub token_decimals: u8,
     pub total_liquidity: u64,
+    pub min_liquidity_floor: u64,
+    pub liquidity_grace_period: u64,
+    pub pending_deposits: Vec<LiquidityDeposit>,
     pub total_fees_collected: u64,
+    pub insurance_fund: Pubkey,
+    pub insurance_fee_share_bps: u64,
+    pub insurance_collected: u64,
     pub is_initialized: bool,
-    pub is_paused: bool,
+    pub pause_level: PauseLevel,
     pub is_finalized: bool,
     pub pool_start_time: u64,
     pub last_update: u64,
+    pub event_seq: u64,
     pub fee_tiers: Vec<FeeTier>,
     pub fee_tiers_locked: bool,
+    pub max_fee_tiers: u16,
     pub default_fee_bps: Option<u16>,
     pub volume: VolumeSettings,
     pub rate_limit: RateLimitSettings,
@@ -1378,12 +4406,100 @@ pub struct PoolState {
     pub protection: ProtectionSettings,
     pub trade_settings: TradeSettings,
     pub pending_update: Option<PendingUpdate>,
-    pub emergency_action_scheduled_time: u64,
-    pub trader_blacklist: Vec<Pubkey>,
+    pub timelocks: TimelockSettings,
+    /// Scheduled emergency pause or resume, independent of `pending_update`
+    pub pending_emergency: Option<PendingEmergency>,
+    pub emergency_paused_at: u64,
+    pub trader_blacklist: Vec<BlacklistEntry>,
     pub whitelist: Vec<Pubkey>,
     pub is_emergency_paused: bool,
     pub bump: u8,
     pub pool_id: [u8; 32],
+    pub fee_rounding: FeeRounding,
+    pub referral_share_bps: u64,
+    /// Referral fees accrued but not yet claimed; held in the pool token account
+    pub referral_fees_owed: u64,
+    /// Programs allowed to trade via CPI; empty means unrestricted
+    pub allowed_callers: Vec<Pubkey>,
+    /// Cap on trade instructions against the pool per transaction; 0 means unlimited
+    pub max_trades_per_tx: u16,
+    /// Protocol fee charged on top of the LP fee, in bps of the trade
+    pub protocol_fee_bps: u64,
+    /// Protocol fees accrued but not yet withdrawn; held in the pool token account
+    pub protocol_fees: u64,
+    /// Operations paused individually, as `PAUSE_OP_*` bits
+    pub paused_ops: u8,
+    /// Set once the pool has migrated to a successor; blocks trades and deposits
+    pub retired: bool,
+    /// Per-call cap on blacklist batches, up to `MAX_BLACKLIST_BATCH`; 0 means the default
+    pub max_blacklist_batch: u16,
+    /// Recent price volatility and the adaptive price impact cap it drives
+    pub volatility: VolatilitySettings,
+    /// Seconds trading stays blocked after an emergency resume; 0 disables it
+    pub post_resume_cooldown: i64,
+    /// When the last emergency resume was applied
+    pub resumed_at: i64,
+    /// Mint whose holders get LP fee discounts; `None` disables them
+    pub governance_mint: Option<Pubkey>,
+    /// Holding tiers sorted by increasing `min_balance`
+    pub governance_discount_tiers: Vec<GovernanceDiscountTier>,
+    /// Cap on trades within one hourly window; 0 means `MAX_HOURLY_TRADES`
+    pub max_hourly_trades: u32,
+    /// Trades executed in the current hourly window
+    pub hourly_trade_count: u32,
+    /// Start of the current hourly trade window, set by the first trade after the last one lapsed
+    pub hourly_trades_window_start: u64,
+    /// Require the trade's token account to be owned by the trader, not just delegated to them
+    pub enforce_output_owner: bool,
+    /// Sandwich detection thresholds and the last large trade
+    pub sandwich: SandwichSettings,
+    /// Running totals for the open accounting period
+    pub accounting_period: AccountingPeriod,
+    /// Start of the early-trade phase; 0 means the pool start time
+    pub launch_ts: i64,
+    /// Largest share of reserves one trade may take out, in bps; 10000 disables the guard
+    pub max_output_fraction_bps: u64,
+    /// Token accounts `distribute_fees` splits the accrued fees among
+    pub fee_recipients: Vec<FeeRecipient>,
+    /// Set at initialization to accept a mint with a permanent delegate or close authority
+    pub allow_risky_mint: bool,
+    /// Trades of at most this amount pay no fee; 0 disables it. Set through
+    /// the timelocked fee settings.
+    pub fee_free_threshold: u64,
+    /// Most recent trades, for audits without an indexer
+    pub recent_trades: RecentTrades,
+    /// Ceiling on `total_fees_collected`; 0 means `u64::MAX`
+    pub fee_high_water_mark: u64,
+    /// What a trade does once its fee would cross `fee_high_water_mark`
+    pub fee_overflow_mode: FeeOverflowMode,
+    /// Longest gap since `last_update` a trade accepts, in seconds; 0 disables the check
+    pub max_staleness: i64,
+    /// Minimum seconds between fee withdrawals; 0 disables the check
+    pub fee_withdraw_cooldown: i64,
+    /// When fees were last withdrawn or distributed
+    pub last_fee_withdraw_ts: i64,
+    /// When parameter settings were last applied; 0 if never
+    pub last_param_update_ts: i64,
+    /// Admin who last applied parameter settings
+    pub last_param_update_admin: Pubkey,
+    /// Batch trade legs rolled back and reported through `TradeExecutionFailed`
+    pub failed_trade_count: u64,
+    /// Liquidity operations reported through `LiquidityOperationFailed`
+    pub failed_liquidity_count: u64,
+    /// Admin operations reported through `AdminOperationFailed`
+    pub failed_admin_count: u64,
+    pub low_reserve: LowReserveSettings,
+    /// Unexpired locks on deposited liquidity, at most `MAX_LIQUIDITY_LOCKS`
+    pub liquidity_locks: Vec<LiquidityLock>,
+    /// When a scheduled fee tier unlock may be applied; 0 if none is scheduled
+    pub fee_tiers_unlock_time: u64,
+    /// Token account `emergency_withdraw` sends reserves to; unset until the admin designates one
+    pub recovery_account: Pubkey,
+    /// Signs fee withdrawals and distributions; unset on pools written before
+    /// it existed, which leaves fees with the admin
+    pub fee_authority: Pubkey,
+    /// Proposed fee authority, until it accepts or the admin cancels
+    pub pending_fee_authority: Option<Pubkey>,
 }
 
 impl PoolState {
@@ -1393,11 +4509,17 @@ impl PoolState {
         
         // Add space for dynamic fields
         let fee_tiers_size = MAX_FEE_TIERS * std::mem::size_of::<FeeTier>();
-        let blacklist_size = MAX_BLACKLIST_SIZE * std::mem::size_of::<Pubkey>();
-        let pending_update_size = MAX_PENDING_UPDATE_SIZE;
+        let blacklist_size = MAX_BLACKLIST_SIZE * std::mem::size_of::<BlacklistEntry>();
+        let pending_deposits_size = MAX_PENDING_DEPOSITS * std::mem::size_of::<LiquidityDeposit>();
+        let pending_update_size = MAX_PENDING_UPDATE_SIZE * std::mem::size_of::<ParameterUpdate>();
+        let allowed_callers_size = MAX_ALLOWED_CALLERS * std::mem::size_of::<Pubkey>();
+        let governance_discount_size = MAX_GOVERNANCE_DISCOUNT_TIERS * std::mem::size_of::<GovernanceDiscountTier>();
+        let fee_recipients_size = MAX_FEE_RECIPIENTS * std::mem::size_of::<FeeRecipient>();
+        let liquidity_locks_size = MAX_LIQUIDITY_LOCKS * std::mem::size_of::<LiquidityLock>();
         
         // Add buffer for future-proofing
-        base_size + fee_tiers_size + blacklist_size + pending_update_size + 32
+        base_size + fee_tiers_size + blacklist_size + pending_deposits_size + pending_update_size
+            + allowed_callers_size + governance_discount_size + fee_recipients_size + liquidity_locks_size + 32
     }
 
     pub fn initialize_default(&mut self) -> Result<()> {
@@ -1410,73 +4532,238 @@ impl PoolState {
         Ok(())
     }
 
-    pub fn toggle_pause(&mut self, current_time: u64) -> Result<()> {
-        self.is_paused = !self.is_paused;
+    /// Moves the pool to a new pause level, enforcing the full-pause freeze
+    pub fn apply_pause_level(&mut self, admin: &Pubkey, new_level: PauseLevel, current_time: u64) -> Result<()> {
+        // Lifting the pause is always allowed; anything else goes through the freeze check
+        if new_level == PauseLevel::None {
+            validation::validate_admin_signer(self, admin, current_time)?;
+        } else {
+            validation::validate_admin_action(self, admin, current_time)?;
+        }
+
+        let old_level = self.pause_level;
+        self.pause_level = new_level;
+        self.circuit_breaker.paused_by_breaker = false;
         self.last_update = current_time;
 
-        if self.is_paused {
+        if new_level == PauseLevel::None {
+            if old_level.halts_trading() {
+                emit!(PoolResumed {
+                    pool: self.key(),
+                    admin_pubkey: *admin,
+                    ts: current_time as i64,
+                });
+            }
+        } else if new_level != old_level {
             emit!(PoolPaused {
                 pool: self.key(),
-                admin_pubkey: self.admin,
-                ts: current_time,
-            });
-        } else {
-            emit!(PoolResumed {
-                pool: self.key(),
-                admin_pubkey: self.admin,
-                ts: current_time,
+                admin_pubkey: *admin,
+                pause_level: new_level,
+                ts: current_time as i64,
             });
         }
 
         Ok(())
     }
 
+    /// Decays tracked volume to the live 24h window, logging any change
+    ///
+    /// A clock reading behind `last_decay` decays nothing and emits nothing.
     pub fn decay_volume(&mut self, current_time: u64) -> Result<()> {
-        if current_time < self.volume.last_reset {
-            return Err(crate::ErrorCode::InvalidTimestamp.into());
+        if let Some(event) = self.decay_volume_deferred(current_time)? {
+            emit!(event);
         }
+        Ok(())
+    }
 
-        let time_diff = current_time - self.volume.last_reset;
-        if time_diff >= self.volume.decay_period {
-            let decay_factor = (time_diff as f64 / self.volume.decay_period as f64).floor() as u64;
-            self.volume.current_volume = self.volume.current_volume
-                .saturating_sub(
-                    self.volume.current_volume.saturating_mul(decay_factor) / self.volume.decay_period
-                );
-            self.volume.last_reset = current_time;
+    /// Decays tracked volume like `decay_volume`, returning the event instead of emitting it
+    ///
+    /// A trade emits it only once the trade settles, so a rolled-back batch leg
+    /// does not log a decay that never persisted.
+    pub fn decay_volume_deferred(&mut self, current_time: u64) -> Result<Option<VolumeDecayed>> {
+        let Some(elapsed) = current_time.checked_sub(self.volume.last_decay) else {
+            return Ok(None);
+        };
+
+        let old_volume = self.volume.current_volume;
+        self.volume.apply_decay(current_time)?;
+
+        if self.volume.current_volume == old_volume {
+            return Ok(None);
         }
-        Ok(())
+        Ok(Some(VolumeDecayed {
+            pool: self.key(),
+            old_volume,
+            new_volume: self.volume.current_volume,
+            hours_passed: elapsed / VOLUME_BUCKET_SECONDS,
+            ts: current_time as i64,
+        }))
+    }
+
+    /// Returns the volume settings with decay applied, without mutating state
+    pub fn decayed_volume(&self, current_time: u64) -> Result<VolumeSettings> {
+        let mut volume = self.volume.clone();
+        volume.apply_decay(current_time)?;
+        Ok(volume)
+    }
+
+    /// Circuit breaker accumulator as the next trade would see it, without mutating state
+    ///
+    /// Applies decay, and empties the accumulator once the cooldown has lapsed.
+    pub fn decayed_circuit_breaker_amount(&self, current_time: u64) -> u64 {
+        let mut state = PoolState { circuit_breaker: self.circuit_breaker.clone(), ..Default::default() };
+        state.decay_circuit_breaker(current_time);
+
+        let time_diff = current_time.saturating_sub(self.circuit_breaker.last_trigger);
+        if time_diff >= self.circuit_breaker.cooldown_period {
+            0
+        } else {
+            state.circuit_breaker.current_amount
+        }
+    }
+
+    /// Largest part of `amount_in` the volume caps and circuit breaker still admit at `current_time`
+    pub fn fillable_amount(&self, amount_in: u64, current_time: u64) -> Result<u64> {
+        let volume = self.decayed_volume(current_time)?;
+        let daily_headroom = volume.max_daily.saturating_sub(volume.rolling_volume_at(current_time));
+        let hourly_headroom = volume.hourly_limit().saturating_sub(volume.hourly_volume_at(current_time));
+        let breaker_headroom = self.circuit_breaker.max_amount
+            .saturating_sub(self.decayed_circuit_breaker_amount(current_time));
+        Ok(amount_in.min(daily_headroom).min(hourly_headroom).min(breaker_headroom))
+    }
+
+    /// Summarizes every protection subsystem at `current_time`
+    pub fn health(&self, current_time: u64) -> Result<PoolHealth> {
+        let headroom_bps = |used: u64, cap: u64| -> u64 {
+            if cap == 0 {
+                return 0;
+            }
+            ((cap - used.min(cap)) as u128 * 10000 / cap as u128) as u64
+        };
+        let near_cap = |headroom: u64| headroom <= 10000 - HEALTH_NEAR_CAP_BPS;
+
+        let circuit_breaker_headroom_bps = headroom_bps(
+            self.decayed_circuit_breaker_amount(current_time),
+            self.circuit_breaker.max_amount,
+        );
+        let volume = self.decayed_volume(current_time)?;
+        let volume_headroom_bps = headroom_bps(volume.rolling_volume_at(current_time), volume.max_daily);
+        let hourly_volume_headroom_bps = headroom_bps(volume.hourly_volume_at(current_time), volume.hourly_limit());
+        let hourly_trades_headroom_bps = headroom_bps(
+            self.hourly_trade_count_at(current_time) as u64,
+            self.hourly_trade_limit() as u64,
+        );
+
+        let mut health = PoolHealth {
+            healthy: false,
+            paused: self.pause_level.halts_trading() || self.retired,
+            emergency_paused: self.is_emergency_paused,
+            post_resume_cooldown: self.check_post_resume_cooldown(current_time as i64).is_err(),
+            circuit_breaker_tripped: self.circuit_breaker.paused_by_breaker || circuit_breaker_headroom_bps == 0,
+            circuit_breaker_headroom_bps,
+            volume_near_cap: near_cap(volume_headroom_bps) || near_cap(hourly_volume_headroom_bps),
+            volume_headroom_bps,
+            hourly_volume_headroom_bps,
+            hourly_trades_near_cap: near_cap(hourly_trades_headroom_bps),
+            hourly_trades_headroom_bps,
+        };
+        health.healthy = !(health.paused
+            || health.emergency_paused
+            || health.post_resume_cooldown
+            || health.circuit_breaker_tripped
+            || health.volume_near_cap
+            || health.hourly_trades_near_cap);
+        Ok(health)
+    }
+
+    /// Rolls the hourly volume buckets forward to the current hour
+    pub fn roll_volume_buckets(&mut self, current_time: u64) {
+        self.volume.roll_buckets(current_time);
     }
 
     pub fn update_volume(&mut self, amount: u64, current_time: u64) -> Result<()> {
-        self.decay_volume(current_time)?;
-        self.volume.current_volume = self.volume.current_volume.saturating_add(amount);
+        self.roll_volume_buckets(current_time);
+        self.volume.record(amount, current_time);
+        self.volume.record_hourly(amount, current_time);
         Ok(())
     }
 
-    pub fn check_volume_limit(&self, current_time: u64) -> Result<()> {
-        if current_time < self.volume.last_reset {
-            return Err(crate::ErrorCode::InvalidTimestamp.into());
+    /// Trade count cap for one hourly window
+    ///
+    /// Pools written before the cap existed read it as zero and get the default.
+    pub fn hourly_trade_limit(&self) -> u32 {
+        if self.max_hourly_trades == 0 {
+            MAX_HOURLY_TRADES
+        } else {
+            self.max_hourly_trades
         }
+    }
 
-        let time_diff = current_time - self.volume.last_reset;
-        if time_diff >= self.volume.decay_period {
-            return Ok(());
+    /// Trades in the hourly window live at `current_time`, zero once it has lapsed
+    pub fn hourly_trade_count_at(&self, current_time: u64) -> u32 {
+        if current_time >= self.hourly_trades_window_start.saturating_add(MAX_HOURLY_TRADES_WINDOW) {
+            0
+        } else {
+            self.hourly_trade_count
         }
+    }
 
+    /// Checks that one more trade fits in the hourly window
+    pub fn check_hourly_trade_limit(&self, current_time: u64) -> Result<()> {
+        let count = self.hourly_trade_count_at(current_time);
         validate_condition!(
-            self.volume.current_volume <= self.volume.max_daily,
-            crate::ErrorCode::VolumeLimitExceeded
+            count < self.hourly_trade_limit(),
+            crate::ErrorCode::HourlyTradeLimitExceeded,
+            "Hourly trade limit reached: {} (max: {})",
+            count,
+            self.hourly_trade_limit()
         );
         Ok(())
     }
 
-    pub fn check_rate_limit(&self, amount: u64, current_time: u64) -> Result<()> {
-        if current_time < self.rate_limit.last_reset {
-            return Err(crate::ErrorCode::InvalidTimestamp.into());
+    /// Counts a trade in the hourly window, starting a new window if the last one lapsed
+    pub fn record_hourly_trade(&mut self, current_time: u64) {
+        if current_time >= self.hourly_trades_window_start.saturating_add(MAX_HOURLY_TRADES_WINDOW) {
+            self.hourly_trades_window_start = current_time;
+            self.hourly_trade_count = 0;
         }
+        self.hourly_trade_count = self.hourly_trade_count.saturating_add(1);
+    }
 
-        let time_diff = current_time - self.rate_limit.last_reset;
+    /// Checks that `amount` fits under the hourly cap, for bursts well inside the daily cap
+    pub fn check_hourly_volume_limit(&self, amount: u64, current_time: u64) -> Result<()> {
+        let new_volume = self.volume.hourly_volume_at(current_time).saturating_add(amount);
+        validate_condition!(
+            new_volume <= self.volume.hourly_limit(),
+            crate::ErrorCode::HourlyVolumeLimitExceeded,
+            "Hourly volume limit exceeded: {} > {}",
+            new_volume,
+            self.volume.hourly_limit()
+        );
+        Ok(())
+    }
+
+    /// Daily volume the pool would reach with a trade of `amount`
+    pub fn attempted_daily_volume(&self, amount: u64, current_time: u64) -> u64 {
+        self.volume.rolling_volume_at(current_time).saturating_add(amount)
+    }
+
+    /// Checks that `amount` fits under the daily cap, measured over the live hourly buckets
+    pub fn check_volume_limit(&self, amount: u64, current_time: u64) -> Result<()> {
+        let new_volume = self.attempted_daily_volume(amount, current_time);
+        validate_condition!(
+            new_volume <= self.volume.max_daily,
+            crate::ErrorCode::VolumeLimitExceeded,
+            "Daily volume limit exceeded: {} > {}",
+            new_volume,
+            self.volume.max_daily
+        );
+        Ok(())
+    }
+
+    pub fn check_rate_limit(&self, amount: u64, current_time: u64) -> Result<()> {
+        // A clock behind the last reset counts as no time passed
+        let time_diff = current_time.saturating_sub(self.rate_limit.last_reset);
         if time_diff >= self.rate_limit.window_size {
             return Ok(());
         }
@@ -1489,11 +4776,8 @@ impl PoolState {
     }
 
     pub fn update_rate_limit(&mut self, amount: u64, current_time: u64) -> Result<()> {
-        if current_time < self.rate_limit.last_reset {
-            return Err(crate::ErrorCode::InvalidTimestamp.into());
-        }
-
-        let time_diff = current_time - self.rate_limit.last_reset;
+        // A clock behind the last reset counts as no time passed
+        let time_diff = current_time.saturating_sub(self.rate_limit.last_reset);
         if time_diff >= self.rate_limit.window_size {
             self.rate_limit.last_reset = current_time;
             return Ok(());
@@ -1501,98 +4785,649 @@ impl PoolState {
 
         self.rate_limit.current_window = self.rate_limit.current_window.saturating_add(amount);
         validate_condition!(
-            self.rate_limit.current_window <= self.rate_limit.max_per_window,
-            crate::ErrorCode::RateLimitExceeded
+            self.rate_limit.current_window <= self.rate_limit.max_per_window,
+            crate::ErrorCode::RateLimitExceeded
+        );
+        Ok(())
+    }
+
+    pub fn check_circuit_breaker(&self, amount: u64, current_time: u64) -> Result<()> {
+        // A clock behind the last trigger counts as no time passed
+        let time_diff = current_time.saturating_sub(self.circuit_breaker.last_trigger);
+        if time_diff >= self.circuit_breaker.cooldown_period {
+            return Ok(());
+        }
+
+        validate_condition!(
+            amount <= self.circuit_breaker.max_amount,
+            crate::ErrorCode::CircuitBreakerTriggered
+        );
+        Ok(())
+    }
+
+    /// Accumulates `amount` into the circuit breaker window
+    ///
+    /// Returns `true` when the amount would push the accumulator past `max_amount`.
+    /// When `auto_pause_on_breaker` is set, tripping also pauses the pool until an
+    /// admin calls `reset_circuit_breaker`. A clock reading behind the last
+    /// trigger counts as no time passed: the cooldown does not lapse and
+    /// nothing decays, but the amount is still checked.
+    pub fn update_circuit_breaker(&mut self, amount: u64, current_time: u64) -> Result<bool> {
+        let (tripped, warning) = self.update_circuit_breaker_deferred(amount, current_time)?;
+        if let Some(event) = warning {
+            emit!(event);
+        }
+        Ok(tripped)
+    }
+
+    /// Accumulates like `update_circuit_breaker`, returning the warning event instead of emitting it
+    ///
+    /// A trip is logged only when it pauses the pool: a trip that leaves the
+    /// pool trading fails the caller and is rolled back. The warning is held
+    /// for the caller to emit once the trade settles.
+    pub fn update_circuit_breaker_deferred(
+        &mut self,
+        amount: u64,
+        current_time: u64,
+    ) -> Result<(bool, Option<CircuitBreakerApproaching>)> {
+        self.decay_circuit_breaker(current_time);
+
+        let time_diff = current_time.saturating_sub(self.circuit_breaker.last_trigger);
+        if time_diff >= self.circuit_breaker.cooldown_period {
+            self.circuit_breaker.current_amount = 0;
+            self.circuit_breaker.last_trigger = current_time;
+            self.circuit_breaker.last_decay = current_time;
+        }
+
+        let new_amount = self.circuit_breaker.current_amount.saturating_add(amount);
+        if new_amount > self.circuit_breaker.max_amount {
+            self.circuit_breaker.last_trigger = current_time;
+
+            let was_halted = self.pause_level.halts_trading();
+            if self.circuit_breaker.auto_pause_on_breaker && !was_halted {
+                self.pause_level = PauseLevel::TradingOnly;
+                self.circuit_breaker.paused_by_breaker = true;
+                self.last_update = current_time;
+            }
+
+            if self.pause_level.halts_trading() {
+                emit!(CircuitBreakerTriggered {
+                    pool: self.key(),
+                    volume_24h: new_amount,
+                    threshold: self.circuit_breaker.max_amount,
+                    ts: current_time as i64,
+                });
+            }
+            if !was_halted && self.pause_level.halts_trading() {
+                emit!(PoolPaused {
+                    pool: self.key(),
+                    admin_pubkey: self.admin,
+                    pause_level: PauseLevel::TradingOnly,
+                    ts: current_time as i64,
+                });
+            }
+            return Ok((true, None));
+        }
+
+        // Flag the first crossing of the warning threshold
+        let warning = self.crosses_circuit_breaker_warning(new_amount).then(|| CircuitBreakerApproaching {
+            pool: self.key(),
+            current_amount: new_amount,
+            max_amount: self.circuit_breaker.max_amount,
+            ts: current_time as i64,
+        });
+
+        self.circuit_breaker.current_amount = new_amount;
+        Ok((false, warning))
+    }
+
+    /// Accumulator level that emits `CircuitBreakerApproaching`
+    ///
+    /// Pools written before the threshold was configurable read it as zero and
+    /// get `CIRCUIT_BREAKER_HIGH_WATER_BPS`.
+    pub fn circuit_breaker_warn_threshold(&self) -> u64 {
+        let warn_threshold_bps = match self.circuit_breaker.warn_threshold_bps {
+            0 => CIRCUIT_BREAKER_HIGH_WATER_BPS,
+            bps => bps,
+        };
+        ((self.circuit_breaker.max_amount as u128 * warn_threshold_bps as u128) / 10000) as u64
+    }
+
+    /// Whether raising the accumulator to `new_amount` first crosses the warning threshold
+    pub fn crosses_circuit_breaker_warning(&self, new_amount: u64) -> bool {
+        let warn_threshold = self.circuit_breaker_warn_threshold();
+        self.circuit_breaker.current_amount < warn_threshold && new_amount >= warn_threshold
+    }
+
+    /// Drains the circuit breaker accumulator for the time since it last decayed
+    ///
+    /// The accumulator leaks `decay_bps` of `max_amount` per decay window, so a
+    /// long idle period clamps it to zero. A clock reading behind `last_decay`
+    /// decays nothing and leaves the decay clock where it is.
+    pub fn decay_circuit_breaker(&mut self, current_time: u64) {
+        let Some(elapsed) = current_time.checked_sub(self.circuit_breaker.last_decay) else {
+            return;
+        };
+
+        // Time spent empty must not build up credit against later trades
+        if self.circuit_breaker.current_amount == 0 {
+            self.circuit_breaker.last_decay = current_time;
+            return;
+        }
+
+        let drained = (self.circuit_breaker.max_amount as u128)
+            .saturating_mul(self.circuit_breaker.decay_bps as u128)
+            .saturating_mul(elapsed as u128)
+            / (10000 * CIRCUIT_BREAKER_DECAY_WINDOW as u128);
+
+        // Keep the clock until a whole unit drains, so frequent trades still decay
+        if drained == 0 {
+            return;
+        }
+
+        let drained = u64::try_from(drained).unwrap_or(u64::MAX);
+        self.circuit_breaker.current_amount = self.circuit_breaker.current_amount.saturating_sub(drained);
+        self.circuit_breaker.last_decay = current_time;
+    }
+
+    /// Starts a new rate-limit window if the current one has lapsed
+    ///
+    /// Returns whether the window rolled. A clock behind the last reset rolls nothing.
+    pub fn roll_rate_limit_window(&mut self, current_time: u64) -> bool {
+        if current_time.saturating_sub(self.rate_limit.last_reset) < self.rate_limit.window_seconds {
+            return false;
+        }
+        self.rate_limit.count = 0;
+        self.rate_limit.current_window = 0;
+        self.rate_limit.last_reset = current_time;
+        true
+    }
+
+    /// Advances every time-decayed protection counter to `current_time`
+    ///
+    /// Each step is a no-op when its state is already current, so repeated
+    /// calls are cheap and change nothing.
+    pub fn tick(&mut self, current_time: u64) -> Result<()> {
+        self.decay_volume(current_time)?;
+        self.roll_rate_limit_window(current_time);
+        self.decay_circuit_breaker(current_time);
+        Ok(())
+    }
+
+    pub fn reset_rate_limit(&mut self, current_time: u64) -> Result<()> {
+        let old_count = self.rate_limit.count;
+        self.rate_limit.count = 0;
+        self.rate_limit.last_reset = current_time;
+
+        emit!(RateLimitReset {
+            pool: self.key(),
+            old_count,
+            new_count: 0,
+            ts: current_time as i64,
+        });
+
+        Ok(())
+    }
+
+    /// Price impact cap for the next trade
+    ///
+    /// The configured `max_price_impact_bps`, narrowed toward the volatility
+    /// floor when adaptive mode is on and enough price history exists.
+    pub fn effective_max_price_impact_bps(&self) -> u64 {
+        self.volatility.max_price_impact_bps(self.protection.max_price_impact_bps)
+    }
+
+    /// Price impact cap for a trade of `amount_in`
+    ///
+    /// As `effective_max_price_impact_bps`, starting from the size tier's cap.
+    pub fn effective_max_price_impact_bps_for_size(&self, amount_in: u64) -> u64 {
+        self.volatility.max_price_impact_bps(self.protection.max_price_impact_bps_for_size(amount_in))
+    }
+
+    /// Checks volatility-adaptive thresholds: floor at or below `max_price_impact_bps`,
+    /// and volatility scale within 1..=10000 while enabled
+    pub fn validate_volatility_settings(settings: &VolatilitySettingsUpdate, max_price_impact_bps: u64) -> Result<()> {
+        validate_condition!(
+            settings.floor_impact_bps <= max_price_impact_bps,
+            crate::ErrorCode::InvalidVolatilitySettings,
+            "Impact floor {} exceeds the static cap {}",
+            settings.floor_impact_bps,
+            max_price_impact_bps
+        );
+        // Disabled mode keeps whatever scale it had, including none
+        validate_parameter!(
+            settings.max_volatility_bps,
+            u64::from(settings.enabled),
+            10000,
+            crate::ErrorCode::InvalidVolatilitySettings
+        );
+        Ok(())
+    }
+
+    /// Checks sandwich detection thresholds: window within 1..=MAX_SANDWICH_WINDOW_SLOTS
+    /// and large trade size within 1..=10000 while enabled, similarity at most 10000,
+    /// surcharge at most MAX_SANDWICH_SURCHARGE_BPS
+    pub fn validate_sandwich_settings(settings: &SandwichSettingsUpdate) -> Result<()> {
+        // Disabled detection keeps whatever thresholds it had, including none
+        let min = u64::from(settings.enabled);
+        validate_parameter!(
+            settings.window_slots,
+            min,
+            MAX_SANDWICH_WINDOW_SLOTS,
+            crate::ErrorCode::InvalidSandwichSettings
+        );
+        validate_parameter!(settings.large_trade_bps, min, 10000, crate::ErrorCode::InvalidSandwichSettings);
+        validate_parameter!(settings.similarity_bps, 0, 10000, crate::ErrorCode::InvalidSandwichSettings);
+        validate_parameter!(
+            settings.surcharge_bps,
+            0,
+            MAX_SANDWICH_SURCHARGE_BPS,
+            crate::ErrorCode::InvalidSandwichSettings
+        );
+        Ok(())
+    }
+
+    /// Checks size-tiered price impact caps: bounded count, strictly increasing
+    /// thresholds, and each cap within 1..=10000
+    pub fn validate_price_impact_tiers(tiers: &[PriceImpactTier]) -> Result<()> {
+        validate_condition!(
+            tiers.len() <= MAX_PRICE_IMPACT_TIERS,
+            crate::ErrorCode::InvalidPriceImpactTiers,
+            "Too many price impact tiers: {} (max: {})",
+            tiers.len(),
+            MAX_PRICE_IMPACT_TIERS
+        );
+
+        let mut prev_threshold = None;
+        for (i, tier) in tiers.iter().enumerate() {
+            validate_condition!(
+                !prev_threshold.is_some_and(|prev| tier.size_threshold <= prev),
+                crate::ErrorCode::InvalidPriceImpactTiers,
+                "Price impact tier {} threshold {} not strictly increasing",
+                i,
+                tier.size_threshold
+            );
+            validate_condition!(
+                (1..=10000).contains(&tier.max_impact_bps),
+                crate::ErrorCode::InvalidPriceImpactTiers,
+                "Price impact tier {} cap {} bps outside 1..=10000",
+                i,
+                tier.max_impact_bps
+            );
+            prev_threshold = Some(tier.size_threshold);
+        }
+        Ok(())
+    }
+
+    /// Whether nothing has updated the pool for longer than `max_staleness`
+    pub fn is_stale(&self, current_time: u64) -> bool {
+        self.max_staleness > 0
+            && current_time.saturating_sub(self.last_update) > self.max_staleness as u64
+    }
+
+    /// Rejects trading on a stale pool until an admin refreshes it
+    pub fn check_staleness(&self, current_time: u64) -> Result<()> {
+        validate_condition!(
+            !self.is_stale(current_time),
+            crate::ErrorCode::PoolStale,
+            "Pool last updated at {}, more than {}s before {}",
+            self.last_update,
+            self.max_staleness,
+            current_time
+        );
+        Ok(())
+    }
+
+    /// Key that signs fee withdrawals and distributions
+    pub fn fee_authority(&self) -> Pubkey {
+        if self.fee_authority == Pubkey::default() {
+            self.admin
+        } else {
+            self.fee_authority
+        }
+    }
+
+    /// Records `new_fee_authority` as pending; it takes over once it accepts
+    pub fn propose_fee_authority(&mut self, new_fee_authority: Pubkey) -> Result<()> {
+        validate_condition!(
+            new_fee_authority != Pubkey::default() && new_fee_authority != self.fee_authority(),
+            crate::ErrorCode::InvalidFeeAuthority,
+            "Invalid fee authority: {}",
+            new_fee_authority
+        );
+        self.pending_fee_authority = Some(new_fee_authority);
+        Ok(())
+    }
+
+    /// Hands fees to the pending fee authority once it signs; returns the old one
+    pub fn accept_fee_authority(&mut self, signer: &Pubkey) -> Result<Pubkey> {
+        let pending = self.pending_fee_authority.ok_or(crate::ErrorCode::NoPendingFeeAuthority)?;
+        validate_condition!(
+            signer == &pending,
+            crate::ErrorCode::Unauthorized,
+            "Only the pending fee authority {} can accept",
+            pending
+        );
+        let old_fee_authority = self.fee_authority();
+        self.fee_authority = pending;
+        self.pending_fee_authority = None;
+        Ok(old_fee_authority)
+    }
+
+    /// Drops the pending fee authority; returns it
+    pub fn cancel_fee_authority_transfer(&mut self) -> Result<Pubkey> {
+        let pending = self.pending_fee_authority.take().ok_or(crate::ErrorCode::NoPendingFeeAuthority)?;
+        Ok(pending)
+    }
+
+    /// Rejects a fee withdrawal until `fee_withdraw_cooldown` has passed since the last one
+    pub fn check_fee_withdraw_cooldown(&self, current_time: i64) -> Result<()> {
+        if self.fee_withdraw_cooldown == 0 || self.last_fee_withdraw_ts == 0 {
+            return Ok(());
+        }
+        let ends_at = self.last_fee_withdraw_ts.saturating_add(self.fee_withdraw_cooldown);
+        validate_condition!(
+            current_time >= ends_at,
+            crate::ErrorCode::FeeWithdrawCooldown,
+            "Fees can next be withdrawn at {} (now: {})",
+            ends_at,
+            current_time
         );
         Ok(())
     }
 
-    pub fn check_circuit_breaker(&self, amount: u64, current_time: u64) -> Result<()> {
-        if current_time < self.circuit_breaker.last_trigger {
-            return Err(crate::ErrorCode::InvalidTimestamp.into());
-        }
+    /// When trading reopens after the last emergency resume
+    pub fn post_resume_cooldown_ends_at(&self) -> i64 {
+        self.resumed_at.saturating_add(self.post_resume_cooldown)
+    }
 
-        let time_diff = current_time - self.circuit_breaker.last_trigger;
-        if time_diff >= self.circuit_breaker.cooldown_period {
+    /// Rejects trades until the post-resume cooldown has elapsed
+    pub fn check_post_resume_cooldown(&self, current_time: i64) -> Result<()> {
+        if self.post_resume_cooldown == 0 || self.resumed_at == 0 {
             return Ok(());
         }
-
+        let ends_at = self.post_resume_cooldown_ends_at();
         validate_condition!(
-            amount <= self.circuit_breaker.max_amount,
-            crate::ErrorCode::CircuitBreakerTriggered
+            current_time >= ends_at,
+            crate::ErrorCode::PostResumeCooldown,
+            "Trading resumes at {} (now: {})",
+            ends_at,
+            current_time
         );
         Ok(())
     }
 
-    pub fn update_circuit_breaker(&mut self, amount: u64, current_time: u64) -> Result<()> {
-        if current_time < self.circuit_breaker.last_trigger {
-            return Err(crate::ErrorCode::InvalidTimestamp.into());
+    /// LP fee discount in bps for a trader holding `balance` governance tokens
+    ///
+    /// The highest tier the balance reaches applies; no governance mint or a
+    /// zero balance gets no discount.
+    pub fn governance_discount_bps(&self, balance: u64) -> u64 {
+        if self.governance_mint.is_none() || balance == 0 {
+            return 0;
         }
+        self.governance_discount_tiers
+            .iter()
+            .rev()
+            .find(|tier| balance >= tier.min_balance)
+            .map_or(0, |tier| tier.discount_bps)
+    }
 
-        let time_diff = current_time - self.circuit_breaker.last_trigger;
-        if time_diff >= self.circuit_breaker.cooldown_period {
-            self.circuit_breaker.last_trigger = current_time;
+    /// Takes `discount_bps` off the LP fee, never below `MINIMUM_FEE`
+    ///
+    /// The discount rounds down so it never costs the pool more than the
+    /// schedule says. The protocol fee is not discounted.
+    pub fn apply_governance_discount(&self, fees: TradeFees, discount_bps: u64) -> TradeFees {
+        let discount = (fees.lp_fee as u128 * discount_bps.min(10000) as u128 / 10000) as u64;
+        let floor = MINIMUM_FEE.min(fees.lp_fee);
+        TradeFees {
+            lp_fee: fees.lp_fee.saturating_sub(discount).max(floor),
+            ..fees
+        }
+    }
+
+    /// Splits `amount` by the fee recipients' shares, in recipient order
+    ///
+    /// Each share rounds down and the dust goes to the first recipient, so
+    /// the amounts always add up to `amount`.
+    pub fn split_among_fee_recipients(&self, amount: u64) -> Vec<u64> {
+        let mut amounts: Vec<u64> = self
+            .fee_recipients
+            .iter()
+            .map(|recipient| (amount as u128 * recipient.share_bps as u128 / FEE_RECIPIENT_TOTAL_BPS as u128) as u64)
+            .collect();
+        let dust = amount.saturating_sub(amounts.iter().sum());
+        if let Some(first) = amounts.first_mut() {
+            *first += dust;
+        }
+        amounts
+    }
+
+    /// Checks a fee recipient list before it is stored
+    ///
+    /// A non-empty list needs distinct, non-default accounts with nonzero
+    /// shares adding up to exactly FEE_RECIPIENT_TOTAL_BPS.
+    pub fn validate_fee_recipients(recipients: &[FeeRecipient]) -> Result<()> {
+        validate_condition!(
+            recipients.len() <= MAX_FEE_RECIPIENTS,
+            crate::ErrorCode::InvalidFeeRecipients,
+            "Too many fee recipients: {} (max: {})",
+            recipients.len(),
+            MAX_FEE_RECIPIENTS
+        );
+        if recipients.is_empty() {
             return Ok(());
         }
 
+        let mut seen = HashSet::new();
+        let mut total_bps: u64 = 0;
+        for (i, recipient) in recipients.iter().enumerate() {
+            validate_condition!(
+                recipient.pubkey != Pubkey::default() && seen.insert(recipient.pubkey),
+                crate::ErrorCode::InvalidFeeRecipients,
+                "Fee recipient {} is the default pubkey or a duplicate",
+                i
+            );
+            validate_condition!(
+                recipient.share_bps > 0,
+                crate::ErrorCode::InvalidFeeRecipients,
+                "Fee recipient {} has a zero share",
+                i
+            );
+            total_bps = total_bps.saturating_add(recipient.share_bps);
+        }
         validate_condition!(
-            amount <= self.circuit_breaker.max_amount,
-            crate::ErrorCode::CircuitBreakerTriggered
+            total_bps == FEE_RECIPIENT_TOTAL_BPS,
+            crate::ErrorCode::InvalidFeeRecipients,
+            "Fee recipient shares sum to {} bps, expected {}",
+            total_bps,
+            FEE_RECIPIENT_TOTAL_BPS
         );
         Ok(())
     }
 
-    pub fn reset_rate_limit(&mut self, current_time: u64) -> Result<()> {
-        let old_count = self.rate_limit.count;
-        self.rate_limit.count = 0;
-        self.rate_limit.last_reset = current_time;
+    /// Checks a governance discount schedule before it is stored
+    ///
+    /// Tiers must reach strictly higher balances with non-decreasing discounts,
+    /// so a larger holding never earns less.
+    pub fn validate_governance_discount_tiers(tiers: &[GovernanceDiscountTier]) -> Result<()> {
+        validate_condition!(
+            tiers.len() <= MAX_GOVERNANCE_DISCOUNT_TIERS,
+            crate::ErrorCode::InvalidGovernanceDiscount,
+            "Too many governance discount tiers: {} (max: {})",
+            tiers.len(),
+            MAX_GOVERNANCE_DISCOUNT_TIERS
+        );
+        for (i, tier) in tiers.iter().enumerate() {
+            validate_condition!(
+                tier.min_balance > 0,
+                crate::ErrorCode::InvalidGovernanceDiscount,
+                "Governance tier {} has a zero minimum balance",
+                i
+            );
+            validate_condition!(
+                tier.discount_bps > 0 && tier.discount_bps <= MAX_GOVERNANCE_DISCOUNT_BPS,
+                crate::ErrorCode::InvalidGovernanceDiscount,
+                "Governance tier {} discount {} bps outside 1..={}",
+                i,
+                tier.discount_bps,
+                MAX_GOVERNANCE_DISCOUNT_BPS
+            );
+            if i > 0 {
+                let previous = &tiers[i - 1];
+                validate_condition!(
+                    tier.min_balance > previous.min_balance && tier.discount_bps >= previous.discount_bps,
+                    crate::ErrorCode::InvalidGovernanceDiscount,
+                    "Governance tier {} must reach a higher balance for no smaller discount",
+                    i
+                );
+            }
+        }
+        Ok(())
+    }
 
-        emit!(RateLimitReset {
-            pool: self.key(),
-            old_count,
-            new_count: 0,
-            ts: current_time as i64,
-        });
+    /// Most traders one blacklist batch may contain
+    ///
+    /// Pools written before the cap was configurable read it as zero and get
+    /// the default.
+    pub fn blacklist_batch_limit(&self) -> usize {
+        let limit = if self.max_blacklist_batch == 0 {
+            DEFAULT_MAX_BLACKLIST_BATCH
+        } else {
+            self.max_blacklist_batch
+        };
+        limit.min(MAX_BLACKLIST_BATCH) as usize
+    }
 
-        Ok(())
+    /// Copies configuration from `source`, leaving balances and runtime state alone
+    ///
+    /// Used when moving a pool to a new account. Admin keys other than `admin`,
+    /// fee and protection settings, limits, timelocks and access lists carry
+    /// over. Identity (admin, mint, pool id), balances, counters, pause state and
+    /// pending schedules stay as they are on this pool.
+    pub fn copy_config_from(&mut self, source: &PoolState) {
+        self.emergency_admin = source.emergency_admin;
+        self.guardian = source.guardian;
+        self.min_liquidity_floor = source.min_liquidity_floor;
+        self.liquidity_grace_period = source.liquidity_grace_period;
+        self.insurance_fund = source.insurance_fund;
+        self.insurance_fee_share_bps = source.insurance_fee_share_bps;
+        self.recovery_account = source.recovery_account;
+        self.fee_authority = source.fee_authority;
+
+        // Fees; an empty source set keeps this pool's tiers rather than clearing them
+        if !source.fee_tiers.is_empty() {
+            self.fee_tiers = source.fee_tiers.clone();
+        }
+        self.fee_tiers_locked = source.fee_tiers_locked;
+        self.max_fee_tiers = source.max_fee_tiers;
+        self.max_blacklist_batch = source.max_blacklist_batch;
+        self.default_fee_bps = source.default_fee_bps;
+        self.fee_rounding = source.fee_rounding;
+        self.protocol_fee_bps = source.protocol_fee_bps;
+        self.referral_share_bps = source.referral_share_bps;
+
+        // Limits, keeping this pool's windows and accumulators
+        self.volume.max_daily = source.volume.max_daily;
+        self.volume.decay_period = source.volume.decay_period;
+        self.volume.max_hourly = source.volume.max_hourly;
+        self.rate_limit.window_seconds = source.rate_limit.window_seconds;
+        self.rate_limit.max_calls = source.rate_limit.max_calls;
+        self.rate_limit.window_size = source.rate_limit.window_size;
+        self.rate_limit.max_per_window = source.rate_limit.max_per_window;
+        self.circuit_breaker.enabled = source.circuit_breaker.enabled;
+        self.circuit_breaker.threshold = source.circuit_breaker.threshold;
+        self.circuit_breaker.window = source.circuit_breaker.window;
+        self.circuit_breaker.cooldown = source.circuit_breaker.cooldown;
+        self.circuit_breaker.cooldown_period = source.circuit_breaker.cooldown_period;
+        self.circuit_breaker.max_amount = source.circuit_breaker.max_amount;
+        self.circuit_breaker.auto_pause_on_breaker = source.circuit_breaker.auto_pause_on_breaker;
+        self.circuit_breaker.decay_bps = source.circuit_breaker.decay_bps;
+        self.circuit_breaker.warn_threshold_bps = source.circuit_breaker.warn_threshold_bps;
+        self.protection = source.protection.clone();
+        self.trade_settings = TradeSettings {
+            last_trade_time: self.trade_settings.last_trade_time,
+            ..source.trade_settings.clone()
+        };
+        self.timelocks = source.timelocks.clone();
+        self.max_trades_per_tx = source.max_trades_per_tx;
+        self.max_hourly_trades = source.max_hourly_trades;
+        self.enforce_output_owner = source.enforce_output_owner;
+        self.max_output_fraction_bps = source.max_output_fraction_bps;
+        self.sandwich.configure(&source.sandwich.settings());
+        self.volatility.configure(&source.volatility.settings());
+        self.post_resume_cooldown = source.post_resume_cooldown;
+        self.governance_mint = source.governance_mint;
+        self.governance_discount_tiers = source.governance_discount_tiers.clone();
+        self.fee_recipients = source.fee_recipients.clone();
+        self.fee_free_threshold = source.fee_free_threshold;
+        self.fee_withdraw_cooldown = source.fee_withdraw_cooldown;
+        self.low_reserve = source.low_reserve.clone();
+
+        // Access lists
+        self.trader_blacklist = source.trader_blacklist.clone();
+        self.whitelist = source.whitelist.clone();
+        self.allowed_callers = source.allowed_callers.clone();
+    }
+
+    /// Operations currently paused, by `paused_ops` or by the pause level
+    pub fn effective_paused_ops(&self) -> u8 {
+        self.paused_ops | self.pause_level.paused_ops()
+    }
+
+    pub fn is_op_paused(&self, op: u8) -> bool {
+        self.effective_paused_ops() & op != 0
     }
 
     pub fn pause_pool(&mut self, current_time: u64) -> Result<()> {
-        require!(!self.is_paused, crate::ErrorCode::PoolPaused);
-        self.is_paused = true;
+        require!(!self.pause_level.halts_trading(), crate::ErrorCode::PoolPaused);
+        self.pause_level = PauseLevel::TradingOnly;
         self.last_update = current_time;
 
         emit!(PoolPaused {
             pool: self.key(),
             admin_pubkey: self.admin,
-            ts: current_time,
+            pause_level: PauseLevel::TradingOnly,
+            ts: current_time as i64,
         });
 
         Ok(())
     }
 
     pub fn resume_pool(&mut self, current_time: u64) -> Result<()> {
-        require!(self.is_paused, crate::ErrorCode::PoolNotPaused);
-        self.is_paused = false;
+        require!(self.pause_level.halts_trading(), crate::ErrorCode::PoolNotPaused);
+        self.pause_level = PauseLevel::None;
         self.last_update = current_time;
 
         emit!(PoolResumed {
             pool: self.key(),
             admin_pubkey: self.admin,
-            ts: current_time,
+            ts: current_time as i64,
         });
 
         Ok(())
     }
 
-    pub fn check_token_mint(&self, mint: &Account<Mint>) -> Result<()> {
+    pub fn check_token_mint(&self, mint: &InterfaceAccount<Mint>) -> Result<()> {
         validate_condition!(mint.key() == self.token_mint, crate::ErrorCode::InvalidTokenMint);
-        validate_condition!(mint.decimals == self.token_decimals, crate::ErrorCode::InvalidTokenDecimals);
+        self.check_token_decimals(mint.decimals)?;
         validate_condition!(mint.freeze_authority.is_none(), crate::ErrorCode::TokenMintHasFreezeAuthority);
+        utils::mint_authorities(&mint.to_account_info().try_borrow_data()?)?.check(self.allow_risky_mint)?;
+        Ok(())
+    }
+
+    /// Rejects a mint whose decimals differ from those captured at initialization
+    pub fn check_token_decimals(&self, decimals: u8) -> Result<()> {
+        validate_condition!(
+            decimals == self.token_decimals,
+            crate::ErrorCode::InvalidTokenDecimals,
+            "Invalid token decimals: expected {} but got {}",
+            self.token_decimals,
+            decimals
+        );
         Ok(())
     }
 
-    pub fn check_token_account(&self, account: &Account<TokenAccount>, mint: &Pubkey) -> Result<()> {
+    pub fn check_token_account(&self, account: &InterfaceAccount<TokenAccount>, mint: &Pubkey) -> Result<()> {
         if account.mint != *mint {
             msg!("Invalid token account mint: expected {} but got {}", mint, account.mint);
             return Err(crate::ErrorCode::InvalidTokenAccount.into());
@@ -1604,25 +5439,96 @@ impl PoolState {
         Ok(())
     }
 
-    pub fn emit_liquidity_added(&self, admin: &Pubkey, amount: u64, ts: i64) {
+    /// Output cap as a share of reserves, in bps
+    ///
+    /// Pools written before the guard existed read it as zero and are unguarded.
+    pub fn output_fraction_limit_bps(&self) -> u64 {
+        if self.max_output_fraction_bps == 0 {
+            MAX_OUTPUT_FRACTION_BPS
+        } else {
+            self.max_output_fraction_bps
+        }
+    }
+
+    /// Checks that a trade's output stays within the allowed share of reserves
+    pub fn check_output_fraction(&self, amount_out: u64) -> Result<()> {
+        let limit_bps = self.output_fraction_limit_bps();
+        if limit_bps >= MAX_OUTPUT_FRACTION_BPS {
+            return Ok(());
+        }
+        let max_amount_out = (self.total_liquidity as u128)
+            .checked_mul(limit_bps as u128)
+            .ok_or(crate::ErrorCode::Overflow)?
+            / MAX_OUTPUT_FRACTION_BPS as u128;
+        validate_condition!(
+            amount_out as u128 <= max_amount_out,
+            crate::ErrorCode::OutputExceedsReserveFraction,
+            "Output {} exceeds {} bps of reserves {}",
+            amount_out,
+            limit_bps,
+            self.total_liquidity
+        );
+        Ok(())
+    }
+
+    /// Checks a trader's token account owner when `enforce_output_owner` is set
+    ///
+    /// Without it, a signer who is only a delegate on someone else's account
+    /// can trade through that account.
+    pub fn check_token_account_owner(&self, account_owner: &Pubkey, trader: &Pubkey) -> Result<()> {
+        if !self.enforce_output_owner {
+            return Ok(());
+        }
+        validate_condition!(
+            account_owner == trader,
+            crate::ErrorCode::OutputAccountOwnerMismatch,
+            "Token account owner {} is not the trader {}",
+            account_owner,
+            trader
+        );
+        Ok(())
+    }
+
+    /// Returns the next event sequence number
+    ///
+    /// Events that share a timestamp can be ordered by this value.
+    pub fn next_event_seq(&mut self) -> u64 {
+        self.event_seq = self.event_seq.wrapping_add(1);
+        self.event_seq
+    }
+
+    pub fn emit_liquidity_added(&mut self, admin: &Pubkey, amount: u64, ts: i64) {
         emit!(LiquidityAdded {
             pool: self.key(),
             admin_pubkey: *admin,
             amount,
+            event_seq: self.next_event_seq(),
             ts,
         });
     }
 
-    pub fn emit_liquidity_removed(&self, admin: &Pubkey, amount: u64, ts: i64) {
+    pub fn emit_liquidity_removed(&mut self, admin: &Pubkey, amount: u64, ts: i64) {
         emit!(LiquidityRemoved {
             pool: self.key(),
             admin_pubkey: *admin,
             amount,
+            event_seq: self.next_event_seq(),
             ts,
         });
     }
 
-    pub fn emit_trade_executed(&self, buyer: &Pubkey, amount_in: u64, amount_out: u64, fee_amount: u64, fee_mode: u8, ts: i64) {
+    #[allow(clippy::too_many_arguments)]
+    pub fn emit_trade_executed(
+        &mut self,
+        buyer: &Pubkey,
+        amount_in: u64,
+        amount_out: u64,
+        fee_amount: u64,
+        fee_mode: u8,
+        fee_tier_index: Option<u8>,
+        direction: TradeDirection,
+        ts: i64,
+    ) {
         emit!(TradeExecuted {
             pool: self.key(),
             buyer_pubkey: *buyer,
@@ -1630,106 +5536,439 @@ impl PoolState {
             amount_out,
             fee_amount,
             fee_mode,
+            event_seq: self.next_event_seq(),
             ts,
             token_mint: self.token_mint,
+            fee_tier_index: fee_tier_index.unwrap_or(NO_FEE_TIER),
+            direction,
+        });
+    }
+
+    /// Calculates the price impact of a trade
+    /// 
+    /// # Arguments
+    /// * `amount_in` - The amount of tokens being traded
+    /// * `pool_balance` - The current pool balance
+    /// 
+    /// # Returns
+    /// * `Result<u64>` - The price impact in basis points
+    pub fn calculate_price_impact(&self, amount_in: u64, pool_balance: u64) -> Result<u64> {
+        if pool_balance == 0 {
+            return Ok(0);
+        }
+
+        // Calculate price impact in basis points
+        let impact = amount_in
+            .checked_mul(10000)
+            .ok_or(crate::ErrorCode::Overflow)?
+            .checked_div(pool_balance)
+            .ok_or(crate::ErrorCode::Overflow)?;
+
+        Ok(impact)
+    }
+
+    /// Calculate fee for a trade
+    /// 
+    /// This function calculates the fee for a trade based on:
+    /// 1. Whether we're in the early trade window
+    /// 2. The current volume and applicable fee tier
+    /// 3. Returns the fee amount, the fee mode and the fee tier index for tracking
+    ///
+    /// The tier index is `None` unless a fee tier priced the trade.
+    ///
+    /// The fee is the LP fee for its mode plus the protocol fee on top. Both
+    /// portions round per `fee_rounding`; the protocol fee gives way first
+    /// when their sum would pass `MAXIMUM_FEE_BPS`.
+    ///
+    /// Trades of at most `fee_free_threshold` pay nothing, `MINIMUM_FEE`
+    /// included, and report `FEE_MODE_NONE`.
+    pub fn calculate_fee(&self, amount_in: u64, current_time: i64) -> Result<(TradeFees, u8, Option<u8>)> {
+        if self.is_fee_free(amount_in) {
+            return Ok((TradeFees::default(), FEE_MODE_NONE, None));
+        }
+
+        let (lp_fee, fee_mode, tier_index) = self.calculate_lp_fee(amount_in, current_time)?;
+        let protocol_fee = if self.protocol_fee_bps == 0 {
+            0
+        } else {
+            self.fee_at_bps(amount_in, self.protocol_fee_bps)?
+                .min(self.max_fee(amount_in).saturating_sub(lp_fee))
+        };
+        Ok((TradeFees { lp_fee, protocol_fee }, fee_mode, tier_index))
+    }
+
+    /// Whether a trade of `amount_in` falls under the fee-free threshold; zero disables it
+    pub fn is_fee_free(&self, amount_in: u64) -> bool {
+        self.fee_free_threshold > 0 && amount_in <= self.fee_free_threshold
+    }
+
+    fn calculate_lp_fee(&self, amount_in: u64, current_time: i64) -> Result<(u64, u8, Option<u8>)> {
+        // Early trade fee until the launch phase ends
+        if self.is_early_trade(current_time) {
+            let fee_bps = self.total_fee_bps(self.trade_settings.early_trade_fee_bps)?;
+            let fee = self.fee_at_bps(amount_in, fee_bps)?;
+
+            return Ok((self.effective_fee(amount_in, fee)?, FEE_MODE_EARLY_TRADE, None));
+        }
+
+        // Find applicable fee tier based on volume and liquidity
+        if let Some(index) = self.fee_tier_index(self.volume.current_volume, self.total_liquidity) {
+            let fee_bps = self.total_fee_bps(self.fee_tiers[index].fee_bps)?;
+            let fee = self.fee_at_bps(amount_in, fee_bps)?;
+
+            return Ok((self.effective_fee(amount_in, fee)?, FEE_MODE_TIER_BASED, Some(index as u8)));
+        }
+
+        let fee = self.fallback_fee(amount_in)?;
+        Ok((self.effective_fee(amount_in, fee)?, FEE_MODE_NONE, None))
+    }
+
+    /// LP fee rate for a trade, checked against the fee cap
+    ///
+    /// A base rate above `MAXIMUM_FEE_BPS` is a misconfiguration and fails with
+    /// `FeeTooHigh`; surcharges added later are clamped to the cap instead.
+    pub fn total_fee_bps(&self, base_fee_bps: u64) -> Result<u64> {
+        validate_condition!(
+            base_fee_bps <= MAXIMUM_FEE_BPS,
+            crate::ErrorCode::FeeTooHigh,
+            "Base fee {} bps exceeds maximum {}",
+            base_fee_bps,
+            MAXIMUM_FEE_BPS
+        );
+        Ok(base_fee_bps)
+    }
+
+    /// Fee for `amount_in` at `fee_bps`, rounded per the pool's `fee_rounding`
+    fn fee_at_bps(&self, amount_in: u64, fee_bps: u64) -> Result<u64> {
+        let numerator = amount_in as u128 * fee_bps as u128;
+        let fee = match self.fee_rounding {
+            FeeRounding::Down => numerator / 10000,
+            FeeRounding::Up => numerator.div_ceil(10000),
+        };
+        u64::try_from(fee).map_err(|_| {
+            msg!("Fee calculation overflow: {} * {} bps", amount_in, fee_bps);
+            error!(crate::ErrorCode::Overflow)
+        })
+    }
+
+    /// Index of the fee tier that applies at the given volume and liquidity
+    ///
+    /// Tiers are sorted by increasing threshold, so this is the first tier
+    /// whose volume threshold the volume has not exceeded, or whose liquidity
+    /// threshold, if set, the liquidity has not exceeded. Earlier tiers charge
+    /// more, so whichever measure qualifies first picks the stricter tier.
+    pub fn fee_tier_index(&self, volume: u64, liquidity: u64) -> Option<usize> {
+        self.fee_tiers.iter().position(|tier| {
+            volume <= tier.volume_threshold
+                || tier.liquidity_threshold.is_some_and(|threshold| liquidity <= threshold)
+        })
+    }
+
+    /// Fee rate in bps at the given volume and the pool's current liquidity,
+    /// before the minimum fee is applied
+    ///
+    /// Falls back to `default_fee_bps` (or zero) when the volume is past every tier.
+    pub fn fee_bps_for_volume(&self, volume: u64) -> u16 {
+        match self.fee_tier_index(volume, self.total_liquidity) {
+            Some(index) => self.fee_tiers[index].fee_bps as u16,
+            None => self.default_fee_bps.unwrap_or(0),
+        }
+    }
+
+    /// Replaces a zero computed fee with the fallback fee, then clamps it
+    ///
+    /// Any nonzero trade pays at least `MINIMUM_FEE`, but never more than
+    /// `MAXIMUM_FEE_BPS` of `amount_in` (rounded up, so dust still pays the minimum).
+    fn effective_fee(&self, amount_in: u64, fee: u64) -> Result<u64> {
+        let fee = if fee == 0 {
+            self.fallback_fee(amount_in)?
+        } else {
+            fee
+        };
+        Ok(fee.max(MINIMUM_FEE).min(self.max_fee(amount_in)).min(amount_in))
+    }
+
+    /// When the early-trade phase ends
+    ///
+    /// The phase runs `early_trade_window_seconds` from `launch_ts`, or from
+    /// the pool start for pools that never scheduled a launch.
+    pub fn early_trade_ends_at(&self) -> i64 {
+        let launch = if self.launch_ts > 0 { self.launch_ts } else { self.pool_start_time as i64 };
+        launch.saturating_add(self.trade_settings.early_trade_window_seconds as i64)
+    }
+
+    /// Whether a trade at `current_time` pays the early-trade fee
+    ///
+    /// Trades before a scheduled launch count as early too. A zero window has
+    /// no early phase at all.
+    pub fn is_early_trade(&self, current_time: i64) -> bool {
+        self.trade_settings.early_trade_window_seconds > 0 && current_time < self.early_trade_ends_at()
+    }
+
+    /// Surcharge on a suspected sandwich back-run, within what `fees` leaves under the fee cap
+    pub fn sandwich_surcharge(&self, amount_in: u64, fees: TradeFees) -> Result<u64> {
+        let surcharge = self.fee_at_bps(amount_in, self.sandwich.surcharge_bps)?;
+        Ok(surcharge.min(self.max_fee(amount_in).saturating_sub(fees.total())))
+    }
+
+    /// Whether reserves are below the low reserve threshold
+    pub fn is_low_reserve(&self) -> bool {
+        self.total_liquidity < self.low_reserve.threshold
+    }
+
+    /// Rejects a trade above the size allowed while reserves are low
+    pub fn check_low_reserve_trade_size(&self, amount_in: u64) -> Result<()> {
+        let max_trade_size = self.low_reserve.max_trade_size;
+        validate_condition!(
+            max_trade_size == 0 || amount_in <= max_trade_size,
+            crate::ErrorCode::LowReserveTradeTooLarge,
+            "Trade {} exceeds {} allowed while reserves {} are below {}",
+            amount_in,
+            max_trade_size,
+            self.total_liquidity,
+            self.low_reserve.threshold
+        );
+        Ok(())
+    }
+
+    /// Low reserve surcharge on `amount_in`, within what `fees` leaves under the fee cap
+    pub fn low_reserve_surcharge(&self, amount_in: u64, fees: TradeFees) -> Result<u64> {
+        let surcharge = self.fee_at_bps(amount_in, self.low_reserve.surcharge_bps)?;
+        Ok(surcharge.min(self.max_fee(amount_in).saturating_sub(fees.total())))
+    }
+
+    /// Most a trade of `amount_in` may pay in total, rounded up so dust can pay `MINIMUM_FEE`
+    fn max_fee(&self, amount_in: u64) -> u64 {
+        (amount_in as u128 * MAXIMUM_FEE_BPS as u128).div_ceil(10000) as u64
+    }
+
+    /// Default fee if configured, otherwise minimum fee
+    fn fallback_fee(&self, amount_in: u64) -> Result<u64> {
+        match self.default_fee_bps {
+            Some(bps) => self.fee_at_bps(amount_in, self.total_fee_bps(bps as u64)?),
+            None => Ok(MINIMUM_FEE),
+        }
+    }
+
+    /// Liquidity that has been in the pool for at least the grace period
+    ///
+    /// Deposits still inside `liquidity_grace_period` are excluded, so liquidity
+    /// added just before a trade cannot loosen that trade's price impact check.
+    pub fn effective_liquidity(&self, current_time: u64) -> u64 {
+        let immature: u64 = self
+            .pending_deposits
+            .iter()
+            .filter(|deposit| !deposit.is_mature(self.liquidity_grace_period, current_time))
+            .map(|deposit| deposit.amount)
+            .fold(0u64, |sum, amount| sum.saturating_add(amount));
+        self.total_liquidity.saturating_sub(immature)
+    }
+
+    /// Records a deposit so it only counts as effective once the grace period ends
+    pub fn record_deposit(&mut self, amount: u64, current_time: u64) {
+        // Drop deposits that have matured; they are already fully effective
+        let grace_period = self.liquidity_grace_period;
+        self.pending_deposits
+            .retain(|deposit| !deposit.is_mature(grace_period, current_time));
+
+        // The first deposit into an empty pool has nothing to inflate, and a zero
+        // grace period keeps the previous behavior
+        if grace_period == 0 || self.total_liquidity == 0 {
+            return;
+        }
+
+        // When the queue is full, fold into the newest entry, which only delays maturity
+        if self.pending_deposits.len() >= MAX_PENDING_DEPOSITS {
+            if let Some(newest) = self.pending_deposits.last_mut() {
+                newest.amount = newest.amount.saturating_add(amount);
+                newest.deposited_at = current_time;
+            }
+            return;
+        }
+        self.pending_deposits.push(LiquidityDeposit {
+            amount,
+            deposited_at: current_time,
         });
     }
 
-    /// Calculates the price impact of a trade
-    /// 
-    /// # Arguments
-    /// * `amount_in` - The amount of tokens being traded
-    /// * `pool_balance` - The current pool balance
-    /// 
-    /// # Returns
-    /// * `Result<u64>` - The price impact in basis points
-    pub fn calculate_price_impact(&self, amount_in: u64, pool_balance: u64) -> Result<u64> {
-        if pool_balance == 0 {
-            return Ok(0);
-        }
-
-        // Calculate price impact in basis points
-        let impact = amount_in
-            .checked_mul(10000)
-            .ok_or(crate::ErrorCode::Overflow)?
-            .checked_div(pool_balance)
-            .ok_or(crate::ErrorCode::Overflow)?;
+    /// Withdrawals consume the freshest deposits first, so effective liquidity
+    /// only drops once all pending deposits have been withdrawn
+    pub fn consume_pending_deposits(&mut self, mut amount: u64) {
+        while amount > 0 {
+            let Some(newest) = self.pending_deposits.last_mut() else {
+                break;
+            };
+            let taken = newest.amount.min(amount);
+            newest.amount -= taken;
+            amount -= taken;
+            if newest.amount == 0 {
+                self.pending_deposits.pop();
+            }
+        }
+    }
+
+    /// Checks that a fee update leaves locked tiers alone
+    ///
+    /// While the tiers are locked an update must carry no tiers and keep the
+    /// lock; unlocking goes through `schedule_fee_tiers_unlock` instead.
+    pub fn validate_fee_tiers_lock(&self, settings: &FeeSettingsUpdate) -> Result<()> {
+        if !self.fee_tiers_locked {
+            return Ok(());
+        }
+        validate_condition!(
+            settings.fee_tiers.is_empty(),
+            crate::ErrorCode::FeeTiersLocked,
+            "Fee update changes {} locked tiers",
+            settings.fee_tiers.len()
+        );
+        validate_condition!(
+            settings.fee_tiers_locked,
+            crate::ErrorCode::FeeTiersLocked,
+            "Fee update unlocks the tiers; schedule the unlock instead"
+        );
+        Ok(())
+    }
+
+    /// Schedules unlocking the fee tiers after PARAMETER_UPDATE_TIMELOCK, returning when
+    pub fn schedule_fee_tiers_unlock(&mut self, current_time: u64) -> Result<u64> {
+        validate_condition!(self.fee_tiers_locked, crate::ErrorCode::FeeTiersNotLocked);
+        validate_condition!(
+            self.fee_tiers_unlock_time == 0,
+            crate::ErrorCode::FeeTiersUnlockPending,
+            "Fee tier unlock already scheduled for {}",
+            self.fee_tiers_unlock_time
+        );
+
+        let scheduled_time = utils::scheduled_time_after(current_time, PARAMETER_UPDATE_TIMELOCK)?;
+        self.fee_tiers_unlock_time = scheduled_time;
+        Ok(scheduled_time)
+    }
+
+    /// Unlocks the fee tiers once the scheduled unlock's timelock has expired
+    pub fn apply_fee_tiers_unlock(&mut self, current_time: u64) -> Result<()> {
+        validate_condition!(self.fee_tiers_unlock_time != 0, crate::ErrorCode::NoPendingFeeTiersUnlock);
+        validate_condition!(
+            current_time >= self.fee_tiers_unlock_time,
+            crate::ErrorCode::TimelockNotExpired,
+            "Fee tiers unlock at {}",
+            self.fee_tiers_unlock_time
+        );
+
+        self.fee_tiers_locked = false;
+        self.fee_tiers_unlock_time = 0;
+        Ok(())
+    }
+
+    /// Records a lock on `amount` of liquidity until `unlock_ts`
+    ///
+    /// Expired locks are dropped first, so only unexpired ones count against
+    /// `MAX_LIQUIDITY_LOCKS`.
+    pub fn lock_liquidity(&mut self, provider: &Pubkey, amount: u64, unlock_ts: i64, current_time: i64) -> Result<()> {
+        validate_condition!(
+            unlock_ts > current_time,
+            crate::ErrorCode::InvalidLiquidityLock,
+            "Unlock time {} is not after now {}",
+            unlock_ts,
+            current_time
+        );
+
+        self.release_expired_liquidity_locks(current_time);
+        validate_condition!(
+            self.liquidity_locks.len() < MAX_LIQUIDITY_LOCKS,
+            crate::ErrorCode::TooManyLiquidityLocks,
+            "Liquidity locks at capacity: {}",
+            MAX_LIQUIDITY_LOCKS
+        );
+
+        self.liquidity_locks.push(LiquidityLock { provider: *provider, amount, unlock_ts });
+        Ok(())
+    }
+
+    pub fn release_expired_liquidity_locks(&mut self, current_time: i64) {
+        self.liquidity_locks.retain(|lock| !lock.is_expired(current_time));
+    }
+
+    /// Liquidity still under an unexpired lock
+    pub fn locked_liquidity(&self, current_time: i64) -> u64 {
+        self.liquidity_locks
+            .iter()
+            .filter(|lock| !lock.is_expired(current_time))
+            .fold(0u64, |total, lock| total.saturating_add(lock.amount))
+    }
+
+    /// Rejects a removal that would dig into locked liquidity
+    pub fn check_liquidity_unlocked(&self, amount: u64, current_time: i64) -> Result<()> {
+        let locked = self.locked_liquidity(current_time);
+        let unlocked = self.total_liquidity.saturating_sub(locked);
+        validate_condition!(
+            amount <= unlocked,
+            crate::ErrorCode::LiquidityLocked,
+            "Removal {} exceeds unlocked liquidity {} ({} locked)",
+            amount,
+            unlocked,
+            locked
+        );
+        Ok(())
+    }
+
+    /// Seconds left on the pending parameter update's timelock, saturating at zero
+    pub fn timelock_remaining(&self, current_time: u64) -> Result<u64> {
+        let pending_update = self
+            .pending_update
+            .as_ref()
+            .ok_or(crate::ErrorCode::NoPendingUpdate)?;
+        Ok(pending_update.scheduled_time.saturating_sub(current_time))
+    }
 
-        Ok(impact)
+    pub fn schedule_emergency_pause(&mut self, current_time: u64) -> Result<u64> {
+        self.schedule_emergency(EmergencyActionKind::Pause, current_time)
     }
 
-    /// Calculate fee for a trade
-    /// 
-    /// This function calculates the fee for a trade based on:
-    /// 1. Whether we're in the early trade window
-    /// 2. The current volume and applicable fee tier
-    /// 3. Returns both the fee amount and the fee mode for tracking
-    fn calculate_fee(pool_state: &PoolState, amount_in: u64, current_time: i64) -> Result<(u64, u8)> {
-        // Early trade fee if within protection window
-        if current_time - pool_state.pool_start_time as i64 <= pool_state.trade_settings.early_trade_window_seconds as i64 {
-            let fee = amount_in
-                .checked_mul(pool_state.trade_settings.early_trade_fee_bps)
-                .ok_or(crate::ErrorCode::Overflow)?
-                .checked_div(10000)
-                .ok_or(crate::ErrorCode::Overflow)?;
-            
-            // Use default fee if configured, otherwise minimum fee
-            let effective_fee = if fee == 0 {
-                pool_state.default_fee_bps
-                    .map(|bps| amount_in.checked_mul(bps).ok_or(crate::ErrorCode::Overflow)?.checked_div(10000).ok_or(crate::ErrorCode::Overflow)?)
-                    .unwrap_or(MINIMUM_FEE)
-            } else {
-                fee.max(MINIMUM_FEE)
-            };
-            
-            return Ok((effective_fee, FEE_MODE_EARLY_TRADE));
-        }
-
-        // Find applicable fee tier based on volume
-        for tier in &pool_state.fee_tiers {
-            if pool_state.volume.current_volume <= tier.volume_threshold {
-                let fee = amount_in
-                    .checked_mul(tier.fee_bps)
-                    .ok_or(crate::ErrorCode::Overflow)?
-                    .checked_div(10000)
-                    .ok_or(crate::ErrorCode::Overflow)?;
-                
-                // Use default fee if configured, otherwise minimum fee
-                let effective_fee = if fee == 0 {
-                    pool_state.default_fee_bps
-                        .map(|bps| amount_in.checked_mul(bps).ok_or(crate::ErrorCode::Overflow)?.checked_div(10000).ok_or(crate::ErrorCode::Overflow)?)
-                        .unwrap_or(MINIMUM_FEE)
-                } else {
-                    fee.max(MINIMUM_FEE)
-                };
-                
-                return Ok((effective_fee, FEE_MODE_TIER_BASED));
-            }
-        }
+    pub fn schedule_emergency_resume(&mut self, current_time: u64) -> Result<u64> {
+        self.schedule_emergency(EmergencyActionKind::Resume, current_time)
+    }
 
-        // Use default fee if configured, otherwise minimum fee
-        let fallback_fee = pool_state.default_fee_bps
-            .map(|bps| amount_in.checked_mul(bps).ok_or(crate::ErrorCode::Overflow)?.checked_div(10000).ok_or(crate::ErrorCode::Overflow)?)
-            .unwrap_or(MINIMUM_FEE);
+    fn schedule_emergency(&mut self, action: EmergencyActionKind, current_time: u64) -> Result<u64> {
+        let scheduled_time = utils::scheduled_time_after(current_time, self.timelocks.emergency_seconds(action))?;
 
-        Ok((fallback_fee, FEE_MODE_NONE))
+        self.pending_emergency = Some(PendingEmergency { action, scheduled_time });
+        Ok(scheduled_time)
     }
 
-    pub fn schedule_emergency_pause(&mut self, current_time: u64) -> Result<()> {
-        let scheduled_time = current_time
-            .checked_add(EMERGENCY_TIMELOCK_SECONDS)
-            .ok_or(crate::ErrorCode::Overflow)?;
+    /// Consumes the pending emergency action once it matches `action` and its timelock has expired
+    pub fn take_pending_emergency(&mut self, action: EmergencyActionKind, current_time: u64) -> Result<()> {
+        let pending = self
+            .pending_emergency
+            .as_ref()
+            .ok_or(crate::ErrorCode::NoPendingEmergency)?;
+        validate_condition!(
+            pending.action == action,
+            crate::ErrorCode::EmergencyActionMismatch,
+            "Scheduled emergency action is {:?}",
+            pending.action
+        );
+        validate_condition!(
+            current_time >= pending.scheduled_time,
+            crate::ErrorCode::TimelockNotExpired,
+            "Emergency action available at {}",
+            pending.scheduled_time
+        );
 
-        self.emergency_action_scheduled_time = scheduled_time;
+        self.pending_emergency = None;
         Ok(())
     }
 
-    pub fn schedule_emergency_resume(&mut self, current_time: u64) -> Result<()> {
-        let scheduled_time = current_time
-            .checked_add(EMERGENCY_TIMELOCK_SECONDS)
-            .ok_or(crate::ErrorCode::Overflow)?;
-
-        self.emergency_action_scheduled_time = scheduled_time;
+    /// Invariant checked before every trade: the pool must have fee tiers
+    ///
+    /// Tier updates either replace the set with a validated non-empty one or
+    /// leave it alone, so this only fires on a corrupted or hand-built pool.
+    /// Failing here beats silently pricing every trade at the fallback fee.
+    pub fn check_fee_tiers_present(&self) -> Result<()> {
+        validate_condition!(
+            !self.fee_tiers.is_empty(),
+            crate::ErrorCode::FeeTiersEmpty,
+            "Pool has no fee tiers; schedule a fee update to restore them"
+        );
         Ok(())
     }
 
@@ -1740,14 +5979,16 @@ impl PoolState {
             return Err(crate::ErrorCode::InvalidFeeTier.into());
         }
 
-        // Check if too many fee tiers
-        if fee_tiers.len() > MAX_FEE_TIERS {
-            msg!("Too many fee tiers: {} > {}", fee_tiers.len(), MAX_FEE_TIERS);
+        // Check if too many fee tiers for the hard limit or this pool's cap
+        let max_fee_tiers = MAX_FEE_TIERS.min(self.max_fee_tiers as usize);
+        if fee_tiers.len() > max_fee_tiers {
+            msg!("Too many fee tiers: {} > {}", fee_tiers.len(), max_fee_tiers);
             return Err(crate::ErrorCode::TooManyFeeTiers.into());
         }
 
         // Validate each tier
         let mut prev_threshold = 0;
+        let mut prev_liquidity_threshold = None;
         let mut prev_fee = MAXIMUM_FEE_BPS + 1; // Start with a value higher than max allowed
 
         for (i, tier) in fee_tiers.iter().enumerate() {
@@ -1761,6 +6002,19 @@ impl PoolState {
                 return Err(crate::ErrorCode::InvalidFeeTierSpacing.into());
             }
 
+            // Check liquidity threshold; those set must also be strictly increasing
+            if let Some(liquidity_threshold) = tier.liquidity_threshold {
+                if prev_liquidity_threshold.is_some_and(|prev| liquidity_threshold <= prev) {
+                    msg!("Invalid fee tier liquidity threshold at index {}: {} <= {:?}",
+                        i,
+                        liquidity_threshold,
+                        prev_liquidity_threshold
+                    );
+                    return Err(crate::ErrorCode::InvalidFeeTierSpacing.into());
+                }
+                prev_liquidity_threshold = Some(liquidity_threshold);
+            }
+
             // Check fee bounds
             if tier.fee_bps < MINIMUM_FEE_BPS {
                 msg!("Fee too low at index {}: {} < {}", 
@@ -1795,6 +6049,18 @@ impl PoolState {
                 return Err(crate::ErrorCode::DuplicateFeeTierThreshold.into());
             }
 
+            // Check minimum fee spacing; spacing applies to fees, since thresholds
+            // are token amounts and only need to be strictly increasing
+            if i > 0 && prev_fee - tier.fee_bps < MIN_FEE_TIER_SPACING_BPS {
+                msg!("Fee tiers too close at index {}: {} -> {} (min spacing: {} bps)",
+                    i,
+                    prev_fee,
+                    tier.fee_bps,
+                    MIN_FEE_TIER_SPACING_BPS
+                );
+                return Err(crate::ErrorCode::InvalidFeeTierSpacing.into());
+            }
+
             prev_threshold = tier.volume_threshold;
             prev_fee = tier.fee_bps;
         }
@@ -1814,10 +6080,612 @@ impl PoolState {
         Ok(())
     }
 
-    pub fn is_address_forbidden(&self, address: &Pubkey) -> bool {
+    /// Validates a single parameter update before it is scheduled
+    /// Validates a batch of updates for scheduling and returns its timelock
+    ///
+    /// The batch waits for the longest timelock of the categories it contains.
+    pub fn validate_update_batch(&self, updates: &[ParameterUpdate]) -> Result<u64> {
+        validate_condition!(!updates.is_empty(), crate::ErrorCode::InvalidAmount);
+        validate_condition!(
+            updates.len() <= MAX_PENDING_UPDATE_SIZE,
+            crate::ErrorCode::TooManyPendingUpdates,
+            "Too many pending updates: {} > {}",
+            updates.len(),
+            MAX_PENDING_UPDATE_SIZE
+        );
+
+        for update in updates {
+            self.validate_parameter_update(update)?;
+        }
+
+        Ok(self.update_batch_timelock(updates))
+    }
+
+    /// Timelock for a batch: that of its slowest category
+    pub fn update_batch_timelock(&self, updates: &[ParameterUpdate]) -> u64 {
+        updates
+            .iter()
+            .map(|update| self.timelocks.duration_for(update))
+            .max()
+            .unwrap_or(PARAMETER_UPDATE_TIMELOCK)
+    }
+
+    pub fn validate_parameter_update(&self, update: &ParameterUpdate) -> Result<()> {
+        match update {
+            ParameterUpdate::Trade(settings) => {
+                validate_parameter!(
+                    settings.max_trade_size_bps,
+                    settings.min_trade_size,
+                    u64::MAX,
+                    crate::ErrorCode::InvalidParameterRelationship
+                );
+                validate_parameter!(
+                    settings.early_trade_fee_bps,
+                    0,
+                    MAX_EARLY_TRADE_FEE_BPS,
+                    crate::ErrorCode::FeeTooHigh
+                );
+                validate_parameter!(
+                    settings.early_trade_window_seconds,
+                    0,
+                    MAX_EARLY_TRADE_DURATION,
+                    crate::ErrorCode::InvalidTradeSettings
+                );
+            }
+            ParameterUpdate::Protection(settings) => {
+                validate_parameter!(
+                    settings.max_price_impact_bps,
+                    0,
+                    10000,
+                    crate::ErrorCode::PriceImpactTooHigh
+                );
+                validate_parameter!(
+                    settings.max_slippage_bps,
+                    0,
+                    10000,
+                    crate::ErrorCode::InvalidProtectionSettings
+                );
+                validate_condition!(
+                    settings.min_trade_interval >= 0,
+                    crate::ErrorCode::InvalidProtectionSettings
+                );
+                validate_parameter!(
+                    settings.large_withdrawal_bps,
+                    1,
+                    10000,
+                    crate::ErrorCode::InvalidProtectionSettings
+                );
+                validate_parameter!(
+                    settings.circuit_breaker_decay_bps,
+                    0,
+                    10000,
+                    crate::ErrorCode::InvalidProtectionSettings
+                );
+                validate_condition!(settings.max_hourly_volume > 0, crate::ErrorCode::InvalidMaxHourlyVolume);
+                validate_parameter!(
+                    settings.max_output_fraction_bps,
+                    1,
+                    MAX_OUTPUT_FRACTION_BPS,
+                    crate::ErrorCode::InvalidMaxOutputFraction
+                );
+                validate_parameter!(
+                    settings.low_reserve.surcharge_bps,
+                    0,
+                    MAX_LOW_RESERVE_SURCHARGE_BPS,
+                    crate::ErrorCode::InvalidLowReserveSettings
+                );
+                validate_parameter!(
+                    settings.fee_withdraw_cooldown,
+                    0,
+                    MAX_FEE_WITHDRAW_COOLDOWN_SECONDS,
+                    crate::ErrorCode::InvalidFeeWithdrawCooldown
+                );
+                PoolState::validate_sandwich_settings(&settings.sandwich)?;
+                validate_condition!(settings.max_hourly_trades > 0, crate::ErrorCode::InvalidMaxHourlyTrades);
+                PoolState::validate_volatility_settings(&settings.volatility, settings.max_price_impact_bps)?;
+                validate_parameter!(
+                    settings.post_resume_cooldown,
+                    0,
+                    MAX_POST_RESUME_COOLDOWN_SECONDS,
+                    crate::ErrorCode::InvalidPostResumeCooldown
+                );
+                PoolState::validate_allowed_callers(&settings.allowed_callers)?;
+            }
+            ParameterUpdate::Fee(settings) => {
+                self.validate_fee_tiers_lock(settings)?;
+                if !settings.fee_tiers.is_empty() {
+                    validation::validate_fee_parameters(self, &settings.fee_tiers)?;
+                }
+                validation::validate_protocol_fee(settings.protocol_fee_bps)?;
+            }
+            ParameterUpdate::State(_) => {}
+            ParameterUpdate::Timelocks(timelocks) => {
+                validate_parameter!(
+                    timelocks.trade_seconds,
+                    MIN_TRADE_TIMELOCK,
+                    MAX_PARAMETER_UPDATE_TIMELOCK,
+                    crate::ErrorCode::InvalidTimelockDuration
+                );
+                validate_parameter!(
+                    timelocks.protection_seconds,
+                    MIN_PROTECTION_TIMELOCK,
+                    MAX_PARAMETER_UPDATE_TIMELOCK,
+                    crate::ErrorCode::InvalidTimelockDuration
+                );
+                validate_parameter!(
+                    timelocks.fee_seconds,
+                    MIN_FEE_TIMELOCK,
+                    MAX_PARAMETER_UPDATE_TIMELOCK,
+                    crate::ErrorCode::InvalidTimelockDuration
+                );
+                validate_parameter!(
+                    timelocks.state_seconds,
+                    MIN_STATE_TIMELOCK,
+                    MAX_PARAMETER_UPDATE_TIMELOCK,
+                    crate::ErrorCode::InvalidTimelockDuration
+                );
+                validate_parameter!(
+                    timelocks.emergency_pause_seconds,
+                    MIN_EMERGENCY_PAUSE_TIMELOCK,
+                    MAX_PARAMETER_UPDATE_TIMELOCK,
+                    crate::ErrorCode::InvalidTimelockDuration
+                );
+                validate_parameter!(
+                    timelocks.emergency_resume_seconds,
+                    MIN_EMERGENCY_RESUME_TIMELOCK,
+                    MAX_PARAMETER_UPDATE_TIMELOCK,
+                    crate::ErrorCode::InvalidTimelockDuration
+                );
+                // Resuming must never be quicker than pausing
+                validate_condition!(
+                    timelocks.emergency_resume_seconds >= timelocks.emergency_pause_seconds,
+                    crate::ErrorCode::InvalidTimelockDuration,
+                    "Emergency resume delay {} below pause delay {}",
+                    timelocks.emergency_resume_seconds,
+                    timelocks.emergency_pause_seconds
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The live protection settings, in the shape of a protection update
+    ///
+    /// Lets a proposed update be compared field by field with what it replaces.
+    pub fn current_protection_settings(&self) -> ProtectionSettingsUpdate {
+        ProtectionSettingsUpdate {
+            max_daily_volume: self.volume.max_daily,
+            max_price_impact_bps: self.protection.max_price_impact_bps,
+            max_slippage_bps: self.protection.max_slippage_bps,
+            blacklist_enabled: self.protection.blacklist_enabled,
+            min_trade_interval: self.protection.min_trade_interval,
+            min_liquidity_floor: self.min_liquidity_floor,
+            large_withdrawal_bps: self.protection.large_withdrawal_bps,
+            circuit_breaker_threshold: self.circuit_breaker.threshold,
+            circuit_breaker_window: self.circuit_breaker.window,
+            circuit_breaker_cooldown: self.circuit_breaker.cooldown,
+            circuit_breaker_decay_bps: self.circuit_breaker.decay_bps,
+            rate_limit_window: self.rate_limit.window_seconds,
+            rate_limit_max: self.rate_limit.max_calls,
+            max_hourly_volume: self.volume.hourly_limit(),
+            max_output_fraction_bps: self.output_fraction_limit_bps(),
+            max_daily_volume_per_trader: self.protection.max_daily_volume_per_trader,
+            low_reserve: self.low_reserve.clone(),
+            fee_withdraw_cooldown: self.fee_withdraw_cooldown,
+            sandwich: self.sandwich.settings(),
+            max_hourly_trades: self.hourly_trade_limit(),
+            max_trades_per_tx: self.max_trades_per_tx,
+            volatility: self.volatility.settings(),
+            post_resume_cooldown: self.post_resume_cooldown,
+            allowed_callers: self.allowed_callers.clone(),
+        }
+    }
+
+    /// Checks that a protection update only tightens the live settings
+    ///
+    /// Anything looser has to wait out the timelock, so it fails with
+    /// `Unauthorized` here.
+    pub fn validate_protection_tightening(&self, settings: &ProtectionSettingsUpdate) -> Result<()> {
+        validate_condition!(
+            settings.is_no_looser_than(&self.current_protection_settings()),
+            crate::ErrorCode::Unauthorized,
+            "Loosening protection settings requires the timelocked update path"
+        );
+        self.validate_parameter_update(&ParameterUpdate::Protection(settings.clone()))
+    }
+
+    /// Records who applied parameter settings and when, for `get_last_parameter_update`
+    pub fn record_parameter_update(&mut self, admin: &Pubkey, current_time: u64) {
+        self.last_param_update_ts = current_time as i64;
+        self.last_param_update_admin = *admin;
+    }
+
+    /// Tallies a soft-failed operation; saturates so counting never fails the caller
+    pub fn record_failure(&mut self, category: FailureCategory) {
+        let counter = match category {
+            FailureCategory::Trade => &mut self.failed_trade_count,
+            FailureCategory::Liquidity => &mut self.failed_liquidity_count,
+            FailureCategory::Admin => &mut self.failed_admin_count,
+        };
+        *counter = counter.saturating_add(1);
+    }
+
+    /// Soft-failure counters since the last reset
+    pub fn failure_stats(&self) -> FailureStats {
+        FailureStats {
+            failed_trade_count: self.failed_trade_count,
+            failed_liquidity_count: self.failed_liquidity_count,
+            failed_admin_count: self.failed_admin_count,
+        }
+    }
+
+    /// Zeroes the soft-failure counters, returning their old values
+    pub fn reset_failure_stats(&mut self) -> FailureStats {
+        let old_stats = self.failure_stats();
+        self.failed_trade_count = 0;
+        self.failed_liquidity_count = 0;
+        self.failed_admin_count = 0;
+        old_stats
+    }
+
+    /// The last applied parameter update, or `None` if there has been none
+    pub fn last_parameter_update(&self) -> Option<ParameterUpdateRecord> {
+        (self.last_param_update_ts != 0).then(|| ParameterUpdateRecord {
+            ts: self.last_param_update_ts,
+            admin: self.last_param_update_admin,
+        })
+    }
+
+    /// Applies a single parameter update and emits its settings event
+    pub fn apply_update(&mut self, update: &ParameterUpdate, admin: &Pubkey, current_time: u64) -> Result<()> {
+        match update {
+            ParameterUpdate::Trade(trade_settings) => {
+                self.trade_settings.early_trade_fee_bps = trade_settings.early_trade_fee_bps;
+                self.trade_settings.early_trade_window_seconds = trade_settings.early_trade_window_seconds;
+                self.trade_settings.max_size_bps = trade_settings.max_trade_size_bps;
+                self.trade_settings.min_size = trade_settings.min_trade_size;
+                self.trade_settings.cooldown_seconds = trade_settings.cooldown_seconds;
+
+                emit!(TradeSettingsUpdated {
+                    pool: self.key(),
+                    admin_pubkey: *admin,
+                    early_trade_fee_bps: trade_settings.early_trade_fee_bps,
+                    early_trade_window_seconds: trade_settings.early_trade_window_seconds,
+                    max_trade_size_bps: trade_settings.max_trade_size_bps,
+                    min_trade_size: trade_settings.min_trade_size,
+                    cooldown_seconds: trade_settings.cooldown_seconds,
+                    ts: current_time as i64,
+                });
+            }
+            ParameterUpdate::Protection(protection_settings) => {
+                // Settle decay at the old rate before switching to the new one
+                self.decay_circuit_breaker(current_time);
+
+                self.volume.max_daily = protection_settings.max_daily_volume;
+                self.protection.max_price_impact_bps = protection_settings.max_price_impact_bps;
+                self.protection.max_slippage_bps = protection_settings.max_slippage_bps;
+                self.protection.blacklist_enabled = protection_settings.blacklist_enabled;
+                self.protection.min_trade_interval = protection_settings.min_trade_interval;
+                self.min_liquidity_floor = protection_settings.min_liquidity_floor;
+                self.protection.large_withdrawal_bps = protection_settings.large_withdrawal_bps;
+                self.circuit_breaker.threshold = protection_settings.circuit_breaker_threshold;
+                self.circuit_breaker.max_amount = protection_settings.circuit_breaker_threshold;
+                self.circuit_breaker.window = protection_settings.circuit_breaker_window;
+                self.circuit_breaker.cooldown = protection_settings.circuit_breaker_cooldown;
+                self.circuit_breaker.cooldown_period = protection_settings.circuit_breaker_cooldown;
+                self.circuit_breaker.decay_bps = protection_settings.circuit_breaker_decay_bps;
+                self.rate_limit.window_seconds = protection_settings.rate_limit_window;
+                self.rate_limit.max_calls = protection_settings.rate_limit_max;
+                self.volume.max_hourly = protection_settings.max_hourly_volume;
+                self.max_output_fraction_bps = protection_settings.max_output_fraction_bps;
+                self.protection.max_daily_volume_per_trader = protection_settings.max_daily_volume_per_trader;
+                self.low_reserve = protection_settings.low_reserve.clone();
+                self.fee_withdraw_cooldown = protection_settings.fee_withdraw_cooldown;
+                if protection_settings.sandwich != self.sandwich.settings() {
+                    // Only a change of thresholds forgets the last large trade
+                    self.sandwich.configure(&protection_settings.sandwich);
+                }
+                self.max_hourly_trades = protection_settings.max_hourly_trades;
+                self.max_trades_per_tx = protection_settings.max_trades_per_tx;
+                self.volatility.configure(&protection_settings.volatility);
+                self.post_resume_cooldown = protection_settings.post_resume_cooldown;
+                self.allowed_callers = protection_settings.allowed_callers.clone();
+
+                emit!(ProtectionSettingsUpdated {
+                    pool: self.key(),
+                    admin_pubkey: *admin,
+                    max_daily_volume: protection_settings.max_daily_volume,
+                    max_price_impact_bps: protection_settings.max_price_impact_bps,
+                    max_slippage_bps: protection_settings.max_slippage_bps,
+                    blacklist_enabled: protection_settings.blacklist_enabled,
+                    min_trade_interval: protection_settings.min_trade_interval,
+                    min_liquidity_floor: protection_settings.min_liquidity_floor,
+                    large_withdrawal_bps: protection_settings.large_withdrawal_bps,
+                    circuit_breaker_threshold: protection_settings.circuit_breaker_threshold,
+                    circuit_breaker_window: protection_settings.circuit_breaker_window,
+                    circuit_breaker_cooldown: protection_settings.circuit_breaker_cooldown,
+                    circuit_breaker_decay_bps: protection_settings.circuit_breaker_decay_bps,
+                    rate_limit_window: protection_settings.rate_limit_window,
+                    rate_limit_max: protection_settings.rate_limit_max,
+                    max_hourly_volume: protection_settings.max_hourly_volume,
+                    max_output_fraction_bps: protection_settings.max_output_fraction_bps,
+                    max_daily_volume_per_trader: protection_settings.max_daily_volume_per_trader,
+                    low_reserve: protection_settings.low_reserve.clone(),
+                    fee_withdraw_cooldown: protection_settings.fee_withdraw_cooldown,
+                    sandwich: protection_settings.sandwich.clone(),
+                    max_hourly_trades: protection_settings.max_hourly_trades,
+                    max_trades_per_tx: protection_settings.max_trades_per_tx,
+                    volatility: protection_settings.volatility.clone(),
+                    post_resume_cooldown: protection_settings.post_resume_cooldown,
+                    allowed_callers: protection_settings.allowed_callers.clone(),
+                    ts: current_time as i64,
+                });
+            }
+            ParameterUpdate::Fee(fee_settings) => {
+                // Re-check in case the tiers were locked while pending
+                self.validate_fee_tiers_lock(fee_settings)?;
+                if !fee_settings.fee_tiers.is_empty() {
+                    // Re-check in case the per-pool cap was lowered while pending
+                    self.validate_fee_tiers(&fee_settings.fee_tiers)?;
+                    self.fee_tiers = fee_settings.fee_tiers.clone();
+                }
+                self.fee_tiers_locked = fee_settings.fee_tiers_locked;
+                if !self.fee_tiers_locked {
+                    // A scheduled unlock must not outlive the lock it was for
+                    self.fee_tiers_unlock_time = 0;
+                }
+                self.fee_rounding = fee_settings.fee_rounding;
+                self.protocol_fee_bps = fee_settings.protocol_fee_bps;
+                self.fee_free_threshold = fee_settings.fee_free_threshold;
+
+                emit!(FeeSettingsUpdated {
+                    pool: self.key(),
+                    admin_pubkey: *admin,
+                    fee_tiers_count: self.fee_tiers.len() as u32,
+                    fee_tiers_locked: self.fee_tiers_locked,
+                    fee_rounding: self.fee_rounding,
+                    protocol_fee_bps: self.protocol_fee_bps,
+                    fee_free_threshold: self.fee_free_threshold,
+                    ts: current_time as i64,
+                });
+            }
+            ParameterUpdate::State(state_settings) => {
+                // Entering an emergency pause starts the emergency withdraw timelock
+                if state_settings.is_emergency_paused && !self.is_emergency_paused {
+                    self.emergency_paused_at = current_time;
+                }
+                self.pause_level = state_settings.pause_level;
+                self.is_emergency_paused = state_settings.is_emergency_paused;
+
+                emit!(StateSettingsUpdated {
+                    pool: self.key(),
+                    admin_pubkey: *admin,
+                    pause_level: state_settings.pause_level,
+                    is_emergency_paused: state_settings.is_emergency_paused,
+                    ts: current_time as i64,
+                });
+            }
+            ParameterUpdate::Timelocks(timelocks) => {
+                self.timelocks = timelocks.clone();
+
+                emit!(TimelockSettingsUpdated {
+                    pool: self.key(),
+                    admin_pubkey: *admin,
+                    trade_seconds: timelocks.trade_seconds,
+                    protection_seconds: timelocks.protection_seconds,
+                    fee_seconds: timelocks.fee_seconds,
+                    state_seconds: timelocks.state_seconds,
+                    emergency_pause_seconds: timelocks.emergency_pause_seconds,
+                    emergency_resume_seconds: timelocks.emergency_resume_seconds,
+                    ts: current_time as i64,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Size of a liquidity removal relative to current reserves, in bps
+    pub fn withdrawal_bps(&self, amount: u64) -> Result<u64> {
+        if self.total_liquidity == 0 {
+            return Ok(0);
+        }
+        let bps = (amount as u128)
+            .checked_mul(10000)
+            .ok_or(crate::ErrorCode::Overflow)?
+            / self.total_liquidity as u128;
+        Ok(bps as u64)
+    }
+
+    /// Accrued-fee ceiling, with 0 read as `u64::MAX`
+    pub fn fee_high_water_mark_limit(&self) -> u64 {
+        if self.fee_high_water_mark == 0 {
+            u64::MAX
+        } else {
+            self.fee_high_water_mark
+        }
+    }
+
+    /// Adds a trade's treasury fee to `total_fees_collected`
+    ///
+    /// Past the high-water mark the trade is either rejected until fees are
+    /// withdrawn, or accrual stops at the mark and the excess stays in the pool
+    /// as untracked surplus, per `fee_overflow_mode`.
+    pub fn accrue_treasury_fee(&mut self, fee: u64, current_time: u64) -> Result<()> {
+        let limit = self.fee_high_water_mark_limit();
+        let room = limit.saturating_sub(self.total_fees_collected);
+        if fee <= room {
+            self.total_fees_collected += fee;
+            return Ok(());
+        }
+
+        match self.fee_overflow_mode {
+            FeeOverflowMode::RequireWithdrawal => {
+                msg!(
+                    "Fee accrual past high-water mark: {} + {} > {}",
+                    self.total_fees_collected,
+                    fee,
+                    limit
+                );
+                Err(crate::ErrorCode::FeeWithdrawalRequired.into())
+            }
+            FeeOverflowMode::CapAccrual => {
+                self.total_fees_collected += room;
+                emit!(FeeAccrualCapped {
+                    pool: self.key(),
+                    total_fees_collected: self.total_fees_collected,
+                    dropped_amount: fee - room,
+                    ts: current_time as i64,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    /// Fee balances held in the pool token account for later withdrawal or claim
+    pub fn fees_owed(&self) -> Result<u64> {
+        self.total_fees_collected
+            .checked_add(self.insurance_collected)
+            .and_then(|fees| fees.checked_add(self.referral_fees_owed))
+            .and_then(|fees| fees.checked_add(self.protocol_fees))
+            .ok_or_else(|| {
+                msg!(
+                    "Fee total overflow: {} + {} + {} + {}",
+                    self.total_fees_collected,
+                    self.insurance_collected,
+                    self.referral_fees_owed,
+                    self.protocol_fees
+                );
+                error!(crate::ErrorCode::Overflow)
+            })
+    }
+
+    /// Zeroes the balances an emergency withdraw moves out of the pool
+    ///
+    /// Liquidity, treasury, insurance and protocol fees all leave with the
+    /// rescued reserves, and liquidity locks lapse with the liquidity.
+    /// Referral fees owed are not rescued and stay tracked.
+    pub fn clear_rescued_balances(&mut self) {
+        self.total_liquidity = 0;
+        self.total_fees_collected = 0;
+        self.insurance_collected = 0;
+        self.protocol_fees = 0;
+        self.liquidity_locks.clear();
+    }
+
+    /// Portion of the pool token balance an emergency withdraw moves out
+    ///
+    /// Everything except unclaimed referral fees, which referrers can still claim.
+    pub fn rescuable_amount(&self, token_balance: u64) -> u64 {
+        token_balance.saturating_sub(self.referral_fees_owed)
+    }
+
+    /// Everything the pool token account must cover: liquidity plus fees owed
+    pub fn obligations(&self) -> Result<u64> {
+        self.total_liquidity
+            .checked_add(self.fees_owed()?)
+            .ok_or_else(|| error!(crate::ErrorCode::Overflow))
+    }
+
+    /// Portion of the pool token balance that is neither liquidity nor owed to referrers
+    ///
+    /// Treasury and insurance fees are custodied in the same token account as
+    /// liquidity, so fee withdrawals must stay within this amount.
+    pub fn fee_reserve(&self, token_balance: u64) -> u64 {
+        token_balance
+            .saturating_sub(self.total_liquidity)
+            .saturating_sub(self.referral_fees_owed)
+            .saturating_sub(self.protocol_fees)
+    }
+
+    /// Rejects a CPI allow-list with the default pubkey or more than `MAX_ALLOWED_CALLERS` entries
+    pub fn validate_allowed_callers(allowed_callers: &[Pubkey]) -> Result<()> {
+        validate_condition!(
+            !allowed_callers.contains(&Pubkey::default()),
+            crate::ErrorCode::InvalidProtectionSettings,
+            "Allowed callers contain the default pubkey"
+        );
+        validate_condition!(
+            allowed_callers.len() <= MAX_ALLOWED_CALLERS,
+            crate::ErrorCode::TooManyAllowedCallers,
+            "Too many allowed callers: {} (max: {})",
+            allowed_callers.len(),
+            MAX_ALLOWED_CALLERS
+        );
+        Ok(())
+    }
+
+    /// Checks a trade's top-level program against `allowed_callers`
+    ///
+    /// A top-level call to this program is a direct trade, not a CPI, and is
+    /// always allowed.
+    pub fn check_caller_allowed(&self, top_level_program: &Pubkey) -> Result<()> {
+        if self.allowed_callers.is_empty() || top_level_program == &crate::ID {
+            return Ok(());
+        }
+        validate_condition!(
+            self.allowed_callers.contains(top_level_program),
+            crate::ErrorCode::CallerNotAllowed,
+            "Caller not allowed: {}",
+            top_level_program
+        );
+        Ok(())
+    }
+
+    /// Referrer's share of a trade fee, rounded down in the pool's favor
+    pub fn referral_fee(&self, fee_amount: u64) -> Result<u64> {
+        fee_amount
+            .checked_mul(self.referral_share_bps)
+            .ok_or(crate::ErrorCode::Overflow)?
+            .checked_div(10000)
+            .ok_or_else(|| error!(crate::ErrorCode::Overflow))
+    }
+
+    /// Splits a trade fee into treasury and insurance portions
+    ///
+    /// The insurance share rounds down, so any rounding remainder stays with the treasury.
+    pub fn split_insurance_fee(&self, fee_amount: u64) -> Result<(u64, u64)> {
+        let insurance_fee = fee_amount
+            .checked_mul(self.insurance_fee_share_bps)
+            .ok_or(crate::ErrorCode::Overflow)?
+            .checked_div(10000)
+            .ok_or(crate::ErrorCode::Overflow)?;
+        let treasury_fee = fee_amount
+            .checked_sub(insurance_fee)
+            .ok_or(crate::ErrorCode::Underflow)?;
+        Ok((treasury_fee, insurance_fee))
+    }
+
+    /// Returns the slippage ceiling for a trade, in basis points
+    ///
+    /// The pool-configured `max_slippage_bps` always wins; the caller's value
+    /// is only honored when it is stricter.
+    pub fn effective_max_slippage_bps(&self, requested_bps: u64) -> u64 {
+        requested_bps.min(self.protection.max_slippage_bps)
+    }
+
+    pub fn blacklist_entry(&self, trader: &Pubkey) -> Option<&BlacklistEntry> {
+        self.trader_blacklist.iter().find(|entry| &entry.trader == trader)
+    }
+
+    /// Returns whether the trader has a blacklist entry that has not expired
+    pub fn is_blacklisted(&self, trader: &Pubkey, now: i64) -> bool {
+        self.blacklist_entry(trader)
+            .is_some_and(|entry| !entry.is_expired(now))
+    }
+
+    /// Removes the trader's blacklist entry if it has expired, returning it
+    pub fn prune_expired_blacklist_entry(&mut self, trader: &Pubkey, now: i64) -> Option<BlacklistEntry> {
+        let index = self
+            .trader_blacklist
+            .iter()
+            .position(|entry| &entry.trader == trader && entry.is_expired(now))?;
+        Some(self.trader_blacklist.remove(index))
+    }
+
+    pub fn is_address_forbidden(&self, address: &Pubkey, now: i64) -> bool {
         address == &self.admin || 
         address == &self.emergency_admin || 
-        self.trader_blacklist.contains(address)
+        self.is_blacklisted(address, now)
     }
 
     /// Initializes a new pool state with the given parameters
@@ -1890,8 +6758,8 @@ impl PoolState {
     }
 
     pub fn check_rate_limit(&mut self, current_time: u64) -> Result<()> {
-        // Check if we're in a new window
-        if current_time - self.rate_limit.last_reset >= self.rate_limit.window_seconds {
+        // Check if we're in a new window; a clock behind the last reset is not
+        if current_time.saturating_sub(self.rate_limit.last_reset) >= self.rate_limit.window_seconds {
             self.rate_limit.count = 0;
             self.rate_limit.last_reset = current_time;
         }
@@ -1927,18 +6795,12 @@ impl ValidationHelpers for PoolState {
         Ok(())
     }
 
-    fn check_token_mint(&self, mint: &Account<Mint>) -> Result<()> {
+    fn check_token_mint(&self, mint: &InterfaceAccount<Mint>) -> Result<()> {
         if mint.key() != self.token_mint {
             msg!("Invalid token mint: expected {} but got {}", self.token_mint, mint.key());
             return Err(crate::ErrorCode::InvalidTokenMint.into());
         }
-        if mint.decimals != self.token_decimals {
-            msg!("Invalid token decimals: expected {} but got {}", 
-                self.token_decimals, 
-                mint.decimals
-            );
-            return Err(crate::ErrorCode::InvalidTokenDecimals.into());
-        }
+        self.check_token_decimals(mint.decimals)?;
         if mint.freeze_authority.is_some() {
             msg!("Token mint has freeze authority: {}", mint.freeze_authority.unwrap());
             return Err(crate::ErrorCode::TokenMintHasFreezeAuthority.into());
@@ -1946,7 +6808,7 @@ impl ValidationHelpers for PoolState {
         Ok(())
     }
 
-    fn check_token_account(&self, account: &Account<TokenAccount>, mint: &Pubkey) -> Result<()> {
+    fn check_token_account(&self, account: &InterfaceAccount<TokenAccount>, mint: &Pubkey) -> Result<()> {
         if account.mint != *mint {
             msg!("Invalid token account mint: expected {} but got {}", mint, account.mint);
             return Err(crate::ErrorCode::InvalidTokenAccount.into());
@@ -1993,6 +6855,155 @@ impl ValidationHelpers for PoolState {
     }
 }
 
+/// Referral fees accrued to a referrer on a pool
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ReferralState {
+    pub pool: Pubkey,
+    pub referrer: Pubkey,
+    /// Unclaimed referral fees
+    pub accrued: u64,
+    pub total_claimed: u64,
+    pub bump: u8,
+}
+
+/// Lock held across a pool's trades and fee withdrawals, at the reentrancy guard PDA
+///
+/// Unlike the in-memory checks, the lock is written to the account before any
+/// CPI, so a call re-entering the program within the same transaction sees it.
+#[account]
+#[derive(Default, InitSpace)]
+pub struct ReentrancyLock {
+    pub pool: Pubkey,
+    pub bump: u8,
+    pub locked: bool,
+}
+
+impl ReentrancyLock {
+    /// Takes the lock, failing with `ReentrancyLocked` if it is already held
+    pub fn acquire(&mut self, pool: Pubkey, bump: u8) -> Result<()> {
+        validate_condition!(
+            !self.locked,
+            crate::ErrorCode::ReentrancyLocked,
+            "Reentrancy lock for pool {} is already held",
+            pool
+        );
+        self.pool = pool;
+        self.bump = bump;
+        self.locked = true;
+        Ok(())
+    }
+
+    pub fn release(&mut self) {
+        self.locked = false;
+    }
+}
+
+/// One trader's volume in the current daily window, tracked for each pool
+#[account]
+#[derive(Default, InitSpace)]
+pub struct TraderVolume {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub bump: u8,
+    /// Start of the current daily window, set by the first trade after the last one lapsed
+    pub window_start: u64,
+    /// Volume traded in the current daily window
+    pub volume: u64,
+}
+
+impl TraderVolume {
+    /// Volume in the daily window live at `current_time`, zero once it has lapsed
+    pub fn volume_at(&self, current_time: u64) -> u64 {
+        if current_time >= self.window_start.saturating_add(MAX_DAILY_VOLUME_WINDOW) {
+            0
+        } else {
+            self.volume
+        }
+    }
+
+    /// Checks that `amount` fits under the trader's daily cap; a zero cap disables it
+    pub fn check_daily_limit(&self, amount: u64, max_daily: u64, current_time: u64) -> Result<()> {
+        if max_daily == 0 {
+            return Ok(());
+        }
+
+        let new_volume = self.volume_at(current_time).saturating_add(amount);
+        validate_condition!(
+            new_volume <= max_daily,
+            crate::ErrorCode::TraderDailyVolumeExceeded,
+            "Trader daily volume limit exceeded: {} > {}",
+            new_volume,
+            max_daily
+        );
+        Ok(())
+    }
+
+    /// Adds a settled trade, opening a new window if the last one lapsed
+    pub fn record_trade(&mut self, pool: Pubkey, trader: Pubkey, bump: u8, amount: u64, current_time: u64) {
+        self.pool = pool;
+        self.trader = trader;
+        self.bump = bump;
+        if current_time >= self.window_start.saturating_add(MAX_DAILY_VOLUME_WINDOW) {
+            self.window_start = current_time;
+            self.volume = 0;
+        }
+        self.volume = self.volume.saturating_add(amount);
+    }
+}
+
+/// Per-trader state tracked for each pool
+#[account]
+#[derive(Default, InitSpace)]
+pub struct TraderState {
+    pub pool: Pubkey,
+    pub trader: Pubkey,
+    pub last_trade_ts: i64,
+    pub bump: u8,
+    /// Slot of the trader's last governance-discounted trade
+    pub last_discount_slot: u64,
+}
+
+impl TraderState {
+    /// Checks the minimum interval since this trader's last trade
+    ///
+    /// A zero interval disables the check, and a trader with no prior trade
+    /// always passes. A clock that moved backwards counts as zero elapsed time.
+    pub fn check_trade_cooldown(&self, min_trade_interval: i64, current_time: i64) -> Result<()> {
+        if min_trade_interval <= 0 || self.last_trade_ts == 0 {
+            return Ok(());
+        }
+
+        let elapsed = current_time.saturating_sub(self.last_trade_ts).max(0);
+        validate_condition!(
+            elapsed >= min_trade_interval,
+            crate::ErrorCode::TradeCooldownActive,
+            "Trade cooldown active: {} seconds remaining",
+            min_trade_interval - elapsed
+        );
+        Ok(())
+    }
+
+    pub fn record_trade(&mut self, pool: Pubkey, trader: Pubkey, bump: u8, current_time: i64) {
+        self.pool = pool;
+        self.trader = trader;
+        self.bump = bump;
+        self.last_trade_ts = current_time;
+    }
+
+    /// Claims this slot's governance discount; false if already claimed
+    ///
+    /// One discounted trade per trader per slot keeps a balance from being
+    /// counted again by later trades in the same transaction.
+    pub fn claim_discount_slot(&mut self, slot: u64) -> bool {
+        if self.last_discount_slot == slot {
+            return false;
+        }
+        self.last_discount_slot = slot;
+        true
+    }
+}
+
 impl anchor_lang::Key for PoolState {
     fn key(&self) -> Pubkey {
         self.to_account_info().key()
@@ -2021,11 +7032,21 @@ macro_rules! validate_condition {
     };
 }
 
+#[macro_export]
+macro_rules! validate_parameter {
+    ($value:expr, $min:expr, $max:expr, $error:expr) => {
+        if $value < $min || $value > $max {
+            msg!("Parameter {} outside allowed range [{}, {}]", $value, $min, $max);
+            return Err($error.into());
+        }
+    };
+}
+
 pub trait ValidationHelpers {
     fn check_token_account_ownership(&self, owner: &Pubkey) -> Result<()>;
     fn check_pool_authority(&self, authority: &Pubkey, program_id: &Pubkey) -> Result<()>;
-    fn check_token_mint(&self, mint: &Account<Mint>) -> Result<()>;
-    fn check_token_account(&self, account: &Account<TokenAccount>, mint: &Pubkey) -> Result<()>;
+    fn check_token_mint(&self, mint: &InterfaceAccount<Mint>) -> Result<()>;
+    fn check_token_account(&self, account: &InterfaceAccount<TokenAccount>, mint: &Pubkey) -> Result<()>;
     fn check_circuit_breaker(&self, current_time: i64) -> Result<()>;
     fn check_rate_limit(&self, current_time: i64) -> Result<()>;
     fn check_volume_limit(&self, amount: u64) -> Result<()>;
@@ -1,4 +1,10 @@
 use anchor_lang::prelude::*;
+use crate::constants::{
+    CIRCUIT_BREAKER_DECAY, DEFAULT_LARGE_WITHDRAWAL_BPS, EMERGENCY_PAUSE_TIMELOCK_SECONDS, EMERGENCY_TIMELOCK_SECONDS,
+    MAX_HOURLY_TRADES, MAX_HOURLY_VOLUME, MAX_HOURLY_VOLUME_WINDOW, MAX_OUTPUT_FRACTION_BPS, MIN_VOLATILITY_SAMPLES,
+    PARAMETER_UPDATE_TIMELOCK, PAUSE_OP_ALL, PAUSE_OP_TRADE, RECENT_TRADES_CAPACITY, VOLATILITY_EWMA_WEIGHT,
+    VOLATILITY_PRICE_SCALE, VOLUME_BUCKET_COUNT, VOLUME_BUCKET_SECONDS,
+};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct TradeSettings {
@@ -15,8 +21,38 @@ pub struct ProtectionSettings {
     pub enabled: bool,
     pub snipe_protection_seconds: u64,
     pub max_price_impact_bps: u64,
-    pub max_slippage: u64,
+    pub max_slippage_bps: u64,
     pub blacklist_enabled: bool,
+    /// Minimum seconds between trades from the same address (0 disables)
+    pub min_trade_interval: i64,
+    /// Share of reserves (bps) above which a liquidity removal is guarded
+    pub large_withdrawal_bps: u64,
+    /// Cap on one address's volume within a daily window (0 disables)
+    pub max_daily_volume_per_trader: u64,
+    /// Impact caps by trade size, by increasing threshold, never above
+    /// `max_price_impact_bps`; trades past every threshold, or any trade when
+    /// empty, use `max_price_impact_bps`
+    pub price_impact_tiers: Vec<PriceImpactTier>,
+}
+
+impl ProtectionSettings {
+    /// Price impact cap for a trade of `amount_in`, before volatility narrowing
+    ///
+    /// A tier can only narrow the flat cap, so lowering `max_price_impact_bps`
+    /// still binds trades of every size.
+    pub fn max_price_impact_bps_for_size(&self, amount_in: u64) -> u64 {
+        self.price_impact_tiers
+            .iter()
+            .find(|tier| amount_in <= tier.size_threshold)
+            .map_or(self.max_price_impact_bps, |tier| tier.max_impact_bps.min(self.max_price_impact_bps))
+    }
+}
+
+/// Price impact cap for trades up to `size_threshold`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceImpactTier {
+    pub size_threshold: u64,
+    pub max_impact_bps: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -28,33 +64,381 @@ pub struct TradeSettingsUpdate {
     pub cooldown_seconds: u64,
 }
 
+impl TradeSettingsUpdate {
+    /// No fee above `previous` and no trade limit wider than it
+    pub fn is_no_looser_than(&self, previous: &TradeSettingsUpdate) -> bool {
+        self.early_trade_fee_bps <= previous.early_trade_fee_bps
+            && self.early_trade_window_seconds <= previous.early_trade_window_seconds
+            && self.max_trade_size_bps <= previous.max_trade_size_bps
+            && self.min_trade_size >= previous.min_trade_size
+            && self.cooldown_seconds >= previous.cooldown_seconds
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ProtectionSettingsUpdate {
     pub max_daily_volume: u64,
     pub max_price_impact_bps: u64,
+    pub max_slippage_bps: u64,
+    pub blacklist_enabled: bool,
+    pub min_trade_interval: i64,
+    pub min_liquidity_floor: u64,
+    pub large_withdrawal_bps: u64,
     pub circuit_breaker_threshold: u64,
     pub circuit_breaker_window: u64,
     pub circuit_breaker_cooldown: u64,
+    pub circuit_breaker_decay_bps: u64,
     pub rate_limit_window: u64,
     pub rate_limit_max: u32,
+    /// Volume cap for one hourly window; must be nonzero
+    pub max_hourly_volume: u64,
+    /// Largest share of reserves one trade may take out, in bps; 10000 disables it
+    pub max_output_fraction_bps: u64,
+    /// Cap on one trader's volume per daily window; 0 disables it
+    pub max_daily_volume_per_trader: u64,
+    /// Low reserve mode; a higher surcharge counts as looser
+    pub low_reserve: LowReserveSettings,
+    /// Minimum seconds between fee withdrawals; 0 disables it
+    pub fee_withdraw_cooldown: i64,
+    /// Sandwich detection; a higher surcharge or a wider similarity band counts as looser
+    pub sandwich: SandwichSettingsUpdate,
+    /// Trade count cap for one hourly window; must be nonzero
+    pub max_hourly_trades: u32,
+    /// Cap on trades against the pool in one transaction; 0 disables it
+    pub max_trades_per_tx: u16,
+    /// Volatility-adaptive price impact cap; a higher floor or scale counts as looser
+    pub volatility: VolatilitySettingsUpdate,
+    /// Seconds trading stays blocked after an emergency resume; 0 disables it
+    pub post_resume_cooldown: i64,
+    /// Programs allowed to trade via CPI; empty allows any, so adding a caller or clearing the list counts as looser
+    pub allowed_callers: Vec<Pubkey>,
+}
+
+impl ProtectionSettingsUpdate {
+    /// Every limit at or inside `previous`, and the blacklist switch unchanged
+    ///
+    /// Turning the blacklist on or off changes who may trade rather than how
+    /// much, so either direction waits out the full timelock.
+    pub fn is_no_looser_than(&self, previous: &ProtectionSettingsUpdate) -> bool {
+        // A zero per-trader or per-transaction cap disables it, i.e. no cap at all
+        let per_trader_cap = |cap: u64| if cap == 0 { u64::MAX } else { cap };
+        let trades_per_tx_cap = |cap: u16| if cap == 0 { u32::MAX } else { cap as u32 };
+
+        self.max_daily_volume <= previous.max_daily_volume
+            && self.max_price_impact_bps <= previous.max_price_impact_bps
+            && self.max_slippage_bps <= previous.max_slippage_bps
+            && self.blacklist_enabled == previous.blacklist_enabled
+            && self.min_trade_interval >= previous.min_trade_interval
+            && self.min_liquidity_floor >= previous.min_liquidity_floor
+            && self.large_withdrawal_bps <= previous.large_withdrawal_bps
+            && self.circuit_breaker_threshold <= previous.circuit_breaker_threshold
+            && self.circuit_breaker_window >= previous.circuit_breaker_window
+            && self.circuit_breaker_cooldown >= previous.circuit_breaker_cooldown
+            && self.circuit_breaker_decay_bps <= previous.circuit_breaker_decay_bps
+            && self.rate_limit_window >= previous.rate_limit_window
+            && self.rate_limit_max <= previous.rate_limit_max
+            && self.max_hourly_volume <= previous.max_hourly_volume
+            && self.max_output_fraction_bps <= previous.max_output_fraction_bps
+            && per_trader_cap(self.max_daily_volume_per_trader) <= per_trader_cap(previous.max_daily_volume_per_trader)
+            && self.low_reserve.is_no_looser_than(&previous.low_reserve)
+            && self.fee_withdraw_cooldown >= previous.fee_withdraw_cooldown
+            && self.sandwich.is_no_looser_than(&previous.sandwich)
+            && self.max_hourly_trades <= previous.max_hourly_trades
+            && trades_per_tx_cap(self.max_trades_per_tx) <= trades_per_tx_cap(previous.max_trades_per_tx)
+            && self.volatility.is_no_looser_than(&previous.volatility)
+            && self.post_resume_cooldown >= previous.post_resume_cooldown
+            && Self::allowed_callers_no_looser_than(&self.allowed_callers, &previous.allowed_callers)
+    }
+
+    /// Whether `callers` restricts CPI trades at least as much as `previous`
+    ///
+    /// An empty list allows every caller, so any list is no looser than it; a
+    /// non-empty list is no looser only if it drops callers without adding any.
+    pub fn allowed_callers_no_looser_than(callers: &[Pubkey], previous: &[Pubkey]) -> bool {
+        previous.is_empty() || (!callers.is_empty() && callers.iter().all(|caller| previous.contains(caller)))
+    }
+
+    /// Tightest preset: small limits, a slow breaker, the blacklist and sandwich detection on
+    pub fn conservative() -> Self {
+        Self {
+            max_daily_volume: 10_000_000,
+            max_price_impact_bps: 300,
+            max_slippage_bps: 50,
+            blacklist_enabled: true,
+            min_trade_interval: 30,
+            min_liquidity_floor: 0,
+            large_withdrawal_bps: 1000,
+            circuit_breaker_threshold: 250_000,
+            circuit_breaker_window: 3600,
+            circuit_breaker_cooldown: 7200,
+            circuit_breaker_decay_bps: 50,
+            rate_limit_window: 3600,
+            rate_limit_max: 30,
+            max_hourly_volume: 1_000_000,
+            max_output_fraction_bps: 1000,
+            max_daily_volume_per_trader: 1_000_000,
+            low_reserve: LowReserveSettings::default(),
+            fee_withdraw_cooldown: 86_400,
+            sandwich: SandwichSettingsUpdate {
+                enabled: true,
+                window_slots: 10,
+                large_trade_bps: 500,
+                similarity_bps: 1000,
+                surcharge_bps: 100,
+            },
+            max_hourly_trades: 30,
+            max_trades_per_tx: 1,
+            volatility: VolatilitySettingsUpdate { enabled: true, floor_impact_bps: 100, max_volatility_bps: 500 },
+            post_resume_cooldown: 3600,
+            allowed_callers: Vec::new(),
+        }
+    }
+
+    /// The defaults `InitialConfig` has always used
+    pub fn standard() -> Self {
+        Self {
+            max_daily_volume: 100_000_000,
+            max_price_impact_bps: 1000,
+            max_slippage_bps: 100, // 1% slippage ceiling
+            blacklist_enabled: false,
+            min_trade_interval: 0,
+            min_liquidity_floor: 0,
+            large_withdrawal_bps: DEFAULT_LARGE_WITHDRAWAL_BPS,
+            circuit_breaker_threshold: 1_000_000,
+            circuit_breaker_window: 3600,
+            circuit_breaker_cooldown: 3600,
+            circuit_breaker_decay_bps: CIRCUIT_BREAKER_DECAY,
+            rate_limit_window: 3600,
+            rate_limit_max: 100,
+            max_hourly_volume: MAX_HOURLY_VOLUME,
+            max_output_fraction_bps: MAX_OUTPUT_FRACTION_BPS,
+            max_daily_volume_per_trader: 0,
+            low_reserve: LowReserveSettings::default(),
+            fee_withdraw_cooldown: 0,
+            sandwich: SandwichSettingsUpdate::default(),
+            max_hourly_trades: MAX_HOURLY_TRADES,
+            max_trades_per_tx: 0,
+            volatility: VolatilitySettingsUpdate::default(),
+            post_resume_cooldown: 0,
+            allowed_callers: Vec::new(),
+        }
+    }
+
+    /// Loosest preset, for deep pools that expect heavy flow
+    pub fn aggressive() -> Self {
+        Self {
+            max_daily_volume: 1_000_000_000,
+            max_price_impact_bps: 2500,
+            max_slippage_bps: 300,
+            blacklist_enabled: false,
+            min_trade_interval: 0,
+            min_liquidity_floor: 0,
+            large_withdrawal_bps: 5000,
+            circuit_breaker_threshold: 10_000_000,
+            circuit_breaker_window: 3600,
+            circuit_breaker_cooldown: 1800,
+            circuit_breaker_decay_bps: 500,
+            rate_limit_window: 3600,
+            rate_limit_max: 1000,
+            max_hourly_volume: 100_000_000,
+            max_output_fraction_bps: MAX_OUTPUT_FRACTION_BPS,
+            max_daily_volume_per_trader: 0,
+            low_reserve: LowReserveSettings::default(),
+            fee_withdraw_cooldown: 0,
+            sandwich: SandwichSettingsUpdate::default(),
+            max_hourly_trades: 1000,
+            max_trades_per_tx: 0,
+            volatility: VolatilitySettingsUpdate::default(),
+            post_resume_cooldown: 0,
+            allowed_callers: Vec::new(),
+        }
+    }
+}
+
+/// Named protection settings `initialize_pool` can start from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProtectionPreset {
+    /// Use `InitialConfig::protection` as given
+    #[default]
+    Custom,
+    Conservative,
+    Standard,
+    Aggressive,
+}
+
+impl ProtectionPreset {
+    /// The preset's settings, or `None` for `Custom`
+    pub fn protection_settings(&self) -> Option<ProtectionSettingsUpdate> {
+        match self {
+            ProtectionPreset::Custom => None,
+            ProtectionPreset::Conservative => Some(ProtectionSettingsUpdate::conservative()),
+            ProtectionPreset::Standard => Some(ProtectionSettingsUpdate::standard()),
+            ProtectionPreset::Aggressive => Some(ProtectionSettingsUpdate::aggressive()),
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct FeeSettingsUpdate {
     pub fee_tiers: Vec<FeeTier>,
     pub fee_tiers_locked: bool,
+    pub fee_rounding: FeeRounding,
+    /// Protocol fee charged on top of the LP fee, in bps of the trade
+    pub protocol_fee_bps: u64,
+    /// Trades of at most this amount pay no fee; 0 disables it
+    pub fee_free_threshold: u64,
+}
+
+impl FeeSettingsUpdate {
+    /// No rate above `previous`, on the same tier thresholds
+    ///
+    /// Tiers must keep their thresholds so each rate compares against the one
+    /// it replaces. Locking the tiers, rounding down and waiving fees on more
+    /// trades all favor traders, so only those directions are allowed.
+    pub fn is_no_looser_than(&self, previous: &FeeSettingsUpdate) -> bool {
+        self.fee_tiers.len() == previous.fee_tiers.len()
+            && self.fee_tiers.iter().zip(&previous.fee_tiers).all(|(new, old)| {
+                new.volume_threshold == old.volume_threshold
+                    && new.liquidity_threshold == old.liquidity_threshold
+                    && new.fee_bps <= old.fee_bps
+            })
+            && (self.fee_tiers_locked || !previous.fee_tiers_locked)
+            && (self.fee_rounding == FeeRounding::Down || previous.fee_rounding == FeeRounding::Up)
+            && self.protocol_fee_bps <= previous.protocol_fee_bps
+            && self.fee_free_threshold >= previous.fee_free_threshold
+    }
+}
+
+/// Starting settings for `initialize_pool`
+///
+/// Reuses the parameter update structs so the same validators apply at
+/// initialization as to later timelocked updates.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct InitialConfig {
+    pub trade: TradeSettingsUpdate,
+    pub protection: ProtectionSettingsUpdate,
+    pub fee: FeeSettingsUpdate,
+    pub snipe_protection_seconds: u64,
+    pub auto_pause_on_breaker: bool,
+    /// Seconds before new liquidity counts toward effective liquidity
+    pub liquidity_grace_period: u64,
+    pub timelocks: TimelockSettings,
+    /// Accept a Token-2022 mint with a permanent delegate or close authority
+    pub allow_risky_mint: bool,
+    /// Replaces `protection` unless `Custom`
+    pub preset: ProtectionPreset,
+}
+
+impl Default for InitialConfig {
+    fn default() -> Self {
+        Self {
+            trade: TradeSettingsUpdate {
+                early_trade_fee_bps: 0,
+                early_trade_window_seconds: 0,
+                max_trade_size_bps: 1000,
+                min_trade_size: 1,
+                cooldown_seconds: 0,
+            },
+            protection: ProtectionSettingsUpdate::standard(),
+            fee: FeeSettingsUpdate {
+                fee_tiers: vec![
+                    FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None },
+                    FeeTier { volume_threshold: 10_000_000, fee_bps: 20, liquidity_threshold: None },
+                ],
+                fee_tiers_locked: false,
+                fee_rounding: FeeRounding::Down,
+                protocol_fee_bps: 0,
+                fee_free_threshold: 0,
+            },
+            snipe_protection_seconds: 0,
+            auto_pause_on_breaker: false,
+            liquidity_grace_period: 0,
+            timelocks: TimelockSettings::default(),
+            allow_risky_mint: false,
+            preset: ProtectionPreset::Custom,
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct StateSettingsUpdate {
-    pub is_paused: bool,
+    pub pause_level: PauseLevel,
     pub is_emergency_paused: bool,
 }
 
+impl StateSettingsUpdate {
+    /// Pause changes are never treated as a tweak; only an identical state is
+    pub fn is_no_looser_than(&self, previous: &StateSettingsUpdate) -> bool {
+        self.pause_level == previous.pause_level && self.is_emergency_paused == previous.is_emergency_paused
+    }
+}
+
+/// How much of the pool a pause halts
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PauseLevel {
+    /// Pool is fully operational
+    #[default]
+    None,
+    /// Trading is halted; admin actions continue to work
+    TradingOnly,
+    /// Trading and admin actions are halted, except lifting the pause
+    Full,
+}
+
+impl PauseLevel {
+    pub fn halts_trading(&self) -> bool {
+        *self != PauseLevel::None
+    }
+
+    pub fn halts_admin(&self) -> bool {
+        *self == PauseLevel::Full
+    }
+
+    /// Operation bits this level pauses on its own; a full pause sets them all
+    pub fn paused_ops(&self) -> u8 {
+        match self {
+            PauseLevel::None => 0,
+            PauseLevel::TradingOnly => PAUSE_OP_TRADE,
+            PauseLevel::Full => PAUSE_OP_ALL,
+        }
+    }
+}
+
+/// Rounding direction for fractional fees
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeRounding {
+    /// Truncate fractional fees, favoring the trader
+    #[default]
+    Down,
+    /// Round fractional fees up, favoring the pool
+    Up,
+}
+
+/// What a trade does when its fee would push `total_fees_collected` past the high-water mark
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeOverflowMode {
+    /// Reject trades until the admin withdraws the accrued fees
+    #[default]
+    RequireWithdrawal,
+    /// Accrue up to the mark and leave the excess fee in the pool untracked
+    CapAccrual,
+}
+
+/// Side of a trade relative to the pool's token
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeDirection {
+    /// Pays in another token to receive the pool's token
+    Buy,
+    /// Pays in the pool's token
+    Sell,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct FeeTier {
     pub volume_threshold: u64,
     pub fee_bps: u64,
+    /// Also applies while `total_liquidity` is at or below this; `None` keys off volume alone
+    pub liquidity_threshold: Option<u64>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
@@ -78,6 +462,301 @@ pub struct CircuitBreakerSettings {
     pub cooldown_period: u64,
     pub max_amount: u64,
     pub current_amount: u64,
+    /// Pause the pool when the breaker trips, until an admin resets it
+    pub auto_pause_on_breaker: bool,
+    /// Whether the current pause was engaged by the breaker
+    pub paused_by_breaker: bool,
+    /// Share of `max_amount` drained from the accumulator per decay window, in bps
+    pub decay_bps: u64,
+    /// When the accumulator last decayed
+    pub last_decay: u64,
+    /// Share of `max_amount` that emits `CircuitBreakerApproaching`, in bps; zero uses the default
+    pub warn_threshold_bps: u64,
+}
+
+/// Volatility tracking for the adaptive price impact cap
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct VolatilitySettings {
+    /// Narrow the price impact cap as volatility rises
+    pub enabled: bool,
+    /// Price impact cap at or above `max_volatility_bps`
+    pub floor_impact_bps: u64,
+    /// Volatility at which the cap reaches the floor, in bps per trade
+    pub max_volatility_bps: u64,
+    /// Execution price of the last trade, scaled by `VOLATILITY_PRICE_SCALE`
+    pub last_price: u64,
+    /// Moving average of the price move between trades, in bps
+    pub volatility_bps: u64,
+    /// Price moves folded into the average so far
+    pub samples: u32,
+}
+
+impl VolatilitySettings {
+    /// The configured thresholds, without the recorded prices
+    pub fn settings(&self) -> VolatilitySettingsUpdate {
+        VolatilitySettingsUpdate {
+            enabled: self.enabled,
+            floor_impact_bps: self.floor_impact_bps,
+            max_volatility_bps: self.max_volatility_bps,
+        }
+    }
+
+    /// Replaces the thresholds; recorded volatility is kept
+    pub fn configure(&mut self, settings: &VolatilitySettingsUpdate) {
+        self.enabled = settings.enabled;
+        self.floor_impact_bps = settings.floor_impact_bps;
+        self.max_volatility_bps = settings.max_volatility_bps;
+    }
+
+    /// Folds a trade's execution price into the volatility average
+    ///
+    /// The first trade only sets the reference price. After that, each move is
+    /// measured against the previous price and weighted by
+    /// `1 / VOLATILITY_EWMA_WEIGHT`.
+    pub fn record_price(&mut self, amount_in: u64, amount_out: u64) {
+        if amount_in == 0 {
+            return;
+        }
+        let price = (amount_out as u128 * VOLATILITY_PRICE_SCALE / amount_in as u128).min(u64::MAX as u128) as u64;
+
+        if self.last_price > 0 {
+            let change_bps = (price.abs_diff(self.last_price) as u128 * 10000 / self.last_price as u128)
+                .min(u64::MAX as u128) as u64;
+            self.volatility_bps = if self.samples == 0 {
+                change_bps
+            } else {
+                self.volatility_bps
+                    .saturating_mul(VOLATILITY_EWMA_WEIGHT - 1)
+                    .saturating_add(change_bps)
+                    / VOLATILITY_EWMA_WEIGHT
+            };
+            self.samples = self.samples.saturating_add(1);
+        }
+        self.last_price = price;
+    }
+
+    /// Price impact cap for the current volatility, between the floor and `ceiling_bps`
+    ///
+    /// Falls back to `ceiling_bps` while disabled or before `MIN_VOLATILITY_SAMPLES`
+    /// price moves have been seen.
+    pub fn max_price_impact_bps(&self, ceiling_bps: u64) -> u64 {
+        if !self.enabled || self.samples < MIN_VOLATILITY_SAMPLES || self.max_volatility_bps == 0 {
+            return ceiling_bps;
+        }
+
+        let floor_bps = self.floor_impact_bps.min(ceiling_bps);
+        let volatility = self.volatility_bps.min(self.max_volatility_bps);
+        let narrowing = (ceiling_bps - floor_bps) as u128 * volatility as u128 / self.max_volatility_bps as u128;
+        ceiling_bps - narrowing as u64
+    }
+}
+
+/// Volatility-adaptive price impact thresholds, as set by an update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VolatilitySettingsUpdate {
+    pub enabled: bool,
+    pub floor_impact_bps: u64,
+    pub max_volatility_bps: u64,
+}
+
+impl VolatilitySettingsUpdate {
+    /// An impact cap at or below what `previous` allows at any volatility
+    ///
+    /// Disabled, the cap stays at the static one, so any settings tighten it.
+    /// Enabled, turning it off, a higher floor or a higher volatility scale
+    /// each let through more impact.
+    pub fn is_no_looser_than(&self, previous: &VolatilitySettingsUpdate) -> bool {
+        !previous.enabled
+            || (self.enabled
+                && self.floor_impact_bps <= previous.floor_impact_bps
+                && self.max_volatility_bps <= previous.max_volatility_bps)
+    }
+}
+
+/// Volume and fees for the current accounting period
+///
+/// Kept apart from the protection counters, which decay and reset on their
+/// own windows; these only reset when a period is closed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AccountingPeriod {
+    /// Zero-based index of this period
+    pub index: u64,
+    pub volume: u64,
+    /// Everything traders paid in fees, LP and protocol alike
+    pub fees: u64,
+    pub started_at: i64,
+}
+
+impl AccountingPeriod {
+    /// Adds a settled trade to the period
+    pub fn record_trade(&mut self, amount_in: u64, fee_amount: u64) {
+        self.volume = self.volume.saturating_add(amount_in);
+        self.fees = self.fees.saturating_add(fee_amount);
+    }
+
+    /// Returns the closed period and starts the next one at `current_time`
+    pub fn close(&mut self, current_time: i64) -> AccountingPeriod {
+        let next = AccountingPeriod {
+            index: self.index.saturating_add(1),
+            volume: 0,
+            fees: 0,
+            started_at: current_time,
+        };
+        std::mem::replace(self, next)
+    }
+}
+
+/// One settled trade, as kept in the pool's recent trade history
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TradeRecord {
+    pub trader: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Total fee paid, LP and protocol alike
+    pub fee: u64,
+    pub ts: i64,
+}
+
+/// Ring buffer of the last `RECENT_TRADES_CAPACITY` trades
+///
+/// Once full, each new trade overwrites the oldest one.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct RecentTrades {
+    pub records: [TradeRecord; RECENT_TRADES_CAPACITY],
+    /// Slot the next trade is written to
+    pub next: u8,
+    /// Slots holding a trade, up to `RECENT_TRADES_CAPACITY`
+    pub len: u8,
+}
+
+impl RecentTrades {
+    /// Records a trade, overwriting the oldest once the buffer is full
+    pub fn push(&mut self, record: TradeRecord) {
+        let next = self.next as usize % RECENT_TRADES_CAPACITY;
+        self.records[next] = record;
+        self.next = ((next + 1) % RECENT_TRADES_CAPACITY) as u8;
+        self.len = (self.len as usize + 1).min(RECENT_TRADES_CAPACITY) as u8;
+    }
+
+    /// Retained trades, oldest first
+    pub fn to_vec(&self) -> Vec<TradeRecord> {
+        let len = (self.len as usize).min(RECENT_TRADES_CAPACITY);
+        let start = (self.next as usize + RECENT_TRADES_CAPACITY - len) % RECENT_TRADES_CAPACITY;
+        (0..len)
+            .map(|i| self.records[(start + i) % RECENT_TRADES_CAPACITY])
+            .collect()
+    }
+}
+
+/// Heuristic sandwich detection: thresholds and the last large trade seen
+///
+/// A trade opposite to a recent large trade and of similar size looks like
+/// the back-run of a sandwich. Flagged trades are surcharged, never rejected.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct SandwichSettings {
+    pub enabled: bool,
+    /// Slots after a large trade during which a back-run is looked for
+    pub window_slots: u64,
+    /// Size, in bps of liquidity, from which a trade counts as a possible front-run
+    pub large_trade_bps: u64,
+    /// Largest size difference, in bps of the front-run, that still counts as similar
+    pub similarity_bps: u64,
+    /// Extra LP fee charged on a flagged trade, in bps of its input
+    pub surcharge_bps: u64,
+    /// Direction of the last large trade; `None` once it has been matched
+    pub last_large_direction: Option<TradeDirection>,
+    pub last_large_amount: u64,
+    pub last_large_slot: u64,
+}
+
+impl SandwichSettings {
+    /// The configured thresholds, without the last large trade
+    pub fn settings(&self) -> SandwichSettingsUpdate {
+        SandwichSettingsUpdate {
+            enabled: self.enabled,
+            window_slots: self.window_slots,
+            large_trade_bps: self.large_trade_bps,
+            similarity_bps: self.similarity_bps,
+            surcharge_bps: self.surcharge_bps,
+        }
+    }
+
+    /// Replaces the thresholds and forgets the last large trade
+    pub fn configure(&mut self, settings: &SandwichSettingsUpdate) {
+        *self = SandwichSettings {
+            enabled: settings.enabled,
+            window_slots: settings.window_slots,
+            large_trade_bps: settings.large_trade_bps,
+            similarity_bps: settings.similarity_bps,
+            surcharge_bps: settings.surcharge_bps,
+            ..SandwichSettings::default()
+        };
+    }
+
+    /// Size of the large trade this one looks like the back-run of, if any
+    pub fn suspected_front_run(&self, direction: TradeDirection, amount_in: u64, slot: u64) -> Option<u64> {
+        if !self.enabled {
+            return None;
+        }
+        let front_run_direction = self.last_large_direction?;
+        if front_run_direction == direction
+            || slot < self.last_large_slot
+            || slot - self.last_large_slot > self.window_slots
+        {
+            return None;
+        }
+
+        let difference = amount_in.abs_diff(self.last_large_amount) as u128;
+        if difference * 10000 > self.last_large_amount as u128 * self.similarity_bps as u128 {
+            return None;
+        }
+        Some(self.last_large_amount)
+    }
+
+    /// Records a trade after it settles
+    ///
+    /// A flagged trade consumes the front-run it matched. Otherwise a trade
+    /// of at least `large_trade_bps` of `liquidity` becomes the trade to match
+    /// against, and smaller ones leave the record as it is.
+    pub fn record_trade(&mut self, direction: TradeDirection, amount_in: u64, slot: u64, liquidity: u64, flagged: bool) {
+        if !self.enabled {
+            return;
+        }
+        if flagged {
+            self.last_large_direction = None;
+            return;
+        }
+        if liquidity > 0 && amount_in as u128 * 10000 >= liquidity as u128 * self.large_trade_bps as u128 {
+            self.last_large_direction = Some(direction);
+            self.last_large_amount = amount_in;
+            self.last_large_slot = slot;
+        }
+    }
+}
+
+/// Sandwich detection thresholds, as set by an update
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SandwichSettingsUpdate {
+    pub enabled: bool,
+    pub window_slots: u64,
+    pub large_trade_bps: u64,
+    pub similarity_bps: u64,
+    pub surcharge_bps: u64,
+}
+
+impl SandwichSettingsUpdate {
+    /// Detection at least as strong as `previous`, for no higher a surcharge
+    ///
+    /// Turning detection off, a shorter window or a larger front-run size lets
+    /// more sandwiches through. A wider similarity band or a higher surcharge
+    /// raises what honest traders pay, so each waits out the timelock.
+    pub fn is_no_looser_than(&self, previous: &SandwichSettingsUpdate) -> bool {
+        (self.enabled || !previous.enabled)
+            && self.window_slots >= previous.window_slots
+            && self.large_trade_bps <= previous.large_trade_bps
+            && self.similarity_bps <= previous.similarity_bps
+            && self.surcharge_bps <= previous.surcharge_bps
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
@@ -89,20 +768,548 @@ pub struct VolumeSettings {
     pub current_volume: u64,
     pub last_reset: u64,
     pub decay_period: u64,
-    pub current_volume: u64,
+    /// Per-hour volume, indexed by `hour % VOLUME_BUCKET_COUNT`
+    pub hourly_buckets: [u64; VOLUME_BUCKET_COUNT],
+    /// Hour (unix time / 3600) of the most recently written bucket
+    pub bucket_hour: u64,
+    /// Cap on volume within one hourly window; 0 means `MAX_HOURLY_VOLUME`
+    pub max_hourly: u64,
+    /// Volume traded in the current hourly window
+    pub hourly_volume: u64,
+    /// Start of the current hourly window, set by the first trade after the last one lapsed
+    pub hourly_window_start: u64,
+    /// Per-hour sell volume, indexed like `hourly_buckets`; buys are the remainder
+    pub sell_buckets: [u64; VOLUME_BUCKET_COUNT],
+    /// Part of `volume_24h` that paid in another token for the pool's token
+    pub buy_volume_24h: u64,
+    /// Part of `volume_24h` that paid in the pool's token
+    pub sell_volume_24h: u64,
+}
+
+impl VolumeSettings {
+    /// Zeroes buckets for any hours that have elapsed since `bucket_hour`
+    ///
+    /// A clock that has not advanced past `bucket_hour` leaves the buckets untouched.
+    pub fn roll_buckets(&mut self, current_time: u64) {
+        let current_hour = current_time / VOLUME_BUCKET_SECONDS;
+        if current_hour <= self.bucket_hour {
+            return;
+        }
+
+        let elapsed = (current_hour - self.bucket_hour).min(VOLUME_BUCKET_COUNT as u64);
+        for offset in 1..=elapsed {
+            let index = ((self.bucket_hour + offset) % VOLUME_BUCKET_COUNT as u64) as usize;
+            self.hourly_buckets[index] = 0;
+            self.sell_buckets[index] = 0;
+        }
+        self.bucket_hour = current_hour;
+    }
+
+    /// Adds `amount` to the current hour's bucket and refreshes the 24h totals
+    ///
+    /// Expects `roll_buckets` to have been called for `current_time` first.
+    pub fn record(&mut self, amount: u64, current_time: u64) {
+        let index = (self.bucket_hour % VOLUME_BUCKET_COUNT as u64) as usize;
+        self.hourly_buckets[index] = self.hourly_buckets[index].saturating_add(amount);
+        self.current_volume = self.rolling_volume_at(current_time);
+        self.volume_24h = self.current_volume;
+        self.last_update = current_time;
+    }
+
+    /// Attributes `amount`, already passed to `record`, to the buy or sell side
+    pub fn record_side(&mut self, direction: TradeDirection, amount: u64, current_time: u64) {
+        if direction == TradeDirection::Sell {
+            let index = (self.bucket_hour % VOLUME_BUCKET_COUNT as u64) as usize;
+            self.sell_buckets[index] = self.sell_buckets[index].saturating_add(amount);
+        }
+        self.refresh_side_totals(current_time);
+    }
+
+    /// Splits `volume_24h` into its buy and sell parts
+    fn refresh_side_totals(&mut self, current_time: u64) {
+        self.sell_volume_24h = Self::rolling_sum_at(&self.sell_buckets, self.bucket_hour, current_time);
+        self.buy_volume_24h = self.volume_24h.saturating_sub(self.sell_volume_24h);
+    }
+
+    /// Volume cap for one hourly window
+    ///
+    /// Pools written before the cap existed read it as zero and get the default.
+    pub fn hourly_limit(&self) -> u64 {
+        if self.max_hourly == 0 {
+            MAX_HOURLY_VOLUME
+        } else {
+            self.max_hourly
+        }
+    }
+
+    /// Volume in the hourly window live at `current_time`, zero once it has lapsed
+    pub fn hourly_volume_at(&self, current_time: u64) -> u64 {
+        if current_time >= self.hourly_window_start.saturating_add(MAX_HOURLY_VOLUME_WINDOW) {
+            0
+        } else {
+            self.hourly_volume
+        }
+    }
+
+    /// Adds `amount` to the hourly window, starting a new window if the last one lapsed
+    ///
+    /// A clock behind the window start keeps adding to the current window.
+    pub fn record_hourly(&mut self, amount: u64, current_time: u64) {
+        if current_time >= self.hourly_window_start.saturating_add(MAX_HOURLY_VOLUME_WINDOW) {
+            self.hourly_window_start = current_time;
+            self.hourly_volume = 0;
+        }
+        self.hourly_volume = self.hourly_volume.saturating_add(amount);
+    }
+
+    /// Sums the buckets still inside the 24h window at `current_time`, without mutating state
+    pub fn rolling_volume_at(&self, current_time: u64) -> u64 {
+        Self::rolling_sum_at(&self.hourly_buckets, self.bucket_hour, current_time)
+    }
+
+    /// Sums `buckets` for the hours still inside the 24h window at `current_time`
+    fn rolling_sum_at(buckets: &[u64; VOLUME_BUCKET_COUNT], bucket_hour: u64, current_time: u64) -> u64 {
+        let current_hour = (current_time / VOLUME_BUCKET_SECONDS).max(bucket_hour);
+        let oldest_live_hour = current_hour.saturating_sub(VOLUME_BUCKET_COUNT as u64 - 1);
+        if oldest_live_hour > bucket_hour {
+            return 0;
+        }
+
+        (oldest_live_hour..=bucket_hour)
+            .map(|hour| buckets[(hour % VOLUME_BUCKET_COUNT as u64) as usize])
+            .fold(0u64, |total, bucket| total.saturating_add(bucket))
+    }
+
+    /// Decays the tracked volume to what is still inside the 24h window
+    ///
+    /// Rolls the buckets forward and refreshes the totals, so volume older than
+    /// the window stops counting even when no trade has recorded since. A clock
+    /// behind the last decay leaves the totals as they are.
+    pub fn apply_decay(&mut self, current_time: u64) -> Result<()> {
+        if current_time < self.last_decay {
+            return Ok(());
+        }
+
+        self.roll_buckets(current_time);
+        self.current_volume = self.rolling_volume_at(current_time);
+        self.volume_24h = self.current_volume;
+        self.refresh_side_totals(current_time);
+        self.last_decay = current_time;
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FeeMode {
+    #[default]
+    None,
+    EarlyTrade,
+    TierBased,
+    VolumeBased,
+    CircuitBreaker,
+}
+
+impl FeeMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            crate::FEE_MODE_NONE => Some(FeeMode::None),
+            crate::FEE_MODE_EARLY_TRADE => Some(FeeMode::EarlyTrade),
+            crate::FEE_MODE_TIER_BASED => Some(FeeMode::TierBased),
+            crate::FEE_MODE_VOLUME_BASED => Some(FeeMode::VolumeBased),
+            crate::FEE_MODE_CIRCUIT_BREAKER => Some(FeeMode::CircuitBreaker),
+            _ => None,
+        }
+    }
+}
+
+/// Fee charged on a trade, split by who it accrues to
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TradeFees {
+    /// Fee kept by the pool, then split between referrer, treasury and insurance
+    pub lp_fee: u64,
+    /// Fee owed to the protocol on top of the LP fee
+    pub protocol_fee: u64,
+}
+
+impl TradeFees {
+    /// Everything the trader pays
+    pub fn total(&self) -> u64 {
+        self.lp_fee.saturating_add(self.protocol_fee)
+    }
+}
+
+/// Result of an executed trade
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct TradeOutcome {
+    /// Input actually traded; below the request after a partial fill
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee_amount: u64,
+    pub fee_mode: FeeMode,
+    pub price_impact: u64,
+    pub timestamp: i64,
 }
 
+/// Read-only snapshot of the live protection configuration
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct PendingUpdate {
-    /// When the update should be applied
-    pub scheduled_time: u64,
+pub struct PoolConfig {
+    pub rate_limit: RateLimitSettings,
+    pub circuit_breaker: CircuitBreakerSettings,
+    /// Volume settings with decay applied as of the current clock
+    pub volume: VolumeSettings,
+    pub protection: ProtectionSettings,
+    pub fee_tiers: Vec<FeeTier>,
+    pub fee_tiers_locked: bool,
+    pub fee_rounding: FeeRounding,
+    pub protocol_fee_bps: u64,
+    pub referral_share_bps: u64,
+    pub max_fee_tiers: u16,
+    pub max_blacklist_batch: u16,
+    pub timelocks: TimelockSettings,
+}
+
+/// A liquidity deposit still inside the grace period
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LiquidityDeposit {
+    pub amount: u64,
+    pub deposited_at: u64,
+}
+
+impl LiquidityDeposit {
+    pub fn is_mature(&self, grace_period: u64, current_time: u64) -> bool {
+        current_time.saturating_sub(self.deposited_at) >= grace_period
+    }
+}
+
+/// Liquidity a provider cannot remove before `unlock_ts`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LiquidityLock {
+    pub provider: Pubkey,
+    pub amount: u64,
+    pub unlock_ts: i64,
+}
+
+impl LiquidityLock {
+    pub fn is_expired(&self, current_time: i64) -> bool {
+        current_time >= self.unlock_ts
+    }
+}
+
+/// One trade within `execute_trades_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TradeLeg {
+    pub amount_in: u64,
+    pub minimum_amount_out: u64,
+    pub max_slippage_bps: u64,
+}
+
+/// Result of `execute_trades_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct BatchTradeSummary {
+    pub executed: u32,
+    pub failed: u32,
+    pub total_amount_out: u64,
+    /// Legs that tripped an auto-pausing circuit breaker and moved nothing
+    pub skipped: u32,
+}
+
+/// Authorities on a mint that can move or destroy tokens the pool holds
+///
+/// The permanent delegate and close authority are Token-2022 extensions; a
+/// legacy SPL Token mint only ever has a freeze authority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MintAuthorities {
+    pub freeze_authority: Option<Pubkey>,
+    pub permanent_delegate: Option<Pubkey>,
+    pub close_authority: Option<Pubkey>,
+}
+
+impl MintAuthorities {
+    /// Whether the mint has a permanent delegate or close authority
+    pub fn is_risky(&self) -> bool {
+        self.permanent_delegate.is_some() || self.close_authority.is_some()
+    }
+
+    /// Rejects a permanent delegate or close authority unless `allow_risky_mint` is set
+    ///
+    /// The freeze authority is checked on its own and is never allowed.
+    pub fn check(&self, allow_risky_mint: bool) -> Result<()> {
+        if allow_risky_mint {
+            return Ok(());
+        }
+        if let Some(delegate) = self.permanent_delegate {
+            msg!("Token mint has permanent delegate: {}", delegate);
+            return Err(crate::ErrorCode::RiskyMintAuthority.into());
+        }
+        if let Some(close_authority) = self.close_authority {
+            msg!("Token mint has close authority: {}", close_authority);
+            return Err(crate::ErrorCode::RiskyMintAuthority.into());
+        }
+        Ok(())
+    }
+}
+
+/// Recipient of `share_bps` of the fees paid out by `distribute_fees`
+///
+/// `pubkey` is the recipient's token account for the pool's mint.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeRecipient {
+    pub pubkey: Pubkey,
+    pub share_bps: u64,
+}
+
+/// LP fee discount for traders holding at least `min_balance` governance tokens
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GovernanceDiscountTier {
+    pub min_balance: u64,
+    pub discount_bps: u64,
+}
+
+/// Result of `get_health`
+///
+/// Headroom is the unused share of each limit in bps, so 10000 means untouched
+/// and 0 means exhausted.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PoolHealth {
+    /// No pause, no tripped breaker and no limit near its cap
+    pub healthy: bool,
+    pub paused: bool,
+    pub emergency_paused: bool,
+    pub post_resume_cooldown: bool,
+    /// The breaker paused the pool or has no headroom left in its cooldown
+    pub circuit_breaker_tripped: bool,
+    pub circuit_breaker_headroom_bps: u64,
+    pub volume_near_cap: bool,
+    pub volume_headroom_bps: u64,
+    pub hourly_volume_headroom_bps: u64,
+    pub hourly_trades_near_cap: bool,
+    pub hourly_trades_headroom_bps: u64,
+}
+
+/// Result of `preview_fee_tier`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FeeTierPreview {
+    pub fee_bps: u16,
+    /// `None` when the volume is past every tier and the fallback fee applies
+    pub tier_index: Option<u8>,
+}
+
+/// Result of `get_last_parameter_update`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParameterUpdateRecord {
+    pub ts: i64,
+    pub admin: Pubkey,
+}
+
+/// How trades are handled while the pool's reserves are below a threshold
+///
+/// Protects the remaining LPs in a run on the pool: trades pay a surcharge and
+/// large ones are rejected until reserves recover.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct LowReserveSettings {
+    /// Liquidity below which low reserve mode applies; zero disables it
+    pub threshold: u64,
+    /// Extra LP fee charged in low reserve mode, in bps of the input
+    pub surcharge_bps: u64,
+    /// Largest input accepted in low reserve mode; zero accepts any size
+    pub max_trade_size: u64,
+}
+
+impl LowReserveSettings {
+    /// Protection at least as strong as `previous`, for no higher a surcharge
+    ///
+    /// A lower threshold or a larger trade size loosens the mode, and a higher
+    /// surcharge raises what traders pay, so each waits out the timelock.
+    pub fn is_no_looser_than(&self, previous: &LowReserveSettings) -> bool {
+        // A zero size cap accepts any size
+        let size_cap = |size: u64| if size == 0 { u64::MAX } else { size };
+
+        self.threshold >= previous.threshold
+            && size_cap(self.max_trade_size) <= size_cap(previous.max_trade_size)
+            && self.surcharge_bps <= previous.surcharge_bps
+    }
+}
+
+/// Which counter a soft-failed operation is tallied under
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailureCategory {
+    Trade,
+    Liquidity,
+    Admin,
+}
+
+/// Result of `get_failure_stats`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FailureStats {
+    pub failed_trade_count: u64,
+    pub failed_liquidity_count: u64,
+    pub failed_admin_count: u64,
+}
+
+/// A blacklisted trader with the reason for the ban and when it lapses
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlacklistEntry {
+    pub trader: Pubkey,
+    /// Admin-defined code recording why the trader was blacklisted
+    pub reason_code: u8,
+    /// Unix timestamp at which the ban lapses; 0 means permanent
+    pub expires_at: i64,
+}
+
+impl BlacklistEntry {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+}
+
+/// A page of the trader blacklist
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BlacklistPage {
+    pub entries: Vec<BlacklistEntry>,
+    pub total_count: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TimelockSettings {
+    /// Delay before trade setting changes can be applied
+    pub trade_seconds: u64,
+    /// Delay before protection setting changes can be applied
+    pub protection_seconds: u64,
+    /// Delay before fee setting changes can be applied
+    pub fee_seconds: u64,
+    /// Delay before state setting changes can be applied
+    pub state_seconds: u64,
+    /// Delay before a scheduled emergency pause can be applied
+    pub emergency_pause_seconds: u64,
+    /// Delay before a scheduled emergency resume can be applied
+    pub emergency_resume_seconds: u64,
+}
+
+impl Default for TimelockSettings {
+    fn default() -> Self {
+        Self {
+            trade_seconds: PARAMETER_UPDATE_TIMELOCK,
+            protection_seconds: PARAMETER_UPDATE_TIMELOCK,
+            fee_seconds: PARAMETER_UPDATE_TIMELOCK,
+            state_seconds: PARAMETER_UPDATE_TIMELOCK,
+            emergency_pause_seconds: EMERGENCY_PAUSE_TIMELOCK_SECONDS,
+            emergency_resume_seconds: EMERGENCY_TIMELOCK_SECONDS,
+        }
+    }
+}
+
+impl TimelockSettings {
+    /// No delay shorter than in `previous`
+    pub fn is_no_looser_than(&self, previous: &TimelockSettings) -> bool {
+        self.trade_seconds >= previous.trade_seconds
+            && self.protection_seconds >= previous.protection_seconds
+            && self.fee_seconds >= previous.fee_seconds
+            && self.state_seconds >= previous.state_seconds
+            && self.emergency_pause_seconds >= previous.emergency_pause_seconds
+            && self.emergency_resume_seconds >= previous.emergency_resume_seconds
+    }
+
+    /// Delay before the given emergency action can be applied
+    pub fn emergency_seconds(&self, action: EmergencyActionKind) -> u64 {
+        match action {
+            EmergencyActionKind::Pause => self.emergency_pause_seconds,
+            EmergencyActionKind::Resume => self.emergency_resume_seconds,
+        }
+    }
+
+    /// Longest configured delay across all parameter update categories
+    pub fn longest(&self) -> u64 {
+        self.trade_seconds
+            .max(self.protection_seconds)
+            .max(self.fee_seconds)
+            .max(self.state_seconds)
+    }
+
+    /// Delay that applies to the given update
+    ///
+    /// Changes to the timelocks themselves use the longest existing delay so
+    /// they cannot be used to shortcut any other category.
+    pub fn duration_for(&self, update: &ParameterUpdate) -> u64 {
+        match update {
+            ParameterUpdate::Trade(_) => self.trade_seconds,
+            ParameterUpdate::Protection(_) => self.protection_seconds,
+            ParameterUpdate::Fee(_) => self.fee_seconds,
+            ParameterUpdate::State(_) => self.state_seconds,
+            ParameterUpdate::Timelocks(_) => self.longest(),
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub enum ParameterUpdate {
     /// Updates to trade-related parameters
-    pub trade_settings: Option<TradeSettingsUpdate>,
+    Trade(TradeSettingsUpdate),
     /// Updates to protection mechanisms
-    pub protection_settings: Option<ProtectionSettingsUpdate>,
+    Protection(ProtectionSettingsUpdate),
     /// Updates to fee structure
-    pub fee_settings: Option<FeeSettingsUpdate>,
+    Fee(FeeSettingsUpdate),
     /// Updates to pool state
-    pub state_settings: Option<StateSettingsUpdate>,
+    State(StateSettingsUpdate),
+    /// Updates to the per-category timelock durations
+    Timelocks(TimelockSettings),
+}
+
+impl ParameterUpdate {
+    /// Whether this update is at least as conservative as `previous`
+    ///
+    /// Only updates of the same category compare; see each settings struct for
+    /// what counts as looser.
+    pub fn is_no_looser_than(&self, previous: &ParameterUpdate) -> bool {
+        match (self, previous) {
+            (ParameterUpdate::Trade(new), ParameterUpdate::Trade(old)) => new.is_no_looser_than(old),
+            (ParameterUpdate::Protection(new), ParameterUpdate::Protection(old)) => new.is_no_looser_than(old),
+            (ParameterUpdate::Fee(new), ParameterUpdate::Fee(old)) => new.is_no_looser_than(old),
+            (ParameterUpdate::State(new), ParameterUpdate::State(old)) => new.is_no_looser_than(old),
+            (ParameterUpdate::Timelocks(new), ParameterUpdate::Timelocks(old)) => new.is_no_looser_than(old),
+            _ => false,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PendingUpdate {
+    /// When the update should be applied
+    pub scheduled_time: u64,
+    /// Updates applied together once the timelock expires
+    pub updates: Vec<ParameterUpdate>,
+}
+
+impl PendingUpdate {
+    /// Whether `updates` may replace this batch without restarting its clock
+    ///
+    /// Every replacement must have a pending update of the same category that
+    /// it is no looser than, so a reschedule can only narrow what the waiting
+    /// period already announced. Categories may be dropped but not added.
+    pub fn keeps_schedule_for(&self, updates: &[ParameterUpdate]) -> bool {
+        updates.iter().all(|new| self.updates.iter().any(|old| new.is_no_looser_than(old)))
+    }
+
+    /// Scheduled time with the timelock restarted from `current_time`
+    ///
+    /// Never later than the current schedule, so re-anchoring only rescues a
+    /// schedule pushed out too far and cannot be used to delay an update.
+    pub fn re_anchored_time(&self, current_time: u64, timelock: u64) -> u64 {
+        self.scheduled_time.min(current_time.saturating_add(timelock))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmergencyActionKind {
+    Pause,
+    Resume,
+}
+
+/// Emergency action awaiting its timelock, kept apart from parameter updates
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PendingEmergency {
+    /// Which action was scheduled
+    pub action: EmergencyActionKind,
+    /// When the action may be applied
+    pub scheduled_time: u64,
 }
 
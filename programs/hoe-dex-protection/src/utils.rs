@@ -0,0 +1,706 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        mint_close_authority::MintCloseAuthority, permanent_delegate::PermanentDelegate, BaseStateWithExtensions,
+        ExtensionType, StateWithExtensions,
+    },
+    state::Mint as Token2022Mint,
+};
+use std::collections::HashSet;
+use crate::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlacklistOperation {
+    Add { reason_code: u8, expires_at: i64 },
+    Remove,
+}
+
+/// Apply a blacklist operation to a batch of traders
+///
+/// The batch is deduplicated before processing so repeated entries are only
+/// counted once. Traders already in the requested state are skipped, and the
+/// emitted `BatchBlacklistCompleted.count` reflects only addresses that changed.
+pub fn process_blacklist_operations(
+    state: &mut PoolState,
+    traders: Vec<Pubkey>,
+    operation: BlacklistOperation,
+    current_time: u64,
+) -> Result<()> {
+    // Validate blacklist is enabled
+    validate_condition!(
+        state.protection.blacklist_enabled,
+        crate::ErrorCode::Unauthorized,
+        "Blacklist is disabled for this pool"
+    );
+
+    // Validate batch size
+    validate_condition!(!traders.is_empty(), crate::ErrorCode::InvalidAmount);
+    let max_batch = state.blacklist_batch_limit();
+    validate_condition!(
+        traders.len() <= max_batch,
+        crate::ErrorCode::BlacklistBatchTooLarge,
+        "Invalid blacklist batch size: {} (max: {})",
+        traders.len(),
+        max_batch
+    );
+
+    // Reject the whole batch if it contains the default pubkey
+    validate_condition!(
+        !traders.contains(&Pubkey::default()),
+        crate::ErrorCode::InvalidTraderAddress,
+        "Blacklist batch contains the default pubkey"
+    );
+
+    // Deduplicate while preserving input order
+    let mut seen = HashSet::with_capacity(traders.len());
+    let unique_traders: Vec<Pubkey> = traders
+        .into_iter()
+        .filter(|trader| seen.insert(*trader))
+        .collect();
+
+    let mut changed: u64 = 0;
+    match operation {
+        BlacklistOperation::Add { reason_code, expires_at } => {
+            validate_condition!(
+                expires_at == 0 || expires_at > current_time as i64,
+                crate::ErrorCode::InvalidBlacklistExpiry,
+                "Blacklist expiry {} is not in the future",
+                expires_at
+            );
+            for trader in unique_traders {
+                validate_condition!(
+                    trader != state.admin && trader != state.emergency_admin,
+                    crate::ErrorCode::InvalidTraderAddress,
+                    "Cannot blacklist admin or emergency admin: {}",
+                    trader
+                );
+                if state.is_blacklisted(&trader, current_time as i64) {
+                    continue;
+                }
+                // An expired entry is replaced rather than duplicated
+                state.trader_blacklist.retain(|entry| entry.trader != trader);
+                validate_condition!(
+                    state.trader_blacklist.len() < MAX_BLACKLIST_SIZE,
+                    crate::ErrorCode::BlacklistFull,
+                    "Blacklist full: {} entries (max: {})",
+                    state.trader_blacklist.len(),
+                    MAX_BLACKLIST_SIZE
+                );
+                state.trader_blacklist.push(BlacklistEntry {
+                    trader,
+                    reason_code,
+                    expires_at,
+                });
+                changed += 1;
+
+                emit!(TraderBlacklisted {
+                    pool: state.key(),
+                    trader_pubkey: trader,
+                    reason_code,
+                    expires_at,
+                    ts: current_time as i64,
+                });
+            }
+        }
+        BlacklistOperation::Remove => {
+            for trader in unique_traders {
+                let Some(index) = state.trader_blacklist.iter().position(|entry| entry.trader == trader) else {
+                    continue;
+                };
+                state.trader_blacklist.remove(index);
+                changed += 1;
+
+                emit!(TraderRemovedFromBlacklist {
+                    pool: state.key(),
+                    trader_pubkey: trader,
+                    ts: current_time as i64,
+                });
+            }
+        }
+    }
+
+    state.last_update = current_time;
+
+    emit!(BatchBlacklistCompleted {
+        pool: state.key(),
+        admin_pubkey: state.admin,
+        count: changed,
+        ts: current_time as i64,
+    });
+
+    Ok(())
+}
+
+/// Reads the authorities on a legacy SPL Token or Token-2022 mint
+///
+/// Token-2022 mint data starts with the legacy layout, so both parse the
+/// same way; only Token-2022 data can carry the extensions.
+pub fn mint_authorities(data: &[u8]) -> Result<MintAuthorities> {
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(data).map_err(|_| {
+        msg!("Token mint data could not be parsed");
+        crate::ErrorCode::InvalidTokenMint
+    })?;
+
+    Ok(MintAuthorities {
+        freeze_authority: mint.base.freeze_authority.into(),
+        permanent_delegate: mint
+            .get_extension::<PermanentDelegate>()
+            .ok()
+            .and_then(|extension| Option::<Pubkey>::from(extension.delegate)),
+        close_authority: mint
+            .get_extension::<MintCloseAuthority>()
+            .ok()
+            .and_then(|extension| Option::<Pubkey>::from(extension.close_authority)),
+    })
+}
+
+/// Rejects a Token-2022 mint whose transfers the pool cannot account for
+///
+/// A transfer fee delivers less than the amount the pool books, and a
+/// transfer hook needs accounts the pool's transfers do not pass.
+pub fn check_mint_transfer_extensions(data: &[u8]) -> Result<()> {
+    let mint = StateWithExtensions::<Token2022Mint>::unpack(data).map_err(|_| {
+        msg!("Token mint data could not be parsed");
+        crate::ErrorCode::InvalidTokenMint
+    })?;
+    let extensions = mint.get_extension_types().map_err(|_| crate::ErrorCode::InvalidTokenMint)?;
+
+    for unsupported in [ExtensionType::TransferFeeConfig, ExtensionType::TransferHook] {
+        validate_condition!(
+            !extensions.contains(&unsupported),
+            crate::ErrorCode::InvalidTokenMint,
+            "Token mint has unsupported extension {:?}",
+            unsupported
+        );
+    }
+    Ok(())
+}
+
+/// Maps a trade validation error to its `TradeRejected` reason code
+///
+/// Errors that are not a protection rejection map to `TRADE_REJECT_OTHER`.
+pub fn trade_reject_reason(err: &Error) -> u8 {
+    const REASONS: &[(crate::ErrorCode, u8)] = &[
+        (crate::ErrorCode::PoolPaused, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::EmergencyPaused, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::OperationPaused, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::PostResumeCooldown, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::PoolStale, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::PoolRetired, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::PoolFrozen, TRADE_REJECT_PAUSED),
+        (crate::ErrorCode::TraderBlacklisted, TRADE_REJECT_BLACKLISTED),
+        (crate::ErrorCode::RateLimitExceeded, TRADE_REJECT_RATE_LIMIT),
+        (crate::ErrorCode::TradeCooldownActive, TRADE_REJECT_RATE_LIMIT),
+        (crate::ErrorCode::HourlyTradeLimitExceeded, TRADE_REJECT_RATE_LIMIT),
+        (crate::ErrorCode::TooManyTradesPerTx, TRADE_REJECT_RATE_LIMIT),
+        (crate::ErrorCode::VolumeLimitExceeded, TRADE_REJECT_VOLUME),
+        (crate::ErrorCode::DailyVolumeLimitExceeded, TRADE_REJECT_VOLUME),
+        (crate::ErrorCode::HourlyVolumeLimitExceeded, TRADE_REJECT_VOLUME),
+        (crate::ErrorCode::TraderDailyVolumeExceeded, TRADE_REJECT_VOLUME),
+        (crate::ErrorCode::CircuitBreakerTriggered, TRADE_REJECT_CIRCUIT_BREAKER),
+        (crate::ErrorCode::CircuitBreakerCooldown, TRADE_REJECT_CIRCUIT_BREAKER),
+        (crate::ErrorCode::InvalidAmount, TRADE_REJECT_TRADE_SIZE),
+        (crate::ErrorCode::TradeTooSmall, TRADE_REJECT_TRADE_SIZE),
+        (crate::ErrorCode::ZeroOutput, TRADE_REJECT_TRADE_SIZE),
+        (crate::ErrorCode::TradeTooLarge, TRADE_REJECT_TRADE_SIZE),
+        (crate::ErrorCode::PriceImpactTooHigh, TRADE_REJECT_PRICE_IMPACT),
+        (crate::ErrorCode::SlippageExceeded, TRADE_REJECT_SLIPPAGE),
+        (crate::ErrorCode::BelowLiquidityFloor, TRADE_REJECT_LIQUIDITY),
+        (crate::ErrorCode::OutputExceedsReserveFraction, TRADE_REJECT_LIQUIDITY),
+        (crate::ErrorCode::LowReserveTradeTooLarge, TRADE_REJECT_LIQUIDITY),
+    ];
+
+    REASONS
+        .iter()
+        .find(|(code, _)| *err == Error::from(*code))
+        .map_or(TRADE_REJECT_OTHER, |(_, reason)| *reason)
+}
+
+/// Shortfall of `amount_out` against `amount_in`, in basis points (rounded down)
+///
+/// Uses u128 intermediates so `(amount_in - amount_out) * 10000` cannot
+/// overflow for large but otherwise valid trades.
+pub fn calculate_slippage_bps(amount_in: u64, amount_out: u64) -> Result<u64> {
+    let shortfall = amount_in.checked_sub(amount_out).ok_or_else(|| {
+        msg!("Slippage calculation underflow: {} - {}", amount_in, amount_out);
+        error!(crate::ErrorCode::Overflow)
+    })?;
+    let slippage_bps = (shortfall as u128 * 10000)
+        .checked_div(amount_in as u128)
+        .ok_or(crate::ErrorCode::Overflow)?;
+
+    // shortfall <= amount_in, so the result is at most 10000
+    Ok(slippage_bps as u64)
+}
+
+/// When an action scheduled now with `timelock` becomes due
+///
+/// Configured timelocks are validated against `MAX_PARAMETER_UPDATE_TIMELOCK`,
+/// but this re-checks the computed delay so a value that slipped past, e.g.
+/// on a pool written before the bound existed, cannot lock a schedule away.
+pub fn scheduled_time_after(current_time: u64, timelock: u64) -> Result<u64> {
+    validate_condition!(
+        timelock <= MAX_PARAMETER_UPDATE_TIMELOCK,
+        crate::ErrorCode::InvalidTimelockDuration,
+        "Timelock {}s exceeds maximum {}s",
+        timelock,
+        MAX_PARAMETER_UPDATE_TIMELOCK
+    );
+    current_time.checked_add(timelock).ok_or_else(|| error!(crate::ErrorCode::Overflow))
+}
+
+/// Input left to trade once `fee_amount` is taken out
+///
+/// Fails with `ZeroOutput` when the fee would consume the whole input, so a
+/// dust trade is rejected instead of paying in for nothing.
+pub fn amount_after_fee(amount_in: u64, fee_amount: u64) -> Result<u64> {
+    validate_condition!(
+        fee_amount < amount_in,
+        crate::ErrorCode::ZeroOutput,
+        "Fee {} consumes the whole input {}",
+        fee_amount,
+        amount_in
+    );
+    Ok(amount_in - fee_amount)
+}
+
+/// Minimum acceptable output for a quote of `expected_out` and a tolerance in bps
+///
+/// Rounds down, so the derived minimum never rejects an output the tolerance allows.
+pub fn min_amount_out_from_tolerance(expected_out: u64, tolerance_bps: u64) -> Result<u64> {
+    validate_condition!(expected_out > 0, crate::ErrorCode::InvalidAmount, "Expected output must be non-zero");
+    validate_condition!(
+        tolerance_bps <= 10000,
+        crate::ErrorCode::InvalidSlippageTolerance,
+        "Invalid slippage tolerance: {} bps (max: 10000)",
+        tolerance_bps
+    );
+
+    // (10000 - tolerance_bps) <= 10000, so the result fits back into u64
+    Ok((expected_out as u128 * (10000 - tolerance_bps) as u128 / 10000) as u64)
+}
+
+/// Credits the referrer's share of a trade fee to their referral account
+///
+/// The referral account must be passed with the trade and belong to this pool
+/// and referrer. Returns the accrual event, which the trade emits once it
+/// settles, or `None` when the referral fee rounds to zero.
+fn accrue_referral_fee(
+    accounts: &mut contexts::ExecuteTrade,
+    referrer: Pubkey,
+    fee_amount: u64,
+    current_time: u64,
+) -> Result<Option<ReferralFeeAccrued>> {
+    let pool = accounts.pool_state.key();
+    let trader = accounts.buyer.key();
+    validate_condition!(
+        referrer != trader,
+        crate::ErrorCode::InvalidReferrer,
+        "Trader cannot refer their own trade"
+    );
+
+    let Some(referral_state) = accounts.referral_state.as_mut() else {
+        msg!("Referral account missing for referrer {}", referrer);
+        return Err(crate::ErrorCode::InvalidReferrer.into());
+    };
+    validate_condition!(
+        referral_state.pool == pool && referral_state.referrer == referrer,
+        crate::ErrorCode::InvalidReferrer,
+        "Referral account does not belong to referrer {}",
+        referrer
+    );
+
+    let referral_fee = accounts.pool_state.referral_fee(fee_amount)?;
+    if referral_fee == 0 {
+        return Ok(None);
+    }
+
+    referral_state.accrued = referral_state.accrued
+        .checked_add(referral_fee)
+        .ok_or_else(|| {
+            msg!("Referral accrual overflow: {} + {}", referral_state.accrued, referral_fee);
+            error!(crate::ErrorCode::Overflow)
+        })?;
+    accounts.pool_state.referral_fees_owed = accounts.pool_state.referral_fees_owed
+        .checked_add(referral_fee)
+        .ok_or_else(|| {
+            msg!("Referral fees overflow: {} + {}", accounts.pool_state.referral_fees_owed, referral_fee);
+            error!(crate::ErrorCode::Overflow)
+        })?;
+
+    Ok(Some(ReferralFeeAccrued {
+        pool,
+        referrer,
+        trader,
+        amount: referral_fee,
+        accrued: referral_state.accrued,
+        ts: current_time as i64,
+    }))
+}
+
+/// Governance token balance backing the trader's fee discount
+///
+/// Zero when the pool has no governance mint or the trader passed no account.
+/// A passed account must hold the governance mint and belong to the trader,
+/// so one holder's balance cannot discount someone else's trade.
+pub fn governance_balance(accounts: &contexts::ExecuteTrade) -> Result<u64> {
+    let (Some(governance_mint), Some(account)) =
+        (accounts.pool_state.governance_mint, accounts.governance_token_account.as_ref())
+    else {
+        return Ok(0);
+    };
+    validate_condition!(
+        account.mint == governance_mint && account.owner == accounts.buyer.key(),
+        crate::ErrorCode::InvalidGovernanceTokenAccount,
+        "Governance token account {} is not the trader's {} account",
+        account.key(),
+        governance_mint
+    );
+    Ok(account.amount)
+}
+
+/// Direction of a trade, derived from the token it pays in
+///
+/// Taken from the accounts rather than an instruction argument so a trader
+/// cannot claim the other side. Paying in the pool's token is a sell.
+pub fn trade_direction(accounts: &contexts::ExecuteTrade) -> TradeDirection {
+    if accounts.buyer_token_account.mint == accounts.pool_state.token_mint {
+        TradeDirection::Sell
+    } else {
+        TradeDirection::Buy
+    }
+}
+
+/// Sizes a trade down to what the pool's limits still admit
+///
+/// Returns the fillable input, and `minimum_amount_out` scaled by the same
+/// fraction. A trade that fits, or one with no headroom left at all, comes back
+/// unchanged so it succeeds or fails as a full trade would.
+pub fn partial_fill(
+    state: &PoolState,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    current_time: u64,
+) -> Result<(u64, u64)> {
+    let fillable = state.fillable_amount(amount_in, current_time)?;
+    if fillable == 0 || fillable >= amount_in {
+        return Ok((amount_in, minimum_amount_out));
+    }
+
+    let scaled_minimum = (minimum_amount_out as u128 * fillable as u128 / amount_in as u128) as u64;
+    Ok((fillable, scaled_minimum))
+}
+
+/// Takes the pool's reentrancy lock and writes it through to the account
+///
+/// Written before any CPI so that a call re-entering the program sees the lock
+/// held. The caller releases it before returning; a failed instruction reverts
+/// the lock with everything else.
+pub fn acquire_reentrancy_lock(lock: &mut Account<ReentrancyLock>, pool: Pubkey, bump: u8) -> Result<()> {
+    lock.acquire(pool, bump)?;
+    lock.exit(&crate::ID)
+}
+
+/// Validate and settle a single trade against the pool
+///
+/// All checks and fallible state updates run before the token transfer, so an
+/// error returned from here leaves no tokens moved. `execute_trades_batch` relies
+/// on this to roll back a failed leg by restoring the account snapshots. Volume
+/// is decayed before the checks and recorded with the other state updates,
+/// ahead of the transfer, following checks-effects-interactions.
+pub fn process_trade(
+    accounts: &mut contexts::ExecuteTrade,
+    trader_state_bump: u8,
+    trader_volume_bump: u8,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    max_slippage_bps: u64,
+    referrer: Option<Pubkey>,
+    current_time: u64,
+) -> Result<TradeOutcome> {
+    // Validate the fee tier invariant, the mint, the calling program, then trade parameters
+    accounts.pool_state.check_fee_tiers_present()?;
+    accounts.pool_state.check_token_mint(&accounts.token_mint)?;
+    validation::validate_trade_caller(&accounts.pool_state, &accounts.instructions)?;
+    validation::validate_trades_per_tx(&accounts.pool_state, &accounts.instructions)?;
+    let trader = accounts.buyer.key();
+    // Decay up front so validation finds nothing left to log; the event is
+    // held with the others below until the trade settles, since a failed
+    // batch leg is rolled back
+    let volume_decayed = accounts.pool_state.decay_volume_deferred(current_time)?;
+    validation::validate_trade_parameters(&mut accounts.pool_state, &trader, amount_in, current_time)?;
+    accounts.pool_state.check_token_account_owner(&accounts.buyer_token_account.owner, &trader)?;
+
+    // Enforce per-trader cooldown between consecutive trades
+    accounts.trader_state.check_trade_cooldown(
+        accounts.pool_state.protection.min_trade_interval,
+        current_time as i64,
+    )?;
+
+    // Enforce the per-trader daily volume cap
+    accounts.trader_volume.check_daily_limit(
+        amount_in,
+        accounts.pool_state.protection.max_daily_volume_per_trader,
+        current_time,
+    )?;
+
+    // Reject large trades while reserves are low
+    let low_reserve = accounts.pool_state.is_low_reserve();
+    if low_reserve {
+        accounts.pool_state.check_low_reserve_trade_size(amount_in)?;
+    }
+
+    // Calculate fee and amount out
+    let direction = trade_direction(accounts);
+    let (fees, fee_mode, fee_tier_index) = accounts.pool_state.calculate_fee(amount_in, current_time as i64)?;
+
+    // Discount the LP fee for governance token holders, once per trader per slot
+    let slot = Clock::get()?.slot;
+    let discount_bps = accounts.pool_state.governance_discount_bps(governance_balance(accounts)?);
+    let fees = if discount_bps > 0 && accounts.trader_state.claim_discount_slot(slot) {
+        accounts.pool_state.apply_governance_discount(fees, discount_bps)
+    } else {
+        fees
+    };
+
+    // Surcharge a likely sandwich back-run; it is flagged, never rejected
+    let liquidity_before = accounts.pool_state.total_liquidity;
+    let front_run_amount = accounts.pool_state.sandwich.suspected_front_run(direction, amount_in, slot);
+    let sandwich_surcharge = match front_run_amount {
+        Some(_) => accounts.pool_state.sandwich_surcharge(amount_in, fees)?,
+        None => 0,
+    };
+    let fees = TradeFees { lp_fee: fees.lp_fee + sandwich_surcharge, ..fees };
+
+    // Surcharge every trade while reserves are low
+    let low_reserve_surcharge = if low_reserve {
+        accounts.pool_state.low_reserve_surcharge(amount_in, fees)?
+    } else {
+        0
+    };
+    let fees = TradeFees { lp_fee: fees.lp_fee + low_reserve_surcharge, ..fees };
+    let fee_amount = fees.total();
+    let amount_after_fee = amount_after_fee(amount_in, fee_amount)?;
+
+    // Calculate price impact against liquidity that is past its grace period,
+    // capped by trade size and lower while recent prices are volatile
+    let effective_liquidity = accounts.pool_state.effective_liquidity(current_time);
+    let price_impact = accounts.pool_state.calculate_price_impact(amount_after_fee, effective_liquidity)?;
+    let max_price_impact_bps = accounts.pool_state.effective_max_price_impact_bps_for_size(amount_in);
+    if price_impact > max_price_impact_bps {
+        msg!("Price impact too high: {} > {}", price_impact, max_price_impact_bps);
+        return Err(crate::ErrorCode::PriceImpactTooHigh.into());
+    }
+
+    // Calculate amount out
+    let amount_out = amount_after_fee.checked_mul(accounts.pool_state.total_liquidity)
+        .ok_or_else(|| {
+            msg!("Amount calculation overflow: {} * {}", amount_after_fee, accounts.pool_state.total_liquidity);
+            error!(crate::ErrorCode::Overflow)
+        })?
+        .checked_div(accounts.pool_state.total_liquidity.checked_add(amount_after_fee)
+            .ok_or_else(|| {
+                msg!("Pool balance overflow: {} + {}", accounts.pool_state.total_liquidity, amount_after_fee);
+                error!(crate::ErrorCode::Overflow)
+            })?)
+        .ok_or_else(|| {
+            msg!("Division by zero in amount calculation");
+            error!(crate::ErrorCode::Overflow)
+        })?;
+
+    // Dust input can round the output down to nothing
+    validate_condition!(
+        amount_out > 0,
+        crate::ErrorCode::ZeroOutput,
+        "Trade of {} rounds to zero output",
+        amount_in
+    );
+
+    // Check slippage
+    if amount_out < minimum_amount_out {
+        msg!("Slippage exceeded: got {} < minimum {}", amount_out, minimum_amount_out);
+        return Err(crate::ErrorCode::SlippageExceeded.into());
+    }
+
+    // Check slippage against the pool ceiling (caller can only tighten it)
+    let effective_max_slippage_bps = accounts.pool_state.effective_max_slippage_bps(max_slippage_bps);
+    let slippage_bps = calculate_slippage_bps(amount_in, amount_out)?;
+    if slippage_bps > effective_max_slippage_bps {
+        msg!("Slippage exceeded: {} bps > {} bps allowed", slippage_bps, effective_max_slippage_bps);
+        return Err(crate::ErrorCode::SlippageExceeded.into());
+    }
+
+    // Check the output against the pool's drain guard
+    accounts.pool_state.check_output_fraction(amount_out)?;
+
+    // Check the rolling 24h volume cap
+    accounts.pool_state.check_volume_limit(amount_in, current_time)?;
+
+    // Accumulate into the circuit breaker; an auto-pausing breach commits the
+    // pause and skips the trade instead of reverting, so the pause sticks
+    let (breaker_tripped, breaker_warning) =
+        accounts.pool_state.update_circuit_breaker_deferred(amount_in, current_time)?;
+    if breaker_tripped {
+        if accounts.pool_state.pause_level.halts_trading() {
+            msg!("Circuit breaker tripped: pool auto-paused, trade skipped");
+            if let Some(event) = volume_decayed {
+                emit!(event);
+            }
+            return Ok(TradeOutcome {
+                timestamp: current_time as i64,
+                ..TradeOutcome::default()
+            });
+        }
+        msg!("Circuit breaker tripped: amount {} exceeds remaining headroom", amount_in);
+        return Err(crate::ErrorCode::CircuitBreakerTriggered.into());
+    }
+
+    // Update pool state; only the non-fee portion of the input is liquidity
+    accounts.pool_state.total_liquidity = accounts.pool_state.total_liquidity
+        .checked_add(amount_after_fee)
+        .ok_or_else(|| {
+            msg!("Liquidity overflow: {} + {}", accounts.pool_state.total_liquidity, amount_after_fee);
+            error!(crate::ErrorCode::Overflow)
+        })?;
+
+    // Carve out the referrer's share before the treasury and insurance split
+    let referral_accrued = match referrer {
+        Some(referrer) => accrue_referral_fee(accounts, referrer, fees.lp_fee, current_time)?,
+        None => None,
+    };
+    let referral_fee = referral_accrued.as_ref().map_or(0, |event| event.amount);
+
+    // Split the remaining LP fee between the treasury and the insurance fund
+    let pool_fee = fees.lp_fee.checked_sub(referral_fee).ok_or(crate::ErrorCode::Underflow)?;
+    let (treasury_fee, insurance_fee) = accounts.pool_state.split_insurance_fee(pool_fee)?;
+
+    accounts.pool_state.accrue_treasury_fee(treasury_fee, current_time)?;
+
+    accounts.pool_state.insurance_collected = accounts.pool_state.insurance_collected
+        .checked_add(insurance_fee)
+        .ok_or_else(|| {
+            msg!("Insurance collection overflow: {} + {}", accounts.pool_state.insurance_collected, insurance_fee);
+            error!(crate::ErrorCode::Overflow)
+        })?;
+
+    // The protocol fee is tracked apart from every LP-side balance
+    accounts.pool_state.protocol_fees = accounts.pool_state.protocol_fees
+        .checked_add(fees.protocol_fee)
+        .ok_or_else(|| {
+            msg!("Protocol fee overflow: {} + {}", accounts.pool_state.protocol_fees, fees.protocol_fee);
+            error!(crate::ErrorCode::Overflow)
+        })?;
+
+    accounts.pool_state.trade_settings.last_trade_time = current_time;
+    accounts.pool_state.last_update = current_time;
+    accounts.trader_state.record_trade(
+        accounts.pool_state.key(),
+        accounts.buyer.key(),
+        trader_state_bump,
+        current_time as i64,
+    );
+    accounts.trader_volume.record_trade(
+        accounts.pool_state.key(),
+        accounts.buyer.key(),
+        trader_volume_bump,
+        amount_in,
+        current_time,
+    );
+
+    // Record volume, trade count, period totals, price, sandwich state and history
+    accounts.pool_state.update_volume(amount_in, current_time)?;
+    accounts.pool_state.volume.record_side(direction, amount_in, current_time);
+    accounts.pool_state.record_hourly_trade(current_time);
+    accounts.pool_state.accounting_period.record_trade(amount_in, fee_amount);
+    accounts.pool_state.volatility.record_price(amount_after_fee, amount_out);
+    accounts.pool_state.sandwich.record_trade(direction, amount_in, slot, liquidity_before, front_run_amount.is_some());
+    accounts.pool_state.recent_trades.push(TradeRecord {
+        trader: accounts.buyer.key(),
+        amount_in,
+        amount_out,
+        fee: fee_amount,
+        ts: current_time as i64,
+    });
+    let blacklist_pruned = accounts.pool_state.prune_expired_blacklist_entry(&trader, current_time as i64);
+
+    // Transfer from buyer to pool; done last so every state update above is in
+    // place before the token program runs, and a failed check never leaves a
+    // moved balance
+    token_interface::transfer_checked(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.buyer_token_account.to_account_info(),
+                mint: accounts.token_mint.to_account_info(),
+                to: accounts.pool_token_account.to_account_info(),
+                authority: accounts.buyer.to_account_info(),
+            },
+        ),
+        amount_in,
+        accounts.token_mint.decimals,
+    )?;
+
+    // The trade has settled; log what was held back while it could still fail
+    if let Some(event) = volume_decayed {
+        emit!(event);
+    }
+    if let Some(entry) = blacklist_pruned {
+        emit!(TraderRemovedFromBlacklist {
+            pool: accounts.pool_state.key(),
+            trader_pubkey: entry.trader,
+            ts: current_time as i64,
+        });
+    }
+    if let Some(event) = breaker_warning {
+        emit!(event);
+    }
+    if let Some(event) = referral_accrued {
+        emit!(event);
+    }
+
+    if let Some(front_run_amount) = front_run_amount {
+        emit!(SandwichSuspected {
+            pool: accounts.pool_state.key(),
+            trader: accounts.buyer.key(),
+            direction,
+            amount_in,
+            front_run_amount,
+            surcharge: sandwich_surcharge,
+            slot,
+            ts: current_time as i64,
+        });
+    }
+
+    if low_reserve {
+        emit!(LowReserveMode {
+            pool: accounts.pool_state.key(),
+            trader: accounts.buyer.key(),
+            reserves: liquidity_before,
+            threshold: accounts.pool_state.low_reserve.threshold,
+            amount_in,
+            surcharge: low_reserve_surcharge,
+            ts: current_time as i64,
+        });
+    }
+
+    // Emit trade event
+    accounts.pool_state.emit_trade_executed(
+        &accounts.buyer.key(),
+        amount_in,
+        amount_out,
+        fee_amount,
+        fee_mode as u8,
+        fee_tier_index,
+        direction,
+        current_time as i64,
+    );
+
+    Ok(TradeOutcome {
+        amount_in,
+        amount_out,
+        fee_amount,
+        fee_mode: FeeMode::from_u8(fee_mode).unwrap_or(FeeMode::None),
+        price_impact,
+        timestamp: current_time as i64,
+    })
+}
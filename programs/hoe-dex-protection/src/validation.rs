@@ -1,7 +1,50 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
 use crate::*;
 
-pub fn validate_admin_action(state: &PoolState, admin: &Pubkey, current_time: i64) -> Result<()> {
+pub fn validate_admin_action(state: &PoolState, admin: &Pubkey, current_time: u64) -> Result<()> {
+    validate_admin_signer(state, admin, current_time)?;
+    validate_condition!(
+        !state.pause_level.halts_admin(),
+        crate::ErrorCode::PoolFrozen,
+        "Admin actions are halted by a full pause"
+    );
+    Ok(())
+}
+
+/// Checks the fee authority's identity, and that a full pause has not halted admin actions
+pub fn validate_fee_authority_action(state: &PoolState, fee_authority: &Pubkey) -> Result<()> {
+    validate_condition!(
+        fee_authority == &state.fee_authority(),
+        crate::ErrorCode::Unauthorized,
+        "Fee authority {} expected, got {}",
+        state.fee_authority(),
+        fee_authority
+    );
+    validate_condition!(
+        !state.pause_level.halts_admin(),
+        crate::ErrorCode::PoolFrozen,
+        "Admin actions are halted by a full pause"
+    );
+    Ok(())
+}
+
+/// Rejects an operation whose bit is paused, by `paused_ops` or the pause level
+pub fn validate_operation_not_paused(state: &PoolState, op: u8) -> Result<()> {
+    validate_condition!(
+        !state.is_op_paused(op),
+        crate::ErrorCode::OperationPaused,
+        "Operation {:#04x} is paused (paused ops: {:#04x})",
+        op,
+        state.effective_paused_ops()
+    );
+    Ok(())
+}
+
+/// Checks only the admin's identity; used by the instruction that lifts a full pause
+pub fn validate_admin_signer(state: &PoolState, admin: &Pubkey, _current_time: u64) -> Result<()> {
     validate_condition!(
         admin == &state.admin || admin == &state.emergency_admin,
         crate::ErrorCode::Unauthorized
@@ -9,7 +52,117 @@ pub fn validate_admin_action(state: &PoolState, admin: &Pubkey, current_time: i6
     Ok(())
 }
 
+/// Checks the trade's top-level program against the pool's caller allow-list
+///
+/// The instructions sysvar only exposes top-level instructions, so for a CPI
+/// this is the outermost program in the call chain.
+pub fn validate_trade_caller(state: &PoolState, instructions: &AccountInfo) -> Result<()> {
+    if state.allowed_callers.is_empty() {
+        return Ok(());
+    }
+
+    let current_index = load_current_index_checked(instructions)?;
+    let top_level = load_instruction_at_checked(current_index as usize, instructions)?;
+    state.check_caller_allowed(&top_level.program_id)
+}
+
+/// Caps the trade instructions against this pool in the current transaction
+///
+/// Counts top-level `execute_trade` and `execute_trades_batch` instructions
+/// whose pool account is this pool; a batch counts once however many legs it
+/// has. A zero `max_trades_per_tx` disables the check.
+pub fn validate_trades_per_tx(state: &PoolState, instructions: &AccountInfo) -> Result<()> {
+    if state.max_trades_per_tx == 0 {
+        return Ok(());
+    }
+
+    let pool = state.key();
+    let mut trade_count: u16 = 0;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        let is_trade = ix.program_id == crate::ID
+            && (ix.data.starts_with(&crate::instruction::ExecuteTrade::DISCRIMINATOR[..])
+                || ix.data.starts_with(&crate::instruction::ExecuteTradesBatch::DISCRIMINATOR[..]));
+        if is_trade && ix.accounts.first().is_some_and(|meta| meta.pubkey == pool) {
+            trade_count = trade_count.saturating_add(1);
+        }
+        index += 1;
+    }
+
+    validate_condition!(
+        trade_count <= state.max_trades_per_tx,
+        crate::ErrorCode::TooManyTradesPerTx,
+        "Too many trades in transaction: {} (max: {})",
+        trade_count,
+        state.max_trades_per_tx
+    );
+    Ok(())
+}
+
 pub fn validate_fee_parameters(state: &PoolState, fee_tiers: &[FeeTier]) -> Result<()> {
     state.validate_fee_tiers(fee_tiers)?;
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Checks a protocol fee against the configured maximum
+pub fn validate_protocol_fee(protocol_fee_bps: u64) -> Result<()> {
+    validate_condition!(
+        protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+        crate::ErrorCode::InvalidProtocolFee,
+        "Protocol fee {} bps exceeds maximum {}",
+        protocol_fee_bps,
+        MAX_PROTOCOL_FEE_BPS
+    );
+    Ok(())
+}
+
+/// Validates a trade before any state changes
+///
+/// An expired blacklist entry stops blocking the trader as soon as it lapses;
+/// the trade prunes it once it settles. Stale volume is decayed here so the
+/// volume caps see the live window.
+pub fn validate_trade_parameters(
+    state: &mut PoolState,
+    trader: &Pubkey,
+    amount_in: u64,
+    current_time: u64,
+) -> Result<()> {
+    validate_condition!(!state.retired, crate::ErrorCode::PoolRetired);
+    validate_condition!(!state.is_emergency_paused, crate::ErrorCode::EmergencyPaused);
+    state.check_post_resume_cooldown(current_time as i64)?;
+    state.check_staleness(current_time)?;
+    validate_condition!(!state.pause_level.halts_trading(), crate::ErrorCode::PoolPaused);
+    validate_operation_not_paused(state, PAUSE_OP_TRADE)?;
+    if state.protection.blacklist_enabled {
+        validate_condition!(
+            !state.is_blacklisted(trader, current_time as i64),
+            crate::ErrorCode::TraderBlacklisted,
+            "Trader is blacklisted: {}",
+            trader
+        );
+    }
+    validate_condition!(amount_in > 0, crate::ErrorCode::InvalidAmount);
+    validate_condition!(
+        amount_in >= state.trade_settings.min_size,
+        crate::ErrorCode::TradeTooSmall,
+        "Trade too small: {} < {}",
+        amount_in,
+        state.trade_settings.min_size
+    );
+    // Fail here rather than deep in the payout transfer from an empty pool
+    validate_condition!(
+        state.total_liquidity > 0,
+        crate::ErrorCode::InsufficientLiquidity,
+        "Pool has no liquidity to trade against"
+    );
+    validate_condition!(
+        current_time >= state.pool_start_time,
+        crate::ErrorCode::InvalidTimestamp
+    );
+
+    // Decay stale volume so the volume cap and fee tiers see the live window
+    state.decay_volume(current_time)?;
+    state.check_hourly_volume_limit(amount_in, current_time)?;
+    state.check_hourly_trade_limit(current_time)?;
+    Ok(())
+}
@@ -0,0 +1,40 @@
+//! Accounting period tests.
+
+use hoe_dex_protection::{AccountingPeriod, PoolState, VolumeSettings};
+
+const START: i64 = 1_700_000_000;
+
+#[test]
+fn close_reports_the_period_and_starts_the_next() {
+    let mut period = AccountingPeriod { started_at: START, ..Default::default() };
+    period.record_trade(100_000, 300);
+    period.record_trade(50_000, 150);
+
+    let closed = period.close(START + 86_400);
+
+    assert_eq!(closed, AccountingPeriod { index: 0, volume: 150_000, fees: 450, started_at: START });
+    assert_eq!(period, AccountingPeriod { index: 1, volume: 0, fees: 0, started_at: START + 86_400 });
+}
+
+#[test]
+fn closing_leaves_protection_counters_alone() {
+    let mut pool = PoolState {
+        volume: VolumeSettings { current_volume: 150_000, volume_24h: 150_000, ..Default::default() },
+        ..Default::default()
+    };
+    pool.accounting_period.record_trade(150_000, 450);
+
+    pool.accounting_period.close(START);
+
+    assert_eq!(pool.accounting_period.volume, 0);
+    assert_eq!(pool.volume.current_volume, 150_000);
+    assert_eq!(pool.volume.volume_24h, 150_000);
+}
+
+#[test]
+fn totals_saturate_instead_of_overflowing() {
+    let mut period = AccountingPeriod { volume: u64::MAX - 1, ..Default::default() };
+    period.record_trade(10, 0);
+
+    assert_eq!(period.volume, u64::MAX);
+}
@@ -0,0 +1,96 @@
+//! CPI caller allow-list tests.
+//!
+//! Exercising a real CPI needs a second on-chain program, so these check the
+//! decision `validate_trade_caller` makes for a given top-level program.
+//! Only a narrower list applies without the timelock.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ErrorCode, ParameterUpdate, PoolState, ProtectionSettings, ProtectionSettingsUpdate};
+
+fn pool_with_callers(allowed_callers: Vec<Pubkey>) -> PoolState {
+    PoolState {
+        allowed_callers,
+        ..Default::default()
+    }
+}
+
+/// A pool with `allowed_callers` whose other protection settings pass validation
+fn configured_pool(allowed_callers: Vec<Pubkey>) -> PoolState {
+    PoolState {
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..pool_with_callers(allowed_callers)
+    }
+}
+
+fn is_caller_not_allowed(result: anchor_lang::Result<()>) -> bool {
+    match result {
+        Err(anchor_lang::error::Error::AnchorError(err)) => {
+            err.error_code_number == anchor_lang::error::ERROR_CODE_OFFSET + ErrorCode::CallerNotAllowed as u32
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn allows_any_caller_when_the_list_is_empty() {
+    let pool = pool_with_callers(vec![]);
+
+    assert!(pool.check_caller_allowed(&Pubkey::new_unique()).is_ok());
+}
+
+#[test]
+fn allows_a_listed_caller() {
+    let router = Pubkey::new_unique();
+    let pool = pool_with_callers(vec![Pubkey::new_unique(), router]);
+
+    assert!(pool.check_caller_allowed(&router).is_ok());
+}
+
+#[test]
+fn rejects_an_unlisted_caller() {
+    let pool = pool_with_callers(vec![Pubkey::new_unique()]);
+
+    assert!(is_caller_not_allowed(pool.check_caller_allowed(&Pubkey::new_unique())));
+}
+
+#[test]
+fn always_allows_direct_calls() {
+    let pool = pool_with_callers(vec![Pubkey::new_unique()]);
+
+    assert!(pool.check_caller_allowed(&hoe_dex_protection::ID).is_ok());
+}
+
+#[test]
+fn dropping_callers_is_a_tightening() {
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let pool = configured_pool(vec![first, second]);
+    let current = pool.current_protection_settings();
+
+    let narrower = ProtectionSettingsUpdate { allowed_callers: vec![second], ..current.clone() };
+    assert!(pool.validate_protection_tightening(&narrower).is_ok());
+
+    for allowed_callers in [vec![first, second, Pubkey::new_unique()], vec![]] {
+        let looser = ProtectionSettingsUpdate { allowed_callers, ..current.clone() };
+        let err = pool.validate_protection_tightening(&looser).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+}
+
+#[test]
+fn any_list_restricts_an_unrestricted_pool() {
+    let pool = configured_pool(vec![]);
+    let settings =
+        ProtectionSettingsUpdate { allowed_callers: vec![Pubkey::new_unique()], ..pool.current_protection_settings() };
+
+    assert!(pool.validate_protection_tightening(&settings).is_ok());
+}
+
+#[test]
+fn timelocked_update_bounds_the_list() {
+    let pool = configured_pool(vec![]);
+    let settings =
+        ProtectionSettingsUpdate { allowed_callers: vec![Pubkey::default()], ..pool.current_protection_settings() };
+
+    let err = pool.validate_parameter_update(&ParameterUpdate::Protection(settings)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidProtectionSettings.into());
+}
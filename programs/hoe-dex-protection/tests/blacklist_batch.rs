@@ -0,0 +1,70 @@
+//! Configurable blacklist batch size tests.
+//!
+//! Batch size is checked before any entry is written, so these drive
+//! `process_blacklist_operations` directly against an in-memory pool.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    utils::process_blacklist_operations, BlacklistOperation, ErrorCode, PoolState, ProtectionSettings,
+    DEFAULT_MAX_BLACKLIST_BATCH, MAX_BLACKLIST_BATCH,
+};
+
+const NOW: u64 = 1_700_000_000;
+
+fn pool(max_blacklist_batch: u16) -> PoolState {
+    PoolState {
+        max_blacklist_batch,
+        protection: ProtectionSettings { blacklist_enabled: true, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+fn traders(count: usize) -> Vec<Pubkey> {
+    (0..count).map(|_| Pubkey::new_unique()).collect()
+}
+
+fn add(pool: &mut PoolState, count: usize) -> anchor_lang::Result<()> {
+    let operation = BlacklistOperation::Add { reason_code: 0, expires_at: 0 };
+    process_blacklist_operations(pool, traders(count), operation, NOW)
+}
+
+fn is_batch_too_large(result: anchor_lang::Result<()>) -> bool {
+    match result {
+        Err(anchor_lang::error::Error::AnchorError(err)) => {
+            err.error_code_number
+                == anchor_lang::error::ERROR_CODE_OFFSET + ErrorCode::BlacklistBatchTooLarge as u32
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn batch_at_the_configured_limit_passes() {
+    let mut pool = pool(120);
+
+    add(&mut pool, 120).unwrap();
+
+    assert_eq!(pool.trader_blacklist.len(), 120);
+}
+
+#[test]
+fn batch_over_the_configured_limit_fails() {
+    let mut pool = pool(10);
+
+    assert!(is_batch_too_large(add(&mut pool, 11)));
+    assert!(pool.trader_blacklist.is_empty());
+}
+
+#[test]
+fn unset_limit_uses_the_default() {
+    let pool = pool(0);
+
+    assert_eq!(pool.blacklist_batch_limit(), DEFAULT_MAX_BLACKLIST_BATCH as usize);
+}
+
+#[test]
+fn limit_never_exceeds_the_hard_cap() {
+    let pool = pool(u16::MAX);
+
+    assert_eq!(pool.blacklist_batch_limit(), MAX_BLACKLIST_BATCH as usize);
+}
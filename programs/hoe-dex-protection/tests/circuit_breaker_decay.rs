@@ -0,0 +1,86 @@
+//! Circuit breaker decay tests.
+//!
+//! Decay depends only on elapsed time, so these drive `decay_circuit_breaker`
+//! with simulated timestamps instead of waiting on a validator clock.
+
+use hoe_dex_protection::{CircuitBreakerSettings, PoolState, CIRCUIT_BREAKER_DECAY_WINDOW};
+
+const START: u64 = 1_700_000_000;
+
+fn pool_with_breaker(current_amount: u64, decay_bps: u64) -> PoolState {
+    PoolState {
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: 1_000_000,
+            current_amount,
+            decay_bps,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn drains_the_decay_rate_per_window() {
+    // 10% of the 1M threshold per window
+    let mut pool = pool_with_breaker(800_000, 1000);
+
+    pool.decay_circuit_breaker(START + CIRCUIT_BREAKER_DECAY_WINDOW);
+    assert_eq!(pool.circuit_breaker.current_amount, 700_000);
+
+    pool.decay_circuit_breaker(START + CIRCUIT_BREAKER_DECAY_WINDOW * 3 / 2);
+    assert_eq!(pool.circuit_breaker.current_amount, 650_000);
+    assert_eq!(pool.circuit_breaker.last_decay, START + CIRCUIT_BREAKER_DECAY_WINDOW * 3 / 2);
+}
+
+#[test]
+fn long_idle_periods_clamp_to_zero() {
+    let mut pool = pool_with_breaker(800_000, 1000);
+
+    pool.decay_circuit_breaker(START + CIRCUIT_BREAKER_DECAY_WINDOW * 1_000_000);
+
+    assert_eq!(pool.circuit_breaker.current_amount, 0);
+}
+
+#[test]
+fn clock_behind_last_decay_is_ignored() {
+    let mut pool = pool_with_breaker(800_000, 1000);
+
+    pool.decay_circuit_breaker(START - 600);
+
+    assert_eq!(pool.circuit_breaker.current_amount, 800_000);
+    assert_eq!(pool.circuit_breaker.last_decay, START);
+}
+
+#[test]
+fn sub_unit_drains_carry_over() {
+    // 0.01% of 1M per window drains one unit every 36 seconds
+    let mut pool = pool_with_breaker(500, 1);
+
+    pool.decay_circuit_breaker(START + 20);
+    assert_eq!(pool.circuit_breaker.current_amount, 500);
+    assert_eq!(pool.circuit_breaker.last_decay, START);
+
+    pool.decay_circuit_breaker(START + 40);
+    assert_eq!(pool.circuit_breaker.current_amount, 499);
+}
+
+#[test]
+fn idle_time_while_empty_builds_no_credit() {
+    let mut pool = pool_with_breaker(0, 1000);
+
+    pool.decay_circuit_breaker(START + CIRCUIT_BREAKER_DECAY_WINDOW * 5);
+    pool.circuit_breaker.current_amount = 300_000;
+    pool.decay_circuit_breaker(START + CIRCUIT_BREAKER_DECAY_WINDOW * 5);
+
+    assert_eq!(pool.circuit_breaker.current_amount, 300_000);
+}
+
+#[test]
+fn zero_rate_never_decays() {
+    let mut pool = pool_with_breaker(800_000, 0);
+
+    pool.decay_circuit_breaker(START + CIRCUIT_BREAKER_DECAY_WINDOW * 24);
+
+    assert_eq!(pool.circuit_breaker.current_amount, 800_000);
+}
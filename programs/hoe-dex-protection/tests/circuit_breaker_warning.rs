@@ -0,0 +1,81 @@
+//! Circuit breaker warning threshold tests.
+//!
+//! The warning fires at a configurable share of `max_amount` while the breaker
+//! itself still trips only past the ceiling.
+
+use hoe_dex_protection::{CircuitBreakerSettings, PoolState, CIRCUIT_BREAKER_HIGH_WATER_BPS};
+
+const START: u64 = 1_700_000_000;
+
+fn pool_with_warning(current_amount: u64, warn_threshold_bps: u64) -> PoolState {
+    PoolState {
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: 1_000_000,
+            current_amount,
+            cooldown_period: 3600,
+            last_trigger: START,
+            last_decay: START,
+            warn_threshold_bps,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn zero_threshold_uses_the_default() {
+    let pool = pool_with_warning(0, 0);
+
+    assert_eq!(
+        pool.circuit_breaker_warn_threshold(),
+        1_000_000 * CIRCUIT_BREAKER_HIGH_WATER_BPS / 10000
+    );
+}
+
+#[test]
+fn warning_fires_at_the_configured_fraction() {
+    let pool = pool_with_warning(850_000, 9000);
+
+    assert_eq!(pool.circuit_breaker_warn_threshold(), 900_000);
+    assert!(!pool.crosses_circuit_breaker_warning(899_999));
+    assert!(pool.crosses_circuit_breaker_warning(900_000));
+}
+
+#[test]
+fn warning_fires_only_on_the_first_crossing() {
+    let pool = pool_with_warning(900_000, 9000);
+
+    assert!(!pool.crosses_circuit_breaker_warning(950_000));
+}
+
+#[test]
+fn hard_stop_stays_at_the_ceiling() {
+    let mut pool = pool_with_warning(850_000, 9000);
+
+    assert!(!pool.update_circuit_breaker(150_000, START + 60).unwrap());
+    assert_eq!(pool.circuit_breaker.current_amount, 1_000_000);
+
+    assert!(pool.update_circuit_breaker(1, START + 120).unwrap());
+    assert_eq!(pool.circuit_breaker.current_amount, 1_000_000);
+}
+
+#[test]
+fn deferred_update_hands_back_the_warning() {
+    let mut pool = pool_with_warning(850_000, 9000);
+
+    let (tripped, warning) = pool.update_circuit_breaker_deferred(100_000, START + 60).unwrap();
+
+    assert!(!tripped);
+    assert_eq!(warning.unwrap().current_amount, 950_000);
+    assert_eq!(pool.circuit_breaker.current_amount, 950_000);
+}
+
+#[test]
+fn deferred_trip_carries_no_warning() {
+    let mut pool = pool_with_warning(850_000, 9000);
+
+    let (tripped, warning) = pool.update_circuit_breaker_deferred(200_000, START + 60).unwrap();
+
+    assert!(tripped);
+    assert!(warning.is_none());
+}
@@ -0,0 +1,81 @@
+//! Backwards clock tests.
+//!
+//! The cluster clock can step back slightly between slots, so these feed
+//! timestamps earlier than the last recorded one and expect a quiet no-op.
+
+use hoe_dex_protection::{CircuitBreakerSettings, PoolState, RateLimitSettings, VolumeSettings};
+
+const START: u64 = 1_700_000_000;
+
+#[test]
+fn volume_decay_skips_an_earlier_timestamp() {
+    let mut pool = PoolState {
+        volume: VolumeSettings { current_volume: 5_000, volume_24h: 5_000, last_decay: START, ..Default::default() },
+        ..Default::default()
+    };
+
+    pool.decay_volume(START - 5).unwrap();
+
+    assert_eq!(pool.volume.current_volume, 5_000);
+    assert_eq!(pool.volume.last_decay, START);
+}
+
+#[test]
+fn circuit_breaker_treats_an_earlier_timestamp_as_no_time_passed() {
+    let mut pool = PoolState {
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: 1_000,
+            current_amount: 400,
+            cooldown_period: 60,
+            decay_bps: 1000,
+            last_trigger: START,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let tripped = pool.update_circuit_breaker(100, START - 5).unwrap();
+
+    // No cooldown reset and no decay, but the amount still accumulates
+    assert!(!tripped);
+    assert_eq!(pool.circuit_breaker.current_amount, 500);
+    assert_eq!(pool.circuit_breaker.last_trigger, START);
+    assert_eq!(pool.circuit_breaker.last_decay, START);
+}
+
+#[test]
+fn circuit_breaker_still_trips_on_an_earlier_timestamp() {
+    let mut pool = PoolState {
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: 1_000,
+            current_amount: 900,
+            cooldown_period: 60,
+            last_trigger: START,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    assert!(pool.update_circuit_breaker(200, START - 5).unwrap());
+}
+
+#[test]
+fn rate_limit_window_does_not_reset_on_an_earlier_timestamp() {
+    let mut pool = PoolState {
+        rate_limit: RateLimitSettings {
+            window_size: 60,
+            max_per_window: 1_000,
+            current_window: 300,
+            last_reset: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    pool.update_rate_limit(100, START - 5).unwrap();
+
+    assert_eq!(pool.rate_limit.current_window, 400);
+    assert_eq!(pool.rate_limit.last_reset, START);
+}
@@ -0,0 +1,51 @@
+//! Buy and sell volume tests.
+//!
+//! Sides share the hourly buckets with the total, so these record trades
+//! the way `process_trade` does and check each side's 24h counter.
+
+use hoe_dex_protection::{PoolState, TradeDirection, VolumeSettings, VOLUME_BUCKET_SECONDS};
+
+const START: u64 = 1_700_000_000;
+
+fn pool() -> PoolState {
+    PoolState {
+        volume: VolumeSettings {
+            max_daily: 100_000_000,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn record(pool: &mut PoolState, direction: TradeDirection, amount: u64, current_time: u64) {
+    pool.update_volume(amount, current_time).unwrap();
+    pool.volume.record_side(direction, amount, current_time);
+}
+
+#[test]
+fn buys_and_sells_accrue_to_their_own_counters() {
+    let mut pool = pool();
+    record(&mut pool, TradeDirection::Buy, 30_000, START);
+    record(&mut pool, TradeDirection::Sell, 50_000, START + 60);
+    record(&mut pool, TradeDirection::Buy, 5_000, START + 120);
+
+    assert_eq!(pool.volume.buy_volume_24h, 35_000);
+    assert_eq!(pool.volume.sell_volume_24h, 50_000);
+    assert_eq!(pool.volume.volume_24h, 85_000);
+}
+
+#[test]
+fn sides_decay_with_the_24h_window() {
+    let mut pool = pool();
+    record(&mut pool, TradeDirection::Sell, 50_000, START);
+    record(&mut pool, TradeDirection::Buy, 20_000, START + 2 * VOLUME_BUCKET_SECONDS);
+
+    // The first hour has left the window, the later one has not
+    let later = START + 24 * VOLUME_BUCKET_SECONDS;
+    pool.decay_volume(later).unwrap();
+
+    assert_eq!(pool.volume.sell_volume_24h, 0);
+    assert_eq!(pool.volume.buy_volume_24h, 20_000);
+}
@@ -0,0 +1,80 @@
+//! Early-trade phase tests.
+//!
+//! The phase runs from the scheduled launch (or the pool start) for the
+//! configured window; these check the fee and the reported mode on each side.
+
+use hoe_dex_protection::{
+    ErrorCode, FeeMode, FeeTier, ParameterUpdate, PoolState, TradeSettings, TradeSettingsUpdate, FEE_MODE_EARLY_TRADE,
+    FEE_MODE_TIER_BASED, MAX_EARLY_TRADE_DURATION,
+};
+
+const LAUNCH: i64 = 1_700_000_000;
+const WINDOW: u64 = 600;
+
+fn pool(launch_ts: i64, early_trade_window_seconds: u64) -> PoolState {
+    PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None }],
+        trade_settings: TradeSettings { early_trade_fee_bps: 500, early_trade_window_seconds, ..Default::default() },
+        launch_ts,
+        pool_start_time: (LAUNCH - 86_400) as u64,
+        ..Default::default()
+    }
+}
+
+fn fee_at(pool: &PoolState, current_time: i64) -> (u64, u8) {
+    let (fees, fee_mode, _) = pool.calculate_fee(100_000, current_time).unwrap();
+    (fees.lp_fee, fee_mode)
+}
+
+#[test]
+fn trades_inside_the_window_pay_the_early_fee() {
+    let pool = pool(LAUNCH, WINDOW);
+
+    assert_eq!(fee_at(&pool, LAUNCH), (5_000, FEE_MODE_EARLY_TRADE));
+    assert_eq!(fee_at(&pool, LAUNCH + WINDOW as i64 - 1), (5_000, FEE_MODE_EARLY_TRADE));
+}
+
+#[test]
+fn trades_after_the_window_revert_to_tiers() {
+    let pool = pool(LAUNCH, WINDOW);
+
+    assert_eq!(fee_at(&pool, LAUNCH + WINDOW as i64), (300, FEE_MODE_TIER_BASED));
+}
+
+#[test]
+fn zero_window_has_no_early_phase() {
+    let pool = pool(0, 0);
+
+    assert_eq!(fee_at(&pool, LAUNCH), (300, FEE_MODE_TIER_BASED));
+    assert_ne!(FEE_MODE_EARLY_TRADE, FEE_MODE_TIER_BASED);
+    assert_eq!(FeeMode::from_u8(FEE_MODE_EARLY_TRADE), Some(FeeMode::EarlyTrade));
+    assert_eq!(FeeMode::from_u8(FEE_MODE_TIER_BASED), Some(FeeMode::TierBased));
+}
+
+#[test]
+fn unscheduled_launch_counts_from_the_pool_start() {
+    let pool = pool(0, WINDOW);
+    let start = pool.pool_start_time as i64;
+
+    assert_eq!(pool.early_trade_ends_at(), start + WINDOW as i64);
+    assert!(pool.is_early_trade(start));
+    assert!(!pool.is_early_trade(start + WINDOW as i64));
+}
+
+#[test]
+fn window_is_bounded_in_the_timelocked_trade_settings() {
+    let pool = pool(0, 0);
+    let update = |early_trade_window_seconds| {
+        ParameterUpdate::Trade(TradeSettingsUpdate {
+            early_trade_fee_bps: 500,
+            early_trade_window_seconds,
+            max_trade_size_bps: 1000,
+            min_trade_size: 1,
+            cooldown_seconds: 0,
+        })
+    };
+
+    assert!(pool.validate_parameter_update(&update(MAX_EARLY_TRADE_DURATION)).is_ok());
+    let err = pool.validate_parameter_update(&update(MAX_EARLY_TRADE_DURATION + 1)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidTradeSettings.into());
+}
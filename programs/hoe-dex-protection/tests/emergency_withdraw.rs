@@ -0,0 +1,74 @@
+//! Emergency withdraw tests.
+//!
+//! The withdraw timelock counts from `emergency_paused_at`, so every path
+//! that sets `is_emergency_paused` must also record when it did. A rescue
+//! drains the pool, so no balance it moved may stay tracked afterwards, while
+//! unclaimed referral fees stay behind for their referrers.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ParameterUpdate, PauseLevel, PoolState, StateSettingsUpdate};
+
+const NOW: u64 = 1_700_000_000;
+
+fn state_update(is_emergency_paused: bool) -> ParameterUpdate {
+    ParameterUpdate::State(StateSettingsUpdate { pause_level: PauseLevel::None, is_emergency_paused })
+}
+
+#[test]
+fn rescue_clears_every_drained_balance() {
+    let mut pool = PoolState {
+        total_liquidity: 5_000_000,
+        total_fees_collected: 1_000,
+        insurance_collected: 300,
+        protocol_fees: 200,
+        ..Default::default()
+    };
+
+    pool.clear_rescued_balances();
+
+    assert_eq!(pool.insurance_collected, 0);
+    assert_eq!(pool.obligations().unwrap(), 0);
+}
+
+#[test]
+fn rescue_leaves_referral_fees_claimable() {
+    let mut pool = PoolState {
+        total_liquidity: 5_000_000,
+        total_fees_collected: 1_000,
+        referral_fees_owed: 400,
+        ..Default::default()
+    };
+
+    let rescued = pool.rescuable_amount(5_001_400);
+    pool.clear_rescued_balances();
+
+    assert_eq!(rescued, 5_001_000);
+    assert_eq!(pool.referral_fees_owed, 400);
+    assert_eq!(pool.obligations().unwrap(), 5_001_400 - rescued);
+}
+
+#[test]
+fn rescue_never_exceeds_the_balance() {
+    let pool = PoolState { referral_fees_owed: 400, ..Default::default() };
+
+    assert_eq!(pool.rescuable_amount(300), 0);
+}
+
+#[test]
+fn state_update_entering_emergency_pause_records_the_time() {
+    let mut pool = PoolState::default();
+
+    pool.apply_update(&state_update(true), &Pubkey::new_unique(), NOW).unwrap();
+
+    assert!(pool.is_emergency_paused);
+    assert_eq!(pool.emergency_paused_at, NOW);
+}
+
+#[test]
+fn state_update_keeping_emergency_pause_keeps_the_original_time() {
+    let mut pool = PoolState { is_emergency_paused: true, emergency_paused_at: NOW - 100, ..Default::default() };
+
+    pool.apply_update(&state_update(true), &Pubkey::new_unique(), NOW).unwrap();
+
+    assert_eq!(pool.emergency_paused_at, NOW - 100);
+}
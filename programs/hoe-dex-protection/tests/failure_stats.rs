@@ -0,0 +1,49 @@
+//! Soft-failure counter tests.
+//!
+//! Each category counts independently, and a reset zeroes all of them while
+//! handing back what they held.
+
+use hoe_dex_protection::{FailureCategory, FailureStats, PoolState};
+
+#[test]
+fn counters_start_at_zero() {
+    assert_eq!(PoolState::default().failure_stats(), FailureStats::default());
+}
+
+#[test]
+fn each_category_counts_separately() {
+    let mut pool = PoolState::default();
+    pool.record_failure(FailureCategory::Trade);
+    pool.record_failure(FailureCategory::Trade);
+    pool.record_failure(FailureCategory::Liquidity);
+    pool.record_failure(FailureCategory::Admin);
+    pool.record_failure(FailureCategory::Admin);
+    pool.record_failure(FailureCategory::Admin);
+
+    assert_eq!(
+        pool.failure_stats(),
+        FailureStats { failed_trade_count: 2, failed_liquidity_count: 1, failed_admin_count: 3 }
+    );
+}
+
+#[test]
+fn counters_saturate() {
+    let mut pool = PoolState { failed_trade_count: u64::MAX, ..Default::default() };
+    pool.record_failure(FailureCategory::Trade);
+    assert_eq!(pool.failed_trade_count, u64::MAX);
+}
+
+#[test]
+fn reset_zeroes_and_returns_old_counts() {
+    let mut pool = PoolState {
+        failed_trade_count: 4,
+        failed_liquidity_count: 2,
+        failed_admin_count: 1,
+        ..Default::default()
+    };
+
+    let old = pool.reset_failure_stats();
+
+    assert_eq!(old, FailureStats { failed_trade_count: 4, failed_liquidity_count: 2, failed_admin_count: 1 });
+    assert_eq!(pool.failure_stats(), FailureStats::default());
+}
@@ -0,0 +1,77 @@
+//! Fee authority transfer tests.
+//!
+//! The fee authority signs fee withdrawals and only changes once the
+//! proposed key accepts; pools written before it existed leave fees with the
+//! admin.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{validation, ErrorCode, PoolState};
+
+fn pool() -> PoolState {
+    let admin = Pubkey::new_unique();
+    PoolState { admin, fee_authority: admin, ..Default::default() }
+}
+
+#[test]
+fn unset_fee_authority_falls_back_to_the_admin() {
+    let pool = PoolState { admin: Pubkey::new_unique(), ..Default::default() };
+
+    assert_eq!(pool.fee_authority(), pool.admin);
+    assert!(validation::validate_fee_authority_action(&pool, &pool.admin).is_ok());
+}
+
+#[test]
+fn proposed_key_takes_over_once_it_accepts() {
+    let mut pool = pool();
+    let admin = pool.admin;
+    let new_fee_authority = Pubkey::new_unique();
+
+    pool.propose_fee_authority(new_fee_authority).unwrap();
+    assert_eq!(pool.fee_authority(), admin);
+
+    assert_eq!(pool.accept_fee_authority(&new_fee_authority).unwrap(), admin);
+    assert_eq!(pool.fee_authority(), new_fee_authority);
+    assert_eq!(pool.pending_fee_authority, None);
+
+    let err = validation::validate_fee_authority_action(&pool, &admin).unwrap_err();
+    assert_eq!(err, ErrorCode::Unauthorized.into());
+    assert!(validation::validate_fee_authority_action(&pool, &new_fee_authority).is_ok());
+}
+
+#[test]
+fn accept_by_another_key_is_rejected() {
+    let mut pool = pool();
+    let admin = pool.admin;
+    pool.propose_fee_authority(Pubkey::new_unique()).unwrap();
+
+    for signer in [Pubkey::new_unique(), admin] {
+        let err = pool.accept_fee_authority(&signer).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+    assert_eq!(pool.fee_authority(), admin);
+}
+
+#[test]
+fn cancel_drops_the_pending_key() {
+    let mut pool = pool();
+    let new_fee_authority = Pubkey::new_unique();
+    pool.propose_fee_authority(new_fee_authority).unwrap();
+
+    assert_eq!(pool.cancel_fee_authority_transfer().unwrap(), new_fee_authority);
+
+    let err = pool.accept_fee_authority(&new_fee_authority).unwrap_err();
+    assert_eq!(err, ErrorCode::NoPendingFeeAuthority.into());
+    let err = pool.cancel_fee_authority_transfer().unwrap_err();
+    assert_eq!(err, ErrorCode::NoPendingFeeAuthority.into());
+}
+
+#[test]
+fn default_or_current_key_cannot_be_proposed() {
+    let mut pool = pool();
+    let admin = pool.admin;
+
+    for key in [Pubkey::default(), admin] {
+        let err = pool.propose_fee_authority(key).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeAuthority.into());
+    }
+}
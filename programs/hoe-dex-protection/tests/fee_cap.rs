@@ -0,0 +1,86 @@
+//! Total fee cap tests.
+//!
+//! The protocol fee stacks on top of the base rate, so these check the
+//! combined fee from `calculate_fee` against `MAXIMUM_FEE_BPS`.
+
+use hoe_dex_protection::{ErrorCode, FeeTier, PoolState, MAXIMUM_FEE_BPS, MAX_PROTOCOL_FEE_BPS};
+
+const NOW: i64 = 1_700_000_000;
+
+fn pool(tier_fee_bps: u64, protocol_fee_bps: u64) -> PoolState {
+    PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: tier_fee_bps, liquidity_threshold: None }],
+        protocol_fee_bps,
+        ..Default::default()
+    }
+}
+
+fn max_fee(amount_in: u64) -> u64 {
+    (amount_in as u128 * MAXIMUM_FEE_BPS as u128).div_ceil(10000) as u64
+}
+
+fn is_fee_too_high<T>(result: anchor_lang::Result<T>) -> bool {
+    match result {
+        Err(anchor_lang::error::Error::AnchorError(err)) => {
+            err.error_code_number == anchor_lang::error::ERROR_CODE_OFFSET + ErrorCode::FeeTooHigh as u32
+        }
+        _ => false,
+    }
+}
+
+#[test]
+fn protocol_fee_is_clamped_to_the_cap() {
+    // 800 bps plus 500 bps would be 1300 bps
+    let pool = pool(800, MAX_PROTOCOL_FEE_BPS);
+
+    let (fee, ..) = pool.calculate_fee(100_000, NOW).unwrap();
+
+    assert_eq!(fee.total(), 100_000 * MAXIMUM_FEE_BPS / 10000);
+}
+
+#[test]
+fn combined_fee_never_exceeds_the_cap() {
+    for tier_fee_bps in [1, 30, 500, MAXIMUM_FEE_BPS] {
+        for protocol_fee_bps in [0, 1, 250, MAX_PROTOCOL_FEE_BPS] {
+            let pool = pool(tier_fee_bps, protocol_fee_bps);
+            for amount_in in [1, 9, 10, 333, 100_001, u64::MAX / 2] {
+                let (fee, ..) = pool.calculate_fee(amount_in, NOW).unwrap();
+                assert!(
+                    fee.total() <= max_fee(amount_in),
+                    "fee {} over cap for {} at {} + {} bps",
+                    fee.total(),
+                    amount_in,
+                    tier_fee_bps,
+                    protocol_fee_bps
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn dust_still_pays_the_minimum_fee() {
+    let pool = pool(30, 0);
+
+    let (fee, ..) = pool.calculate_fee(5, NOW).unwrap();
+
+    assert_eq!(fee.total(), 1);
+}
+
+#[test]
+fn rejects_a_base_tier_above_the_cap() {
+    // Tiers are validated on the way in; this simulates a pool stored before that
+    let pool = pool(MAXIMUM_FEE_BPS + 1, 0);
+
+    assert!(is_fee_too_high(pool.calculate_fee(100_000, NOW)));
+}
+
+#[test]
+fn rejects_an_early_trade_fee_above_the_cap() {
+    let mut pool = pool(30, 0);
+    pool.pool_start_time = NOW as u64;
+    pool.trade_settings.early_trade_window_seconds = 3600;
+    pool.trade_settings.early_trade_fee_bps = MAXIMUM_FEE_BPS + 1;
+
+    assert!(is_fee_too_high(pool.calculate_fee(100_000, NOW)));
+}
@@ -0,0 +1,42 @@
+//! Fee-free threshold tests.
+
+use hoe_dex_protection::{FeeTier, PoolState, FEE_MODE_NONE, FEE_MODE_TIER_BASED, MINIMUM_FEE};
+
+const NOW: i64 = 1_700_000_000;
+
+fn pool(fee_free_threshold: u64) -> PoolState {
+    PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None }],
+        protocol_fee_bps: 10,
+        fee_free_threshold,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sub_threshold_trade_pays_nothing() {
+    for amount_in in [1, 500, 1_000] {
+        let (fees, fee_mode, tier_index) = pool(1_000).calculate_fee(amount_in, NOW).unwrap();
+
+        assert_eq!(fees.total(), 0, "amount {}", amount_in);
+        assert_eq!(fee_mode, FEE_MODE_NONE);
+        assert_eq!(tier_index, None);
+    }
+}
+
+#[test]
+fn above_threshold_trade_pays_the_tier_fee() {
+    let (fees, fee_mode, _) = pool(1_000).calculate_fee(100_000, NOW).unwrap();
+
+    assert_eq!(fees.lp_fee, 300);
+    assert_eq!(fees.protocol_fee, 100);
+    assert_eq!(fee_mode, FEE_MODE_TIER_BASED);
+}
+
+#[test]
+fn zero_threshold_keeps_the_minimum_fee() {
+    let (fees, fee_mode, _) = pool(0).calculate_fee(1, NOW).unwrap();
+
+    assert!(fees.lp_fee >= MINIMUM_FEE);
+    assert_eq!(fee_mode, FEE_MODE_TIER_BASED);
+}
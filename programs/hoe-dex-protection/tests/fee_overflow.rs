@@ -0,0 +1,61 @@
+//! Fee accrual overflow tests.
+//!
+//! `total_fees_collected` near `u64::MAX` would take years of real trading,
+//! so these start the counter there and accrue directly.
+
+use hoe_dex_protection::{ErrorCode, FeeOverflowMode, PoolState};
+
+const NOW: u64 = 1_700_000_000;
+
+fn pool(total_fees_collected: u64, fee_high_water_mark: u64, fee_overflow_mode: FeeOverflowMode) -> PoolState {
+    PoolState { total_fees_collected, fee_high_water_mark, fee_overflow_mode, ..Default::default() }
+}
+
+#[test]
+fn accrues_normally_below_the_mark() {
+    let mut pool = pool(1_000, 10_000, FeeOverflowMode::RequireWithdrawal);
+
+    pool.accrue_treasury_fee(500, NOW).unwrap();
+
+    assert_eq!(pool.total_fees_collected, 1_500);
+}
+
+#[test]
+fn near_overflow_requires_withdrawal_instead_of_overflowing() {
+    let mut pool = pool(u64::MAX - 10, 0, FeeOverflowMode::RequireWithdrawal);
+
+    let err = pool.accrue_treasury_fee(100, NOW).unwrap_err();
+
+    assert_eq!(err, ErrorCode::FeeWithdrawalRequired.into());
+    assert_eq!(pool.total_fees_collected, u64::MAX - 10);
+}
+
+#[test]
+fn near_overflow_caps_accrual_at_the_mark() {
+    let mut pool = pool(u64::MAX - 10, 0, FeeOverflowMode::CapAccrual);
+
+    pool.accrue_treasury_fee(100, NOW).unwrap();
+    assert_eq!(pool.total_fees_collected, u64::MAX);
+
+    pool.accrue_treasury_fee(100, NOW).unwrap();
+    assert_eq!(pool.total_fees_collected, u64::MAX);
+}
+
+#[test]
+fn configured_mark_applies_below_u64_max() {
+    let mut pool = pool(9_900, 10_000, FeeOverflowMode::CapAccrual);
+
+    pool.accrue_treasury_fee(500, NOW).unwrap();
+
+    assert_eq!(pool.total_fees_collected, 10_000);
+}
+
+#[test]
+fn withdrawal_clears_the_way_for_trades() {
+    let mut pool = pool(10_000, 10_000, FeeOverflowMode::RequireWithdrawal);
+    assert!(pool.accrue_treasury_fee(1, NOW).is_err());
+
+    pool.total_fees_collected = 0;
+
+    assert!(pool.accrue_treasury_fee(1, NOW).is_ok());
+}
@@ -0,0 +1,52 @@
+//! Fee recipient split tests.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ErrorCode, FeeRecipient, PoolState};
+
+fn recipient(share_bps: u64) -> FeeRecipient {
+    FeeRecipient { pubkey: Pubkey::new_unique(), share_bps }
+}
+
+#[test]
+fn fees_split_by_share() {
+    let pool = PoolState { fee_recipients: vec![recipient(5_000), recipient(3_000), recipient(2_000)], ..Default::default() };
+
+    assert_eq!(pool.split_among_fee_recipients(10_000), vec![5_000, 3_000, 2_000]);
+}
+
+#[test]
+fn rounding_dust_goes_to_the_first_recipient() {
+    let pool = PoolState { fee_recipients: vec![recipient(3_333), recipient(3_333), recipient(3_334)], ..Default::default() };
+
+    let amounts = pool.split_among_fee_recipients(100);
+
+    assert_eq!(amounts, vec![34, 33, 33]);
+    assert_eq!(amounts.iter().sum::<u64>(), 100);
+}
+
+#[test]
+fn shares_must_sum_to_exactly_10000() {
+    assert!(PoolState::validate_fee_recipients(&[recipient(6_000), recipient(4_000)]).is_ok());
+
+    for recipients in [vec![recipient(6_000), recipient(3_999)], vec![recipient(6_000), recipient(4_001)]] {
+        let err = PoolState::validate_fee_recipients(&recipients).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeRecipients.into());
+    }
+}
+
+#[test]
+fn bad_recipient_lists_are_rejected() {
+    let duplicate = recipient(5_000);
+    let cases = [
+        vec![recipient(10_000), recipient(0)],
+        vec![duplicate, duplicate],
+        vec![FeeRecipient { pubkey: Pubkey::default(), share_bps: 10_000 }],
+        (0..9).map(|_| recipient(1_000)).collect(),
+    ];
+
+    for recipients in cases {
+        let err = PoolState::validate_fee_recipients(&recipients).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeRecipients.into());
+    }
+    assert!(PoolState::validate_fee_recipients(&[]).is_ok());
+}
@@ -0,0 +1,64 @@
+//! Fee tier index reporting tests.
+//!
+//! `TradeExecuted` carries the tier index `calculate_fee` returns, so these
+//! check it against the tier the pool's volume falls in.
+
+use hoe_dex_protection::{FeeTier, PoolState, TradeSettings, VolumeSettings, FEE_MODE_NONE, FEE_MODE_TIER_BASED};
+
+const NOW: i64 = 1_700_000_000;
+
+fn pool_at_volume(current_volume: u64) -> PoolState {
+    PoolState {
+        fee_tiers: vec![
+            FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None },
+            FeeTier { volume_threshold: 10_000_000, fee_bps: 20, liquidity_threshold: None },
+        ],
+        volume: VolumeSettings { current_volume, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+fn tier_index(pool: &PoolState) -> Option<u8> {
+    let (_, _, tier_index) = pool.calculate_fee(100_000, NOW).unwrap();
+    tier_index
+}
+
+#[test]
+fn index_follows_the_volume_tier() {
+    for (volume, expected) in [(0, 0), (1_000_000, 0), (1_000_001, 1), (10_000_000, 1)] {
+        let pool = pool_at_volume(volume);
+
+        assert_eq!(tier_index(&pool), Some(expected), "volume {}", volume);
+        assert_eq!(pool.fee_tier_index(volume, 0), Some(expected as usize));
+    }
+}
+
+#[test]
+fn no_index_past_every_tier() {
+    let pool = pool_at_volume(10_000_001);
+
+    let (_, fee_mode, tier_index) = pool.calculate_fee(100_000, NOW).unwrap();
+    assert_eq!(fee_mode, FEE_MODE_NONE);
+    assert_eq!(tier_index, None);
+}
+
+#[test]
+fn no_index_inside_the_early_trade_window() {
+    let pool = PoolState {
+        pool_start_time: NOW as u64,
+        trade_settings: TradeSettings { early_trade_window_seconds: 60, early_trade_fee_bps: 100, ..Default::default() },
+        ..pool_at_volume(0)
+    };
+
+    let (fees, _, tier_index) = pool.calculate_fee(100_000, NOW).unwrap();
+    assert_eq!(fees.lp_fee, 1000);
+    assert_eq!(tier_index, None);
+}
+
+#[test]
+fn tier_based_trades_report_the_tier_mode() {
+    let pool = pool_at_volume(0);
+
+    let (_, fee_mode, _) = pool.calculate_fee(100_000, NOW).unwrap();
+    assert_eq!(fee_mode, FEE_MODE_TIER_BASED);
+}
@@ -0,0 +1,62 @@
+//! Fee tier invariant tests.
+//!
+//! An empty tier set would price every trade at the fallback fee, so these
+//! check that no update path can produce one and that trades refuse one.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    ErrorCode, FeeRounding, FeeSettingsUpdate, FeeTier, ParameterUpdate, PoolState, DEFAULT_MAX_FEE_TIERS,
+};
+
+const NOW: u64 = 1_700_000_000;
+
+fn pool() -> PoolState {
+    PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None }],
+        max_fee_tiers: DEFAULT_MAX_FEE_TIERS,
+        ..Default::default()
+    }
+}
+
+fn fee_update(fee_tiers: Vec<FeeTier>) -> ParameterUpdate {
+    ParameterUpdate::Fee(FeeSettingsUpdate {
+        fee_tiers,
+        fee_tiers_locked: false,
+        fee_rounding: FeeRounding::Down,
+        protocol_fee_bps: 0,
+        fee_free_threshold: 0,
+    })
+}
+
+#[test]
+fn empty_tier_set_is_rejected_as_a_tier_list() {
+    let err = pool().validate_fee_tiers(&[]).unwrap_err();
+
+    assert_eq!(err, ErrorCode::InvalidFeeTier.into());
+}
+
+#[test]
+fn applying_an_empty_tier_set_keeps_the_existing_tiers() {
+    let mut pool = pool();
+
+    pool.apply_update(&fee_update(vec![]), &Pubkey::new_unique(), NOW).unwrap();
+
+    assert_eq!(pool.fee_tiers.len(), 1);
+    assert!(pool.check_fee_tiers_present().is_ok());
+}
+
+#[test]
+fn migrating_from_an_empty_source_keeps_the_existing_tiers() {
+    let mut pool = pool();
+
+    pool.copy_config_from(&PoolState::default());
+
+    assert_eq!(pool.fee_tiers.len(), 1);
+}
+
+#[test]
+fn trades_refuse_a_pool_without_tiers() {
+    let err = PoolState::default().check_fee_tiers_present().unwrap_err();
+
+    assert_eq!(err, ErrorCode::FeeTiersEmpty.into());
+}
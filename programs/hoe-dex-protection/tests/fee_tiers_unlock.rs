@@ -0,0 +1,100 @@
+//! Timelocked fee tier unlock tests.
+//!
+//! Scheduling an unlock leaves the tiers locked; only applying it after
+//! `PARAMETER_UPDATE_TIMELOCK` lifts the lock. Timelocked fee updates can
+//! neither change locked tiers nor lift the lock.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    ErrorCode, FeeRounding, FeeSettingsUpdate, FeeTier, ParameterUpdate, PoolState, PARAMETER_UPDATE_TIMELOCK,
+};
+
+const NOW: u64 = 1_700_000_000;
+
+fn locked_pool() -> PoolState {
+    PoolState { fee_tiers_locked: true, ..Default::default() }
+}
+
+fn fee_update(fee_tiers: Vec<FeeTier>, fee_tiers_locked: bool) -> ParameterUpdate {
+    ParameterUpdate::Fee(FeeSettingsUpdate {
+        fee_tiers,
+        fee_tiers_locked,
+        fee_rounding: FeeRounding::Down,
+        protocol_fee_bps: 0,
+        fee_free_threshold: 0,
+    })
+}
+
+fn tiers() -> Vec<FeeTier> {
+    vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 10, liquidity_threshold: None }]
+}
+
+#[test]
+fn scheduling_keeps_the_tiers_locked() {
+    let mut pool = locked_pool();
+
+    let scheduled_time = pool.schedule_fee_tiers_unlock(NOW).unwrap();
+
+    assert_eq!(scheduled_time, NOW + PARAMETER_UPDATE_TIMELOCK);
+    assert!(pool.fee_tiers_locked);
+}
+
+#[test]
+fn tiers_stay_locked_until_the_timelock_elapses() {
+    let mut pool = locked_pool();
+    pool.schedule_fee_tiers_unlock(NOW).unwrap();
+
+    let err = pool.apply_fee_tiers_unlock(NOW + PARAMETER_UPDATE_TIMELOCK - 1).unwrap_err();
+    assert_eq!(err, ErrorCode::TimelockNotExpired.into());
+    assert!(pool.fee_tiers_locked);
+
+    pool.apply_fee_tiers_unlock(NOW + PARAMETER_UPDATE_TIMELOCK).unwrap();
+    assert!(!pool.fee_tiers_locked);
+    assert_eq!(pool.fee_tiers_unlock_time, 0);
+}
+
+#[test]
+fn unlocking_requires_a_lock() {
+    let err = PoolState::default().schedule_fee_tiers_unlock(NOW).unwrap_err();
+    assert_eq!(err, ErrorCode::FeeTiersNotLocked.into());
+}
+
+#[test]
+fn an_unlock_cannot_be_scheduled_twice() {
+    let mut pool = locked_pool();
+    pool.schedule_fee_tiers_unlock(NOW).unwrap();
+
+    let err = pool.schedule_fee_tiers_unlock(NOW + 1).unwrap_err();
+    assert_eq!(err, ErrorCode::FeeTiersUnlockPending.into());
+}
+
+#[test]
+fn applying_requires_a_scheduled_unlock() {
+    let err = locked_pool().apply_fee_tiers_unlock(NOW).unwrap_err();
+    assert_eq!(err, ErrorCode::NoPendingFeeTiersUnlock.into());
+}
+
+#[test]
+fn fee_updates_cannot_change_locked_tiers() {
+    let pool = locked_pool();
+
+    assert!(pool.validate_parameter_update(&fee_update(Vec::new(), true)).is_ok());
+    let err = pool.validate_parameter_update(&fee_update(tiers(), true)).unwrap_err();
+    assert_eq!(err, ErrorCode::FeeTiersLocked.into());
+}
+
+#[test]
+fn fee_updates_cannot_lift_the_lock() {
+    let err = locked_pool().validate_parameter_update(&fee_update(Vec::new(), false)).unwrap_err();
+    assert_eq!(err, ErrorCode::FeeTiersLocked.into());
+}
+
+#[test]
+fn a_pending_update_is_refused_once_the_tiers_lock() {
+    // Scheduled while unlocked, then applied after `lock_fee_tiers`
+    let mut pool = locked_pool();
+    let err = pool.apply_update(&fee_update(tiers(), false), &Pubkey::default(), NOW).unwrap_err();
+    assert_eq!(err, ErrorCode::FeeTiersLocked.into());
+    assert!(pool.fee_tiers_locked);
+    assert!(pool.fee_tiers.is_empty());
+}
@@ -0,0 +1,73 @@
+//! Fee withdrawal cooldown tests.
+//!
+//! `withdraw_fees` and `distribute_fees` share one cooldown, checked against
+//! the time of the last withdrawal. Only a longer cooldown applies at once.
+
+use hoe_dex_protection::{ErrorCode, ParameterUpdate, PoolState, ProtectionSettings, ProtectionSettingsUpdate};
+
+const WITHDRAWN_AT: i64 = 1_700_000_000;
+const COOLDOWN: i64 = 3600;
+
+fn pool(fee_withdraw_cooldown: i64, last_fee_withdraw_ts: i64) -> PoolState {
+    PoolState { fee_withdraw_cooldown, last_fee_withdraw_ts, ..Default::default() }
+}
+
+/// A pool whose other protection settings pass validation
+fn configured_pool() -> PoolState {
+    PoolState {
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..pool(COOLDOWN, WITHDRAWN_AT)
+    }
+}
+
+#[test]
+fn second_withdrawal_within_the_cooldown_is_rejected() {
+    let pool = pool(COOLDOWN, WITHDRAWN_AT);
+
+    for now in [WITHDRAWN_AT, WITHDRAWN_AT + COOLDOWN - 1] {
+        let err = pool.check_fee_withdraw_cooldown(now).unwrap_err();
+        assert_eq!(err, ErrorCode::FeeWithdrawCooldown.into());
+    }
+}
+
+#[test]
+fn withdrawal_after_the_cooldown_succeeds() {
+    let pool = pool(COOLDOWN, WITHDRAWN_AT);
+
+    assert!(pool.check_fee_withdraw_cooldown(WITHDRAWN_AT + COOLDOWN).is_ok());
+}
+
+#[test]
+fn first_withdrawal_is_never_blocked() {
+    assert!(pool(COOLDOWN, 0).check_fee_withdraw_cooldown(WITHDRAWN_AT).is_ok());
+}
+
+#[test]
+fn zero_cooldown_disables_the_check() {
+    assert!(pool(0, WITHDRAWN_AT).check_fee_withdraw_cooldown(WITHDRAWN_AT).is_ok());
+}
+
+#[test]
+fn shortening_the_cooldown_is_not_a_tightening() {
+    let pool = configured_pool();
+    let current = pool.current_protection_settings();
+    assert_eq!(current.fee_withdraw_cooldown, COOLDOWN);
+
+    let longer = ProtectionSettingsUpdate { fee_withdraw_cooldown: COOLDOWN + 1, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&longer).is_ok());
+
+    for fee_withdraw_cooldown in [COOLDOWN - 1, 0] {
+        let shorter = ProtectionSettingsUpdate { fee_withdraw_cooldown, ..current.clone() };
+        let err = pool.validate_protection_tightening(&shorter).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+}
+
+#[test]
+fn timelocked_update_bounds_the_cooldown() {
+    let pool = configured_pool();
+    let settings = ProtectionSettingsUpdate { fee_withdraw_cooldown: -1, ..pool.current_protection_settings() };
+
+    let err = pool.validate_parameter_update(&ParameterUpdate::Protection(settings)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidFeeWithdrawCooldown.into());
+}
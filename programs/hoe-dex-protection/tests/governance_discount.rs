@@ -0,0 +1,107 @@
+//! Governance token fee discount tests.
+//!
+//! The discount is applied to the fee `calculate_fee` returns, so these check
+//! tier selection and the discounted fee without executing trades.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    FeeTier, GovernanceDiscountTier, PoolState, TradeFees, TraderState, MAX_GOVERNANCE_DISCOUNT_BPS, MINIMUM_FEE,
+};
+
+const NOW: i64 = 1_700_000_000;
+
+fn tiers() -> Vec<GovernanceDiscountTier> {
+    vec![
+        GovernanceDiscountTier { min_balance: 1_000, discount_bps: 1000 },
+        GovernanceDiscountTier { min_balance: 100_000, discount_bps: 5000 },
+    ]
+}
+
+fn pool() -> PoolState {
+    PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None }],
+        governance_mint: Some(Pubkey::new_unique()),
+        governance_discount_tiers: tiers(),
+        ..Default::default()
+    }
+}
+
+fn discounted_fee(pool: &PoolState, amount_in: u64, balance: u64) -> u64 {
+    let (fees, _, _) = pool.calculate_fee(amount_in, NOW).unwrap();
+    pool.apply_governance_discount(fees, pool.governance_discount_bps(balance)).lp_fee
+}
+
+#[test]
+fn discount_follows_the_holding_tier() {
+    let pool = pool();
+
+    assert_eq!(discounted_fee(&pool, 100_000, 999), 300);
+    assert_eq!(discounted_fee(&pool, 100_000, 1_000), 270);
+    assert_eq!(discounted_fee(&pool, 100_000, 99_999), 270);
+    assert_eq!(discounted_fee(&pool, 100_000, 100_000), 150);
+    assert_eq!(discounted_fee(&pool, 100_000, u64::MAX), 150);
+}
+
+#[test]
+fn zero_balance_gets_no_discount() {
+    let pool = pool();
+
+    assert_eq!(pool.governance_discount_bps(0), 0);
+    assert_eq!(discounted_fee(&pool, 100_000, 0), 300);
+}
+
+#[test]
+fn no_governance_mint_gets_no_discount() {
+    let pool = PoolState { governance_mint: None, ..pool() };
+
+    assert_eq!(pool.governance_discount_bps(1_000_000), 0);
+}
+
+#[test]
+fn discount_never_goes_below_the_minimum_fee() {
+    let pool = pool();
+    let fees = TradeFees { lp_fee: MINIMUM_FEE, protocol_fee: 0 };
+
+    assert_eq!(pool.apply_governance_discount(fees, MAX_GOVERNANCE_DISCOUNT_BPS).lp_fee, MINIMUM_FEE);
+    // Dust still pays the minimum fee after the discount
+    assert_eq!(discounted_fee(&pool, 10, 1_000_000), MINIMUM_FEE);
+}
+
+#[test]
+fn protocol_fee_is_not_discounted() {
+    let pool = pool();
+    let fees = TradeFees { lp_fee: 300, protocol_fee: 50 };
+
+    assert_eq!(pool.apply_governance_discount(fees, 5000), TradeFees { lp_fee: 150, protocol_fee: 50 });
+}
+
+#[test]
+fn discount_is_claimed_once_per_slot() {
+    let mut trader_state = TraderState::default();
+
+    assert!(trader_state.claim_discount_slot(42));
+    assert!(!trader_state.claim_discount_slot(42));
+    assert!(trader_state.claim_discount_slot(43));
+}
+
+#[test]
+fn schedule_validation() {
+    assert!(PoolState::validate_governance_discount_tiers(&tiers()).is_ok());
+    assert!(PoolState::validate_governance_discount_tiers(&[]).is_ok());
+
+    let mut reversed = tiers();
+    reversed.reverse();
+    assert!(PoolState::validate_governance_discount_tiers(&reversed).is_err());
+
+    let zero_balance = [GovernanceDiscountTier { min_balance: 0, discount_bps: 100 }];
+    assert!(PoolState::validate_governance_discount_tiers(&zero_balance).is_err());
+
+    let too_deep = [GovernanceDiscountTier { min_balance: 1, discount_bps: MAX_GOVERNANCE_DISCOUNT_BPS + 1 }];
+    assert!(PoolState::validate_governance_discount_tiers(&too_deep).is_err());
+
+    let shrinking = [
+        GovernanceDiscountTier { min_balance: 1, discount_bps: 500 },
+        GovernanceDiscountTier { min_balance: 2, discount_bps: 400 },
+    ];
+    assert!(PoolState::validate_governance_discount_tiers(&shrinking).is_err());
+}
@@ -0,0 +1,78 @@
+//! Hourly trade count tests.
+//!
+//! Trades are validated and then counted the way `process_trade` does, so
+//! these exhaust the window without executing transfers.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    validation, ErrorCode, ParameterUpdate, PoolState, ProtectionSettings, ProtectionSettingsUpdate, MAX_HOURLY_TRADES,
+    MAX_HOURLY_TRADES_WINDOW,
+};
+
+const START: u64 = 1_700_000_000;
+
+fn pool(max_hourly_trades: u32) -> PoolState {
+    PoolState { total_liquidity: 10_000_000, max_hourly_trades, ..Default::default() }
+}
+
+fn trade(pool: &mut PoolState, current_time: u64) -> anchor_lang::Result<()> {
+    validation::validate_trade_parameters(pool, &Pubkey::new_unique(), 1, current_time)?;
+    pool.record_hourly_trade(current_time);
+    Ok(())
+}
+
+#[test]
+fn trade_after_the_limit_fails_until_the_hour_rolls_over() {
+    let mut pool = pool(5);
+    for second in 0..5 {
+        trade(&mut pool, START + second).unwrap();
+    }
+
+    let err = trade(&mut pool, START + 10).unwrap_err();
+    assert_eq!(err, ErrorCode::HourlyTradeLimitExceeded.into());
+    let err = trade(&mut pool, START + MAX_HOURLY_TRADES_WINDOW - 1).unwrap_err();
+    assert_eq!(err, ErrorCode::HourlyTradeLimitExceeded.into());
+
+    trade(&mut pool, START + MAX_HOURLY_TRADES_WINDOW).unwrap();
+    assert_eq!(pool.hourly_trade_count, 1);
+    assert_eq!(pool.hourly_trades_window_start, START + MAX_HOURLY_TRADES_WINDOW);
+}
+
+#[test]
+fn default_limit_applies_when_unset() {
+    let mut pool = pool(0);
+    assert_eq!(pool.hourly_trade_limit(), MAX_HOURLY_TRADES);
+
+    for second in 0..MAX_HOURLY_TRADES as u64 {
+        trade(&mut pool, START + second).unwrap();
+    }
+    assert!(trade(&mut pool, START + 200).is_err());
+}
+
+#[test]
+fn lapsed_window_reads_as_empty() {
+    let mut pool = pool(5);
+    pool.record_hourly_trade(START);
+
+    assert_eq!(pool.hourly_trade_count_at(START + 1), 1);
+    assert_eq!(pool.hourly_trade_count_at(START + MAX_HOURLY_TRADES_WINDOW), 0);
+}
+
+#[test]
+fn raising_the_cap_is_not_a_tightening() {
+    let pool =
+        PoolState { protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() }, ..pool(10) };
+    let current = pool.current_protection_settings();
+    assert_eq!(current.max_hourly_trades, 10);
+
+    let lower = ProtectionSettingsUpdate { max_hourly_trades: 9, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&lower).is_ok());
+
+    let higher = ProtectionSettingsUpdate { max_hourly_trades: u32::MAX, ..current.clone() };
+    let err = pool.validate_protection_tightening(&higher).unwrap_err();
+    assert_eq!(err, ErrorCode::Unauthorized.into());
+
+    let zero = ProtectionSettingsUpdate { max_hourly_trades: 0, ..current };
+    let err = pool.validate_parameter_update(&ParameterUpdate::Protection(zero)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidMaxHourlyTrades.into());
+}
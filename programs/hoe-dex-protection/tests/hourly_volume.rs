@@ -0,0 +1,98 @@
+//! Hourly volume cap tests.
+//!
+//! The hourly cap runs on its own window next to the 24h buckets, so these
+//! check bursts against it with the daily cap left wide open.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    validation, ErrorCode, ParameterUpdate, PoolState, ProtectionSettings, ProtectionSettingsUpdate, VolumeSettings,
+    MAX_HOURLY_VOLUME, MAX_HOURLY_VOLUME_WINDOW, VOLUME_BUCKET_SECONDS,
+};
+
+const START: u64 = 1_700_000_000;
+const MAX_HOURLY: u64 = 100_000;
+
+fn pool() -> PoolState {
+    PoolState {
+        total_liquidity: 10_000_000,
+        volume: VolumeSettings {
+            max_daily: 100_000_000,
+            max_hourly: MAX_HOURLY,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        // Valid, so protection updates fail only on the field under test
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+fn trade(pool: &mut PoolState, amount: u64, current_time: u64) -> anchor_lang::Result<()> {
+    validation::validate_trade_parameters(pool, &Pubkey::new_unique(), amount, current_time)?;
+    pool.update_volume(amount, current_time)
+}
+
+#[test]
+fn burst_hits_the_hourly_cap_under_the_daily_cap() {
+    let mut pool = pool();
+    for minute in 0..4 {
+        trade(&mut pool, 25_000, START + minute * 60).unwrap();
+    }
+
+    let err = trade(&mut pool, 1, START + 300).unwrap_err();
+    assert_eq!(err, ErrorCode::HourlyVolumeLimitExceeded.into());
+    assert!(pool.check_volume_limit(1, START + 300).is_ok());
+}
+
+#[test]
+fn trade_that_would_cross_the_cap_is_rejected_whole() {
+    let mut pool = pool();
+    trade(&mut pool, 60_000, START).unwrap();
+
+    let err = trade(&mut pool, 50_000, START + 60).unwrap_err();
+    assert_eq!(err, ErrorCode::HourlyVolumeLimitExceeded.into());
+    assert_eq!(pool.volume.hourly_volume, 60_000);
+}
+
+#[test]
+fn window_rolls_over_after_an_hour() {
+    let mut pool = pool();
+    trade(&mut pool, MAX_HOURLY, START + 600).unwrap();
+    assert!(trade(&mut pool, 1, START + 600 + MAX_HOURLY_VOLUME_WINDOW - 1).is_err());
+
+    let next_window = START + 600 + MAX_HOURLY_VOLUME_WINDOW;
+    trade(&mut pool, MAX_HOURLY, next_window).unwrap();
+    assert_eq!(pool.volume.hourly_window_start, next_window);
+    assert_eq!(pool.volume.hourly_volume, MAX_HOURLY);
+}
+
+#[test]
+fn zero_cap_falls_back_to_the_default() {
+    let volume = VolumeSettings::default();
+
+    assert_eq!(volume.hourly_limit(), MAX_HOURLY_VOLUME);
+}
+
+#[test]
+fn raising_the_cap_is_not_a_tightening() {
+    let pool = pool();
+    let current = pool.current_protection_settings();
+    assert_eq!(current.max_hourly_volume, MAX_HOURLY);
+
+    let lower = ProtectionSettingsUpdate { max_hourly_volume: MAX_HOURLY - 1, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&lower).is_ok());
+
+    let higher = ProtectionSettingsUpdate { max_hourly_volume: MAX_HOURLY + 1, ..current };
+    let err = pool.validate_protection_tightening(&higher).unwrap_err();
+    assert_eq!(err, ErrorCode::Unauthorized.into());
+}
+
+#[test]
+fn timelocked_update_rejects_a_zero_cap() {
+    let pool = pool();
+    let settings = ProtectionSettingsUpdate { max_hourly_volume: 0, ..pool.current_protection_settings() };
+
+    let err = pool.validate_parameter_update(&ParameterUpdate::Protection(settings)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidMaxHourlyVolume.into());
+}
@@ -0,0 +1,40 @@
+//! Last parameter update record tests.
+//!
+//! The record persists who applied parameter settings and when, which the
+//! `ParametersUpdated` event alone does not.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ParameterUpdateRecord, PoolState};
+
+const NOW: u64 = 1_700_000_000;
+
+#[test]
+fn no_record_before_any_update() {
+    assert_eq!(PoolState::default().last_parameter_update(), None);
+}
+
+#[test]
+fn applying_an_update_records_time_and_admin() {
+    let mut pool = PoolState::default();
+    let admin = Pubkey::new_unique();
+
+    pool.record_parameter_update(&admin, NOW);
+
+    assert_eq!(pool.last_param_update_ts, NOW as i64);
+    assert_eq!(pool.last_param_update_admin, admin);
+    assert_eq!(pool.last_parameter_update(), Some(ParameterUpdateRecord { ts: NOW as i64, admin }));
+}
+
+#[test]
+fn a_later_update_replaces_the_record() {
+    let mut pool = PoolState::default();
+    let later_admin = Pubkey::new_unique();
+
+    pool.record_parameter_update(&Pubkey::new_unique(), NOW);
+    pool.record_parameter_update(&later_admin, NOW + 60);
+
+    assert_eq!(
+        pool.last_parameter_update(),
+        Some(ParameterUpdateRecord { ts: NOW as i64 + 60, admin: later_admin })
+    );
+}
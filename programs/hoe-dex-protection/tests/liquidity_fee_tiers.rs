@@ -0,0 +1,81 @@
+//! Liquidity-keyed fee tier tests.
+//!
+//! A tier with a liquidity threshold also applies while the pool is that thin,
+//! so the stricter of the volume and liquidity tiers is selected. Tiers
+//! without one select on volume alone.
+
+use hoe_dex_protection::{ErrorCode, FeeTier, PoolState, VolumeSettings, MAX_FEE_TIERS};
+
+const NOW: i64 = 1_700_000_000;
+
+fn pool(liquidity_threshold: Option<u64>, current_volume: u64, total_liquidity: u64) -> PoolState {
+    PoolState {
+        fee_tiers: vec![
+            FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold },
+            FeeTier { volume_threshold: 10_000_000, fee_bps: 20, liquidity_threshold: None },
+        ],
+        volume: VolumeSettings { current_volume, ..Default::default() },
+        total_liquidity,
+        max_fee_tiers: MAX_FEE_TIERS as u16,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn thin_liquidity_selects_the_stricter_tier() {
+    // Volume alone would pick tier 1
+    let pool = pool(Some(50_000_000), 5_000_000, 20_000_000);
+
+    assert_eq!(pool.fee_tier_index(5_000_000, 20_000_000), Some(0));
+    let (fees, _, tier_index) = pool.calculate_fee(100_000, NOW).unwrap();
+    assert_eq!(tier_index, Some(0));
+    assert_eq!(fees.lp_fee, 300);
+}
+
+#[test]
+fn deep_liquidity_leaves_the_volume_tier() {
+    let pool = pool(Some(50_000_000), 5_000_000, 80_000_000);
+
+    let (_, _, tier_index) = pool.calculate_fee(100_000, NOW).unwrap();
+    assert_eq!(tier_index, Some(1));
+}
+
+#[test]
+fn low_volume_still_selects_the_first_tier() {
+    let pool = pool(Some(50_000_000), 0, 80_000_000);
+    assert_eq!(pool.fee_tier_index(0, 80_000_000), Some(0));
+}
+
+#[test]
+fn no_liquidity_threshold_keys_off_volume() {
+    let pool = pool(None, 5_000_000, 0);
+    assert_eq!(pool.fee_tier_index(5_000_000, 0), Some(1));
+}
+
+#[test]
+fn liquidity_thresholds_must_increase() {
+    let pool = pool(None, 0, 0);
+    let tiers = |first: u64, second: u64| {
+        vec![
+            FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: Some(first) },
+            FeeTier { volume_threshold: 10_000_000, fee_bps: 20, liquidity_threshold: Some(second) },
+        ]
+    };
+
+    assert!(pool.validate_fee_tiers(&tiers(5_000_000, 6_000_000)).is_ok());
+    for second in [5_000_000, 4_000_000] {
+        let err = pool.validate_fee_tiers(&tiers(5_000_000, second)).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidFeeTierSpacing.into());
+    }
+}
+
+#[test]
+fn tiers_may_mix_set_and_unset_thresholds() {
+    let pool = pool(None, 0, 0);
+    let tiers = vec![
+        FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None },
+        FeeTier { volume_threshold: 10_000_000, fee_bps: 20, liquidity_threshold: Some(5_000_000) },
+    ];
+
+    assert!(pool.validate_fee_tiers(&tiers).is_ok());
+}
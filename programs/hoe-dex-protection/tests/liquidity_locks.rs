@@ -0,0 +1,61 @@
+//! Liquidity lock tests.
+//!
+//! Locked liquidity cannot be removed before its unlock time; once a lock
+//! expires it stops counting and is dropped on the next lock or removal.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ErrorCode, PoolState, MAX_LIQUIDITY_LOCKS};
+
+const NOW: i64 = 1_700_000_000;
+
+fn pool() -> PoolState {
+    PoolState { total_liquidity: 1_000_000, ..Default::default() }
+}
+
+#[test]
+fn locked_liquidity_cannot_be_removed_before_expiry() {
+    let mut pool = pool();
+    pool.lock_liquidity(&Pubkey::new_unique(), 600_000, NOW + 3600, NOW).unwrap();
+
+    assert_eq!(pool.locked_liquidity(NOW), 600_000);
+    assert!(pool.check_liquidity_unlocked(400_000, NOW).is_ok());
+    let err = pool.check_liquidity_unlocked(400_001, NOW).unwrap_err();
+    assert_eq!(err, ErrorCode::LiquidityLocked.into());
+}
+
+#[test]
+fn expired_locks_release_their_liquidity() {
+    let mut pool = pool();
+    pool.lock_liquidity(&Pubkey::new_unique(), 600_000, NOW + 3600, NOW).unwrap();
+
+    assert_eq!(pool.locked_liquidity(NOW + 3600), 0);
+    assert!(pool.check_liquidity_unlocked(1_000_000, NOW + 3600).is_ok());
+
+    pool.release_expired_liquidity_locks(NOW + 3600);
+    assert!(pool.liquidity_locks.is_empty());
+}
+
+#[test]
+fn unlock_time_must_be_in_the_future() {
+    let mut pool = pool();
+
+    for unlock_ts in [NOW, NOW - 1] {
+        let err = pool.lock_liquidity(&Pubkey::new_unique(), 1, unlock_ts, NOW).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidLiquidityLock.into());
+    }
+}
+
+#[test]
+fn locks_are_bounded() {
+    let mut pool = pool();
+    for _ in 0..MAX_LIQUIDITY_LOCKS {
+        pool.lock_liquidity(&Pubkey::new_unique(), 1, NOW + 60, NOW).unwrap();
+    }
+
+    let err = pool.lock_liquidity(&Pubkey::new_unique(), 1, NOW + 60, NOW).unwrap_err();
+    assert_eq!(err, ErrorCode::TooManyLiquidityLocks.into());
+
+    // Expired locks free their slots
+    assert!(pool.lock_liquidity(&Pubkey::new_unique(), 1, NOW + 120, NOW + 60).is_ok());
+    assert_eq!(pool.liquidity_locks.len(), 1);
+}
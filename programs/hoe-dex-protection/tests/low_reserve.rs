@@ -0,0 +1,80 @@
+//! Low reserve mode tests.
+//!
+//! Below the threshold, trades are surcharged and large ones rejected; at or
+//! above it, or with the threshold unset, trades are untouched.
+
+use hoe_dex_protection::{ErrorCode, LowReserveSettings, PoolState, ProtectionSettingsUpdate, TradeFees};
+
+fn pool(total_liquidity: u64) -> PoolState {
+    PoolState {
+        total_liquidity,
+        low_reserve: LowReserveSettings { threshold: 1_000_000, surcharge_bps: 100, max_trade_size: 50_000 },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn low_only_below_the_threshold() {
+    assert!(pool(999_999).is_low_reserve());
+    assert!(!pool(1_000_000).is_low_reserve());
+}
+
+#[test]
+fn zero_threshold_disables() {
+    let pool = PoolState { total_liquidity: 0, ..Default::default() };
+    assert!(!pool.is_low_reserve());
+}
+
+#[test]
+fn large_trades_are_rejected() {
+    let pool = pool(500_000);
+
+    assert!(pool.check_low_reserve_trade_size(50_000).is_ok());
+    let err = pool.check_low_reserve_trade_size(50_001).unwrap_err();
+    assert_eq!(err, ErrorCode::LowReserveTradeTooLarge.into());
+}
+
+#[test]
+fn zero_max_trade_size_accepts_any_size() {
+    let pool = PoolState {
+        low_reserve: LowReserveSettings { max_trade_size: 0, ..pool(500_000).low_reserve },
+        ..pool(500_000)
+    };
+    assert!(pool.check_low_reserve_trade_size(u64::MAX).is_ok());
+}
+
+#[test]
+fn surcharge_is_capped_by_the_fee_cap() {
+    let pool = pool(500_000);
+
+    assert_eq!(pool.low_reserve_surcharge(100_000, TradeFees::default()).unwrap(), 1_000);
+    let near_cap = TradeFees { lp_fee: 100_000, protocol_fee: 0 };
+    assert_eq!(pool.low_reserve_surcharge(100_000, near_cap).unwrap(), 0);
+}
+
+#[test]
+fn tightening_keeps_the_surcharge_from_rising() {
+    let current = pool(500_000).low_reserve;
+    let with =
+        |threshold, surcharge_bps, max_trade_size| LowReserveSettings { threshold, surcharge_bps, max_trade_size };
+
+    assert!(with(2_000_000, 50, 10_000).is_no_looser_than(&current));
+    assert!(!with(1_000_000, 101, 50_000).is_no_looser_than(&current));
+    assert!(!with(999_999, 100, 50_000).is_no_looser_than(&current));
+    assert!(!with(1_000_000, 100, 50_001).is_no_looser_than(&current));
+    assert!(!with(1_000_000, 100, 0).is_no_looser_than(&current));
+    assert!(with(1_000_000, 100, 50_000).is_no_looser_than(&LowReserveSettings { max_trade_size: 0, ..current }));
+}
+
+#[test]
+fn immediate_protection_update_cannot_raise_the_surcharge() {
+    let pool = pool(500_000);
+    let current = pool.current_protection_settings();
+    let settings = ProtectionSettingsUpdate {
+        low_reserve: LowReserveSettings { surcharge_bps: 101, ..current.low_reserve.clone() },
+        ..current
+    };
+
+    let err = pool.validate_protection_tightening(&settings).unwrap_err();
+    assert_eq!(err, ErrorCode::Unauthorized.into());
+}
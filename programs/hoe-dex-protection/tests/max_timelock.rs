@@ -0,0 +1,56 @@
+//! Timelock upper bound tests.
+//!
+//! A schedule can never land further out than `MAX_PARAMETER_UPDATE_TIMELOCK`,
+//! whether the delay comes from a new timelock config or one already stored.
+
+use hoe_dex_protection::{
+    utils::scheduled_time_after, ErrorCode, ParameterUpdate, PoolState, TimelockSettings,
+    MAX_PARAMETER_UPDATE_TIMELOCK,
+};
+
+const NOW: u64 = 1_700_000_000;
+
+fn timelocks(fee_seconds: u64) -> TimelockSettings {
+    TimelockSettings {
+        trade_seconds: 86_400,
+        protection_seconds: 86_400,
+        fee_seconds,
+        state_seconds: 86_400,
+        emergency_pause_seconds: 300,
+        emergency_resume_seconds: 3_600,
+    }
+}
+
+#[test]
+fn schedule_up_to_the_bound_is_allowed() {
+    assert_eq!(
+        scheduled_time_after(NOW, MAX_PARAMETER_UPDATE_TIMELOCK).unwrap(),
+        NOW + MAX_PARAMETER_UPDATE_TIMELOCK
+    );
+}
+
+#[test]
+fn too_long_timelock_is_rejected_at_schedule_time() {
+    let err = scheduled_time_after(NOW, MAX_PARAMETER_UPDATE_TIMELOCK + 1).unwrap_err();
+
+    assert_eq!(err, ErrorCode::InvalidTimelockDuration.into());
+}
+
+#[test]
+fn too_long_timelock_config_is_rejected() {
+    let pool = PoolState::default();
+    let update = ParameterUpdate::Timelocks(timelocks(MAX_PARAMETER_UPDATE_TIMELOCK + 1));
+
+    let err = pool.validate_parameter_update(&update).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidTimelockDuration.into());
+}
+
+#[test]
+fn stored_oversized_emergency_timelock_cannot_schedule() {
+    let mut pool = PoolState { timelocks: timelocks(86_400), ..Default::default() };
+    pool.timelocks.emergency_pause_seconds = u64::MAX / 2;
+
+    let err = pool.schedule_emergency_pause(NOW).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidTimelockDuration.into());
+    assert!(pool.pending_emergency.is_none());
+}
@@ -0,0 +1,39 @@
+//! Per-transaction trade limit tests.
+//!
+//! Zero lifts the limit, so it counts as the loosest setting; only a nonzero
+//! limit at or below the current one applies without the timelock.
+
+use hoe_dex_protection::{ErrorCode, PoolState, ProtectionSettings, ProtectionSettingsUpdate};
+
+fn pool(max_trades_per_tx: u16) -> PoolState {
+    PoolState {
+        max_trades_per_tx,
+        // Valid, so protection updates fail only on the field under test
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn raising_or_lifting_the_limit_is_not_a_tightening() {
+    let pool = pool(2);
+    let current = pool.current_protection_settings();
+    assert_eq!(current.max_trades_per_tx, 2);
+
+    let lower = ProtectionSettingsUpdate { max_trades_per_tx: 1, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&lower).is_ok());
+
+    for looser in [3, 0] {
+        let settings = ProtectionSettingsUpdate { max_trades_per_tx: looser, ..current.clone() };
+        let err = pool.validate_protection_tightening(&settings).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+}
+
+#[test]
+fn any_limit_tightens_a_lifted_one() {
+    let pool = pool(0);
+    let settings = ProtectionSettingsUpdate { max_trades_per_tx: u16::MAX, ..pool.current_protection_settings() };
+
+    assert!(pool.validate_protection_tightening(&settings).is_ok());
+}
@@ -0,0 +1,291 @@
+//! Layout migration tests.
+//!
+//! Older layouts cannot be produced through the program's own instructions, so
+//! these tests write legacy account bytes directly and run the migration
+//! against the built program (`anchor build` first, so `target/deploy` exists).
+//! Legacy bytes are laid out field by field rather than through the
+//! program's legacy types, so a mistake in those types cannot hide here.
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize, Discriminator, InstructionData, ToAccountMetas};
+use hoe_dex_protection::{
+    PauseLevel, PoolState, TimelockSettings, DEFAULT_LARGE_WITHDRAWAL_BPS, DEFAULT_MAX_FEE_TIERS,
+    POOL_STATE_VERSION,
+};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signer,
+    system_program,
+    transaction::Transaction,
+};
+
+const BLACKLISTED: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fn put(data: &mut Vec<u8>, value: impl AnchorSerialize) {
+    value.serialize(data).unwrap();
+}
+
+/// A pool in the original layout: no version field, the admin key first
+fn baseline_pool_bytes(admin: Pubkey) -> Vec<u8> {
+    let mut data = PoolState::DISCRIMINATOR.to_vec();
+    put(&mut data, admin); // admin
+    put(&mut data, admin); // emergency_admin
+    put(&mut data, Pubkey::new_unique()); // token_mint
+    put(&mut data, 6u8); // token_decimals
+    put(&mut data, 5_000_000u64); // total_liquidity
+    put(&mut data, 1_234u64); // total_fees_collected
+    put(&mut data, true); // is_initialized
+    put(&mut data, false); // is_paused
+    put(&mut data, false); // is_finalized
+    put(&mut data, 1_700_000_000u64); // pool_start_time
+    put(&mut data, 1_700_000_000u64); // last_update
+    put(&mut data, vec![(1_000_000u64, 30u64), (10_000_000u64, 20u64)]); // fee_tiers
+    put(&mut data, false); // fee_tiers_locked
+    put(&mut data, None::<u16>); // default_fee_bps
+    // volume: volume_24h, last_update, last_decay, max_daily, current_volume,
+    // last_reset, decay_period, current_volume (declared twice)
+    for value in [40_000u64, 1_700_000_000, 1_700_000_000, 9_000_000, 40_000, 1_700_000_000, 86_400, 40_000] {
+        put(&mut data, value);
+    }
+    // rate_limit: window_seconds, count, max_calls, last_reset, window_size, max_per_window, current_window
+    put(&mut data, (60u64, 0u32, 10u32, 1_700_000_000u64, 60u64, 10u64, 0u64));
+    // circuit_breaker: enabled, threshold, window, cooldown, last_trigger, cooldown_period, max_amount, current_amount
+    put(&mut data, true);
+    for value in [2_000_000u64, 3_600, 3_600, 0, 3_600, 2_000_000, 0] {
+        put(&mut data, value);
+    }
+    // protection: enabled, snipe_protection_seconds, max_price_impact_bps, max_slippage, blacklist_enabled
+    put(&mut data, (true, 30u64, 500u64, 300u64, true));
+    // trade_settings: max_size_bps, min_size, cooldown_seconds, last_trade_time,
+    // early_trade_fee_bps, early_trade_window_seconds
+    for value in [1_000u64, 100, 0, 0, 200, 600] {
+        put(&mut data, value);
+    }
+    put(&mut data, None::<u8>); // pending_update
+    put(&mut data, 0u64); // emergency_action_scheduled_time
+    put(&mut data, vec![BLACKLISTED]); // trader_blacklist
+    put(&mut data, Vec::<Pubkey>::new()); // whitelist
+    put(&mut data, false); // is_emergency_paused
+    put(&mut data, 254u8); // bump
+    put(&mut data, [9u8; 32]); // pool_id
+    data
+}
+
+/// A pool in the version 2 layout, with a pending timelock update
+fn v2_pool_bytes(admin: Pubkey) -> Vec<u8> {
+    let mut data = PoolState::DISCRIMINATOR.to_vec();
+    put(&mut data, 2u8); // version
+    put(&mut data, admin); // admin
+    put(&mut data, admin); // emergency_admin
+    put(&mut data, None::<Pubkey>); // guardian
+    put(&mut data, Pubkey::new_unique()); // token_mint
+    put(&mut data, 6u8); // token_decimals
+    put(&mut data, 5_000_000u64); // total_liquidity
+    put(&mut data, 1_000u64); // min_liquidity_floor
+    put(&mut data, 600u64); // liquidity_grace_period
+    put(&mut data, vec![(250_000u64, 1_700_000_000u64)]); // pending_deposits
+    put(&mut data, 1_234u64); // total_fees_collected
+    put(&mut data, Pubkey::new_unique()); // insurance_fund
+    put(&mut data, 1_000u64); // insurance_fee_share_bps
+    put(&mut data, 55u64); // insurance_collected
+    put(&mut data, true); // is_initialized
+    put(&mut data, 1u8); // pause_level: TradingOnly
+    put(&mut data, false); // is_finalized
+    put(&mut data, 1_700_000_000u64); // pool_start_time
+    put(&mut data, 1_700_000_000u64); // last_update
+    put(&mut data, 17u64); // event_seq
+    put(&mut data, vec![(1_000_000u64, 30u64), (10_000_000u64, 20u64)]); // fee_tiers
+    put(&mut data, true); // fee_tiers_locked
+    put(&mut data, 8u16); // max_fee_tiers
+    put(&mut data, Some(25u16)); // default_fee_bps
+    // volume: volume_24h, last_update, last_decay, max_daily, current_volume,
+    // last_reset, decay_period, then the hourly buckets and bucket_hour
+    for value in [40_000u64, 1_700_000_000, 1_700_000_000, 9_000_000, 40_000, 1_700_000_000, 86_400] {
+        put(&mut data, value);
+    }
+    let mut buckets = [0u64; 24];
+    buckets[(1_700_000_000u64 / 3600 % 24) as usize] = 40_000;
+    put(&mut data, buckets);
+    put(&mut data, 1_700_000_000u64 / 3600);
+    // rate_limit: window_seconds, count, max_calls, last_reset, window_size, max_per_window, current_window
+    put(&mut data, (60u64, 0u32, 10u32, 1_700_000_000u64, 60u64, 10u64, 0u64));
+    // circuit_breaker: enabled, threshold, window, cooldown, last_trigger,
+    // cooldown_period, max_amount, current_amount, auto_pause_on_breaker, paused_by_breaker
+    put(&mut data, true);
+    for value in [2_000_000u64, 3_600, 3_600, 0, 3_600, 2_000_000, 0] {
+        put(&mut data, value);
+    }
+    put(&mut data, (true, false));
+    // protection: enabled, snipe_protection_seconds, max_price_impact_bps,
+    // max_slippage_bps, blacklist_enabled, min_trade_interval, large_withdrawal_bps
+    put(&mut data, (true, 30u64, 500u64, 300u64, true, 5i64, 2_500u64));
+    // trade_settings: max_size_bps, min_size, cooldown_seconds, last_trade_time,
+    // early_trade_fee_bps, early_trade_window_seconds
+    for value in [1_000u64, 100, 0, 0, 200, 600] {
+        put(&mut data, value);
+    }
+    // pending_update: Some { scheduled_time, updates: [Timelocks { four timelocks }] }
+    put(&mut data, 1u8);
+    put(&mut data, 1_700_086_400u64);
+    put(&mut data, 1u32);
+    put(&mut data, 4u8);
+    put(&mut data, (7_200u64, 7_200u64, 7_200u64, 7_200u64));
+    put(&mut data, (3_600u64, 3_600u64, 86_400u64, 3_600u64)); // timelocks
+    put(&mut data, 0u64); // emergency_action_scheduled_time
+    put(&mut data, 0u64); // emergency_paused_at
+    put(&mut data, vec![BLACKLISTED]); // trader_blacklist
+    put(&mut data, Vec::<Pubkey>::new()); // whitelist
+    put(&mut data, false); // is_emergency_paused
+    put(&mut data, 254u8); // bump
+    put(&mut data, [9u8; 32]); // pool_id
+    data
+}
+
+/// A pool in the current layout stamped with `version`
+fn versioned_pool_bytes(admin: Pubkey, version: u8) -> Vec<u8> {
+    let mut data = Vec::new();
+    PoolState { version, admin, emergency_admin: admin, ..Default::default() }
+        .try_serialize(&mut data)
+        .unwrap();
+    data
+}
+
+async fn start_with_pool(data: impl FnOnce(Pubkey) -> Vec<u8>) -> (ProgramTestContext, Pubkey) {
+    let mut program_test = ProgramTest::new("hoe_dex_protection", hoe_dex_protection::ID, None);
+    program_test.prefer_bpf(true);
+    let mut ctx = program_test.start_with_context().await;
+
+    let data = data(ctx.payer.pubkey());
+    let pool = Pubkey::new_unique();
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: hoe_dex_protection::ID,
+        executable: false,
+        rent_epoch: 0,
+    };
+    ctx.set_account(&pool, &account.into());
+
+    (ctx, pool)
+}
+
+async fn migrate(ctx: &mut ProgramTestContext, pool: Pubkey) -> Result<(), BanksClientError> {
+    let ix = Instruction {
+        program_id: hoe_dex_protection::ID,
+        accounts: hoe_dex_protection::accounts::MigratePoolState {
+            pool_state: pool,
+            admin: ctx.payer.pubkey(),
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: hoe_dex_protection::instruction::MigratePoolState {}.data(),
+    };
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&ctx.payer.pubkey()), &[&ctx.payer], blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+async fn fetch_data(ctx: &mut ProgramTestContext, pool: Pubkey) -> Vec<u8> {
+    ctx.banks_client.get_account(pool).await.unwrap().unwrap().data
+}
+
+#[tokio::test]
+async fn migrates_a_baseline_pool_preserving_data() {
+    let (mut ctx, pool) = start_with_pool(baseline_pool_bytes).await;
+    let admin = ctx.payer.pubkey();
+    let legacy = fetch_data(&mut ctx, pool).await;
+    assert_eq!(&legacy[8..40], admin.as_ref());
+    assert!(PoolState::try_deserialize(&mut legacy.as_slice()).is_err());
+
+    migrate(&mut ctx, pool).await.unwrap();
+
+    let data = fetch_data(&mut ctx, pool).await;
+    let migrated = PoolState::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(data.len(), PoolState::calculate_space());
+    assert_eq!(migrated.version, POOL_STATE_VERSION);
+    assert_eq!(migrated.admin, admin);
+    assert_eq!(migrated.token_decimals, 6);
+    assert_eq!(migrated.total_liquidity, 5_000_000);
+    assert_eq!(migrated.total_fees_collected, 1_234);
+    assert_eq!(migrated.pause_level, PauseLevel::None);
+    assert_eq!(migrated.fee_tiers.len(), 2);
+    assert_eq!(migrated.fee_tiers[1].fee_bps, 20);
+    assert_eq!(migrated.volume.max_daily, 9_000_000);
+    assert_eq!(migrated.volume.current_volume, 40_000);
+    assert_eq!(migrated.circuit_breaker.max_amount, 2_000_000);
+    assert_eq!(migrated.protection.max_price_impact_bps, 500);
+    assert_eq!(migrated.protection.max_slippage_bps, 300);
+    assert_eq!(migrated.trade_settings.early_trade_window_seconds, 600);
+    assert_eq!(migrated.trader_blacklist.len(), 1);
+    assert_eq!(migrated.trader_blacklist[0].trader, BLACKLISTED);
+    assert_eq!(migrated.bump, 254);
+    assert_eq!(migrated.pool_id, [9u8; 32]);
+    assert_eq!(migrated.max_fee_tiers, DEFAULT_MAX_FEE_TIERS);
+    assert_eq!(migrated.timelocks.longest(), TimelockSettings::default().longest());
+    assert_eq!(migrated.protection.large_withdrawal_bps, DEFAULT_LARGE_WITHDRAWAL_BPS);
+}
+
+#[tokio::test]
+async fn migrates_a_v2_pool_preserving_data() {
+    let (mut ctx, pool) = start_with_pool(v2_pool_bytes).await;
+    assert!(PoolState::try_deserialize(&mut fetch_data(&mut ctx, pool).await.as_slice()).is_err());
+
+    migrate(&mut ctx, pool).await.unwrap();
+
+    let data = fetch_data(&mut ctx, pool).await;
+    let migrated = PoolState::try_deserialize(&mut data.as_slice()).unwrap();
+    assert_eq!(data.len(), PoolState::calculate_space());
+    assert_eq!(migrated.version, POOL_STATE_VERSION);
+    assert_eq!(migrated.admin, ctx.payer.pubkey());
+    assert_eq!(migrated.total_liquidity, 5_000_000);
+    assert_eq!(migrated.pending_deposits.len(), 1);
+    assert_eq!(migrated.pending_deposits[0].amount, 250_000);
+    assert_eq!(migrated.insurance_collected, 55);
+    assert_eq!(migrated.pause_level, PauseLevel::TradingOnly);
+    assert_eq!(migrated.event_seq, 17);
+    assert!(migrated.fee_tiers_locked);
+    assert_eq!(migrated.max_fee_tiers, 8);
+    assert_eq!(migrated.default_fee_bps, Some(25));
+    assert_eq!(migrated.volume.current_volume, 40_000);
+    assert_eq!(migrated.protection.min_trade_interval, 5);
+    assert_eq!(migrated.protection.large_withdrawal_bps, 2_500);
+    assert!(migrated.pending_update.is_none());
+    assert_eq!(migrated.timelocks.fee_seconds, 86_400);
+    assert_eq!(migrated.timelocks.emergency_pause_seconds, TimelockSettings::default().emergency_pause_seconds);
+    assert_eq!(migrated.trader_blacklist[0].trader, BLACKLISTED);
+    assert_eq!(migrated.trader_blacklist[0].expires_at, 0);
+    assert_eq!(migrated.pool_id, [9u8; 32]);
+}
+
+#[tokio::test]
+async fn migration_is_idempotent() {
+    let (mut ctx, pool) = start_with_pool(baseline_pool_bytes).await;
+
+    migrate(&mut ctx, pool).await.unwrap();
+    let first = fetch_data(&mut ctx, pool).await;
+
+    migrate(&mut ctx, pool).await.unwrap();
+    let second = fetch_data(&mut ctx, pool).await;
+
+    assert_eq!(second, first);
+}
+
+#[tokio::test]
+async fn refuses_to_downgrade_newer_layouts() {
+    let (mut ctx, pool) = start_with_pool(|admin| versioned_pool_bytes(admin, POOL_STATE_VERSION + 1)).await;
+
+    assert!(migrate(&mut ctx, pool).await.is_err());
+
+    let data = fetch_data(&mut ctx, pool).await;
+    assert_eq!(data[8], POOL_STATE_VERSION + 1);
+}
+
+#[tokio::test]
+async fn rejects_a_pool_the_signer_does_not_administer() {
+    let (mut ctx, pool) = start_with_pool(|_| baseline_pool_bytes(Pubkey::new_unique())).await;
+
+    assert!(migrate(&mut ctx, pool).await.is_err());
+}
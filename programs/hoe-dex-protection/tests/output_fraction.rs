@@ -0,0 +1,59 @@
+//! Drain guard tests.
+//!
+//! `amount_out` is capped at `max_output_fraction_bps` of reserves,
+//! independently of slippage.
+
+use hoe_dex_protection::{ErrorCode, ParameterUpdate, PoolState, ProtectionSettings, ProtectionSettingsUpdate};
+
+fn pool(max_output_fraction_bps: u64) -> PoolState {
+    PoolState {
+        total_liquidity: 1_000_000,
+        max_output_fraction_bps,
+        // Valid, so protection updates fail only on the field under test
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn near_drain_trade_is_rejected() {
+    let err = pool(2_000).check_output_fraction(900_000).unwrap_err();
+
+    assert_eq!(err, ErrorCode::OutputExceedsReserveFraction.into());
+}
+
+#[test]
+fn modest_trade_passes() {
+    assert!(pool(2_000).check_output_fraction(50_000).is_ok());
+    assert!(pool(2_000).check_output_fraction(200_000).is_ok());
+}
+
+#[test]
+fn full_fraction_disables_the_guard() {
+    assert!(pool(10_000).check_output_fraction(999_999).is_ok());
+    assert!(pool(0).check_output_fraction(999_999).is_ok());
+    assert_eq!(pool(0).output_fraction_limit_bps(), 10_000);
+}
+
+#[test]
+fn raising_the_fraction_is_not_a_tightening() {
+    let pool = pool(2_000);
+    let current = pool.current_protection_settings();
+    assert_eq!(current.max_output_fraction_bps, 2_000);
+
+    let lower = ProtectionSettingsUpdate { max_output_fraction_bps: 1_000, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&lower).is_ok());
+
+    let higher = ProtectionSettingsUpdate { max_output_fraction_bps: 10_000, ..current };
+    let err = pool.validate_protection_tightening(&higher).unwrap_err();
+    assert_eq!(err, ErrorCode::Unauthorized.into());
+}
+
+#[test]
+fn timelocked_update_rejects_a_zero_fraction() {
+    let pool = pool(2_000);
+    let settings = ProtectionSettingsUpdate { max_output_fraction_bps: 0, ..pool.current_protection_settings() };
+
+    let err = pool.validate_parameter_update(&ParameterUpdate::Protection(settings)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidMaxOutputFraction.into());
+}
@@ -0,0 +1,26 @@
+//! Trader token account owner enforcement tests.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ErrorCode, PoolState};
+
+fn pool(enforce_output_owner: bool) -> PoolState {
+    PoolState { enforce_output_owner, ..Default::default() }
+}
+
+#[test]
+fn matching_owner_passes_either_way() {
+    let trader = Pubkey::new_unique();
+
+    assert!(pool(true).check_token_account_owner(&trader, &trader).is_ok());
+    assert!(pool(false).check_token_account_owner(&trader, &trader).is_ok());
+}
+
+#[test]
+fn mismatched_owner_fails_only_when_enforced() {
+    let trader = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+
+    let err = pool(true).check_token_account_owner(&owner, &trader).unwrap_err();
+    assert_eq!(err, ErrorCode::OutputAccountOwnerMismatch.into());
+    assert!(pool(false).check_token_account_owner(&owner, &trader).is_ok());
+}
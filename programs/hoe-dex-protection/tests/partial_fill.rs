@@ -0,0 +1,60 @@
+//! Partial fill sizing tests.
+//!
+//! A partial fill takes the smallest headroom left under the daily and hourly
+//! volume caps and the circuit breaker, and scales the minimum output with it.
+
+use hoe_dex_protection::{utils::partial_fill, CircuitBreakerSettings, PoolState, VolumeSettings, VOLUME_BUCKET_SECONDS};
+
+const START: u64 = 1_700_000_000;
+
+fn pool(max_daily: u64, traded: u64, breaker_max: u64, breaker_used: u64) -> PoolState {
+    let mut pool = PoolState {
+        volume: VolumeSettings {
+            max_daily,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: breaker_max,
+            current_amount: breaker_used,
+            cooldown_period: 3600,
+            last_trigger: START,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    pool.update_volume(traded, START).unwrap();
+    pool
+}
+
+#[test]
+fn a_trade_that_fits_is_unchanged() {
+    let pool = pool(100_000, 20_000, 100_000, 20_000);
+
+    assert_eq!(pool.fillable_amount(10_000, START + 60).unwrap(), 10_000);
+    assert_eq!(partial_fill(&pool, 10_000, 9_000, START + 60).unwrap(), (10_000, 9_000));
+}
+
+#[test]
+fn capped_by_the_daily_volume_headroom() {
+    let pool = pool(25_000, 20_000, 100_000, 0);
+
+    assert_eq!(partial_fill(&pool, 10_000, 9_000, START + 60).unwrap(), (5_000, 4_500));
+}
+
+#[test]
+fn capped_by_the_circuit_breaker_headroom() {
+    let pool = pool(100_000, 0, 50_000, 40_000);
+
+    assert_eq!(partial_fill(&pool, 30_000, 0, START + 60).unwrap(), (10_000, 0));
+}
+
+#[test]
+fn no_headroom_leaves_the_trade_to_fail_in_full() {
+    let pool = pool(25_000, 25_000, 100_000, 0);
+
+    assert_eq!(pool.fillable_amount(10_000, START + 60).unwrap(), 0);
+    assert_eq!(partial_fill(&pool, 10_000, 9_000, START + 60).unwrap(), (10_000, 9_000));
+}
@@ -0,0 +1,37 @@
+//! Per-operation pause tests.
+//!
+//! The pause level and the `paused_ops` bits combine, so these check which
+//! operations `is_op_paused` reports for each mix.
+
+use hoe_dex_protection::{
+    PauseLevel, PoolState, PAUSE_OP_ADD_LIQUIDITY, PAUSE_OP_ALL, PAUSE_OP_REMOVE_LIQUIDITY, PAUSE_OP_TRADE,
+    PAUSE_OP_WITHDRAW,
+};
+
+fn pool(pause_level: PauseLevel, paused_ops: u8) -> PoolState {
+    PoolState { pause_level, paused_ops, ..Default::default() }
+}
+
+#[test]
+fn trade_bit_leaves_other_operations_running() {
+    let pool = pool(PauseLevel::None, PAUSE_OP_TRADE);
+
+    assert!(pool.is_op_paused(PAUSE_OP_TRADE));
+    assert!(!pool.is_op_paused(PAUSE_OP_WITHDRAW));
+    assert!(!pool.is_op_paused(PAUSE_OP_ADD_LIQUIDITY));
+    assert!(!pool.is_op_paused(PAUSE_OP_REMOVE_LIQUIDITY));
+}
+
+#[test]
+fn trading_pause_sets_the_trade_bit() {
+    let pool = pool(PauseLevel::TradingOnly, PAUSE_OP_WITHDRAW);
+
+    assert_eq!(pool.effective_paused_ops(), PAUSE_OP_TRADE | PAUSE_OP_WITHDRAW);
+}
+
+#[test]
+fn full_pause_sets_every_bit() {
+    let pool = pool(PauseLevel::Full, 0);
+
+    assert_eq!(pool.effective_paused_ops(), PAUSE_OP_ALL);
+}
@@ -0,0 +1,166 @@
+//! Emergency actions and parameter updates keep separate pending slots.
+//!
+//! Both timelocks are at least an hour, so these tests write a pool whose
+//! schedules have already matured and apply each action against the built
+//! program (`anchor build` first, so `target/deploy` exists).
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, InstructionData, ToAccountMetas};
+use hoe_dex_protection::{
+    EmergencyActionKind, ParameterUpdate, PendingEmergency, PendingUpdate, PoolState,
+    TradeSettingsUpdate,
+};
+use solana_program_test::{BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signer,
+    transaction::Transaction,
+};
+
+const TRADE_UPDATE: TradeSettingsUpdate = TradeSettingsUpdate {
+    early_trade_fee_bps: 0,
+    early_trade_window_seconds: 0,
+    max_trade_size_bps: 500,
+    min_trade_size: 1,
+    cooldown_seconds: 0,
+};
+
+fn program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: hoe_dex_protection::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+async fn start_with_pool(emergency: EmergencyActionKind) -> (ProgramTestContext, Pubkey) {
+    let mut program_test = ProgramTest::new("hoe_dex_protection", hoe_dex_protection::ID, None);
+    program_test.prefer_bpf(true);
+    let mut ctx = program_test.start_with_context().await;
+
+    let admin = ctx.payer.pubkey();
+    let state = PoolState {
+        admin,
+        emergency_admin: admin,
+        // Both schedules matured long ago
+        pending_update: Some(PendingUpdate {
+            scheduled_time: 1,
+            updates: vec![ParameterUpdate::Trade(TRADE_UPDATE)],
+        }),
+        pending_emergency: Some(PendingEmergency { action: emergency, scheduled_time: 1 }),
+        is_emergency_paused: emergency == EmergencyActionKind::Resume,
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    let pool = Pubkey::new_unique();
+    ctx.set_account(&pool, &program_account(data).into());
+
+    let (guard, _) = Pubkey::find_program_address(
+        &[b"reentrancy_guard", pool.as_ref()],
+        &hoe_dex_protection::ID,
+    );
+    ctx.set_account(&guard, &program_account(vec![0; 8]).into());
+
+    (ctx, pool)
+}
+
+async fn send(ctx: &mut ProgramTestContext, ix: Instruction) -> Result<(), BanksClientError> {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&ctx.payer.pubkey()), &[&ctx.payer], blockhash);
+    ctx.banks_client.process_transaction(tx).await
+}
+
+fn emergency_ix(ctx: &ProgramTestContext, pool: Pubkey, data: Vec<u8>) -> Instruction {
+    Instruction {
+        program_id: hoe_dex_protection::ID,
+        accounts: hoe_dex_protection::accounts::EmergencyAction {
+            pool_state: pool,
+            emergency_admin: ctx.payer.pubkey(),
+        }
+        .to_account_metas(None),
+        data,
+    }
+}
+
+fn apply_parameter_update_ix(ctx: &ProgramTestContext, pool: Pubkey) -> Instruction {
+    let (reentrancy_guard, _) = Pubkey::find_program_address(
+        &[b"reentrancy_guard", pool.as_ref()],
+        &hoe_dex_protection::ID,
+    );
+    Instruction {
+        program_id: hoe_dex_protection::ID,
+        accounts: hoe_dex_protection::accounts::AdminAction {
+            pool_state: pool,
+            admin: ctx.payer.pubkey(),
+            reentrancy_guard,
+        }
+        .to_account_metas(None),
+        data: hoe_dex_protection::instruction::ApplyParameterUpdate {}.data(),
+    }
+}
+
+async fn fetch_pool(ctx: &mut ProgramTestContext, pool: Pubkey) -> PoolState {
+    let account = ctx.banks_client.get_account(pool).await.unwrap().unwrap();
+    PoolState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn emergency_pause_leaves_pending_update_intact() {
+    let (mut ctx, pool) = start_with_pool(EmergencyActionKind::Pause).await;
+
+    let ix = emergency_ix(&ctx, pool, hoe_dex_protection::instruction::ApplyEmergencyPause {}.data());
+    send(&mut ctx, ix).await.unwrap();
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert!(state.is_emergency_paused);
+    assert!(state.pending_emergency.is_none());
+    assert_eq!(state.pending_update.as_ref().unwrap().updates.len(), 1);
+
+    let ix = apply_parameter_update_ix(&ctx, pool);
+    send(&mut ctx, ix).await.unwrap();
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert!(state.pending_update.is_none());
+    assert_eq!(state.trade_settings.max_size_bps, TRADE_UPDATE.max_trade_size_bps);
+}
+
+#[tokio::test]
+async fn parameter_update_leaves_pending_emergency_intact() {
+    let (mut ctx, pool) = start_with_pool(EmergencyActionKind::Pause).await;
+
+    let ix = apply_parameter_update_ix(&ctx, pool);
+    send(&mut ctx, ix).await.unwrap();
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert!(state.pending_update.is_none());
+    assert_eq!(
+        state.pending_emergency.as_ref().map(|pending| pending.action),
+        Some(EmergencyActionKind::Pause)
+    );
+
+    let ix = emergency_ix(&ctx, pool, hoe_dex_protection::instruction::ApplyEmergencyPause {}.data());
+    send(&mut ctx, ix).await.unwrap();
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert!(state.is_emergency_paused);
+    assert!(state.pending_emergency.is_none());
+}
+
+#[tokio::test]
+async fn rejects_applying_a_different_emergency_action() {
+    let (mut ctx, pool) = start_with_pool(EmergencyActionKind::Resume).await;
+
+    let ix = emergency_ix(&ctx, pool, hoe_dex_protection::instruction::ApplyEmergencyPause {}.data());
+    assert!(send(&mut ctx, ix).await.is_err());
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert_eq!(
+        state.pending_emergency.as_ref().map(|pending| pending.action),
+        Some(EmergencyActionKind::Resume)
+    );
+}
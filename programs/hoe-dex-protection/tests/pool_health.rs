@@ -0,0 +1,70 @@
+//! Pool health summary tests.
+//!
+//! `health` only reads state, so these set up breaker and volume counters
+//! directly and check the flags and headroom it derives from them.
+
+use hoe_dex_protection::{CircuitBreakerSettings, PoolState, VolumeSettings, VOLUME_BUCKET_SECONDS};
+
+const START: u64 = 1_700_000_000;
+
+fn pool() -> PoolState {
+    PoolState {
+        total_liquidity: 10_000_000,
+        volume: VolumeSettings {
+            max_daily: 1_000_000,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: 500_000,
+            cooldown_period: 3600,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn untouched_pool_is_healthy() {
+    let health = pool().health(START).unwrap();
+
+    assert!(health.healthy);
+    assert!(!health.paused);
+    assert!(!health.circuit_breaker_tripped);
+    assert!(!health.volume_near_cap);
+    assert_eq!(health.circuit_breaker_headroom_bps, 10_000);
+    assert_eq!(health.volume_headroom_bps, 10_000);
+    assert_eq!(health.hourly_trades_headroom_bps, 10_000);
+}
+
+#[test]
+fn tripped_breaker_and_volume_near_cap_are_reported() {
+    let mut pool = pool();
+    pool.circuit_breaker.current_amount = 500_000;
+    pool.circuit_breaker.last_trigger = START;
+    pool.update_volume(900_000, START).unwrap();
+
+    let health = pool.health(START).unwrap();
+
+    assert!(!health.healthy);
+    assert!(health.circuit_breaker_tripped);
+    assert_eq!(health.circuit_breaker_headroom_bps, 0);
+    assert!(health.volume_near_cap);
+    assert_eq!(health.volume_headroom_bps, 1_000);
+}
+
+#[test]
+fn lapsed_breaker_cooldown_reads_as_full_headroom() {
+    let mut pool = pool();
+    pool.circuit_breaker.current_amount = 500_000;
+    pool.circuit_breaker.last_trigger = START;
+
+    let before = pool.clone();
+    let health = pool.health(START + 3600).unwrap();
+
+    assert!(!health.circuit_breaker_tripped);
+    assert_eq!(health.circuit_breaker_headroom_bps, 10_000);
+    assert_eq!(pool.circuit_breaker.current_amount, before.circuit_breaker.current_amount);
+}
@@ -0,0 +1,61 @@
+//! Pool migration config copy tests.
+//!
+//! `migrate_to_pool` moves tokens through CPI, so these check the state half of
+//! it: which fields `copy_config_from` carries to the successor.
+
+use hoe_dex_protection::{
+    CircuitBreakerSettings, FeeTier, PoolState, ProtectionSettings, TradeSettings, VolumeSettings,
+};
+use solana_sdk::pubkey::Pubkey;
+
+fn source() -> PoolState {
+    PoolState {
+        admin: Pubkey::new_unique(),
+        emergency_admin: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        total_liquidity: 5_000_000,
+        total_fees_collected: 1_000,
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None }],
+        protocol_fee_bps: 10,
+        protection: ProtectionSettings { max_slippage_bps: 300, ..Default::default() },
+        trade_settings: TradeSettings { max_size_bps: 800, last_trade_time: 1_700_000_000, ..Default::default() },
+        volume: VolumeSettings { max_daily: 9_000_000, current_volume: 400_000, ..Default::default() },
+        circuit_breaker: CircuitBreakerSettings { max_amount: 2_000_000, current_amount: 700_000, ..Default::default() },
+        allowed_callers: vec![Pubkey::new_unique()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn copies_configuration() {
+    let source = source();
+    let mut successor = PoolState::default();
+
+    successor.copy_config_from(&source);
+
+    assert_eq!(successor.emergency_admin, source.emergency_admin);
+    assert_eq!(successor.fee_tiers.len(), 1);
+    assert_eq!(successor.protocol_fee_bps, 10);
+    assert_eq!(successor.protection.max_slippage_bps, 300);
+    assert_eq!(successor.trade_settings.max_size_bps, 800);
+    assert_eq!(successor.volume.max_daily, 9_000_000);
+    assert_eq!(successor.circuit_breaker.max_amount, 2_000_000);
+    assert_eq!(successor.allowed_callers, source.allowed_callers);
+}
+
+#[test]
+fn leaves_identity_balances_and_runtime_state() {
+    let source = source();
+    let admin = Pubkey::new_unique();
+    let mut successor = PoolState { admin, ..Default::default() };
+
+    successor.copy_config_from(&source);
+
+    assert_eq!(successor.admin, admin);
+    assert_eq!(successor.token_mint, Pubkey::default());
+    assert_eq!(successor.total_liquidity, 0);
+    assert_eq!(successor.total_fees_collected, 0);
+    assert_eq!(successor.trade_settings.last_trade_time, 0);
+    assert_eq!(successor.volume.current_volume, 0);
+    assert_eq!(successor.circuit_breaker.current_amount, 0);
+}
@@ -0,0 +1,57 @@
+//! Pool staleness tests.
+//!
+//! Staleness is checked with the rest of the trade gates, so these run
+//! `validate_trade_parameters` against a pool last updated at a fixed time.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{validation, ErrorCode, PoolState};
+
+const LAST_UPDATE: u64 = 1_700_000_000;
+const MAX_STALENESS: i64 = 3600;
+
+fn pool(max_staleness: i64) -> PoolState {
+    PoolState {
+        total_liquidity: 1_000_000,
+        last_update: LAST_UPDATE,
+        max_staleness,
+        ..Default::default()
+    }
+}
+
+fn validate_trade_at(pool: &mut PoolState, current_time: u64) -> anchor_lang::Result<()> {
+    validation::validate_trade_parameters(pool, &Pubkey::new_unique(), 1, current_time)
+}
+
+#[test]
+fn stale_pool_is_flagged() {
+    let mut pool = pool(MAX_STALENESS);
+
+    assert!(pool.is_stale(LAST_UPDATE + 3601));
+    let err = validate_trade_at(&mut pool, LAST_UPDATE + 3601).unwrap_err();
+    assert_eq!(err, ErrorCode::PoolStale.into());
+}
+
+#[test]
+fn fresh_pool_passes() {
+    let mut pool = pool(MAX_STALENESS);
+
+    assert!(validate_trade_at(&mut pool, LAST_UPDATE + 3600).is_ok());
+}
+
+#[test]
+fn zero_disables_the_check() {
+    let mut pool = pool(0);
+
+    assert!(!pool.is_stale(LAST_UPDATE + 365 * 24 * 3600));
+    assert!(validate_trade_at(&mut pool, LAST_UPDATE + 365 * 24 * 3600).is_ok());
+}
+
+#[test]
+fn refreshed_pool_trades_again() {
+    let mut pool = pool(MAX_STALENESS);
+    assert!(validate_trade_at(&mut pool, LAST_UPDATE + 7200).is_err());
+
+    pool.last_update = LAST_UPDATE + 7200;
+
+    assert!(validate_trade_at(&mut pool, LAST_UPDATE + 7200).is_ok());
+}
@@ -0,0 +1,92 @@
+//! Post-resume cooldown tests.
+//!
+//! The cooldown is checked with the rest of the trade gates, so these run
+//! `validate_trade_parameters` against a pool that was just resumed. Only a
+//! longer cooldown applies at once.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    validation, ErrorCode, ParameterUpdate, PoolState, ProtectionSettings, ProtectionSettingsUpdate,
+};
+
+const RESUMED_AT: i64 = 1_700_000_000;
+const COOLDOWN: i64 = 600;
+
+fn resumed_pool(post_resume_cooldown: i64) -> PoolState {
+    PoolState {
+        total_liquidity: 1_000_000,
+        post_resume_cooldown,
+        resumed_at: RESUMED_AT,
+        ..Default::default()
+    }
+}
+
+fn validate_trade_at(pool: &mut PoolState, current_time: i64) -> anchor_lang::Result<()> {
+    validation::validate_trade_parameters(pool, &Pubkey::new_unique(), 1, current_time as u64)
+}
+
+#[test]
+fn trade_right_after_resume_is_rejected() {
+    let mut pool = resumed_pool(COOLDOWN);
+
+    let err = validate_trade_at(&mut pool, RESUMED_AT).unwrap_err();
+    assert_eq!(err, ErrorCode::PostResumeCooldown.into());
+
+    let err = validate_trade_at(&mut pool, RESUMED_AT + COOLDOWN - 1).unwrap_err();
+    assert_eq!(err, ErrorCode::PostResumeCooldown.into());
+}
+
+#[test]
+fn trade_after_the_cooldown_succeeds() {
+    let mut pool = resumed_pool(COOLDOWN);
+
+    assert_eq!(pool.post_resume_cooldown_ends_at(), RESUMED_AT + COOLDOWN);
+    validate_trade_at(&mut pool, RESUMED_AT + COOLDOWN).unwrap();
+}
+
+#[test]
+fn zero_cooldown_trades_immediately() {
+    let mut pool = resumed_pool(0);
+
+    validate_trade_at(&mut pool, RESUMED_AT).unwrap();
+}
+
+#[test]
+fn never_resumed_pool_is_unaffected() {
+    let mut pool = PoolState { resumed_at: 0, ..resumed_pool(COOLDOWN) };
+
+    validate_trade_at(&mut pool, RESUMED_AT).unwrap();
+}
+
+/// A resumed pool whose other protection settings pass validation
+fn configured_pool() -> PoolState {
+    PoolState {
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..resumed_pool(COOLDOWN)
+    }
+}
+
+#[test]
+fn shortening_the_cooldown_is_not_a_tightening() {
+    let pool = configured_pool();
+    let current = pool.current_protection_settings();
+    assert_eq!(current.post_resume_cooldown, COOLDOWN);
+
+    let longer = ProtectionSettingsUpdate { post_resume_cooldown: COOLDOWN + 1, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&longer).is_ok());
+
+    for post_resume_cooldown in [COOLDOWN - 1, 0] {
+        let shorter = ProtectionSettingsUpdate { post_resume_cooldown, ..current.clone() };
+        let err = pool.validate_protection_tightening(&shorter).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+}
+
+#[test]
+fn timelocked_update_bounds_the_cooldown() {
+    let pool = configured_pool();
+    let settings = ProtectionSettingsUpdate { post_resume_cooldown: -1, ..pool.current_protection_settings() };
+
+    let err = pool.validate_parameter_update(&ParameterUpdate::Protection(settings)).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidPostResumeCooldown.into());
+}
@@ -0,0 +1,89 @@
+//! Size-tiered price impact cap tests.
+//!
+//! A trade takes the cap of the first tier its size fits under, so the same
+//! impact can pass for a small trade and fail for a large one. Trades past
+//! every tier keep the flat cap.
+
+use hoe_dex_protection::{ErrorCode, PoolState, PriceImpactTier, ProtectionSettings, MAX_PRICE_IMPACT_TIERS};
+
+fn pool(tiers: Vec<PriceImpactTier>) -> PoolState {
+    PoolState {
+        protection: ProtectionSettings { max_price_impact_bps: 1000, price_impact_tiers: tiers, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+fn tiers() -> Vec<PriceImpactTier> {
+    vec![
+        PriceImpactTier { size_threshold: 5_000, max_impact_bps: 500 },
+        PriceImpactTier { size_threshold: 50_000, max_impact_bps: 200 },
+    ]
+}
+
+#[test]
+fn same_impact_passes_small_and_fails_large() {
+    let pool = pool(tiers());
+    let impact = 300;
+
+    assert!(impact <= pool.effective_max_price_impact_bps_for_size(3_000));
+    assert!(impact > pool.effective_max_price_impact_bps_for_size(30_000));
+}
+
+#[test]
+fn tier_boundaries_are_inclusive() {
+    let pool = pool(tiers());
+
+    assert_eq!(pool.effective_max_price_impact_bps_for_size(5_000), 500);
+    assert_eq!(pool.effective_max_price_impact_bps_for_size(5_001), 200);
+    assert_eq!(pool.effective_max_price_impact_bps_for_size(50_000), 200);
+}
+
+#[test]
+fn past_every_tier_uses_the_flat_cap() {
+    assert_eq!(pool(tiers()).effective_max_price_impact_bps_for_size(50_001), 1000);
+    assert_eq!(pool(Vec::new()).effective_max_price_impact_bps_for_size(1), 1000);
+}
+
+#[test]
+fn tier_caps_never_exceed_the_flat_cap() {
+    let mut pool = pool(vec![PriceImpactTier { size_threshold: 5_000, max_impact_bps: 2_000 }]);
+    assert_eq!(pool.effective_max_price_impact_bps_for_size(1_000), 1000);
+
+    pool.protection.max_price_impact_bps = 300;
+    assert_eq!(pool.effective_max_price_impact_bps_for_size(1_000), 300);
+    assert_eq!(pool.effective_max_price_impact_bps_for_size(5_001), 300);
+}
+
+#[test]
+fn valid_tiers_pass() {
+    assert!(PoolState::validate_price_impact_tiers(&tiers()).is_ok());
+    assert!(PoolState::validate_price_impact_tiers(&[]).is_ok());
+}
+
+#[test]
+fn thresholds_must_increase() {
+    let mut tiers = tiers();
+    tiers[1].size_threshold = 5_000;
+
+    let err = PoolState::validate_price_impact_tiers(&tiers).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidPriceImpactTiers.into());
+}
+
+#[test]
+fn caps_must_be_in_range() {
+    for max_impact_bps in [0, 10001] {
+        let tiers = [PriceImpactTier { size_threshold: 1, max_impact_bps }];
+        let err = PoolState::validate_price_impact_tiers(&tiers).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidPriceImpactTiers.into());
+    }
+}
+
+#[test]
+fn tier_count_is_bounded() {
+    let tiers: Vec<_> = (1..=MAX_PRICE_IMPACT_TIERS as u64 + 1)
+        .map(|size_threshold| PriceImpactTier { size_threshold, max_impact_bps: 100 })
+        .collect();
+
+    let err = PoolState::validate_price_impact_tiers(&tiers).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidPriceImpactTiers.into());
+}
@@ -0,0 +1,103 @@
+//! Protection preset tests.
+//!
+//! Each named preset resolves to its documented settings, which must pass the
+//! same validation as custom settings; `Custom` leaves the config alone.
+
+use hoe_dex_protection::{
+    InitialConfig, ParameterUpdate, PoolState, ProtectionPreset, ProtectionSettingsUpdate, CIRCUIT_BREAKER_DECAY,
+    DEFAULT_LARGE_WITHDRAWAL_BPS, MAX_HOURLY_VOLUME, MAX_OUTPUT_FRACTION_BPS,
+};
+
+fn settings(preset: ProtectionPreset) -> ProtectionSettingsUpdate {
+    preset.protection_settings().expect("named preset")
+}
+
+#[test]
+fn custom_has_no_settings() {
+    assert!(ProtectionPreset::Custom.protection_settings().is_none());
+    assert_eq!(InitialConfig::default().preset, ProtectionPreset::Custom);
+}
+
+#[test]
+fn conservative_values() {
+    let s = settings(ProtectionPreset::Conservative);
+
+    assert_eq!(s.max_daily_volume, 10_000_000);
+    assert_eq!(s.max_price_impact_bps, 300);
+    assert_eq!(s.max_slippage_bps, 50);
+    assert!(s.blacklist_enabled);
+    assert_eq!(s.min_trade_interval, 30);
+    assert_eq!(s.large_withdrawal_bps, 1000);
+    assert_eq!(s.circuit_breaker_threshold, 250_000);
+    assert_eq!(s.circuit_breaker_cooldown, 7200);
+    assert_eq!(s.circuit_breaker_decay_bps, 50);
+    assert_eq!(s.rate_limit_max, 30);
+    assert_eq!(s.max_hourly_volume, 1_000_000);
+    assert_eq!(s.max_output_fraction_bps, 1000);
+    assert_eq!(s.max_daily_volume_per_trader, 1_000_000);
+    assert_eq!(s.fee_withdraw_cooldown, 86_400);
+    assert!(s.sandwich.enabled);
+    assert_eq!(s.max_hourly_trades, 30);
+    assert_eq!(s.max_trades_per_tx, 1);
+    assert!(s.volatility.enabled);
+    assert_eq!(s.post_resume_cooldown, 3600);
+    assert!(s.allowed_callers.is_empty());
+}
+
+#[test]
+fn standard_matches_the_defaults() {
+    let s = settings(ProtectionPreset::Standard);
+
+    assert_eq!(s.max_daily_volume, 100_000_000);
+    assert_eq!(s.max_price_impact_bps, 1000);
+    assert_eq!(s.max_slippage_bps, 100);
+    assert!(!s.blacklist_enabled);
+    assert_eq!(s.large_withdrawal_bps, DEFAULT_LARGE_WITHDRAWAL_BPS);
+    assert_eq!(s.circuit_breaker_threshold, 1_000_000);
+    assert_eq!(s.circuit_breaker_decay_bps, CIRCUIT_BREAKER_DECAY);
+    assert_eq!(s.rate_limit_max, 100);
+    assert_eq!(s.max_hourly_volume, MAX_HOURLY_VOLUME);
+    assert_eq!(s.max_output_fraction_bps, MAX_OUTPUT_FRACTION_BPS);
+    assert_eq!(s.max_daily_volume_per_trader, 0);
+
+    let default = InitialConfig::default().protection;
+    assert_eq!(default.max_daily_volume, s.max_daily_volume);
+    assert_eq!(default.circuit_breaker_threshold, s.circuit_breaker_threshold);
+}
+
+#[test]
+fn aggressive_values() {
+    let s = settings(ProtectionPreset::Aggressive);
+
+    assert_eq!(s.max_daily_volume, 1_000_000_000);
+    assert_eq!(s.max_price_impact_bps, 2500);
+    assert_eq!(s.max_slippage_bps, 300);
+    assert!(!s.blacklist_enabled);
+    assert_eq!(s.large_withdrawal_bps, 5000);
+    assert_eq!(s.circuit_breaker_threshold, 10_000_000);
+    assert_eq!(s.circuit_breaker_cooldown, 1800);
+    assert_eq!(s.circuit_breaker_decay_bps, 500);
+    assert_eq!(s.rate_limit_max, 1000);
+    assert_eq!(s.max_hourly_volume, 100_000_000);
+    assert_eq!(s.max_output_fraction_bps, MAX_OUTPUT_FRACTION_BPS);
+}
+
+#[test]
+fn presets_are_ordered_by_strictness() {
+    let conservative = settings(ProtectionPreset::Conservative);
+    let standard = settings(ProtectionPreset::Standard);
+    let aggressive = settings(ProtectionPreset::Aggressive);
+
+    assert!(standard.max_daily_volume <= aggressive.max_daily_volume);
+    assert!(conservative.max_daily_volume <= standard.max_daily_volume);
+    assert!(conservative.circuit_breaker_threshold <= standard.circuit_breaker_threshold);
+    assert!(standard.is_no_looser_than(&aggressive));
+}
+
+#[test]
+fn presets_pass_validation() {
+    let pool = PoolState::default();
+    for preset in [ProtectionPreset::Conservative, ProtectionPreset::Standard, ProtectionPreset::Aggressive] {
+        assert!(pool.validate_parameter_update(&ParameterUpdate::Protection(settings(preset))).is_ok());
+    }
+}
@@ -0,0 +1,60 @@
+//! Immediate protection tightening tests.
+//!
+//! Tightening skips the timelock; anything looser must be rejected.
+
+use hoe_dex_protection::{
+    ErrorCode, PoolState, ProtectionSettings, ProtectionSettingsUpdate, RateLimitSettings, VolumeSettings,
+};
+
+fn pool() -> PoolState {
+    PoolState {
+        volume: VolumeSettings { max_daily: 100_000_000, ..Default::default() },
+        protection: ProtectionSettings {
+            max_price_impact_bps: 1000,
+            max_slippage_bps: 100,
+            large_withdrawal_bps: 2000,
+            ..Default::default()
+        },
+        rate_limit: RateLimitSettings { window_seconds: 3600, max_calls: 100, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn current_settings_round_trip() {
+    let pool = pool();
+    let current = pool.current_protection_settings();
+
+    assert_eq!(current.max_daily_volume, 100_000_000);
+    assert_eq!(current.rate_limit_max, 100);
+    assert!(pool.validate_protection_tightening(&current).is_ok());
+}
+
+#[test]
+fn tighter_settings_pass() {
+    let pool = pool();
+    let settings = ProtectionSettingsUpdate {
+        max_daily_volume: 50_000_000,
+        max_price_impact_bps: 500,
+        rate_limit_max: 10,
+        ..pool.current_protection_settings()
+    };
+
+    assert!(pool.validate_protection_tightening(&settings).is_ok());
+}
+
+#[test]
+fn any_looser_field_is_unauthorized() {
+    let pool = pool();
+    let current = pool.current_protection_settings();
+    let looser = [
+        ProtectionSettingsUpdate { max_daily_volume: 100_000_001, ..current.clone() },
+        ProtectionSettingsUpdate { max_daily_volume: 50_000_000, max_slippage_bps: 200, ..current.clone() },
+        ProtectionSettingsUpdate { rate_limit_window: 60, ..current.clone() },
+    ];
+
+    for settings in looser {
+        let err = pool.validate_protection_tightening(&settings).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+}
@@ -0,0 +1,67 @@
+//! Protocol fee tests.
+//!
+//! The protocol fee is charged on top of the LP fee and tracked apart from it,
+//! so these check the split `calculate_fee` reports for each portion.
+
+use hoe_dex_protection::{FeeRounding, FeeTier, PoolState, TradeFees, MAXIMUM_FEE_BPS};
+
+const NOW: i64 = 1_700_000_000;
+
+fn pool(tier_fee_bps: u64, protocol_fee_bps: u64) -> PoolState {
+    PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: tier_fee_bps, liquidity_threshold: None }],
+        protocol_fee_bps,
+        ..Default::default()
+    }
+}
+
+fn fees(pool: &PoolState, amount_in: u64) -> TradeFees {
+    pool.calculate_fee(amount_in, NOW).unwrap().0
+}
+
+#[test]
+fn protocol_fee_is_charged_on_top_of_the_lp_fee() {
+    let pool = pool(30, 5);
+
+    let fees = fees(&pool, 100_000);
+
+    assert_eq!(fees, TradeFees { lp_fee: 300, protocol_fee: 50 });
+    assert_eq!(fees.total(), 350);
+}
+
+#[test]
+fn zero_protocol_fee_leaves_the_lp_fee_alone() {
+    let pool = pool(30, 0);
+
+    assert_eq!(fees(&pool, 100_000), TradeFees { lp_fee: 300, protocol_fee: 0 });
+}
+
+#[test]
+fn both_fees_at_zero_charge_only_the_minimum() {
+    let pool = pool(0, 0);
+
+    // The minimum fee belongs to the LP side
+    assert_eq!(fees(&pool, 100_000), TradeFees { lp_fee: 1, protocol_fee: 0 });
+}
+
+#[test]
+fn protocol_fee_follows_the_pool_rounding() {
+    let mut pool = pool(30, 5);
+
+    // 5 bps of 1_999 is 0.9995
+    assert_eq!(fees(&pool, 1_999).protocol_fee, 0);
+
+    pool.fee_rounding = FeeRounding::Up;
+    assert_eq!(fees(&pool, 1_999).protocol_fee, 1);
+}
+
+#[test]
+fn protocol_fee_gives_way_at_the_cap() {
+    let pool = pool(MAXIMUM_FEE_BPS - 100, 500);
+
+    let fees = fees(&pool, 100_000);
+
+    assert_eq!(fees.lp_fee, 100_000 * (MAXIMUM_FEE_BPS - 100) / 10000);
+    assert_eq!(fees.protocol_fee, 1_000);
+    assert_eq!(fees.total(), 100_000 * MAXIMUM_FEE_BPS / 10000);
+}
@@ -0,0 +1,49 @@
+//! Recent trade history tests.
+//!
+//! The ring buffer is plain state, so these push records directly and check
+//! what `to_vec` hands back to `get_recent_trades`.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{RecentTrades, TradeRecord, RECENT_TRADES_CAPACITY};
+
+fn record(amount_in: u64) -> TradeRecord {
+    TradeRecord {
+        trader: Pubkey::new_unique(),
+        amount_in,
+        amount_out: amount_in - 1,
+        fee: 1,
+        ts: amount_in as i64,
+    }
+}
+
+fn amounts(trades: &RecentTrades) -> Vec<u64> {
+    trades.to_vec().iter().map(|record| record.amount_in).collect()
+}
+
+#[test]
+fn empty_history_has_no_trades() {
+    assert!(RecentTrades::default().to_vec().is_empty());
+}
+
+#[test]
+fn partial_history_is_oldest_first() {
+    let mut trades = RecentTrades::default();
+    for amount in 1..=3 {
+        trades.push(record(amount));
+    }
+
+    assert_eq!(amounts(&trades), vec![1, 2, 3]);
+}
+
+#[test]
+fn wraparound_keeps_the_most_recent_trades_in_order() {
+    let mut trades = RecentTrades::default();
+    let total = RECENT_TRADES_CAPACITY as u64 + 3;
+    for amount in 1..=total {
+        trades.push(record(amount));
+    }
+
+    let expected: Vec<u64> = (4..=total).collect();
+    assert_eq!(amounts(&trades), expected);
+    assert_eq!(trades.len as usize, RECENT_TRADES_CAPACITY);
+}
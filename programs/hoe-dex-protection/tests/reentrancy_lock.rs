@@ -0,0 +1,41 @@
+//! Reentrancy lock tests.
+//!
+//! The lock lives in its own PDA so that a call re-entering the program sees
+//! it held; these drive `acquire` and `release` directly.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{ErrorCode, ReentrancyLock};
+
+#[test]
+fn acquiring_a_free_lock_holds_it() {
+    let mut lock = ReentrancyLock::default();
+    let pool = Pubkey::new_unique();
+
+    lock.acquire(pool, 254).unwrap();
+
+    assert!(lock.locked);
+    assert_eq!(lock.pool, pool);
+    assert_eq!(lock.bump, 254);
+}
+
+#[test]
+fn a_held_lock_rejects_reentry() {
+    let mut lock = ReentrancyLock::default();
+    let pool = Pubkey::new_unique();
+    lock.acquire(pool, 254).unwrap();
+
+    let err = lock.acquire(pool, 254).unwrap_err();
+    assert_eq!(err, ErrorCode::ReentrancyLocked.into());
+}
+
+#[test]
+fn a_released_lock_can_be_taken_again() {
+    let mut lock = ReentrancyLock::default();
+    let pool = Pubkey::new_unique();
+    lock.acquire(pool, 254).unwrap();
+
+    lock.release();
+
+    assert!(!lock.locked);
+    assert!(lock.acquire(pool, 254).is_ok());
+}
@@ -0,0 +1,102 @@
+//! Rescheduling tests.
+//!
+//! Whether a replacement keeps the original schedule depends only on the two
+//! batches, so these compare them through `PendingUpdate::keeps_schedule_for`.
+//! Forced re-anchoring is checked through `PendingUpdate::re_anchored_time`.
+
+use hoe_dex_protection::{
+    FeeRounding, FeeSettingsUpdate, FeeTier, ParameterUpdate, PendingUpdate, TradeSettingsUpdate,
+    PARAMETER_UPDATE_TIMELOCK,
+};
+
+fn trade(max_trade_size_bps: u64, cooldown_seconds: u64) -> ParameterUpdate {
+    ParameterUpdate::Trade(TradeSettingsUpdate {
+        early_trade_fee_bps: 0,
+        early_trade_window_seconds: 0,
+        max_trade_size_bps,
+        min_trade_size: 1,
+        cooldown_seconds,
+    })
+}
+
+fn fee(fee_bps: u64, fee_rounding: FeeRounding) -> ParameterUpdate {
+    fee_with_threshold(fee_bps, fee_rounding, 0)
+}
+
+fn fee_with_threshold(fee_bps: u64, fee_rounding: FeeRounding, fee_free_threshold: u64) -> ParameterUpdate {
+    ParameterUpdate::Fee(FeeSettingsUpdate {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps, liquidity_threshold: None }],
+        fee_tiers_locked: false,
+        fee_rounding,
+        protocol_fee_bps: 0,
+        fee_free_threshold,
+    })
+}
+
+fn pending(updates: Vec<ParameterUpdate>) -> PendingUpdate {
+    PendingUpdate { scheduled_time: 1_700_086_400, updates }
+}
+
+#[test]
+fn tighter_trade_limits_keep_the_schedule() {
+    let pending = pending(vec![trade(800, 0)]);
+
+    assert!(pending.keeps_schedule_for(&[trade(500, 60)]));
+    assert!(pending.keeps_schedule_for(&[trade(800, 0)]));
+}
+
+#[test]
+fn any_looser_field_restarts_the_clock() {
+    let pending = pending(vec![trade(800, 60)]);
+
+    assert!(!pending.keeps_schedule_for(&[trade(900, 60)]));
+    assert!(!pending.keeps_schedule_for(&[trade(500, 30)]));
+}
+
+#[test]
+fn lower_fees_keep_the_schedule() {
+    let pending = pending(vec![fee(30, FeeRounding::Up)]);
+
+    assert!(pending.keeps_schedule_for(&[fee(25, FeeRounding::Down)]));
+    assert!(!pending.keeps_schedule_for(&[fee(35, FeeRounding::Up)]));
+}
+
+#[test]
+fn waiving_fees_on_fewer_trades_is_looser() {
+    let pending = pending(vec![fee_with_threshold(30, FeeRounding::Down, 1_000)]);
+
+    assert!(pending.keeps_schedule_for(&[fee_with_threshold(30, FeeRounding::Down, 2_000)]));
+    assert!(!pending.keeps_schedule_for(&[fee_with_threshold(30, FeeRounding::Down, 999)]));
+    assert!(!pending.keeps_schedule_for(&[fee(30, FeeRounding::Down)]));
+}
+
+#[test]
+fn rounding_up_is_looser_than_rounding_down() {
+    let pending = pending(vec![fee(30, FeeRounding::Down)]);
+
+    assert!(!pending.keeps_schedule_for(&[fee(30, FeeRounding::Up)]));
+}
+
+#[test]
+fn categories_may_be_dropped_but_not_added() {
+    let pending = pending(vec![trade(800, 0), fee(30, FeeRounding::Down)]);
+
+    assert!(pending.keeps_schedule_for(&[trade(800, 0)]));
+    assert!(!pending(vec![trade(800, 0)]).keeps_schedule_for(&[trade(800, 0), fee(30, FeeRounding::Down)]));
+}
+
+#[test]
+fn far_future_schedule_is_re_anchored_to_now() {
+    let now = 1_700_000_000;
+    let pending = PendingUpdate { scheduled_time: u64::MAX, updates: vec![trade(500, 0)] };
+
+    assert_eq!(pending.re_anchored_time(now, PARAMETER_UPDATE_TIMELOCK), now + PARAMETER_UPDATE_TIMELOCK);
+}
+
+#[test]
+fn re_anchoring_never_delays_a_schedule() {
+    let now = 1_700_000_000;
+    let pending = PendingUpdate { scheduled_time: now + 60, updates: vec![trade(500, 0)] };
+
+    assert_eq!(pending.re_anchored_time(now, PARAMETER_UPDATE_TIMELOCK), now + 60);
+}
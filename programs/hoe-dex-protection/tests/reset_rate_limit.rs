@@ -0,0 +1,148 @@
+//! Admin rate limit reset tests.
+//!
+//! Trades do not drive the call counter through a public path, so these tests
+//! write a pool with a partly used window and reset it against the built
+//! program (`anchor build` first, so `target/deploy` exists).
+
+use anchor_lang::{
+    AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator, InstructionData,
+    ToAccountMetas,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hoe_dex_protection::{PoolState, RateLimitReset, RateLimitSettings};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+
+fn program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: hoe_dex_protection::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+async fn start_with_pool(count: u32) -> (ProgramTestContext, Pubkey, Pubkey) {
+    let mut program_test = ProgramTest::new("hoe_dex_protection", hoe_dex_protection::ID, None);
+    program_test.prefer_bpf(true);
+    let mut ctx = program_test.start_with_context().await;
+
+    let admin = ctx.payer.pubkey();
+    let state = PoolState {
+        admin,
+        emergency_admin: admin,
+        rate_limit: RateLimitSettings {
+            window_seconds: 3600,
+            count,
+            max_calls: 10,
+            last_reset: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    let pool = Pubkey::new_unique();
+    ctx.set_account(&pool, &program_account(data).into());
+
+    let (guard, _) = Pubkey::find_program_address(
+        &[b"reentrancy_guard", pool.as_ref()],
+        &hoe_dex_protection::ID,
+    );
+    ctx.set_account(&guard, &program_account(vec![0; 8]).into());
+
+    (ctx, pool, guard)
+}
+
+fn reset_ix(pool: Pubkey, admin: Pubkey, reentrancy_guard: Pubkey) -> Instruction {
+    Instruction {
+        program_id: hoe_dex_protection::ID,
+        accounts: hoe_dex_protection::accounts::AdminAction {
+            pool_state: pool,
+            admin,
+            reentrancy_guard,
+        }
+        .to_account_metas(None),
+        data: hoe_dex_protection::instruction::ResetRateLimit {}.data(),
+    }
+}
+
+/// Sends the transaction and returns the `RateLimitReset` events it logged
+async fn send(ctx: &mut ProgramTestContext, ix: Instruction, signer: &Keypair) -> Vec<RateLimitReset> {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&ctx.payer.pubkey()), &[&ctx.payer, signer], blockhash);
+    let outcome = ctx.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+    outcome.result.unwrap();
+
+    outcome
+        .metadata
+        .unwrap()
+        .log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .filter(|data| data.starts_with(RateLimitReset::DISCRIMINATOR))
+        .map(|data| RateLimitReset::deserialize(&mut &data[RateLimitReset::DISCRIMINATOR.len()..]).unwrap())
+        .collect()
+}
+
+async fn fetch_pool(ctx: &mut ProgramTestContext, pool: Pubkey) -> PoolState {
+    let account = ctx.banks_client.get_account(pool).await.unwrap().unwrap();
+    PoolState::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+#[tokio::test]
+async fn reports_the_pre_reset_count() {
+    let (mut ctx, pool, guard) = start_with_pool(7).await;
+    let admin = ctx.payer.insecure_clone();
+
+    let events = send(&mut ctx, reset_ix(pool, admin.pubkey(), guard), &admin).await;
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].pool, pool);
+    assert_eq!(events[0].old_count, 7);
+    assert_eq!(events[0].new_count, 0);
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert_eq!(state.rate_limit.count, 0);
+    assert_eq!(state.rate_limit.last_reset as i64, events[0].ts);
+    assert!(state.rate_limit.last_reset > 1);
+}
+
+#[tokio::test]
+async fn resetting_an_idle_limiter_reports_zero() {
+    let (mut ctx, pool, guard) = start_with_pool(0).await;
+    let admin = ctx.payer.insecure_clone();
+
+    let events = send(&mut ctx, reset_ix(pool, admin.pubkey(), guard), &admin).await;
+
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].old_count, 0);
+    assert_eq!(events[0].new_count, 0);
+}
+
+#[tokio::test]
+async fn rejects_non_admin() {
+    let (mut ctx, pool, guard) = start_with_pool(7).await;
+    let outsider = Keypair::new();
+
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(
+        &[reset_ix(pool, outsider.pubkey(), guard)],
+        Some(&ctx.payer.pubkey()),
+        &[&ctx.payer, &outsider],
+        blockhash,
+    );
+    assert!(ctx.banks_client.process_transaction(tx).await.is_err());
+
+    let state = fetch_pool(&mut ctx, pool).await;
+    assert_eq!(state.rate_limit.count, 7);
+}
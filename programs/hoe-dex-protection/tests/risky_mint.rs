@@ -0,0 +1,111 @@
+//! Risky mint authority tests.
+//!
+//! A Token-2022 permanent delegate can move tokens out of any account, and a
+//! close authority can close the mint, so both are rejected unless allowed.
+//! Transfer fees and hooks break the pool's accounting and are never allowed.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::program_option::COption;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{
+        mint_close_authority::MintCloseAuthority, permanent_delegate::PermanentDelegate,
+        transfer_fee::TransferFeeConfig, BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut,
+    },
+    state::Mint,
+};
+use hoe_dex_protection::{
+    utils::{check_mint_transfer_extensions, mint_authorities},
+    ErrorCode, MintAuthorities,
+};
+
+fn base_mint() -> Mint {
+    Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 0,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+}
+
+fn legacy_mint_data() -> Vec<u8> {
+    let mut data = vec![0u8; Mint::LEN];
+    Mint::pack(base_mint(), &mut data).unwrap();
+    data
+}
+
+fn token_2022_mint_data(permanent_delegate: Option<Pubkey>, close_authority: Option<Pubkey>) -> Vec<u8> {
+    let extensions = [ExtensionType::PermanentDelegate, ExtensionType::MintCloseAuthority];
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&extensions).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<PermanentDelegate>(true).unwrap().delegate = permanent_delegate.try_into().unwrap();
+    state.init_extension::<MintCloseAuthority>(true).unwrap().close_authority = close_authority.try_into().unwrap();
+    state.base = base_mint();
+    state.pack_base();
+    state.init_account_type().unwrap();
+    data
+}
+
+#[test]
+fn legacy_mint_has_no_risky_authorities() {
+    let authorities = mint_authorities(&legacy_mint_data()).unwrap();
+
+    assert_eq!(authorities, MintAuthorities::default());
+    assert!(authorities.check(false).is_ok());
+}
+
+#[test]
+fn permanent_delegate_is_rejected_by_default() {
+    let delegate = Pubkey::new_unique();
+    let authorities = mint_authorities(&token_2022_mint_data(Some(delegate), None)).unwrap();
+
+    assert_eq!(authorities.permanent_delegate, Some(delegate));
+    assert_eq!(authorities.check(false).unwrap_err(), ErrorCode::RiskyMintAuthority.into());
+}
+
+#[test]
+fn close_authority_is_rejected_by_default() {
+    let close_authority = Pubkey::new_unique();
+    let authorities = mint_authorities(&token_2022_mint_data(None, Some(close_authority))).unwrap();
+
+    assert_eq!(authorities.close_authority, Some(close_authority));
+    assert_eq!(authorities.check(false).unwrap_err(), ErrorCode::RiskyMintAuthority.into());
+}
+
+#[test]
+fn risky_mint_is_allowed_under_the_flag() {
+    let authorities = mint_authorities(&token_2022_mint_data(Some(Pubkey::new_unique()), None)).unwrap();
+
+    assert!(authorities.is_risky());
+    assert!(authorities.check(true).is_ok());
+}
+
+#[test]
+fn unset_extensions_are_not_risky() {
+    let authorities = mint_authorities(&token_2022_mint_data(None, None)).unwrap();
+
+    assert!(!authorities.is_risky());
+    assert!(authorities.check(false).is_ok());
+}
+
+#[test]
+fn transfer_fee_mint_is_rejected() {
+    let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+    let mut data = vec![0u8; len];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+    state.init_extension::<TransferFeeConfig>(true).unwrap();
+    state.base = base_mint();
+    state.pack_base();
+    state.init_account_type().unwrap();
+
+    let err = check_mint_transfer_extensions(&data).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidTokenMint.into());
+}
+
+#[test]
+fn plain_mints_pass_the_transfer_check() {
+    assert!(check_mint_transfer_extensions(&legacy_mint_data()).is_ok());
+    assert!(check_mint_transfer_extensions(&token_2022_mint_data(Some(Pubkey::new_unique()), None)).is_ok());
+}
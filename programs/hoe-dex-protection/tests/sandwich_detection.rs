@@ -0,0 +1,156 @@
+//! Sandwich detection tests.
+//!
+//! Every trade through `execute_trade` pays in the pool's token, so only one
+//! direction is reachable on-chain; these drive `SandwichSettings` directly
+//! with both sides of a front-run, victim, back-run sequence. Only stronger
+//! detection at no higher a cost to traders applies without the timelock.
+
+use hoe_dex_protection::{
+    ErrorCode, FeeTier, PoolState, SandwichSettings, SandwichSettingsUpdate, TradeDirection, TradeFees, MAXIMUM_FEE_BPS,
+};
+
+const LIQUIDITY: u64 = 1_000_000;
+const SLOT: u64 = 5_000;
+
+fn detector() -> SandwichSettings {
+    SandwichSettings {
+        enabled: true,
+        window_slots: 3,
+        large_trade_bps: 500,
+        similarity_bps: 1000,
+        surcharge_bps: 100,
+        ..Default::default()
+    }
+}
+
+/// Front-runs with a 5% buy, then lets a small victim trade through
+fn after_front_run() -> SandwichSettings {
+    let mut sandwich = detector();
+    sandwich.record_trade(TradeDirection::Buy, 50_000, SLOT, LIQUIDITY, false);
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Buy, 2_000, SLOT), None);
+    sandwich.record_trade(TradeDirection::Buy, 2_000, SLOT, LIQUIDITY, false);
+    sandwich
+}
+
+#[test]
+fn back_run_of_similar_size_is_flagged() {
+    let sandwich = after_front_run();
+
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 48_000, SLOT + 1), Some(50_000));
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 55_000, SLOT + 3), Some(50_000));
+}
+
+#[test]
+fn same_direction_is_not_flagged() {
+    let sandwich = after_front_run();
+
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Buy, 50_000, SLOT + 1), None);
+}
+
+#[test]
+fn dissimilar_size_is_not_flagged() {
+    let sandwich = after_front_run();
+
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 40_000, SLOT + 1), None);
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 60_000, SLOT + 1), None);
+}
+
+#[test]
+fn back_run_outside_the_window_is_not_flagged() {
+    let sandwich = after_front_run();
+
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 50_000, SLOT + 4), None);
+}
+
+#[test]
+fn small_trades_are_not_front_runs() {
+    let mut sandwich = detector();
+    sandwich.record_trade(TradeDirection::Buy, 49_999, SLOT, LIQUIDITY, false);
+
+    assert_eq!(sandwich.last_large_direction, None);
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 49_999, SLOT + 1), None);
+}
+
+#[test]
+fn a_front_run_is_matched_once() {
+    let mut sandwich = after_front_run();
+    sandwich.record_trade(TradeDirection::Sell, 50_000, SLOT + 1, LIQUIDITY, true);
+
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 50_000, SLOT + 2), None);
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Buy, 50_000, SLOT + 2), None);
+}
+
+#[test]
+fn disabled_detector_flags_nothing() {
+    let mut sandwich = after_front_run();
+    sandwich.enabled = false;
+
+    assert_eq!(sandwich.suspected_front_run(TradeDirection::Sell, 50_000, SLOT + 1), None);
+}
+
+#[test]
+fn surcharge_stays_under_the_fee_cap() {
+    let pool = PoolState {
+        fee_tiers: vec![FeeTier { volume_threshold: 1_000_000, fee_bps: 30, liquidity_threshold: None }],
+        sandwich: detector(),
+        ..Default::default()
+    };
+
+    let fees = TradeFees { lp_fee: 300, protocol_fee: 0 };
+    assert_eq!(pool.sandwich_surcharge(100_000, fees).unwrap(), 1_000);
+
+    let capped = TradeFees { lp_fee: 100_000 * MAXIMUM_FEE_BPS / 10000 - 200, protocol_fee: 0 };
+    assert_eq!(pool.sandwich_surcharge(100_000, capped).unwrap(), 200);
+}
+
+#[test]
+fn weaker_detection_or_a_higher_cost_is_looser() {
+    let current = detector().settings();
+    let with = |update: fn(&mut SandwichSettingsUpdate)| {
+        let mut settings = current.clone();
+        update(&mut settings);
+        settings
+    };
+
+    let tighter = SandwichSettingsUpdate {
+        window_slots: 5,
+        large_trade_bps: 300,
+        similarity_bps: 500,
+        surcharge_bps: 50,
+        ..current.clone()
+    };
+    assert!(tighter.is_no_looser_than(&current));
+    for looser in [
+        with(|s| s.enabled = false),
+        with(|s| s.window_slots = 2),
+        with(|s| s.large_trade_bps = 501),
+        with(|s| s.similarity_bps = 1001),
+        with(|s| s.surcharge_bps = 101),
+    ] {
+        assert!(!looser.is_no_looser_than(&current));
+    }
+}
+
+#[test]
+fn configuring_forgets_the_last_large_trade() {
+    let mut sandwich = after_front_run();
+    let settings = SandwichSettingsUpdate { window_slots: 5, ..sandwich.settings() };
+
+    sandwich.configure(&settings);
+
+    assert_eq!(sandwich.settings(), settings);
+    assert_eq!(sandwich.last_large_direction, None);
+}
+
+#[test]
+fn thresholds_are_required_only_while_enabled() {
+    assert!(PoolState::validate_sandwich_settings(&SandwichSettingsUpdate::default()).is_ok());
+
+    let enabled = SandwichSettingsUpdate { enabled: true, ..Default::default() };
+    let err = PoolState::validate_sandwich_settings(&enabled).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidSandwichSettings.into());
+
+    let surcharged = SandwichSettingsUpdate { surcharge_bps: 501, ..detector().settings() };
+    let err = PoolState::validate_sandwich_settings(&surcharged).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidSandwichSettings.into());
+}
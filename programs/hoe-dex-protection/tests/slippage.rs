@@ -0,0 +1,38 @@
+//! Slippage math tests; these exercise the pure helper and need no validator.
+
+use hoe_dex_protection::utils::calculate_slippage_bps;
+
+#[test]
+fn matches_the_bps_formula_for_small_amounts() {
+    assert_eq!(calculate_slippage_bps(10_000, 10_000).unwrap(), 0);
+    assert_eq!(calculate_slippage_bps(10_000, 9_900).unwrap(), 100);
+    assert_eq!(calculate_slippage_bps(1_000_000, 999_999).unwrap(), 0);
+    assert_eq!(calculate_slippage_bps(3, 2).unwrap(), 3333);
+    assert_eq!(calculate_slippage_bps(10_000, 0).unwrap(), 10_000);
+}
+
+#[test]
+fn does_not_overflow_near_u64_max() {
+    let amount_in = u64::MAX;
+
+    assert_eq!(calculate_slippage_bps(amount_in, amount_in).unwrap(), 0);
+    assert_eq!(calculate_slippage_bps(amount_in, amount_in / 2).unwrap(), 5000);
+    assert_eq!(calculate_slippage_bps(amount_in, 0).unwrap(), 10_000);
+
+    // A multiple of 10000 keeps the 1% shortfall exact
+    let amount_in = u64::MAX / 10_000 * 10_000;
+    assert_eq!(calculate_slippage_bps(amount_in, amount_in - amount_in / 100).unwrap(), 100);
+}
+
+#[test]
+fn handles_amounts_just_past_the_old_overflow_point() {
+    // (amount_in - amount_out) * 10000 exceeded u64 above ~1.8e15
+    let amount_in = 2_000_000_000_000_000u64;
+
+    assert_eq!(calculate_slippage_bps(amount_in, amount_in / 10 * 9).unwrap(), 1000);
+}
+
+#[test]
+fn rejects_output_above_input() {
+    assert!(calculate_slippage_bps(100, 101).is_err());
+}
@@ -0,0 +1,38 @@
+//! Slippage tolerance tests.
+//!
+//! `execute_trade_with_tolerance` only derives `minimum_amount_out` before
+//! running the usual trade path, so the derivation is checked on its own.
+
+use hoe_dex_protection::{utils::min_amount_out_from_tolerance, ErrorCode};
+
+#[test]
+fn derives_the_minimum_from_the_tolerance() {
+    assert_eq!(min_amount_out_from_tolerance(1_000_000, 50).unwrap(), 995_000);
+    assert_eq!(min_amount_out_from_tolerance(1_000_000, 0).unwrap(), 1_000_000);
+    assert_eq!(min_amount_out_from_tolerance(1_000_000, 10_000).unwrap(), 0);
+}
+
+#[test]
+fn rounds_the_minimum_down() {
+    assert_eq!(min_amount_out_from_tolerance(999, 50).unwrap(), 994);
+}
+
+#[test]
+fn does_not_overflow_near_u64_max() {
+    assert_eq!(min_amount_out_from_tolerance(u64::MAX, 0).unwrap(), u64::MAX);
+    assert_eq!(min_amount_out_from_tolerance(u64::MAX, 5_000).unwrap(), u64::MAX / 2);
+}
+
+#[test]
+fn rejects_out_of_range_tolerance() {
+    let err = min_amount_out_from_tolerance(1_000_000, 10_001).unwrap_err();
+
+    assert_eq!(err, ErrorCode::InvalidSlippageTolerance.into());
+}
+
+#[test]
+fn rejects_zero_expected_output() {
+    let err = min_amount_out_from_tolerance(0, 50).unwrap_err();
+
+    assert_eq!(err, ErrorCode::InvalidAmount.into());
+}
@@ -0,0 +1,75 @@
+//! Keeper tick tests.
+//!
+//! `tick` only advances time-based state, so these age a busy pool and check
+//! each subsystem after one call.
+
+use hoe_dex_protection::{
+    CircuitBreakerSettings, PoolState, RateLimitSettings, VolumeSettings, VOLUME_BUCKET_SECONDS,
+};
+
+const START: u64 = 1_700_000_000;
+const TWO_DAYS: u64 = 2 * 24 * 3600;
+
+fn busy_pool() -> PoolState {
+    let mut pool = PoolState {
+        volume: VolumeSettings {
+            max_daily: 100_000_000,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        rate_limit: RateLimitSettings {
+            window_seconds: 3600,
+            max_calls: 100,
+            count: 42,
+            last_reset: START,
+            ..Default::default()
+        },
+        circuit_breaker: CircuitBreakerSettings {
+            max_amount: 1_000_000,
+            current_amount: 800_000,
+            decay_bps: 1000,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    pool.update_volume(500_000, START).unwrap();
+    pool
+}
+
+#[test]
+fn single_tick_advances_every_subsystem_after_idle() {
+    let mut pool = busy_pool();
+
+    pool.tick(START + TWO_DAYS).unwrap();
+
+    assert_eq!(pool.volume.current_volume, 0);
+    assert_eq!(pool.rate_limit.count, 0);
+    assert_eq!(pool.rate_limit.last_reset, START + TWO_DAYS);
+    assert_eq!(pool.circuit_breaker.current_amount, 0);
+}
+
+#[test]
+fn repeated_ticks_change_nothing() {
+    let mut pool = busy_pool();
+    pool.tick(START + TWO_DAYS).unwrap();
+    let after_first = (pool.volume.clone(), pool.rate_limit.clone(), pool.circuit_breaker.clone());
+
+    pool.tick(START + TWO_DAYS).unwrap();
+
+    assert_eq!(pool.volume.current_volume, after_first.0.current_volume);
+    assert_eq!(pool.rate_limit.last_reset, after_first.1.last_reset);
+    assert_eq!(pool.circuit_breaker.last_decay, after_first.2.last_decay);
+}
+
+#[test]
+fn tick_inside_the_window_keeps_live_counters() {
+    let mut pool = busy_pool();
+
+    pool.tick(START + 60).unwrap();
+
+    assert_eq!(pool.volume.current_volume, 500_000);
+    assert_eq!(pool.rate_limit.count, 42);
+    assert_eq!(pool.rate_limit.last_reset, START);
+}
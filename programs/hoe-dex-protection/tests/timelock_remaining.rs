@@ -0,0 +1,45 @@
+//! Timelock countdown tests; timelocks are hours long, so the countdown is
+//! checked against fixed clock values rather than a running validator.
+
+use hoe_dex_protection::{ErrorCode, PendingUpdate, PoolState};
+
+const SCHEDULED_TIME: u64 = 1_700_000_000;
+
+fn pool_with_pending_update() -> PoolState {
+    PoolState {
+        pending_update: Some(PendingUpdate {
+            scheduled_time: SCHEDULED_TIME,
+            updates: vec![],
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn counts_down_before_the_scheduled_time() {
+    let pool = pool_with_pending_update();
+
+    assert_eq!(pool.timelock_remaining(SCHEDULED_TIME - 3600).unwrap(), 3600);
+    assert_eq!(pool.timelock_remaining(SCHEDULED_TIME - 1).unwrap(), 1);
+}
+
+#[test]
+fn reports_zero_at_and_after_the_scheduled_time() {
+    let pool = pool_with_pending_update();
+
+    assert_eq!(pool.timelock_remaining(SCHEDULED_TIME).unwrap(), 0);
+    assert_eq!(pool.timelock_remaining(SCHEDULED_TIME + 86_400).unwrap(), 0);
+}
+
+#[test]
+fn fails_when_nothing_is_pending() {
+    let pool = PoolState::default();
+
+    match pool.timelock_remaining(SCHEDULED_TIME) {
+        Err(anchor_lang::error::Error::AnchorError(err)) => assert_eq!(
+            err.error_code_number,
+            anchor_lang::error::ERROR_CODE_OFFSET + ErrorCode::NoPendingUpdate as u32
+        ),
+        other => panic!("expected NoPendingUpdate, got {:?}", other),
+    }
+}
@@ -0,0 +1,23 @@
+//! Token decimals tests.
+//!
+//! The decimals captured at initialization are the only ones a mint passed to
+//! the trade or liquidity paths may have.
+
+use hoe_dex_protection::{ErrorCode, PoolState};
+
+fn pool() -> PoolState {
+    PoolState { token_decimals: 9, ..Default::default() }
+}
+
+#[test]
+fn matching_decimals_pass() {
+    assert!(pool().check_token_decimals(9).is_ok());
+}
+
+#[test]
+fn mismatched_decimals_are_rejected() {
+    for decimals in [0, 6, 8, 10, 18] {
+        let err = pool().check_token_decimals(decimals).unwrap_err();
+        assert_eq!(err, ErrorCode::InvalidTokenDecimals.into());
+    }
+}
@@ -0,0 +1,39 @@
+//! Trade rejection reason code tests.
+//!
+//! `TradeRejected` reports a `TRADE_REJECT_*` code per failed batch leg, so
+//! each protection error has to land in the right category.
+
+use hoe_dex_protection::{
+    utils::trade_reject_reason, ErrorCode, TRADE_REJECT_BLACKLISTED, TRADE_REJECT_CIRCUIT_BREAKER,
+    TRADE_REJECT_LIQUIDITY, TRADE_REJECT_OTHER, TRADE_REJECT_PAUSED, TRADE_REJECT_PRICE_IMPACT,
+    TRADE_REJECT_RATE_LIMIT, TRADE_REJECT_SLIPPAGE, TRADE_REJECT_TRADE_SIZE, TRADE_REJECT_VOLUME,
+};
+
+#[test]
+fn each_rejection_maps_to_its_reason() {
+    let cases = [
+        (ErrorCode::PoolPaused, TRADE_REJECT_PAUSED),
+        (ErrorCode::EmergencyPaused, TRADE_REJECT_PAUSED),
+        (ErrorCode::PostResumeCooldown, TRADE_REJECT_PAUSED),
+        (ErrorCode::TraderBlacklisted, TRADE_REJECT_BLACKLISTED),
+        (ErrorCode::RateLimitExceeded, TRADE_REJECT_RATE_LIMIT),
+        (ErrorCode::HourlyTradeLimitExceeded, TRADE_REJECT_RATE_LIMIT),
+        (ErrorCode::VolumeLimitExceeded, TRADE_REJECT_VOLUME),
+        (ErrorCode::HourlyVolumeLimitExceeded, TRADE_REJECT_VOLUME),
+        (ErrorCode::CircuitBreakerTriggered, TRADE_REJECT_CIRCUIT_BREAKER),
+        (ErrorCode::TradeTooSmall, TRADE_REJECT_TRADE_SIZE),
+        (ErrorCode::PriceImpactTooHigh, TRADE_REJECT_PRICE_IMPACT),
+        (ErrorCode::SlippageExceeded, TRADE_REJECT_SLIPPAGE),
+        (ErrorCode::OutputExceedsReserveFraction, TRADE_REJECT_LIQUIDITY),
+    ];
+
+    for (code, reason) in cases {
+        assert_eq!(trade_reject_reason(&code.into()), reason, "{:?}", code);
+    }
+}
+
+#[test]
+fn unrelated_errors_map_to_other() {
+    assert_eq!(trade_reject_reason(&ErrorCode::Overflow.into()), TRADE_REJECT_OTHER);
+    assert_eq!(trade_reject_reason(&ErrorCode::Unauthorized.into()), TRADE_REJECT_OTHER);
+}
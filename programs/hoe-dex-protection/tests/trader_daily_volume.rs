@@ -0,0 +1,106 @@
+//! Per-trader daily volume cap tests.
+//!
+//! Each trader's window lives in their own `TraderVolume` account, so these
+//! drive it directly next to a pool whose global cap has plenty of room.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{
+    ErrorCode, PoolState, ProtectionSettings, ProtectionSettingsUpdate, TraderVolume, VolumeSettings,
+    MAX_DAILY_VOLUME_WINDOW,
+};
+
+const START: u64 = 1_700_000_000;
+const MAX_PER_TRADER: u64 = 100_000;
+
+fn trade(trader_volume: &mut TraderVolume, pool: &mut PoolState, amount: u64, current_time: u64) -> anchor_lang::Result<()> {
+    trader_volume.check_daily_limit(amount, MAX_PER_TRADER, current_time)?;
+    pool.check_volume_limit(amount, current_time)?;
+    trader_volume.record_trade(Pubkey::default(), Pubkey::default(), 0, amount, current_time);
+    pool.update_volume(amount, current_time)
+}
+
+fn pool() -> PoolState {
+    PoolState {
+        volume: VolumeSettings { max_daily: 100_000_000, ..Default::default() },
+        // Valid, so protection updates fail only on the field under test
+        protection: ProtectionSettings { large_withdrawal_bps: 2000, ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn trader_hits_their_cap_while_the_global_cap_has_room() {
+    let mut pool = pool();
+    let mut trader_volume = TraderVolume::default();
+    trade(&mut trader_volume, &mut pool, 60_000, START).unwrap();
+    trade(&mut trader_volume, &mut pool, 40_000, START + 60).unwrap();
+
+    let err = trade(&mut trader_volume, &mut pool, 1, START + 120).unwrap_err();
+    assert_eq!(err, ErrorCode::TraderDailyVolumeExceeded.into());
+    assert!(pool.check_volume_limit(1, START + 120).is_ok());
+}
+
+#[test]
+fn other_traders_keep_their_own_allowance() {
+    let mut pool = pool();
+    let mut first = TraderVolume::default();
+    let mut second = TraderVolume::default();
+    trade(&mut first, &mut pool, MAX_PER_TRADER, START).unwrap();
+
+    assert!(trade(&mut second, &mut pool, MAX_PER_TRADER, START).is_ok());
+}
+
+#[test]
+fn first_trade_opens_a_window() {
+    let mut trader_volume = TraderVolume::default();
+
+    trader_volume.record_trade(Pubkey::default(), Pubkey::default(), 0, 10_000, START);
+
+    assert_eq!(trader_volume.window_start, START);
+    assert_eq!(trader_volume.volume_at(START), 10_000);
+}
+
+#[test]
+fn allowance_resets_once_the_day_rolls_over() {
+    let mut pool = pool();
+    let mut trader_volume = TraderVolume::default();
+    trade(&mut trader_volume, &mut pool, MAX_PER_TRADER, START).unwrap();
+    assert!(trade(&mut trader_volume, &mut pool, 1, START + MAX_DAILY_VOLUME_WINDOW - 1).is_err());
+
+    trade(&mut trader_volume, &mut pool, MAX_PER_TRADER, START + MAX_DAILY_VOLUME_WINDOW).unwrap();
+    assert_eq!(trader_volume.window_start, START + MAX_DAILY_VOLUME_WINDOW);
+}
+
+#[test]
+fn zero_cap_disables_the_check() {
+    let trader_volume = TraderVolume { volume: u64::MAX, window_start: START, ..Default::default() };
+
+    assert!(trader_volume.check_daily_limit(1, 0, START).is_ok());
+}
+
+#[test]
+fn raising_or_disabling_the_cap_is_not_a_tightening() {
+    let pool = PoolState {
+        protection: ProtectionSettings { max_daily_volume_per_trader: MAX_PER_TRADER, ..pool().protection },
+        ..pool()
+    };
+    let current = pool.current_protection_settings();
+
+    let lower = ProtectionSettingsUpdate { max_daily_volume_per_trader: MAX_PER_TRADER - 1, ..current.clone() };
+    assert!(pool.validate_protection_tightening(&lower).is_ok());
+
+    for looser in [MAX_PER_TRADER + 1, 0] {
+        let settings = ProtectionSettingsUpdate { max_daily_volume_per_trader: looser, ..current.clone() };
+        let err = pool.validate_protection_tightening(&settings).unwrap_err();
+        assert_eq!(err, ErrorCode::Unauthorized.into());
+    }
+}
+
+#[test]
+fn any_cap_tightens_a_disabled_one() {
+    let pool = pool();
+    let current = pool.current_protection_settings();
+    let settings = ProtectionSettingsUpdate { max_daily_volume_per_trader: u64::MAX, ..current };
+
+    assert!(pool.validate_protection_tightening(&settings).is_ok());
+}
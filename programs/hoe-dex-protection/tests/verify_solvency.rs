@@ -0,0 +1,144 @@
+//! Solvency check tests.
+//!
+//! A deficit cannot arise through the program's own instructions, so these
+//! write the pool and its token account directly with the balances under test
+//! and run the check against the built program (`anchor build` first, so
+//! `target/deploy` exists).
+
+use anchor_lang::{AccountSerialize, AnchorDeserialize, Discriminator, InstructionData, ToAccountMetas};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hoe_dex_protection::{ErrorCode, PoolState, SolvencyChecked};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, AccountState};
+
+struct Pool {
+    pool: Pubkey,
+    pool_token_account: Pubkey,
+    pool_authority: Pubkey,
+}
+
+fn owned_account(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Starts a validator with a pool owing 1_000 liquidity and 50 in fees
+async fn start_with_balance(token_balance: u64) -> (ProgramTestContext, Pool) {
+    let mut program_test = ProgramTest::new("hoe_dex_protection", hoe_dex_protection::ID, None);
+    program_test.prefer_bpf(true);
+    let mut ctx = program_test.start_with_context().await;
+
+    let token_mint = Pubkey::new_unique();
+    let state = PoolState {
+        token_mint,
+        total_liquidity: 1_000,
+        total_fees_collected: 30,
+        protocol_fees: 20,
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+    state.try_serialize(&mut data).unwrap();
+    let pool = Pubkey::new_unique();
+    ctx.set_account(&pool, &owned_account(data, hoe_dex_protection::ID).into());
+
+    let (pool_authority, _) =
+        Pubkey::find_program_address(&[b"pool_authority", pool.as_ref()], &hoe_dex_protection::ID);
+    let mut token_data = vec![0; TokenAccount::LEN];
+    TokenAccount {
+        mint: token_mint,
+        owner: pool_authority,
+        amount: token_balance,
+        state: AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut token_data);
+    let pool_token_account = Pubkey::new_unique();
+    ctx.set_account(&pool_token_account, &owned_account(token_data, spl_token::ID).into());
+
+    (ctx, Pool { pool, pool_token_account, pool_authority })
+}
+
+fn verify_ix(pool: &Pool) -> Instruction {
+    Instruction {
+        program_id: hoe_dex_protection::ID,
+        accounts: hoe_dex_protection::accounts::VerifySolvency {
+            pool_state: pool.pool,
+            pool_token_account: pool.pool_token_account,
+            pool_authority: pool.pool_authority,
+        }
+        .to_account_metas(None),
+        data: hoe_dex_protection::instruction::VerifySolvency {}.data(),
+    }
+}
+
+/// Sends the check, signed only by the payer, and returns its result and `SolvencyChecked` events
+async fn verify(ctx: &mut ProgramTestContext, pool: &Pool) -> (Result<(), TransactionError>, Vec<SolvencyChecked>) {
+    let blockhash = ctx.get_new_latest_blockhash().await.unwrap();
+    let tx = Transaction::new_signed_with_payer(&[verify_ix(pool)], Some(&ctx.payer.pubkey()), &[&ctx.payer], blockhash);
+    let outcome = ctx.banks_client.process_transaction_with_metadata(tx).await.unwrap();
+
+    let events = outcome
+        .metadata
+        .unwrap()
+        .log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .filter_map(|encoded| STANDARD.decode(encoded).ok())
+        .filter(|data| data.starts_with(SolvencyChecked::DISCRIMINATOR))
+        .map(|data| SolvencyChecked::deserialize(&mut &data[SolvencyChecked::DISCRIMINATOR.len()..]).unwrap())
+        .collect();
+    (outcome.result, events)
+}
+
+#[tokio::test]
+async fn healthy_pool_reports_no_surplus_or_deficit() {
+    let (mut ctx, pool) = start_with_balance(1_050).await;
+
+    let (result, events) = verify(&mut ctx, &pool).await;
+
+    result.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].obligations, 1_050);
+    assert_eq!(events[0].surplus, 0);
+    assert_eq!(events[0].deficit, 0);
+}
+
+#[tokio::test]
+async fn direct_transfers_show_as_surplus() {
+    let (mut ctx, pool) = start_with_balance(1_500).await;
+
+    let (result, events) = verify(&mut ctx, &pool).await;
+
+    result.unwrap();
+    assert_eq!(events[0].surplus, 450);
+    assert_eq!(events[0].deficit, 0);
+}
+
+#[tokio::test]
+async fn deficit_fails_the_check() {
+    let (mut ctx, pool) = start_with_balance(1_000).await;
+
+    let (result, _) = verify(&mut ctx, &pool).await;
+
+    assert_eq!(
+        result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(anchor_lang::error::ERROR_CODE_OFFSET + ErrorCode::PoolInsolvent as u32)
+        )
+    );
+}
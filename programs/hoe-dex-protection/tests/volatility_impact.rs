@@ -0,0 +1,125 @@
+//! Volatility-adaptive price impact tests.
+//!
+//! The cap depends only on the recorded execution prices, so these feed
+//! prices to `VolatilitySettings` directly instead of executing trades. Once
+//! enabled, only settings that narrow the cap apply without the timelock.
+
+use hoe_dex_protection::{ErrorCode, PoolState, VolatilitySettings, VolatilitySettingsUpdate, MIN_VOLATILITY_SAMPLES};
+
+const CEILING: u64 = 1000;
+
+fn adaptive() -> VolatilitySettings {
+    VolatilitySettings {
+        enabled: true,
+        floor_impact_bps: 200,
+        max_volatility_bps: 1000,
+        ..Default::default()
+    }
+}
+
+/// Records trades alternating between two prices `move_bps` apart
+fn swing(volatility: &mut VolatilitySettings, move_bps: u64, trades: u32) {
+    for i in 0..trades {
+        let amount_out = if i % 2 == 0 { 10_000 } else { 10_000 - move_bps };
+        volatility.record_price(10_000, amount_out);
+    }
+}
+
+#[test]
+fn falls_back_to_the_static_cap_without_history() {
+    let mut volatility = adaptive();
+    swing(&mut volatility, 500, MIN_VOLATILITY_SAMPLES);
+
+    // The first trade only sets the reference price
+    assert_eq!(volatility.samples, MIN_VOLATILITY_SAMPLES - 1);
+    assert_eq!(volatility.max_price_impact_bps(CEILING), CEILING);
+}
+
+#[test]
+fn steady_prices_keep_the_static_cap() {
+    let mut volatility = adaptive();
+    swing(&mut volatility, 0, 8);
+
+    assert_eq!(volatility.volatility_bps, 0);
+    assert_eq!(volatility.max_price_impact_bps(CEILING), CEILING);
+}
+
+#[test]
+fn cap_tightens_as_volatility_builds() {
+    let mut volatility = adaptive();
+    swing(&mut volatility, 500, 4);
+    let moderate = volatility.max_price_impact_bps(CEILING);
+
+    swing(&mut volatility, 1500, 8);
+    let high = volatility.max_price_impact_bps(CEILING);
+
+    assert!(moderate < CEILING, "moderate cap {}", moderate);
+    assert!(high < moderate, "high cap {} not below {}", high, moderate);
+}
+
+#[test]
+fn cap_interpolates_between_floor_and_ceiling() {
+    let volatility = VolatilitySettings { volatility_bps: 500, samples: MIN_VOLATILITY_SAMPLES, ..adaptive() };
+
+    // Halfway to max volatility sits halfway between 1000 and 200
+    assert_eq!(volatility.max_price_impact_bps(CEILING), 600);
+}
+
+#[test]
+fn cap_never_goes_below_the_floor() {
+    let volatility = VolatilitySettings { volatility_bps: 9000, samples: 50, ..adaptive() };
+
+    assert_eq!(volatility.max_price_impact_bps(CEILING), 200);
+}
+
+#[test]
+fn disabled_mode_keeps_the_static_cap() {
+    let volatility = VolatilitySettings { enabled: false, volatility_bps: 9000, samples: 50, ..adaptive() };
+
+    assert_eq!(volatility.max_price_impact_bps(CEILING), CEILING);
+}
+
+#[test]
+fn only_a_narrower_cap_is_a_tightening() {
+    let current = adaptive().settings();
+    let tighter = VolatilitySettingsUpdate { floor_impact_bps: 100, max_volatility_bps: 500, ..current.clone() };
+    assert!(tighter.is_no_looser_than(&current));
+
+    for looser in [
+        VolatilitySettingsUpdate { enabled: false, ..current.clone() },
+        VolatilitySettingsUpdate { floor_impact_bps: 201, ..current.clone() },
+        VolatilitySettingsUpdate { max_volatility_bps: 1001, ..current.clone() },
+    ] {
+        assert!(!looser.is_no_looser_than(&current));
+    }
+}
+
+#[test]
+fn any_settings_tighten_a_disabled_cap() {
+    let disabled = VolatilitySettingsUpdate { enabled: false, ..adaptive().settings() };
+    let loose = VolatilitySettingsUpdate { enabled: true, floor_impact_bps: 900, max_volatility_bps: 10000 };
+
+    assert!(loose.is_no_looser_than(&disabled));
+}
+
+#[test]
+fn configuring_keeps_recorded_volatility() {
+    let mut volatility = adaptive();
+    swing(&mut volatility, 500, MIN_VOLATILITY_SAMPLES + 1);
+    let recorded = volatility.volatility_bps;
+
+    volatility.configure(&VolatilitySettingsUpdate { enabled: true, floor_impact_bps: 100, max_volatility_bps: 500 });
+
+    assert_eq!(volatility.volatility_bps, recorded);
+    assert_eq!(volatility.floor_impact_bps, 100);
+}
+
+#[test]
+fn floor_must_sit_under_the_static_cap() {
+    let settings = adaptive().settings();
+    assert!(PoolState::validate_volatility_settings(&settings, CEILING).is_ok());
+
+    let err = PoolState::validate_volatility_settings(&settings, 199).unwrap_err();
+    assert_eq!(err, ErrorCode::InvalidVolatilitySettings.into());
+    assert!(PoolState::validate_volatility_settings(&VolatilitySettingsUpdate::default(), 0).is_ok());
+}
@@ -0,0 +1,82 @@
+//! Volume decay tests.
+//!
+//! The 24h window spans a day of wall-clock time, so these drive the pool's
+//! volume tracking and trade validation with simulated timestamps.
+
+use anchor_lang::prelude::Pubkey;
+use hoe_dex_protection::{validation, PoolState, VolumeSettings, VOLUME_BUCKET_SECONDS};
+
+const START: u64 = 1_700_000_000;
+const DAY: u64 = 24 * VOLUME_BUCKET_SECONDS;
+
+fn pool_at_volume_cap(max_daily: u64) -> PoolState {
+    let mut pool = PoolState {
+        volume: VolumeSettings {
+            max_daily,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        total_liquidity: 10_000_000,
+        ..Default::default()
+    };
+    pool.update_volume(max_daily, START).unwrap();
+    pool
+}
+
+#[test]
+fn full_window_blocks_a_new_trade() {
+    let mut pool = pool_at_volume_cap(1_000_000);
+    let later = START + VOLUME_BUCKET_SECONDS;
+
+    validation::validate_trade_parameters(&mut pool, &Pubkey::new_unique(), 1, later).unwrap();
+
+    assert!(pool.check_volume_limit(1, later).is_err());
+}
+
+#[test]
+fn volume_older_than_the_window_no_longer_blocks() {
+    let mut pool = pool_at_volume_cap(1_000_000);
+    let next_day = START + DAY + VOLUME_BUCKET_SECONDS;
+
+    validation::validate_trade_parameters(&mut pool, &Pubkey::new_unique(), 500_000, next_day).unwrap();
+
+    assert_eq!(pool.volume.current_volume, 0);
+    assert_eq!(pool.volume.volume_24h, 0);
+    assert!(pool.check_volume_limit(500_000, next_day).is_ok());
+}
+
+#[test]
+fn decayed_view_matches_what_a_trade_sees() {
+    let mut pool = pool_at_volume_cap(1_000_000);
+    let next_day = START + DAY + VOLUME_BUCKET_SECONDS;
+
+    let viewed = pool.decayed_volume(next_day).unwrap().current_volume;
+    // The view leaves stored state alone
+    assert_eq!(pool.volume.current_volume, 1_000_000);
+
+    pool.decay_volume(next_day).unwrap();
+    assert_eq!(pool.volume.current_volume, viewed);
+}
+
+#[test]
+fn clock_behind_last_decay_keeps_totals() {
+    let mut pool = pool_at_volume_cap(1_000_000);
+
+    pool.decay_volume(START - 60).unwrap();
+
+    assert_eq!(pool.volume.current_volume, 1_000_000);
+}
+
+#[test]
+fn deferred_decay_hands_back_its_event_once() {
+    let mut pool = pool_at_volume_cap(1_000_000);
+    let next_day = START + DAY + VOLUME_BUCKET_SECONDS;
+
+    let event = pool.decay_volume_deferred(next_day).unwrap().unwrap();
+    assert_eq!(event.old_volume, 1_000_000);
+    assert_eq!(event.new_volume, 0);
+
+    // Validation at the same instant finds nothing left to decay
+    assert!(pool.decay_volume_deferred(next_day).unwrap().is_none());
+}
@@ -0,0 +1,48 @@
+//! Volume limit hit tests.
+//!
+//! `VolumeLimitHit` reports the daily volume a rejected trade would have
+//! reached, which has to agree with what the cap check measured.
+
+use hoe_dex_protection::{ErrorCode, PoolState, VolumeSettings, VOLUME_BUCKET_SECONDS};
+
+const START: u64 = 1_700_000_000;
+const DAY: u64 = 24 * VOLUME_BUCKET_SECONDS;
+
+fn pool_with_volume(max_daily: u64, traded: u64) -> PoolState {
+    let mut pool = PoolState {
+        volume: VolumeSettings {
+            max_daily,
+            bucket_hour: START / VOLUME_BUCKET_SECONDS,
+            last_decay: START,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    pool.update_volume(traded, START).unwrap();
+    pool
+}
+
+#[test]
+fn attempted_volume_adds_the_trade_to_the_live_window() {
+    let pool = pool_with_volume(25_000, 20_000);
+
+    assert_eq!(pool.attempted_daily_volume(10_000, START + 60), 30_000);
+    assert_eq!(
+        pool.check_volume_limit(10_000, START + 60).unwrap_err(),
+        ErrorCode::VolumeLimitExceeded.into()
+    );
+}
+
+#[test]
+fn attempted_volume_drops_expired_buckets() {
+    let pool = pool_with_volume(25_000, 20_000);
+
+    assert_eq!(pool.attempted_daily_volume(10_000, START + DAY + VOLUME_BUCKET_SECONDS), 10_000);
+}
+
+#[test]
+fn attempted_volume_saturates() {
+    let pool = pool_with_volume(u64::MAX, 20_000);
+
+    assert_eq!(pool.attempted_daily_volume(u64::MAX, START), u64::MAX);
+}
@@ -0,0 +1,33 @@
+//! Dust trade tests.
+//!
+//! A fee that eats the whole input would leave the trader with nothing, so
+//! these check `amount_after_fee` against fees from the pool's own schedule.
+
+use hoe_dex_protection::{utils::amount_after_fee, ErrorCode, PoolState, MINIMUM_FEE};
+
+#[test]
+fn fee_consuming_the_whole_input_is_rejected() {
+    let err = amount_after_fee(MINIMUM_FEE, MINIMUM_FEE).unwrap_err();
+
+    assert_eq!(err, ErrorCode::ZeroOutput.into());
+}
+
+#[test]
+fn fee_above_the_input_is_rejected() {
+    let err = amount_after_fee(1, 2).unwrap_err();
+
+    assert_eq!(err, ErrorCode::ZeroOutput.into());
+}
+
+#[test]
+fn fee_below_the_input_leaves_the_rest() {
+    assert_eq!(amount_after_fee(10_000, 30).unwrap(), 9_970);
+}
+
+#[test]
+fn single_unit_trade_cannot_pay_its_own_fee() {
+    let pool = PoolState::default();
+    let (fees, _, _) = pool.calculate_fee(1, 0).unwrap();
+
+    assert!(amount_after_fee(1, fees.total()).is_err());
+}
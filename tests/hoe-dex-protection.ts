@@ -56,6 +56,340 @@ describe("hoe-dex-protection", () => {
     poolState = anchor.web3.Keypair.generate();
   });
 
+  const defaultFeeTiers = () => [
+    { volumeThreshold: new BN(1_000_000), feeBps: new BN(30), liquidityThreshold: null },
+    { volumeThreshold: new BN(10_000_000), feeBps: new BN(20), liquidityThreshold: null },
+  ];
+
+  type PoolOptions = {
+    earlyTradeFeeBps?: number;
+    earlyTradeWindowSeconds?: number;
+    maxPriceImpactBps?: number;
+    maxDailyVolume?: number;
+    maxSlippageBps?: number;
+    minTradeInterval?: number;
+    rateLimitMax?: number;
+    maxHourlyVolume?: number;
+    maxOutputFractionBps?: number;
+    maxDailyVolumePerTrader?: number;
+    lowReserve?: { threshold: number; surchargeBps: number; maxTradeSize: number };
+    feeWithdrawCooldown?: number;
+    sandwich?: { enabled: boolean; windowSlots: number; largeTradeBps: number; similarityBps: number; surchargeBps: number };
+    maxHourlyTrades?: number;
+    maxTradesPerTx?: number;
+    volatility?: { enabled: boolean; floorImpactBps: number; maxVolatilityBps: number };
+    postResumeCooldown?: number;
+    allowedCallers?: PublicKey[];
+    circuitBreakerThreshold?: number;
+    circuitBreakerCooldown?: number;
+    circuitBreakerDecayBps?: number;
+    autoPauseOnBreaker?: boolean;
+    minLiquidityFloor?: number;
+    liquidityGracePeriod?: number;
+    blacklistEnabled?: boolean;
+    feeRounding?: "down" | "up";
+    protocolFeeBps?: number;
+    feeFreeThreshold?: number;
+    emergencyPauseSeconds?: number;
+    emergencyResumeSeconds?: number;
+    freezeAuthority?: PublicKey;
+    tokenMint?: PublicKey;
+    feeTiers?: { volumeThreshold: BN; feeBps: BN; liquidityThreshold: BN | null }[];
+    liquidity?: number;
+    allowRiskyMint?: boolean;
+    preset?: "custom" | "conservative" | "standard" | "aggressive";
+  };
+
+  type TestPool = {
+    admin: anchor.web3.Keypair;
+    poolState: PublicKey;
+    poolAuthority: PublicKey;
+    tokenMint: PublicKey;
+    adminTokenAccount: PublicKey;
+    poolTokenAccount: PublicKey;
+  };
+
+  const derivePoolState = (admin: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_state"), admin.toBuffer()],
+      program.programId
+    )[0];
+
+  const derivePoolAuthority = (pool: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("pool_authority"), pool.toBuffer()],
+      program.programId
+    )[0];
+
+  const fundedKeypair = async () => {
+    const kp = anchor.web3.Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(
+      kp.publicKey,
+      10 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(sig);
+    return kp;
+  };
+
+  const initialConfig = (opts: PoolOptions) => ({
+    trade: {
+      earlyTradeFeeBps: new BN(opts.earlyTradeFeeBps ?? 0),
+      earlyTradeWindowSeconds: new BN(opts.earlyTradeWindowSeconds ?? 0),
+      maxTradeSizeBps: new BN(1000),
+      minTradeSize: new BN(1),
+      cooldownSeconds: new BN(0),
+    },
+    protection: {
+      maxDailyVolume: new BN(opts.maxDailyVolume ?? 100_000_000),
+      maxPriceImpactBps: new BN(opts.maxPriceImpactBps ?? 1000),
+      maxSlippageBps: new BN(opts.maxSlippageBps ?? 100),
+      blacklistEnabled: opts.blacklistEnabled ?? true,
+      minTradeInterval: new BN(opts.minTradeInterval ?? 0),
+      minLiquidityFloor: new BN(opts.minLiquidityFloor ?? 0),
+      largeWithdrawalBps: new BN(2000),
+      circuitBreakerThreshold: new BN(opts.circuitBreakerThreshold ?? 1_000_000),
+      circuitBreakerWindow: new BN(3600),
+      circuitBreakerCooldown: new BN(opts.circuitBreakerCooldown ?? 3600),
+      circuitBreakerDecayBps: new BN(opts.circuitBreakerDecayBps ?? 0),
+      rateLimitWindow: new BN(3600),
+      rateLimitMax: opts.rateLimitMax ?? 100,
+      maxHourlyVolume: new BN(opts.maxHourlyVolume ?? 10_000_000),
+      maxOutputFractionBps: new BN(opts.maxOutputFractionBps ?? 10_000),
+      maxDailyVolumePerTrader: new BN(opts.maxDailyVolumePerTrader ?? 0),
+      lowReserve: {
+        threshold: new BN(opts.lowReserve?.threshold ?? 0),
+        surchargeBps: new BN(opts.lowReserve?.surchargeBps ?? 0),
+        maxTradeSize: new BN(opts.lowReserve?.maxTradeSize ?? 0),
+      },
+      feeWithdrawCooldown: new BN(opts.feeWithdrawCooldown ?? 0),
+      sandwich: {
+        enabled: opts.sandwich?.enabled ?? false,
+        windowSlots: new BN(opts.sandwich?.windowSlots ?? 0),
+        largeTradeBps: new BN(opts.sandwich?.largeTradeBps ?? 0),
+        similarityBps: new BN(opts.sandwich?.similarityBps ?? 0),
+        surchargeBps: new BN(opts.sandwich?.surchargeBps ?? 0),
+      },
+      maxHourlyTrades: opts.maxHourlyTrades ?? 100,
+      maxTradesPerTx: opts.maxTradesPerTx ?? 0,
+      volatility: {
+        enabled: opts.volatility?.enabled ?? false,
+        floorImpactBps: new BN(opts.volatility?.floorImpactBps ?? 0),
+        maxVolatilityBps: new BN(opts.volatility?.maxVolatilityBps ?? 0),
+      },
+      postResumeCooldown: new BN(opts.postResumeCooldown ?? 0),
+      allowedCallers: opts.allowedCallers ?? [],
+    },
+    fee: {
+      feeTiers: opts.feeTiers ?? defaultFeeTiers(),
+      feeTiersLocked: false,
+      feeRounding: { [opts.feeRounding ?? "down"]: {} } as any,
+      protocolFeeBps: new BN(opts.protocolFeeBps ?? 0),
+      feeFreeThreshold: new BN(opts.feeFreeThreshold ?? 0),
+    },
+    snipeProtectionSeconds: new BN(0),
+    autoPauseOnBreaker: opts.autoPauseOnBreaker ?? false,
+    liquidityGracePeriod: new BN(opts.liquidityGracePeriod ?? 0),
+    timelocks: {
+      tradeSeconds: new BN(86400),
+      protectionSeconds: new BN(86400),
+      feeSeconds: new BN(86400),
+      stateSeconds: new BN(86400),
+      emergencyPauseSeconds: new BN(opts.emergencyPauseSeconds ?? 300),
+      emergencyResumeSeconds: new BN(opts.emergencyResumeSeconds ?? 3600),
+    },
+    allowRiskyMint: opts.allowRiskyMint ?? false,
+    preset: { [opts.preset ?? "custom"]: {} } as any,
+  });
+
+  const setupPool = async (opts: PoolOptions = {}): Promise<TestPool> => {
+    const admin = await fundedKeypair();
+    const mint =
+      opts.tokenMint ??
+      (await spl.createMint(
+        provider.connection,
+        wallet.payer,
+        wallet.publicKey,
+        opts.freezeAuthority ?? null,
+        9
+      ));
+    const pool = derivePoolState(admin.publicKey);
+    const poolAuthority = derivePoolAuthority(pool);
+    const adminAta = await spl.createAccount(
+      provider.connection,
+      wallet.payer,
+      mint,
+      admin.publicKey
+    );
+    const poolAta = await spl.createAccount(
+      provider.connection,
+      wallet.payer,
+      mint,
+      poolAuthority,
+      anchor.web3.Keypair.generate()
+    );
+    await spl.mintTo(
+      provider.connection,
+      wallet.payer,
+      mint,
+      adminAta,
+      wallet.payer,
+      1_000_000_000
+    );
+
+    await program.methods
+      .initializePool(initialConfig(opts))
+      .accounts({
+        poolState: pool,
+        admin: admin.publicKey,
+        tokenMint: mint,
+        systemProgram: SystemProgram.programId,
+        tokenProgram: spl.TOKEN_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([admin])
+      .rpc();
+
+    if (opts.liquidity ?? 100_000_000) {
+      await program.methods
+        .addLiquidity(new BN(opts.liquidity ?? 100_000_000))
+        .accounts({
+          poolState: pool,
+          admin: admin.publicKey,
+          adminTokenAccount: adminAta,
+          poolTokenAccount: poolAta,
+          poolAuthority,
+          tokenMint: mint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+    }
+
+    return {
+      admin,
+      poolState: pool,
+      poolAuthority,
+      tokenMint: mint,
+      adminTokenAccount: adminAta,
+      poolTokenAccount: poolAta,
+    };
+  };
+
+  const fundedTrader = async (pool: TestPool, amount = 10_000_000) => {
+    const trader = await fundedKeypair();
+    const ata = await spl.createAccount(
+      provider.connection,
+      wallet.payer,
+      pool.tokenMint,
+      trader.publicKey
+    );
+    await spl.mintTo(
+      provider.connection,
+      wallet.payer,
+      pool.tokenMint,
+      ata,
+      wallet.payer,
+      amount
+    );
+    return { trader, traderTokenAccount: ata };
+  };
+
+  const deriveReferralState = (pool: PublicKey, referrer: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("referral_state"), pool.toBuffer(), referrer.toBuffer()],
+      program.programId
+    )[0];
+
+  const trade = (
+    pool: TestPool,
+    trader: anchor.web3.Keypair,
+    traderTokenAccount: PublicKey,
+    amountIn: number,
+    minimumAmountOut = 0,
+    maxSlippageBps: BN = new BN(10_000),
+    referrer: PublicKey | null = null,
+    governanceTokenAccount: PublicKey | null = null,
+    allowPartialFill = false
+  ) =>
+    program.methods
+      .executeTrade(new BN(amountIn), new BN(minimumAmountOut), maxSlippageBps, referrer, allowPartialFill)
+      .accounts({
+        poolState: pool.poolState,
+        buyer: trader.publicKey,
+        buyerTokenAccount: traderTokenAccount,
+        poolTokenAccount: pool.poolTokenAccount,
+        poolAuthority: pool.poolAuthority,
+        tokenMint: pool.tokenMint,
+        referralState: referrer ? deriveReferralState(pool.poolState, referrer) : null,
+        instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+        tokenProgram: spl.TOKEN_PROGRAM_ID,
+        governanceTokenAccount,
+      })
+      .signers([trader]);
+
+  const captureEvents = async (name: string, action: () => Promise<unknown>) => {
+    const events: any[] = [];
+    const listener = program.addEventListener(name as any, (event) => events.push(event));
+    try {
+      await action();
+      await new Promise((resolve) => setTimeout(resolve, 500));
+    } finally {
+      await program.removeEventListener(listener);
+    }
+    return events;
+  };
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      const anchorErr = err as anchor.AnchorError;
+      assert.equal(anchorErr.error?.errorCode?.code, code);
+      return;
+    }
+    assert.fail(`expected ${code} error`);
+  };
+
+  const protectionUpdate = {
+    maxDailyVolume: new BN(5_000_000),
+    maxPriceImpactBps: new BN(500),
+    maxSlippageBps: new BN(300),
+    blacklistEnabled: true,
+    minTradeInterval: new BN(0),
+    minLiquidityFloor: new BN(0),
+    largeWithdrawalBps: new BN(2000),
+    circuitBreakerThreshold: new BN(1_000_000),
+    circuitBreakerWindow: new BN(3600),
+    circuitBreakerCooldown: new BN(3600),
+    circuitBreakerDecayBps: new BN(0),
+    rateLimitWindow: new BN(60),
+    rateLimitMax: 10,
+    maxHourlyVolume: new BN(10_000_000),
+    maxOutputFractionBps: new BN(10_000),
+    maxDailyVolumePerTrader: new BN(0),
+    lowReserve: { threshold: new BN(0), surchargeBps: new BN(0), maxTradeSize: new BN(0) },
+    feeWithdrawCooldown: new BN(0),
+    sandwich: {
+      enabled: false,
+      windowSlots: new BN(0),
+      largeTradeBps: new BN(0),
+      similarityBps: new BN(0),
+      surchargeBps: new BN(0),
+    },
+    maxHourlyTrades: 100,
+    maxTradesPerTx: 0,
+    volatility: { enabled: false, floorImpactBps: new BN(0), maxVolatilityBps: new BN(0) },
+    postResumeCooldown: new BN(0),
+    allowedCallers: [],
+  };
+
+  const scheduleUpdates = (pool: TestPool, updates: any[]) =>
+    program.methods
+      .scheduleParameterUpdates(updates)
+      .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+      .signers([pool.admin])
+      .rpc();
+
   it("Initializes the pool protection", async () => {
     const snipeProtectionSeconds = new BN(60);
     const earlyTradeFeeBps = new BN(500);
@@ -98,6 +432,7 @@ describe("hoe-dex-protection", () => {
         admin: wallet.publicKey,
         adminTokenAccount,
         poolTokenAccount,
+        tokenMint,
         tokenProgram: spl.TOKEN_PROGRAM_ID,
         clock: SYSVAR_CLOCK_PUBKEY,
       })
@@ -108,4 +443,4343 @@ describe("hoe-dex-protection", () => {
     assert.notEqual(state.poolStartTime.toNumber(), 0);
     assert.equal(poolTokenAcc.amount.toString(), amount.toString());
   });
-});
\ No newline at end of file
+
+  describe("pool initialization", () => {
+    it("emits PoolInitialized with the pool and admin", async () => {
+      let pool: TestPool;
+      const events = await captureEvents("poolInitialized", async () => {
+        pool = await setupPool({ liquidity: 0 });
+      });
+
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].pool.toString(), pool.poolState.toString());
+      assert.equal(events[0].adminPubkey.toString(), pool.admin.publicKey.toString());
+    });
+
+    it("applies a protection preset over the custom settings", async () => {
+      const pool = await setupPool({
+        liquidity: 0,
+        maxDailyVolume: 42_000_000,
+        rateLimitMax: 7,
+        preset: "conservative",
+      });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.maxDaily.toNumber(), 10_000_000);
+      assert.equal(state.protection.maxPriceImpactBps.toNumber(), 300);
+      assert.equal(state.protection.maxSlippageBps.toNumber(), 50);
+      assert.equal(state.protection.minTradeInterval.toNumber(), 30);
+      assert.equal(state.rateLimit.maxCalls, 30);
+      assert.equal(state.circuitBreaker.threshold.toNumber(), 250_000);
+      assert.equal(state.circuitBreaker.decayBps.toNumber(), 50);
+    });
+
+    it("stores a custom initial config", async () => {
+      const pool = await setupPool({
+        liquidity: 0,
+        maxDailyVolume: 42_000_000,
+        maxPriceImpactBps: 750,
+        maxSlippageBps: 250,
+        minTradeInterval: 30,
+        rateLimitMax: 7,
+        circuitBreakerThreshold: 2_500_000,
+        circuitBreakerDecayBps: 250,
+        autoPauseOnBreaker: true,
+        feeTiers: [
+          { volumeThreshold: new BN(5_000_000), feeBps: new BN(50), liquidityThreshold: null },
+          { volumeThreshold: new BN(50_000_000), feeBps: new BN(25), liquidityThreshold: null },
+        ],
+      });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.maxDaily.toNumber(), 42_000_000);
+      assert.equal(state.protection.maxPriceImpactBps.toNumber(), 750);
+      assert.equal(state.protection.maxSlippageBps.toNumber(), 250);
+      assert.equal(state.protection.minTradeInterval.toNumber(), 30);
+      assert.equal(state.rateLimit.maxCalls, 7);
+      assert.equal(state.circuitBreaker.threshold.toNumber(), 2_500_000);
+      assert.equal(state.circuitBreaker.decayBps.toNumber(), 250);
+      assert.isTrue(state.circuitBreaker.autoPauseOnBreaker);
+      assert.deepEqual(
+        state.feeTiers.map((t) => [t.volumeThreshold.toNumber(), t.feeBps.toNumber()]),
+        [
+          [5_000_000, 50],
+          [50_000_000, 25],
+        ]
+      );
+    });
+
+    it("rejects an initial config that fails the update validators", async () => {
+      await expectError(
+        setupPool({ liquidity: 0, maxSlippageBps: 10_001 }),
+        "InvalidProtectionSettings"
+      );
+    });
+
+    it("stores the mint account passed to initialize", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.tokenMint.toString(), pool.tokenMint.toString());
+    });
+
+    it("rejects a mint with a freeze authority", async () => {
+      await expectError(
+        setupPool({ freezeAuthority: wallet.publicKey, liquidity: 0 }),
+        "MintHasFreezeAuthority"
+      );
+    });
+
+    it("captures the mint decimals", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.tokenDecimals, 9);
+    });
+
+    it("rejects a mint with more decimals than the maximum", async () => {
+      const mint = await spl.createMint(provider.connection, wallet.payer, wallet.publicKey, null, 19);
+
+      await expectError(setupPool({ tokenMint: mint, liquidity: 0 }), "InvalidTokenDecimals");
+    });
+
+    const permanentDelegateMint = async (delegate: PublicKey) => {
+      const mint = anchor.web3.Keypair.generate();
+      const space = spl.getMintLen([spl.ExtensionType.PermanentDelegate]);
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+      const tx = new anchor.web3.Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: mint.publicKey,
+          space,
+          lamports,
+          programId: spl.TOKEN_2022_PROGRAM_ID,
+        }),
+        spl.createInitializePermanentDelegateInstruction(mint.publicKey, delegate, spl.TOKEN_2022_PROGRAM_ID),
+        spl.createInitializeMintInstruction(mint.publicKey, 9, wallet.publicKey, null, spl.TOKEN_2022_PROGRAM_ID)
+      );
+      await provider.sendAndConfirm(tx, [mint]);
+      return mint.publicKey;
+    };
+
+    const initializeWithMint = async (mint: PublicKey, allowRiskyMint: boolean) => {
+      const admin = await fundedKeypair();
+      const pool = derivePoolState(admin.publicKey);
+      const events = await captureEvents("riskyMintAllowed", () =>
+        program.methods
+          .initializePool(initialConfig({ allowRiskyMint }))
+          .accounts({
+            poolState: pool,
+            admin: admin.publicKey,
+            tokenMint: mint,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: spl.TOKEN_2022_PROGRAM_ID,
+            rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+          })
+          .signers([admin])
+          .rpc()
+      );
+      return { admin, pool, events };
+    };
+
+    it("rejects a permanent-delegate mint by default", async () => {
+      const mint = await permanentDelegateMint(wallet.publicKey);
+
+      await expectError(initializeWithMint(mint, false), "RiskyMintAuthority");
+    });
+
+    it("accepts a permanent-delegate mint under allow_risky_mint and names the delegate", async () => {
+      const mint = await permanentDelegateMint(wallet.publicKey);
+
+      const { pool, events } = await initializeWithMint(mint, true);
+
+      assert.lengthOf(events, 1);
+      assert.isTrue(events[0].permanentDelegate.equals(wallet.publicKey));
+      assert.isNull(events[0].closeAuthority);
+      const state = await program.account.poolState.fetch(pool);
+      assert.isTrue(state.allowRiskyMint);
+    });
+
+    it("takes liquidity into a Token-2022 pool", async () => {
+      const mint = await permanentDelegateMint(wallet.publicKey);
+      const { admin, pool } = await initializeWithMint(mint, true);
+      const poolAuthority = derivePoolAuthority(pool);
+      const createAccount = (owner: PublicKey, keypair?: anchor.web3.Keypair) =>
+        spl.createAccount(provider.connection, wallet.payer, mint, owner, keypair, undefined, spl.TOKEN_2022_PROGRAM_ID);
+      const adminTokenAccount = await createAccount(admin.publicKey);
+      const poolTokenAccount = await createAccount(poolAuthority, anchor.web3.Keypair.generate());
+      await spl.mintTo(
+        provider.connection,
+        wallet.payer,
+        mint,
+        adminTokenAccount,
+        wallet.payer,
+        1_000_000,
+        [],
+        undefined,
+        spl.TOKEN_2022_PROGRAM_ID
+      );
+
+      await program.methods
+        .addLiquidity(new BN(1_000_000))
+        .accounts({
+          poolState: pool,
+          admin: admin.publicKey,
+          adminTokenAccount,
+          poolTokenAccount,
+          poolAuthority,
+          tokenMint: mint,
+          tokenProgram: spl.TOKEN_2022_PROGRAM_ID,
+        })
+        .signers([admin])
+        .rpc();
+
+      const account = await spl.getAccount(provider.connection, poolTokenAccount, undefined, spl.TOKEN_2022_PROGRAM_ID);
+      assert.equal(account.amount.toString(), "1000000");
+    });
+
+    it("rejects a transfer-fee mint even under allow_risky_mint", async () => {
+      const mint = anchor.web3.Keypair.generate();
+      const space = spl.getMintLen([spl.ExtensionType.TransferFeeConfig]);
+      const lamports = await provider.connection.getMinimumBalanceForRentExemption(space);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          SystemProgram.createAccount({
+            fromPubkey: wallet.publicKey,
+            newAccountPubkey: mint.publicKey,
+            space,
+            lamports,
+            programId: spl.TOKEN_2022_PROGRAM_ID,
+          }),
+          spl.createInitializeTransferFeeConfigInstruction(
+            mint.publicKey,
+            wallet.publicKey,
+            wallet.publicKey,
+            100,
+            BigInt(1_000),
+            spl.TOKEN_2022_PROGRAM_ID
+          ),
+          spl.createInitializeMintInstruction(mint.publicKey, 9, wallet.publicKey, null, spl.TOKEN_2022_PROGRAM_ID)
+        ),
+        [mint]
+      );
+
+      await expectError(initializeWithMint(mint.publicKey, true), "InvalidTokenMint");
+    });
+  });
+
+  describe("slippage ceiling", () => {
+    it("enforces the pool ceiling when the caller requests u64::MAX", async () => {
+      // 5% early-trade fee puts slippage well above the 1% default ceiling
+      const pool = await setupPool({
+        earlyTradeFeeBps: 500,
+        earlyTradeWindowSeconds: 3600,
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 10_000, 0, new BN("18446744073709551615")).rpc(),
+        "SlippageExceeded"
+      );
+    });
+
+    it("honors a caller bound tighter than the pool ceiling", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      // Pool allows 100 bps; caller asks for zero slippage which the fee alone violates
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 10_000, 0, new BN(0)).rpc(),
+        "SlippageExceeded"
+      );
+    });
+  });
+
+  describe("emergency withdraw", () => {
+    const emergencyWithdraw = (
+      pool: TestPool,
+      signer: anchor.web3.Keypair,
+      recoveryTokenAccount: PublicKey
+    ) =>
+      program.methods
+        .emergencyWithdraw()
+        .accounts({
+          poolState: pool.poolState,
+          emergencyAdmin: signer.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          recoveryTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+
+    const setRecoveryAccount = (pool: TestPool, recoveryAccount: PublicKey) =>
+      program.methods
+        .setRecoveryAccount()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey, recoveryAccount })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rejects withdrawal to an account other than the recovery account", async () => {
+      const pool = await setupPool();
+      const recoveryAccount = await spl.createAccount(
+        provider.connection,
+        wallet.payer,
+        pool.tokenMint,
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate()
+      );
+      await setRecoveryAccount(pool, recoveryAccount);
+
+      await expectError(
+        emergencyWithdraw(pool, pool.admin, pool.adminTokenAccount),
+        "InvalidRecoveryAccount"
+      );
+    });
+
+    it("rejects withdrawal when the pool is not emergency paused", async () => {
+      const pool = await setupPool();
+      await setRecoveryAccount(pool, pool.adminTokenAccount);
+
+      await expectError(
+        emergencyWithdraw(pool, pool.admin, pool.adminTokenAccount),
+        "PoolNotPaused"
+      );
+    });
+
+    it("rejects withdrawal while the emergency pause is still timelocked", async () => {
+      const pool = await setupPool();
+      await setRecoveryAccount(pool, pool.adminTokenAccount);
+      await program.methods
+        .scheduleEmergencyPause()
+        .accounts({ poolState: pool.poolState, emergencyAdmin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+      // The pause itself cannot take effect before the timelock, so funds stay put
+      await expectError(
+        program.methods
+          .applyEmergencyPause()
+          .accounts({ poolState: pool.poolState, emergencyAdmin: pool.admin.publicKey })
+          .signers([pool.admin])
+          .rpc(),
+        "TimelockNotExpired"
+      );
+      await expectError(
+        emergencyWithdraw(pool, pool.admin, pool.adminTokenAccount),
+        "PoolNotPaused"
+      );
+      const poolTokenAcc = await spl.getAccount(provider.connection, pool.poolTokenAccount);
+      assert.equal(poolTokenAcc.amount.toString(), "100000000");
+    });
+  });
+
+  describe("emergency admin rotation", () => {
+    const setEmergencyAdmin = (pool: TestPool, newEmergencyAdmin: PublicKey) =>
+      program.methods
+        .setEmergencyAdmin(newEmergencyAdmin)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rotates the emergency admin", async () => {
+      const pool = await setupPool();
+      const newEmergencyAdmin = anchor.web3.Keypair.generate().publicKey;
+
+      await setEmergencyAdmin(pool, newEmergencyAdmin);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.emergencyAdmin.toString(), newEmergencyAdmin.toString());
+    });
+
+    it("rejects the default pubkey", async () => {
+      const pool = await setupPool();
+
+      await expectError(setEmergencyAdmin(pool, PublicKey.default), "InvalidEmergencyAdmin");
+    });
+  });
+
+  describe("pool config snapshot", () => {
+    it("returns a snapshot matching the stored account", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const config = await program.methods
+        .getPoolConfig()
+        .accounts({ poolState: pool.poolState })
+        .view();
+      const state = await program.account.poolState.fetch(pool.poolState);
+
+      assert.equal(config.rateLimit.maxCalls, state.rateLimit.maxCalls);
+      assert.equal(
+        config.circuitBreaker.threshold.toString(),
+        state.circuitBreaker.threshold.toString()
+      );
+      assert.equal(config.volume.maxDaily.toString(), state.volume.maxDaily.toString());
+      assert.equal(
+        config.protection.maxSlippageBps.toString(),
+        state.protection.maxSlippageBps.toString()
+      );
+      assert.equal(config.feeTiers.length, state.feeTiers.length);
+      assert.equal(config.feeTiersLocked, state.feeTiersLocked);
+    });
+
+    it("does not mutate the pool state", async () => {
+      const pool = await setupPool();
+      const before = await provider.connection.getAccountInfo(pool.poolState);
+
+      await program.methods.getPoolConfig().accounts({ poolState: pool.poolState }).view();
+
+      const after = await provider.connection.getAccountInfo(pool.poolState);
+      assert.isTrue(before.data.equals(after.data));
+    });
+  });
+
+  const deriveReentrancyGuard = (pool: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("reentrancy_guard"), pool.toBuffer()],
+      program.programId
+    )[0];
+
+  const batchBlacklist = (pool: TestPool, traders: PublicKey[], reasonCode = 0, expiresAt = 0) =>
+    program.methods
+      .batchBlacklistTraders(traders, reasonCode, new BN(expiresAt))
+      .accounts({
+        poolState: pool.poolState,
+        admin: pool.admin.publicKey,
+        reentrancyGuard: deriveReentrancyGuard(pool.poolState),
+      })
+      .signers([pool.admin])
+      .rpc();
+
+  describe("batch blacklist", () => {
+    it("collapses duplicate entries and counts only new additions", async () => {
+      const pool = await setupPool();
+      const a = anchor.web3.Keypair.generate().publicKey;
+      const b = anchor.web3.Keypair.generate().publicKey;
+
+      const events = await captureEvents("batchBlacklistCompleted", () =>
+        batchBlacklist(pool, [a, a, b, a])
+      );
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.traderBlacklist.length, 2);
+      assert.equal(events[0].count.toNumber(), 2);
+    });
+
+    it("skips already-blacklisted addresses", async () => {
+      const pool = await setupPool();
+      const a = anchor.web3.Keypair.generate().publicKey;
+      const b = anchor.web3.Keypair.generate().publicKey;
+      await batchBlacklist(pool, [a]);
+
+      const events = await captureEvents("batchBlacklistCompleted", () =>
+        batchBlacklist(pool, [a, b])
+      );
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.traderBlacklist.length, 2);
+      assert.equal(events[0].count.toNumber(), 1);
+    });
+
+    it("rejects a batch containing the default pubkey", async () => {
+      const pool = await setupPool();
+      const a = anchor.web3.Keypair.generate().publicKey;
+
+      await expectError(batchBlacklist(pool, [a, PublicKey.default]), "InvalidTraderAddress");
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.traderBlacklist.length, 0);
+    });
+  });
+
+  describe("blacklist size limits", () => {
+    const randomKeys = (n: number) =>
+      Array.from({ length: n }, () => anchor.web3.Keypair.generate().publicKey);
+
+    it("rejects a batch over the batch size limit with BlacklistBatchTooLarge", async () => {
+      const pool = await setupPool();
+
+      await expectError(batchBlacklist(pool, randomKeys(51)), "BlacklistBatchTooLarge");
+    });
+
+    const setMaxBlacklistBatch = (pool: TestPool, maxBlacklistBatch: number) =>
+      program.methods
+        .setMaxBlacklistBatch(maxBlacklistBatch)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("accepts a batch at the configured limit and rejects one over it", async () => {
+      const pool = await setupPool();
+      await setMaxBlacklistBatch(pool, 5);
+
+      await batchBlacklist(pool, randomKeys(5));
+      await expectError(batchBlacklist(pool, randomKeys(6)), "BlacklistBatchTooLarge");
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.traderBlacklist.length, 5);
+    });
+
+    it("rejects a configured limit above the hard cap", async () => {
+      const pool = await setupPool();
+
+      await expectError(setMaxBlacklistBatch(pool, 201), "InvalidMaxBlacklistBatch");
+      await expectError(setMaxBlacklistBatch(pool, 0), "InvalidMaxBlacklistBatch");
+    });
+
+    it("rejects additions past the blacklist capacity with BlacklistFull", async () => {
+      const pool = await setupPool();
+      for (let i = 0; i < 20; i++) {
+        await batchBlacklist(pool, randomKeys(50));
+      }
+
+      await expectError(batchBlacklist(pool, randomKeys(1)), "BlacklistFull");
+    });
+  });
+
+  describe("blacklist pagination", () => {
+    const getPage = (pool: TestPool, offset: number, limit: number) =>
+      program.methods
+        .getBlacklistPage(offset, limit)
+        .accounts({ poolState: pool.poolState })
+        .view();
+
+    it("enumerates a multi-page blacklist in stable order", async () => {
+      const pool = await setupPool();
+      const traders = Array.from({ length: 45 }, () => anchor.web3.Keypair.generate().publicKey);
+      await batchBlacklist(pool, traders);
+
+      const first = await getPage(pool, 0, 20);
+      const second = await getPage(pool, 20, 20);
+      const third = await getPage(pool, 40, 20);
+
+      assert.equal(first.totalCount, 45);
+      assert.equal(third.entries.length, 5);
+      const all = [...first.entries, ...second.entries, ...third.entries].map((e) => e.trader.toString());
+      assert.deepEqual(all, traders.map((k) => k.toString()));
+    });
+
+    it("caps the page size and returns an empty page past the end", async () => {
+      const pool = await setupPool();
+      await batchBlacklist(
+        pool,
+        Array.from({ length: 40 }, () => anchor.web3.Keypair.generate().publicKey)
+      );
+
+      const capped = await getPage(pool, 0, 1000);
+      const past = await getPage(pool, 100, 10);
+
+      assert.equal(capped.entries.length, 30);
+      assert.equal(past.entries.length, 0);
+      assert.equal(past.totalCount, 40);
+    });
+  });
+
+  describe("blacklist status", () => {
+    it("reports blacklisted and non-blacklisted traders", async () => {
+      const pool = await setupPool();
+      const blocked = anchor.web3.Keypair.generate().publicKey;
+      const allowed = anchor.web3.Keypair.generate().publicKey;
+      await batchBlacklist(pool, [blocked]);
+
+      const check = (trader: PublicKey) =>
+        program.methods
+          .checkBlacklistStatus(trader)
+          .accounts({ poolState: pool.poolState })
+          .view();
+
+      assert.isTrue(await check(blocked));
+      assert.isFalse(await check(allowed));
+    });
+  });
+
+  describe("blacklist reasons and expiry", () => {
+    const chainTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+
+    it("records the reason code and expiry on the entry and event", async () => {
+      const pool = await setupPool();
+      const trader = anchor.web3.Keypair.generate().publicKey;
+      const expiresAt = (await chainTime()) + 3600;
+
+      const events = await captureEvents("traderBlacklisted", () =>
+        batchBlacklist(pool, [trader], 7, expiresAt)
+      );
+
+      const [entry] = (await program.account.poolState.fetch(pool.poolState)).traderBlacklist;
+      assert.equal(entry.trader.toString(), trader.toString());
+      assert.equal(entry.reasonCode, 7);
+      assert.equal(entry.expiresAt.toNumber(), expiresAt);
+      assert.equal(events[0].reasonCode, 7);
+      assert.equal(events[0].expiresAt.toNumber(), expiresAt);
+    });
+
+    it("blocks trades from a blacklisted trader", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await batchBlacklist(pool, [trader.publicKey], 1);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 10_000).rpc(), "TraderBlacklisted");
+    });
+
+    it("stops reporting a time-limited ban once it expires", async () => {
+      const pool = await setupPool();
+      const trader = anchor.web3.Keypair.generate().publicKey;
+      await batchBlacklist(pool, [trader], 2, (await chainTime()) + 2);
+
+      const check = () =>
+        program.methods.checkBlacklistStatus(trader).accounts({ poolState: pool.poolState }).view();
+
+      assert.isTrue(await check());
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      assert.isFalse(await check());
+    });
+
+    it("lets an expired entry trade and prunes it", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await batchBlacklist(pool, [trader.publicKey], 2, (await chainTime()) + 2);
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      const events = await captureEvents("traderRemovedFromBlacklist", () =>
+        trade(pool, trader, traderTokenAccount, 10_000).rpc()
+      );
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.lengthOf(state.traderBlacklist, 0);
+      assert.lengthOf(events, 1);
+    });
+
+    it("rejects an expiry in the past", async () => {
+      const pool = await setupPool();
+      const trader = anchor.web3.Keypair.generate().publicKey;
+
+      await expectError(
+        batchBlacklist(pool, [trader], 1, (await chainTime()) - 60),
+        "InvalidBlacklistExpiry"
+      );
+    });
+  });
+
+  describe("per-trader cooldown", () => {
+    const deriveTraderState = (pool: PublicKey, trader: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("trader_state"), pool.toBuffer(), trader.toBuffer()],
+        program.programId
+      )[0];
+
+    it("lets the first trade through and records its timestamp", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const traderState = await program.account.traderState.fetch(
+        deriveTraderState(pool.poolState, trader.publicKey)
+      );
+      assert.isAbove(traderState.lastTradeTs.toNumber(), 0);
+    });
+
+    it("allows back-to-back trades when the interval is zero", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.protection.minTradeInterval.toNumber(), 0);
+    });
+  });
+
+  describe("insurance fee split", () => {
+    const setupInsurance = async (pool: TestPool, shareBps: number) => {
+      const insuranceFund = await spl.createAccount(
+        provider.connection,
+        wallet.payer,
+        pool.tokenMint,
+        anchor.web3.Keypair.generate().publicKey,
+        anchor.web3.Keypair.generate()
+      );
+      await program.methods
+        .configureInsuranceFund(new BN(shareBps))
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          insuranceFund,
+        })
+        .signers([pool.admin])
+        .rpc();
+      return insuranceFund;
+    };
+
+    const withdrawInsurance = (pool: TestPool, insuranceFund: PublicKey) =>
+      program.methods
+        .withdrawInsuranceFund()
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          insuranceFund,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("splits the fee and rounds the insurance share down", async () => {
+      const pool = await setupPool();
+      await setupInsurance(pool, 3333);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      // 30 bps of 100_000 = 300; 33.33% of 300 = 99.99 -> 99 to insurance
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.insuranceCollected.toNumber(), 99);
+      assert.equal(state.totalFeesCollected.toNumber(), 201);
+    });
+
+    it("handles 0% and 100% splits", async () => {
+      const none = await setupPool();
+      const all = await setupPool();
+      await setupInsurance(all, 10_000);
+      const t1 = await fundedTrader(none);
+      const t2 = await fundedTrader(all);
+
+      await trade(none, t1.trader, t1.traderTokenAccount, 100_000).rpc();
+      await trade(all, t2.trader, t2.traderTokenAccount, 100_000).rpc();
+
+      const noneState = await program.account.poolState.fetch(none.poolState);
+      const allState = await program.account.poolState.fetch(all.poolState);
+      assert.equal(noneState.insuranceCollected.toNumber(), 0);
+      assert.equal(noneState.totalFeesCollected.toNumber(), 300);
+      assert.equal(allState.insuranceCollected.toNumber(), 300);
+      assert.equal(allState.totalFeesCollected.toNumber(), 0);
+    });
+
+    it("withdraws the insurance portion independently of treasury fees", async () => {
+      const pool = await setupPool();
+      const insuranceFund = await setupInsurance(pool, 2500);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      await withdrawInsurance(pool, insuranceFund);
+
+      const fund = await spl.getAccount(provider.connection, insuranceFund);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(fund.amount.toString(), "75");
+      assert.equal(state.insuranceCollected.toNumber(), 0);
+      assert.equal(state.totalFeesCollected.toNumber(), 225);
+      await expectError(withdrawInsurance(pool, insuranceFund), "NoFeesAvailable");
+    });
+  });
+
+  describe("circuit breaker auto-pause", () => {
+    const resetCircuitBreaker = (pool: TestPool) =>
+      program.methods
+        .resetCircuitBreaker()
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          reentrancyGuard: deriveReentrancyGuard(pool.poolState),
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("reverts a breaching trade without pausing when auto-pause is off", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 50_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 60_000).rpc(),
+        "CircuitBreakerTriggered"
+      );
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.pauseLevel, { none: {} });
+    });
+
+    it("pauses the pool on a breach and blocks later trades", async () => {
+      const pool = await setupPool({
+        circuitBreakerThreshold: 50_000,
+        autoPauseOnBreaker: true,
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.pauseLevel, { tradingOnly: {} });
+      assert.isTrue(state.circuitBreaker.pausedByBreaker);
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "PoolPaused");
+    });
+
+    it("clears both the breaker and the pause on reset", async () => {
+      const pool = await setupPool({
+        circuitBreakerThreshold: 50_000,
+        circuitBreakerCooldown: 0,
+        autoPauseOnBreaker: true,
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+
+      await resetCircuitBreaker(pool);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.pauseLevel, { none: {} });
+      assert.isFalse(state.circuitBreaker.pausedByBreaker);
+      assert.equal(state.circuitBreaker.currentAmount.toNumber(), 0);
+      await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+    });
+  });
+
+  describe("circuit breaker events", () => {
+    it("emits nothing for a normal trade", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 1_000_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("circuitBreakerTriggered", () =>
+        trade(pool, trader, traderTokenAccount, 10_000).rpc()
+      );
+
+      assert.lengthOf(events, 0);
+    });
+
+    it("emits an approaching warning once utilization passes the high-water mark", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 100_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("circuitBreakerApproaching", async () => {
+        await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 35_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 5_000).rpc();
+      });
+
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].currentAmount.toNumber(), 85_000);
+    });
+
+    it("emits the approaching warning at a configured threshold", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 100_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await program.methods
+        .setCircuitBreakerWarnThreshold(new BN(9_000))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+      const events = await captureEvents("circuitBreakerApproaching", async () => {
+        await trade(pool, trader, traderTokenAccount, 85_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 5_000).rpc();
+      });
+
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].currentAmount.toNumber(), 90_000);
+      await expectError(trade(pool, trader, traderTokenAccount, 10_001).rpc(), "CircuitBreakerTriggered");
+    });
+
+    it("rejects a warning threshold above 10000 bps", async () => {
+      const pool = await setupPool();
+
+      await expectError(
+        program.methods
+          .setCircuitBreakerWarnThreshold(new BN(10_001))
+          .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+          .signers([pool.admin])
+          .rpc(),
+        "InvalidCircuitBreakerSettings"
+      );
+    });
+
+    it("emits the trigger event exactly once on breach", async () => {
+      const pool = await setupPool({
+        circuitBreakerThreshold: 50_000,
+        autoPauseOnBreaker: true,
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("circuitBreakerTriggered", () =>
+        trade(pool, trader, traderTokenAccount, 60_000).rpc()
+      );
+
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].threshold.toNumber(), 50_000);
+    });
+  });
+
+  describe("rolling volume buckets", () => {
+    it("records trades into the current hour's bucket", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+      await trade(pool, trader, traderTokenAccount, 20_000).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      const bucketIndex = state.volume.bucketHour.toNumber() % 24;
+      assert.equal(state.volume.hourlyBuckets[bucketIndex].toNumber(), 30_000);
+      const rollingSum = state.volume.hourlyBuckets.reduce((sum, b) => sum + b.toNumber(), 0);
+      assert.equal(state.volume.volume24h.toNumber(), rollingSum);
+    });
+
+    it("rejects a trade that would push the rolling sum past the daily cap", async () => {
+      const pool = await setupPool({ maxDailyVolume: 25_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 20_000).rpc();
+
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 10_000).rpc(),
+        "VolumeLimitExceeded"
+      );
+    });
+  });
+
+  describe("batched parameter updates", () => {
+    it("stores every update of the batch under one timelock", async () => {
+      const pool = await setupPool();
+
+      await scheduleUpdates(pool, [
+        { fee: { 0: { feeTiers: defaultFeeTiers(), feeTiersLocked: true, feeRounding: { down: {} }, protocolFeeBps: new BN(0), feeFreeThreshold: new BN(0) } } },
+        { protection: { 0: protectionUpdate } },
+        { state: { 0: { pauseLevel: { none: {} }, isEmergencyPaused: false } } },
+      ]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.lengthOf(state.pendingUpdate.updates, 3);
+    });
+
+    it("applies nothing before the timelock expires", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+
+      await expectError(
+        program.methods
+          .applyParameterUpdate()
+          .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+          .signers([pool.admin])
+          .rpc(),
+        "TimelockNotExpired"
+      );
+    });
+
+    it("rejects an empty batch", async () => {
+      const pool = await setupPool();
+
+      await expectError(scheduleUpdates(pool, []), "InvalidAmount");
+    });
+  });
+
+  describe("per-category timelocks", () => {
+    const categoryUpdates: Record<string, any> = {
+      trade: {
+        trade: {
+          0: {
+            earlyTradeFeeBps: new BN(100),
+            earlyTradeWindowSeconds: new BN(60),
+            maxTradeSizeBps: new BN(500),
+            minTradeSize: new BN(1),
+            cooldownSeconds: new BN(0),
+          },
+        },
+      },
+      protection: { protection: { 0: protectionUpdate } },
+      fee: { fee: { 0: { feeTiers: defaultFeeTiers(), feeTiersLocked: false, feeRounding: { down: {} }, protocolFeeBps: new BN(0), feeFreeThreshold: new BN(0) } } },
+      state: { state: { 0: { pauseLevel: { none: {} }, isEmergencyPaused: false } } },
+    };
+
+    const scheduledDelay = async (pool: TestPool, updates: any[]) => {
+      const before = await provider.connection.getBlockTime(await provider.connection.getSlot());
+      const events = await captureEvents("parameterUpdateScheduled", () =>
+        scheduleUpdates(pool, updates)
+      );
+      assert.lengthOf(events, 1);
+      return events[0].scheduledTime.toNumber() - before;
+    };
+
+    for (const [category, update] of Object.entries(categoryUpdates)) {
+      it(`schedules ${category} updates with the ${category} timelock`, async () => {
+        const pool = await setupPool();
+        const { timelocks } = await program.account.poolState.fetch(pool.poolState);
+
+        const delay = await scheduledDelay(pool, [update]);
+
+        assert.approximately(delay, timelocks[`${category}Seconds`].toNumber(), 5);
+      });
+    }
+
+    it("timelocks changes to the timelocks by the longest existing value", async () => {
+      const pool = await setupPool();
+      const { timelocks } = await program.account.poolState.fetch(pool.poolState);
+      const longest = Math.max(
+        timelocks.tradeSeconds.toNumber(),
+        timelocks.protectionSeconds.toNumber(),
+        timelocks.feeSeconds.toNumber(),
+        timelocks.stateSeconds.toNumber()
+      );
+
+      const delay = await scheduledDelay(pool, [
+        {
+          timelocks: {
+            0: {
+              tradeSeconds: new BN(6 * 3600),
+              protectionSeconds: new BN(12 * 3600),
+              feeSeconds: new BN(48 * 3600),
+              stateSeconds: new BN(3600),
+              emergencyPauseSeconds: new BN(300),
+              emergencyResumeSeconds: new BN(3600),
+            },
+          },
+        },
+      ]);
+
+      assert.approximately(delay, longest, 5);
+    });
+
+    it("rejects timelocks below the category minimum", async () => {
+      const pool = await setupPool();
+
+      await expectError(
+        scheduleUpdates(pool, [
+          {
+            timelocks: {
+              0: {
+                tradeSeconds: new BN(6 * 3600),
+                protectionSeconds: new BN(12 * 3600),
+                feeSeconds: new BN(60),
+                stateSeconds: new BN(3600),
+                emergencyPauseSeconds: new BN(300),
+                emergencyResumeSeconds: new BN(3600),
+              },
+            },
+          },
+        ]),
+        "InvalidTimelockDuration"
+      );
+    });
+
+    it("rejects timelocks beyond the maximum", async () => {
+      const pool = await setupPool();
+
+      await expectError(
+        scheduleUpdates(pool, [
+          {
+            timelocks: {
+              0: {
+                tradeSeconds: new BN(30 * 86400 + 1),
+                protectionSeconds: new BN(12 * 3600),
+                feeSeconds: new BN(48 * 3600),
+                stateSeconds: new BN(3600),
+                emergencyPauseSeconds: new BN(300),
+                emergencyResumeSeconds: new BN(3600),
+              },
+            },
+          },
+        ]),
+        "InvalidTimelockDuration"
+      );
+    });
+  });
+
+  describe("guardian veto", () => {
+    const setGuardian = (pool: TestPool, guardian: PublicKey | null) =>
+      program.methods
+        .setGuardian(guardian)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const veto = (pool: TestPool, signer: anchor.web3.Keypair) =>
+      program.methods
+        .vetoPendingUpdate()
+        .accounts({ poolState: pool.poolState, guardian: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    it("lets the guardian veto a pending update", async () => {
+      const pool = await setupPool();
+      const guardian = await fundedKeypair();
+      await setGuardian(pool, guardian.publicKey);
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+
+      const events = await captureEvents("parameterUpdateVetoed", () => veto(pool, guardian));
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isNull(state.pendingUpdate);
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].guardianPubkey.toString(), guardian.publicKey.toString());
+    });
+
+    it("rejects a veto from a non-guardian", async () => {
+      const pool = await setupPool();
+      const guardian = await fundedKeypair();
+      const impostor = await fundedKeypair();
+      await setGuardian(pool, guardian.publicKey);
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+
+      await expectError(veto(pool, impostor), "InvalidGuardian");
+    });
+
+    it("does not let the guardian schedule updates", async () => {
+      const pool = await setupPool();
+      const guardian = await fundedKeypair();
+      await setGuardian(pool, guardian.publicKey);
+
+      await expectError(
+        program.methods
+          .scheduleParameterUpdates([{ protection: { 0: protectionUpdate } }])
+          .accounts({ poolState: pool.poolState, admin: guardian.publicKey })
+          .signers([guardian])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+
+    it("still lets the admin cancel", async () => {
+      const pool = await setupPool();
+      const guardian = await fundedKeypair();
+      await setGuardian(pool, guardian.publicKey);
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+
+      await program.methods
+        .cancelParameterUpdate()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isNull(state.pendingUpdate);
+    });
+  });
+
+  describe("reserve sync", () => {
+    const syncReserves = (pool: TestPool) =>
+      program.methods
+        .syncReserves()
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("reconciles a direct transfer into the pool account", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await spl.transfer(
+        provider.connection,
+        wallet.payer,
+        pool.adminTokenAccount,
+        pool.poolTokenAccount,
+        pool.admin,
+        250_000
+      );
+
+      const events = await captureEvents("reservesSynced", () => syncReserves(pool));
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalLiquidity.toNumber(), 1_250_000);
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].delta.toNumber(), 250_000);
+    });
+
+    it("reports a zero delta when liquidity is already in sync", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+
+      const events = await captureEvents("reservesSynced", () => syncReserves(pool));
+
+      assert.equal(events[0].delta.toNumber(), 0);
+      assert.equal(events[0].newLiquidity.toNumber(), 1_000_000);
+    });
+
+    it("rejects a sync by anyone but the admin", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      const outsider = await fundedKeypair();
+
+      await expectError(
+        program.methods
+          .syncReserves()
+          .accounts({
+            poolState: pool.poolState,
+            admin: outsider.publicKey,
+            poolTokenAccount: pool.poolTokenAccount,
+            poolAuthority: pool.poolAuthority,
+          })
+          .signers([outsider])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+
+    it("holds synced-in liquidity behind the grace period", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, liquidityGracePeriod: 3600 });
+      await spl.transfer(
+        provider.connection,
+        wallet.payer,
+        pool.adminTokenAccount,
+        pool.poolTokenAccount,
+        pool.admin,
+        250_000
+      );
+
+      await syncReserves(pool);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalLiquidity.toNumber(), 1_250_000);
+      assert.lengthOf(state.pendingDeposits, 1);
+      assert.equal(state.pendingDeposits[0].amount.toNumber(), 250_000);
+    });
+  });
+
+  describe("fee custody", () => {
+    it("withdraws exactly the accrued fees and leaves liquidity intact", async () => {
+      const pool = await setupPool({ liquidity: 50_000_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      for (const amount of [100_000, 250_000, 400_000]) {
+        await trade(pool, trader, traderTokenAccount, amount).rpc();
+      }
+
+      const before = await program.account.poolState.fetch(pool.poolState);
+      const fees = before.totalFeesCollected.toNumber();
+      assert.isAbove(fees, 0);
+      const adminBefore = await spl.getAccount(provider.connection, pool.adminTokenAccount);
+
+      await program.methods
+        .withdrawFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          feeAuthorityTokenAccount: pool.adminTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+      const after = await program.account.poolState.fetch(pool.poolState);
+      const adminAfter = await spl.getAccount(provider.connection, pool.adminTokenAccount);
+      const poolAccount = await spl.getAccount(provider.connection, pool.poolTokenAccount);
+      assert.equal(Number(adminAfter.amount - adminBefore.amount), fees);
+      assert.equal(after.totalFeesCollected.toNumber(), 0);
+      assert.equal(after.totalLiquidity.toNumber(), before.totalLiquidity.toNumber());
+      assert.equal(
+        Number(poolAccount.amount),
+        after.totalLiquidity.toNumber() + after.insuranceCollected.toNumber()
+      );
+    });
+
+    it("credits liquidity with only the non-fee portion of a trade", async () => {
+      const pool = await setupPool({ liquidity: 50_000_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 1_000_000).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      const poolAccount = await spl.getAccount(provider.connection, pool.poolTokenAccount);
+      assert.equal(
+        Number(poolAccount.amount),
+        state.totalLiquidity.toNumber() +
+          state.totalFeesCollected.toNumber() +
+          state.insuranceCollected.toNumber()
+      );
+    });
+  });
+
+  describe("per-pool fee tier cap", () => {
+    const spacedFeeTiers = (count: number) =>
+      Array.from({ length: count }, (_, i) => ({
+        volumeThreshold: new BN((i + 1) * 1_000_000),
+        feeBps: new BN(100 - i * 10),
+        liquidityThreshold: null,
+      }));
+
+    const setMaxFeeTiers = (pool: TestPool, max: number) =>
+      program.methods
+        .setMaxFeeTiers(max)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rejects more tiers than the default cap at initialization", async () => {
+      await expectError(setupPool({ feeTiers: spacedFeeTiers(9) }), "TooManyFeeTiers");
+    });
+
+    it("rejects a fee update over the per-pool cap even under the hard limit", async () => {
+      const pool = await setupPool();
+
+      await expectError(
+        scheduleUpdates(pool, [{ fee: { 0: { feeTiers: spacedFeeTiers(9), feeTiersLocked: false, feeRounding: { down: {} }, protocolFeeBps: new BN(0), feeFreeThreshold: new BN(0) } } }]),
+        "TooManyFeeTiers"
+      );
+    });
+
+    it("accepts the larger tier set once the admin raises the cap", async () => {
+      const pool = await setupPool();
+      await setMaxFeeTiers(pool, 9);
+
+      await scheduleUpdates(pool, [{ fee: { 0: { feeTiers: spacedFeeTiers(9), feeTiersLocked: false, feeRounding: { down: {} }, protocolFeeBps: new BN(0), feeFreeThreshold: new BN(0) } } }]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.maxFeeTiers, 9);
+      assert.lengthOf(state.pendingUpdate.updates, 1);
+    });
+
+    it("rejects a cap above the hard limit", async () => {
+      const pool = await setupPool();
+
+      await expectError(setMaxFeeTiers(pool, 101), "InvalidMaxFeeTiers");
+    });
+  });
+
+  describe("fee tier spacing", () => {
+    it("rejects consecutive tiers whose fees differ by less than the minimum spacing", async () => {
+      await expectError(
+        setupPool({
+          feeTiers: [
+            { volumeThreshold: new BN(1_000_000), feeBps: new BN(30), liquidityThreshold: null },
+            { volumeThreshold: new BN(10_000_000), feeBps: new BN(25), liquidityThreshold: null },
+          ],
+        }),
+        "InvalidFeeTierSpacing"
+      );
+    });
+
+    it("accepts tiers spaced exactly at the minimum", async () => {
+      const pool = await setupPool({
+        feeTiers: [
+          { volumeThreshold: new BN(1_000_000), feeBps: new BN(30), liquidityThreshold: null },
+          { volumeThreshold: new BN(1_000_001), feeBps: new BN(20), liquidityThreshold: null },
+        ],
+      });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.lengthOf(state.feeTiers, 2);
+    });
+  });
+
+  describe("pause levels", () => {
+    const setPauseLevel = (pool: TestPool, pauseLevel: object) =>
+      program.methods
+        .setPauseLevel(pauseLevel)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const adminAction = (pool: TestPool) =>
+      program.methods
+        .setMaxFeeTiers(10)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("allows trades and admin actions with no pause", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+      await adminAction(pool);
+    });
+
+    it("halts trading but not admin actions when trading-only", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await setPauseLevel(pool, { tradingOnly: {} });
+
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "PoolPaused");
+      await adminAction(pool);
+    });
+
+    it("halts trading and admin actions when full, except unpausing", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await setPauseLevel(pool, { full: {} });
+
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "PoolPaused");
+      await expectError(adminAction(pool), "PoolFrozen");
+      await expectError(setPauseLevel(pool, { tradingOnly: {} }), "PoolFrozen");
+
+      await setPauseLevel(pool, { none: {} });
+      await adminAction(pool);
+      await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+    });
+
+    it("lets toggle_pause lift a full pause", async () => {
+      const pool = await setupPool();
+      await setPauseLevel(pool, { full: {} });
+
+      await program.methods
+        .togglePause()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.pauseLevel, { none: {} });
+    });
+  });
+
+  describe("event sequence numbers", () => {
+    it("strictly increases across trades and fee withdrawals", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      let trades: any[] = [];
+      const withdrawals = await captureEvents("feesWithdrawn", async () => {
+        trades = await captureEvents("tradeExecuted", async () => {
+          for (const amount of [10_000, 20_000, 30_000]) {
+            await trade(pool, trader, traderTokenAccount, amount).rpc();
+          }
+        });
+        await program.methods
+          .withdrawFees()
+          .accounts({
+            poolState: pool.poolState,
+            feeAuthority: pool.admin.publicKey,
+            poolTokenAccount: pool.poolTokenAccount,
+            feeAuthorityTokenAccount: pool.adminTokenAccount,
+            poolAuthority: pool.poolAuthority,
+            tokenMint: pool.tokenMint,
+            tokenProgram: spl.TOKEN_PROGRAM_ID,
+          })
+          .signers([pool.admin])
+          .rpc();
+      });
+
+      const seqs = [...trades, ...withdrawals].map((e) => e.eventSeq.toNumber());
+      assert.lengthOf(seqs, 4);
+      for (let i = 1; i < seqs.length; i++) {
+        assert.isAbove(seqs[i], seqs[i - 1]);
+      }
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.eventSeq.toNumber(), seqs[seqs.length - 1]);
+    });
+  });
+
+  describe("liquidity floor", () => {
+    const removeLiquidity = (pool: TestPool, amount: number) =>
+      program.methods
+        .removeLiquidity(new BN(amount))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rejects a removal that would cross the floor", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, minLiquidityFloor: 400_000 });
+
+      await expectError(removeLiquidity(pool, 600_001), "BelowLiquidityFloor");
+    });
+
+    it("allows a removal that stays at or above the floor", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, minLiquidityFloor: 400_000 });
+
+      await removeLiquidity(pool, 600_000);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalLiquidity.toNumber(), 400_000);
+    });
+
+    it("allows draining the pool when the floor is zero", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+
+      await removeLiquidity(pool, 1_000_000);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalLiquidity.toNumber(), 0);
+    });
+  });
+
+  describe("large withdrawal guard", () => {
+    const removeLiquidity = (pool: TestPool, amount: number) =>
+      program.methods
+        .removeLiquidity(new BN(amount))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("flags a large withdrawal and counts it against the circuit breaker", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+
+      const events = await captureEvents("largeWithdrawalFlagged", () =>
+        removeLiquidity(pool, 300_000)
+      );
+
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].withdrawalBps.toNumber(), 3000);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.circuitBreaker.currentAmount.toNumber(), 300_000);
+    });
+
+    it("rejects a large withdrawal that trips the circuit breaker", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, circuitBreakerThreshold: 100_000 });
+
+      await expectError(removeLiquidity(pool, 300_000), "CircuitBreakerTriggered");
+    });
+
+    it("leaves a small withdrawal unaffected", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, circuitBreakerThreshold: 50_000 });
+
+      const events = await captureEvents("largeWithdrawalFlagged", () =>
+        removeLiquidity(pool, 100_000)
+      );
+
+      assert.lengthOf(events, 0);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalLiquidity.toNumber(), 900_000);
+      assert.equal(state.circuitBreaker.currentAmount.toNumber(), 0);
+    });
+  });
+
+  describe("batched trades", () => {
+    const leg = (amountIn: number, minimumAmountOut = 0) => ({
+      amountIn: new BN(amountIn),
+      minimumAmountOut: new BN(minimumAmountOut),
+      maxSlippageBps: new BN(10_000),
+    });
+
+    const tradeBatch = (pool: TestPool, trader: anchor.web3.Keypair, ata: PublicKey, legs: any[]) =>
+      program.methods
+        .executeTradesBatch(legs)
+        .accounts({
+          poolState: pool.poolState,
+          buyer: trader.publicKey,
+          buyerTokenAccount: ata,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          referralState: null,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+          governanceTokenAccount: null,
+        })
+        .signers([trader]);
+
+    it("reports a failing leg and still executes the others", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const legs = [leg(10_000), leg(10_000, 1_000_000_000), leg(20_000)];
+
+      let executed: any[] = [];
+      const failures = await captureEvents("tradeExecutionFailed", async () => {
+        executed = await captureEvents("tradeExecuted", () =>
+          tradeBatch(pool, trader, traderTokenAccount, legs).rpc()
+        );
+      });
+
+      assert.lengthOf(failures, 1);
+      assert.equal(failures[0].amountIn.toNumber(), 10_000);
+      assert.lengthOf(executed, 2);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.volume24h.toNumber(), 30_000);
+    });
+
+    it("reports a reason code for each rejected leg", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const legs = [leg(0), leg(10_000, 1_000_000_000), leg(10_000)];
+
+      const rejections = await captureEvents("tradeRejected", () =>
+        tradeBatch(pool, trader, traderTokenAccount, legs).rpc()
+      );
+
+      assert.deepEqual(
+        rejections.map((event) => event.reasonCode),
+        [6, 8]
+      );
+      assert.isTrue(rejections[0].trader.equals(trader.publicKey));
+    });
+
+    it("reports a leg over the daily volume cap", async () => {
+      const pool = await setupPool({ maxDailyVolume: 25_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const hits = await captureEvents("volumeLimitHit", () =>
+        tradeBatch(pool, trader, traderTokenAccount, [leg(20_000), leg(10_000)]).rpc()
+      );
+
+      assert.lengthOf(hits, 1);
+      assert.equal(hits[0].attemptedVolume.toNumber(), 30_000);
+      assert.equal(hits[0].maxDaily.toNumber(), 25_000);
+    });
+
+    it("logs no breaker trip for a leg it rolls back", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 15_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      let failures: any[] = [];
+      const trips = await captureEvents("circuitBreakerTriggered", async () => {
+        failures = await captureEvents("tradeExecutionFailed", () =>
+          tradeBatch(pool, trader, traderTokenAccount, [leg(10_000), leg(10_000)]).rpc()
+        );
+      });
+
+      assert.lengthOf(failures, 1);
+      assert.lengthOf(trips, 0);
+    });
+
+    it("skips a leg that auto-pauses the pool without counting it as executed", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 15_000, autoPauseOnBreaker: true });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      let executed: any[] = [];
+      const trips = await captureEvents("circuitBreakerTriggered", async () => {
+        executed = await captureEvents("tradeExecuted", () =>
+          tradeBatch(pool, trader, traderTokenAccount, [leg(10_000), leg(10_000)]).rpc()
+        );
+      });
+
+      assert.lengthOf(executed, 1);
+      assert.lengthOf(trips, 1);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.pauseLevel, { tradingOnly: {} });
+      assert.equal(state.volume.volume24h.toNumber(), 10_000);
+    });
+
+    it("counts failed legs until the admin resets them", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const failureStats = () =>
+        program.methods.getFailureStats().accounts({ poolState: pool.poolState }).view();
+
+      await tradeBatch(pool, trader, traderTokenAccount, [leg(0), leg(10_000, 1_000_000_000), leg(10_000)]).rpc();
+
+      const stats = await failureStats();
+      assert.equal(stats.failedTradeCount.toNumber(), 2);
+      assert.equal(stats.failedLiquidityCount.toNumber(), 0);
+      assert.equal(stats.failedAdminCount.toNumber(), 0);
+
+      const resets = await captureEvents("failureStatsReset", () =>
+        program.methods
+          .resetFailureStats()
+          .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+          .signers([pool.admin])
+          .rpc()
+      );
+
+      assert.equal(resets[0].oldStats.failedTradeCount.toNumber(), 2);
+      assert.equal((await failureStats()).failedTradeCount.toNumber(), 0);
+    });
+
+    it("rejects an oversized batch outright", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(
+        tradeBatch(pool, trader, traderTokenAccount, Array(11).fill(leg(1_000))).rpc(),
+        "TradeBatchTooLarge"
+      );
+    });
+  });
+
+  describe("liquidity grace period", () => {
+    const addLiquidity = (pool: TestPool, amount: number) =>
+      program.methods
+        .addLiquidity(new BN(amount))
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          adminTokenAccount: pool.adminTokenAccount,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("does not let fresh liquidity loosen the price impact check", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, liquidityGracePeriod: 3600 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await addLiquidity(pool, 100_000_000);
+
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 150_000).rpc(),
+        "PriceImpactTooHigh"
+      );
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.lengthOf(state.pendingDeposits, 1);
+    });
+
+    it("counts new liquidity immediately when the grace period is zero", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await addLiquidity(pool, 100_000_000);
+
+      await trade(pool, trader, traderTokenAccount, 150_000).rpc();
+    });
+
+    it("consumes pending deposits first when liquidity is withdrawn", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, liquidityGracePeriod: 3600 });
+      await addLiquidity(pool, 500_000);
+
+      await program.methods
+        .removeLiquidity(new BN(200_000))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.pendingDeposits[0].amount.toNumber(), 300_000);
+      assert.equal(state.totalLiquidity.toNumber(), 1_300_000);
+    });
+  });
+
+  describe("pool state migration", () => {
+    it("stamps new pools with the current layout version", async () => {
+      const pool = await setupPool();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.version, 3);
+    });
+
+    it("leaves an up-to-date pool untouched", async () => {
+      const pool = await setupPool();
+      const before = await program.account.poolState.fetch(pool.poolState);
+
+      const events = await captureEvents("poolStateMigrated", () =>
+        program.methods
+          .migratePoolState()
+          .accounts({
+            poolState: pool.poolState,
+            admin: pool.admin.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([pool.admin])
+          .rpc()
+      );
+
+      const after = await program.account.poolState.fetch(pool.poolState);
+      assert.lengthOf(events, 0);
+      assert.equal(after.version, before.version);
+      assert.equal(after.totalLiquidity.toString(), before.totalLiquidity.toString());
+      assert.equal(after.lastUpdate.toString(), before.lastUpdate.toString());
+    });
+
+    it("rejects a migration from anyone but the admin", async () => {
+      const pool = await setupPool();
+      const stranger = await fundedKeypair();
+
+      await expectError(
+        program.methods
+          .migratePoolState()
+          .accounts({
+            poolState: pool.poolState,
+            admin: stranger.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .signers([stranger])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("fee tier preview", () => {
+    const preview = (pool: TestPool, volume: number) =>
+      program.methods
+        .previewFeeTier(new BN(volume))
+        .accounts({ poolState: pool.poolState })
+        .view();
+
+    it("selects the tier covering each volume level", async () => {
+      const pool = await setupPool();
+      const cases: [number, number | null, number][] = [
+        [0, 0, 30],
+        [1_000_000, 0, 30],
+        [1_000_001, 1, 20],
+        [10_000_000, 1, 20],
+        [10_000_001, null, 0],
+      ];
+
+      for (const [volume, tierIndex, feeBps] of cases) {
+        const result = await preview(pool, volume);
+        assert.equal(result.tierIndex, tierIndex, `tier index at volume ${volume}`);
+        assert.equal(result.feeBps, feeBps, `fee bps at volume ${volume}`);
+      }
+    });
+
+    it("matches the tier calculate_fee charges on a trade", async () => {
+      const pool = await setupPool({ earlyTradeWindowSeconds: 0 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      const { volume } = await program.account.poolState.fetch(pool.poolState);
+      const expected = await preview(pool, volume.currentVolume.toNumber());
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, 100_000).rpc()
+      );
+
+      assert.equal(events[0].feeAmount.toNumber(), (100_000 * expected.feeBps) / 10_000);
+      assert.equal(events[0].feeTierIndex, expected.tierIndex);
+    });
+
+    it("reports the tier index shifting as volume crosses a threshold", async () => {
+      const pool = await setupPool({
+        feeTiers: [
+          { volumeThreshold: new BN(50_000), feeBps: new BN(30), liquidityThreshold: null },
+          { volumeThreshold: new BN(10_000_000), feeBps: new BN(20), liquidityThreshold: null },
+        ],
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      const events = await captureEvents("tradeExecuted", async () => {
+        await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+      });
+
+      assert.deepEqual(
+        events.map((event) => event.feeTierIndex),
+        [0, 1]
+      );
+    });
+
+    it("keeps a thin pool on the liquidity tier past its volume threshold", async () => {
+      const pool = await setupPool({
+        feeTiers: [
+          { volumeThreshold: new BN(50_000), feeBps: new BN(30), liquidityThreshold: new BN(200_000_000) },
+          { volumeThreshold: new BN(10_000_000), feeBps: new BN(20), liquidityThreshold: null },
+        ],
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      const events = await captureEvents("tradeExecuted", async () => {
+        await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+      });
+
+      assert.deepEqual(
+        events.map((event) => event.feeTierIndex),
+        [0, 0]
+      );
+    });
+
+    it("rejects liquidity thresholds out of order", async () => {
+      await expectError(
+        setupPool({
+          feeTiers: [
+            { volumeThreshold: new BN(1_000_000), feeBps: new BN(30), liquidityThreshold: new BN(5_000_000) },
+            { volumeThreshold: new BN(10_000_000), feeBps: new BN(20), liquidityThreshold: new BN(5_000_000) },
+          ],
+        }),
+        "InvalidFeeTierSpacing"
+      );
+    });
+  });
+
+  describe("fee rounding", () => {
+    const tradeFee = async (pool: TestPool, amountIn: number) => {
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, amountIn).rpc()
+      );
+      return events[0].feeAmount.toNumber();
+    };
+
+    const pastEarlyWindow = () => new Promise((resolve) => setTimeout(resolve, 1500));
+
+    it("charges a dust trade the minimum fee instead of zero", async () => {
+      const pool = await setupPool();
+      await pastEarlyWindow();
+
+      // 300 * 30 bps truncates to zero
+      assert.equal(await tradeFee(pool, 300), 1);
+    });
+
+    it("rounds a fractional fee down by default", async () => {
+      const pool = await setupPool();
+      await pastEarlyWindow();
+
+      assert.equal(await tradeFee(pool, 100_001), 300);
+    });
+
+    it("rounds a fractional fee up when configured", async () => {
+      const pool = await setupPool({ feeRounding: "up" });
+      await pastEarlyWindow();
+
+      assert.equal(await tradeFee(pool, 100_001), 301);
+    });
+
+    it("leaves an exact fee unchanged when rounding up", async () => {
+      const pool = await setupPool({ feeRounding: "up" });
+      await pastEarlyWindow();
+
+      assert.equal(await tradeFee(pool, 100_000), 300);
+    });
+  });
+
+  describe("referral fees", () => {
+    const setReferralShare = (pool: TestPool, shareBps: number) =>
+      program.methods
+        .setReferralShareBps(new BN(shareBps))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const registerReferrer = async (pool: TestPool) => {
+      const referrer = await fundedKeypair();
+      await program.methods
+        .registerReferrer()
+        .accounts({ poolState: pool.poolState, referrer: referrer.publicKey })
+        .signers([referrer])
+        .rpc();
+      const tokenAccount = await spl.createAccount(
+        provider.connection,
+        wallet.payer,
+        pool.tokenMint,
+        referrer.publicKey
+      );
+      return { referrer, tokenAccount };
+    };
+
+    const pastEarlyWindow = () => new Promise((resolve) => setTimeout(resolve, 1500));
+
+    it("accrues the referral share across trades and pays it out on claim", async () => {
+      const pool = await setupPool();
+      await setReferralShare(pool, 2000);
+      const { referrer, tokenAccount } = await registerReferrer(pool);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await pastEarlyWindow();
+
+      // 100_000 * 30 bps = 300 fee, 20% of which is 60
+      await trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), referrer.publicKey).rpc();
+      await trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), referrer.publicKey).rpc();
+
+      const referralState = deriveReferralState(pool.poolState, referrer.publicKey);
+      const accrued = await program.account.referralState.fetch(referralState);
+      const poolState = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(accrued.accrued.toNumber(), 120);
+      assert.equal(poolState.referralFeesOwed.toNumber(), 120);
+      assert.equal(poolState.totalFeesCollected.toNumber(), 480);
+
+      await program.methods
+        .claimReferralFees()
+        .accounts({
+          poolState: pool.poolState,
+          referrer: referrer.publicKey,
+          referrerTokenAccount: tokenAccount,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([referrer])
+        .rpc();
+
+      const balance = await spl.getAccount(provider.connection, tokenAccount);
+      const claimed = await program.account.referralState.fetch(referralState);
+      const after = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(Number(balance.amount), 120);
+      assert.equal(claimed.accrued.toNumber(), 0);
+      assert.equal(claimed.totalClaimed.toNumber(), 120);
+      assert.equal(after.referralFeesOwed.toNumber(), 0);
+    });
+
+    it("accrues nothing while the referral share is zero", async () => {
+      const pool = await setupPool();
+      const { referrer } = await registerReferrer(pool);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await pastEarlyWindow();
+
+      await trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), referrer.publicKey).rpc();
+
+      const referralState = deriveReferralState(pool.poolState, referrer.publicKey);
+      const state = await program.account.referralState.fetch(referralState);
+      assert.equal(state.accrued.toNumber(), 0);
+    });
+
+    it("rejects a trader naming themselves as referrer", async () => {
+      const pool = await setupPool();
+      await setReferralShare(pool, 2000);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await program.methods
+        .registerReferrer()
+        .accounts({ poolState: pool.poolState, referrer: trader.publicKey })
+        .signers([trader])
+        .rpc();
+
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), trader.publicKey).rpc(),
+        "InvalidReferrer"
+      );
+    });
+
+    it("rejects a share above the maximum", async () => {
+      const pool = await setupPool();
+
+      await expectError(setReferralShare(pool, 5001), "InvalidReferralShare");
+    });
+  });
+
+  describe("allowed CPI callers", () => {
+    const setAllowedCallers = (pool: TestPool, callers: PublicKey[]) =>
+      program.methods
+        .setAllowedCallers(callers)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("stores the allow-list without duplicates", async () => {
+      const pool = await setupPool();
+      const router = anchor.web3.Keypair.generate().publicKey;
+
+      await setAllowedCallers(pool, [router, router]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.allowedCallers.map((k) => k.toString()), [router.toString()]);
+    });
+
+    it("still accepts direct trades when the list is non-empty", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await setAllowedCallers(pool, [anchor.web3.Keypair.generate().publicKey]);
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+    });
+
+    it("rejects an allow-list over the maximum size", async () => {
+      const pool = await setupPool();
+      const callers = Array.from({ length: 11 }, () => anchor.web3.Keypair.generate().publicKey);
+
+      await expectError(setAllowedCallers(pool, callers), "TooManyAllowedCallers");
+    });
+
+    it("refuses to add a caller or clear the list without the timelock", async () => {
+      const [first, second] = [anchor.web3.Keypair.generate().publicKey, anchor.web3.Keypair.generate().publicKey];
+      const pool = await setupPool({ allowedCallers: [first, second] });
+
+      await expectError(setAllowedCallers(pool, [first, second, anchor.web3.Keypair.generate().publicKey]), "Unauthorized");
+      await expectError(setAllowedCallers(pool, []), "Unauthorized");
+      await setAllowedCallers(pool, [second]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(state.allowedCallers.map((k) => k.toString()), [second.toString()]);
+    });
+  });
+
+  describe("max trades per transaction", () => {
+    const setMaxTradesPerTx = (pool: TestPool, max: number) =>
+      program.methods
+        .setMaxTradesPerTx(max)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const bundledTrades = async (pool: TestPool, count: number) => {
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const extra = await Promise.all(
+        Array.from({ length: count - 1 }, () =>
+          trade(pool, trader, traderTokenAccount, 10_000).instruction()
+        )
+      );
+      return trade(pool, trader, traderTokenAccount, 10_000).preInstructions(extra).rpc();
+    };
+
+    it("rejects a transaction with more trades than allowed", async () => {
+      const pool = await setupPool();
+      await setMaxTradesPerTx(pool, 2);
+
+      await expectError(bundledTrades(pool, 3), "TooManyTradesPerTx");
+    });
+
+    it("accepts a transaction within the limit", async () => {
+      const pool = await setupPool();
+      await setMaxTradesPerTx(pool, 2);
+
+      await bundledTrades(pool, 2);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.maxTradesPerTx, 2);
+    });
+
+    it("does not limit bundles when the cap is zero", async () => {
+      const pool = await setupPool();
+
+      await bundledTrades(pool, 3);
+    });
+
+    it("refuses to raise or lift the limit without the timelock", async () => {
+      const pool = await setupPool({ maxTradesPerTx: 2 });
+
+      await expectError(setMaxTradesPerTx(pool, 3), "Unauthorized");
+      await expectError(setMaxTradesPerTx(pool, 0), "Unauthorized");
+      await setMaxTradesPerTx(pool, 1);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.maxTradesPerTx, 1);
+    });
+  });
+
+  describe("timelock remaining", () => {
+    const remaining = (pool: TestPool) =>
+      program.methods.getTimelockRemaining().accounts({ poolState: pool.poolState }).view();
+
+    it("reports the full timelock right after scheduling and counts down", async () => {
+      const pool = await setupPool();
+      const { timelocks } = await program.account.poolState.fetch(pool.poolState);
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+
+      const first = (await remaining(pool)).toNumber();
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+      const second = (await remaining(pool)).toNumber();
+
+      assert.approximately(first, timelocks.protectionSeconds.toNumber(), 5);
+      assert.isBelow(second, first);
+    });
+
+    it("fails when no update is pending", async () => {
+      const pool = await setupPool();
+
+      let failed = false;
+      try {
+        await remaining(pool);
+      } catch (err) {
+        failed = true;
+        assert.include(JSON.stringify(err.simulationResponse?.logs ?? err.logs ?? String(err)), "NoPendingUpdate");
+      }
+      assert.isTrue(failed, "expected NoPendingUpdate");
+    });
+  });
+
+  describe("emergency scheduling", () => {
+    const emergency = (pool: TestPool) => ({ poolState: pool.poolState, emergencyAdmin: pool.admin.publicKey });
+
+    it("keeps a scheduled emergency pause alongside a pending parameter update", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+      await program.methods.scheduleEmergencyPause().accounts(emergency(pool)).signers([pool.admin]).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.pendingUpdate.updates.length, 1);
+      assert.deepEqual(state.pendingEmergency.action, { pause: {} });
+
+      // Cancelling the parameter update leaves the emergency pause scheduled
+      await program.methods
+        .cancelParameterUpdate()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+      const after = await program.account.poolState.fetch(pool.poolState);
+      assert.isNull(after.pendingUpdate);
+      assert.deepEqual(after.pendingEmergency.action, { pause: {} });
+    });
+
+    it("rejects applying an emergency action that was never scheduled", async () => {
+      const pool = await setupPool();
+      await expectError(
+        program.methods.applyEmergencyPause().accounts(emergency(pool)).signers([pool.admin]).rpc(),
+        "NoPendingEmergency"
+      );
+    });
+
+    it("rejects applying a resume when a pause is scheduled", async () => {
+      const pool = await setupPool();
+      await program.methods.scheduleEmergencyPause().accounts(emergency(pool)).signers([pool.admin]).rpc();
+      await expectError(
+        program.methods.applyEmergencyResume().accounts(emergency(pool)).signers([pool.admin]).rpc(),
+        "EmergencyActionMismatch"
+      );
+    });
+  });
+
+  describe("fee cap", () => {
+    const tradeFee = async (pool: TestPool, amountIn: number) => {
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, amountIn).rpc()
+      );
+      return events[0].feeAmount.toNumber();
+    };
+
+    const pastEarlyWindow = () => new Promise((resolve) => setTimeout(resolve, 1500));
+
+    it("clamps the protocol fee to the total fee cap", async () => {
+      const pool = await setupPool({
+        protocolFeeBps: 500,
+        feeTiers: [{ volumeThreshold: new BN(1_000_000), feeBps: new BN(800), liquidityThreshold: null }],
+        maxSlippageBps: 2000,
+      });
+      await pastEarlyWindow();
+
+      // 800 bps plus 500 bps is 1300 bps, held to the 1000 bps cap
+      assert.equal(await tradeFee(pool, 100_000), 10_000);
+    });
+
+    it("rejects an early trade fee above the cap", async () => {
+      await expectError(setupPool({ earlyTradeFeeBps: 1001 }), "FeeTooHigh");
+    });
+  });
+
+  describe("emergency timelocks", () => {
+    const emergency = (pool: TestPool) => ({ poolState: pool.poolState, emergencyAdmin: pool.admin.publicKey });
+
+    const pause = async (pool: TestPool) => {
+      await program.methods.scheduleEmergencyPause().accounts(emergency(pool)).signers([pool.admin]).rpc();
+      await program.methods.applyEmergencyPause().accounts(emergency(pool)).signers([pool.admin]).rpc();
+    };
+
+    it("applies a pause immediately under a zero pause timelock", async () => {
+      const pool = await setupPool({ emergencyPauseSeconds: 0 });
+
+      await pause(pool);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(state.isEmergencyPaused);
+      assert.isNull(state.pendingEmergency);
+    });
+
+    it("holds a resume behind the longer resume timelock", async () => {
+      const pool = await setupPool({ emergencyPauseSeconds: 0, emergencyResumeSeconds: 7200 });
+      await pause(pool);
+
+      await program.methods.scheduleEmergencyResume().accounts(emergency(pool)).signers([pool.admin]).rpc();
+      const state = await program.account.poolState.fetch(pool.poolState);
+      const now = Math.floor(Date.now() / 1000);
+      assert.approximately(state.pendingEmergency.scheduledTime.toNumber() - now, 7200, 10);
+
+      await expectError(
+        program.methods.applyEmergencyResume().accounts(emergency(pool)).signers([pool.admin]).rpc(),
+        "TimelockNotExpired"
+      );
+    });
+
+    it("rejects a resume timelock below the floor", async () => {
+      await expectError(setupPool({ emergencyResumeSeconds: 60 }), "InvalidTimelockDuration");
+    });
+
+    it("rejects a resume timelock shorter than the pause timelock", async () => {
+      await expectError(
+        setupPool({ emergencyPauseSeconds: 7200, emergencyResumeSeconds: 3600 }),
+        "InvalidTimelockDuration"
+      );
+    });
+  });
+
+  describe("current volume view", () => {
+    it("reports the live 24h volume", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 40_000).rpc();
+
+      const volume = await program.methods.getCurrentVolume().accounts({ poolState: pool.poolState }).view();
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(volume.toNumber(), 40_000);
+      assert.equal(volume.toNumber(), state.volume.volume24h.toNumber());
+    });
+  });
+
+  describe("daily volume tracking", () => {
+    it("raises the 24h volume with each trade until the cap blocks", async () => {
+      const pool = await setupPool({ maxDailyVolume: 50_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 20_000).rpc();
+      let state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.volume24h.toNumber(), 20_000);
+
+      await trade(pool, trader, traderTokenAccount, 25_000).rpc();
+      state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.volume24h.toNumber(), 45_000);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 10_000).rpc(), "VolumeLimitExceeded");
+      state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.volume24h.toNumber(), 45_000);
+    });
+  });
+
+  describe("protocol fees", () => {
+    const withdrawAccounts = (pool: TestPool) => ({
+      poolState: pool.poolState,
+      feeAuthority: pool.admin.publicKey,
+      poolTokenAccount: pool.poolTokenAccount,
+      feeAuthorityTokenAccount: pool.adminTokenAccount,
+      poolAuthority: pool.poolAuthority,
+      tokenMint: pool.tokenMint,
+      tokenProgram: spl.TOKEN_PROGRAM_ID,
+    });
+
+    it("accrues protocol fees apart from LP fees and withdraws them separately", async () => {
+      const pool = await setupPool({ protocolFeeBps: 10, liquidity: 50_000_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      const before = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(before.protocolFees.toNumber(), 100);
+      const lpFees = before.totalFeesCollected.toNumber();
+      assert.isAbove(lpFees, 0);
+
+      const adminBefore = await spl.getAccount(provider.connection, pool.adminTokenAccount);
+      await program.methods.withdrawProtocolFees().accounts(withdrawAccounts(pool)).signers([pool.admin]).rpc();
+
+      const after = await program.account.poolState.fetch(pool.poolState);
+      const adminAfter = await spl.getAccount(provider.connection, pool.adminTokenAccount);
+      assert.equal(Number(adminAfter.amount - adminBefore.amount), 100);
+      assert.equal(after.protocolFees.toNumber(), 0);
+      assert.equal(after.totalFeesCollected.toNumber(), lpFees);
+      assert.equal(after.totalLiquidity.toNumber(), before.totalLiquidity.toNumber());
+    });
+
+    it("leaves protocol fees in place when LP fees are withdrawn", async () => {
+      const pool = await setupPool({ protocolFeeBps: 10, liquidity: 50_000_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      await program.methods.withdrawFees().accounts(withdrawAccounts(pool)).signers([pool.admin]).rpc();
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalFeesCollected.toNumber(), 0);
+      assert.equal(state.protocolFees.toNumber(), 100);
+    });
+
+    it("rejects withdrawing with no protocol fees accrued", async () => {
+      const pool = await setupPool();
+
+      await expectError(
+        program.methods.withdrawProtocolFees().accounts(withdrawAccounts(pool)).signers([pool.admin]).rpc(),
+        "NoFeesAvailable"
+      );
+    });
+
+    it("rejects a protocol fee above the maximum", async () => {
+      await expectError(setupPool({ protocolFeeBps: 501 }), "InvalidProtocolFee");
+    });
+  });
+
+  describe("paused operations", () => {
+    const PAUSE_OP_TRADE = 1 << 0;
+    const PAUSE_OP_ADD_LIQUIDITY = 1 << 1;
+    const PAUSE_OP_WITHDRAW = 1 << 3;
+
+    const setPausedOps = (pool: TestPool, pausedOps: number) =>
+      program.methods
+        .setPausedOps(pausedOps)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const withdrawFees = (pool: TestPool) =>
+      program.methods
+        .withdrawFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          feeAuthorityTokenAccount: pool.adminTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    const addLiquidity = (pool: TestPool) =>
+      program.methods
+        .addLiquidity(new BN(1_000))
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          adminTokenAccount: pool.adminTokenAccount,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("blocks trades but permits withdrawals when the trade bit is set", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      await setPausedOps(pool, PAUSE_OP_TRADE);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "OperationPaused");
+      await withdrawFees(pool);
+      await addLiquidity(pool);
+    });
+
+    it("blocks only the operations whose bits are set", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      await setPausedOps(pool, PAUSE_OP_ADD_LIQUIDITY | PAUSE_OP_WITHDRAW);
+
+      await expectError(addLiquidity(pool), "OperationPaused");
+      await expectError(withdrawFees(pool), "OperationPaused");
+      await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+
+      await setPausedOps(pool, 0);
+      await withdrawFees(pool);
+    });
+
+    it("rejects unknown bits", async () => {
+      const pool = await setupPool();
+
+      await expectError(setPausedOps(pool, 1 << 4), "InvalidPausedOps");
+    });
+  });
+
+  describe("rescheduling parameter updates", () => {
+    const tradeUpdate = (maxTradeSizeBps: number) => ({
+      trade: {
+        0: {
+          earlyTradeFeeBps: new BN(0),
+          earlyTradeWindowSeconds: new BN(0),
+          maxTradeSizeBps: new BN(maxTradeSizeBps),
+          minTradeSize: new BN(1),
+          cooldownSeconds: new BN(0),
+        },
+      },
+    });
+
+    const reschedule = (pool: TestPool, updates: any[]) =>
+      program.methods
+        .rescheduleParameterUpdate(updates)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const scheduledTime = async (pool: TestPool) =>
+      (await program.account.poolState.fetch(pool.poolState)).pendingUpdate.scheduledTime.toNumber();
+
+    it("keeps the original schedule for a more conservative batch", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [tradeUpdate(800)]);
+      const original = await scheduledTime(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      await reschedule(pool, [tradeUpdate(500)]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.pendingUpdate.scheduledTime.toNumber(), original);
+      assert.equal(state.pendingUpdate.updates[0].trade[0].maxTradeSizeBps.toNumber(), 500);
+    });
+
+    it("restarts the clock for a looser batch", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [tradeUpdate(800)]);
+      const original = await scheduledTime(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      await reschedule(pool, [tradeUpdate(900)]);
+
+      assert.isAbove(await scheduledTime(pool), original);
+    });
+
+    it("restarts the clock when a category is added", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [tradeUpdate(800)]);
+      const original = await scheduledTime(pool);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      await reschedule(pool, [tradeUpdate(800), { protection: { 0: protectionUpdate } }]);
+
+      assert.isAbove(await scheduledTime(pool), original);
+    });
+
+    it("rejects rescheduling with nothing pending", async () => {
+      const pool = await setupPool();
+
+      await expectError(reschedule(pool, [tradeUpdate(500)]), "NoPendingUpdate");
+    });
+  });
+
+  describe("empty pools", () => {
+    it("rejects a trade against a pool with no liquidity", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "InsufficientLiquidity");
+    });
+  });
+
+  describe("pool migration", () => {
+    const setupPair = async () => {
+      const source = await setupPool({ liquidity: 50_000_000, maxSlippageBps: 300 });
+      const successor = await setupPool({ tokenMint: source.tokenMint, liquidity: 0 });
+      await program.methods
+        .updateAdmin(source.admin.publicKey)
+        .accounts({ poolState: successor.poolState, admin: successor.admin.publicKey })
+        .signers([successor.admin])
+        .rpc();
+      return { source, successor };
+    };
+
+    const setPauseLevel = (pool: TestPool, pauseLevel: object) =>
+      program.methods
+        .setPauseLevel(pauseLevel)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const migrate = (source: TestPool, successor: TestPool) =>
+      program.methods
+        .migrateToPool()
+        .accounts({
+          poolState: source.poolState,
+          newPoolState: successor.poolState,
+          admin: source.admin.publicKey,
+          poolTokenAccount: source.poolTokenAccount,
+          newPoolTokenAccount: successor.poolTokenAccount,
+          poolAuthority: source.poolAuthority,
+          tokenMint: source.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([source.admin])
+        .rpc();
+
+    it("moves funds and configuration, then retires the source", async () => {
+      const { source, successor } = await setupPair();
+      const { trader, traderTokenAccount } = await fundedTrader(source);
+      await trade(source, trader, traderTokenAccount, 100_000).rpc();
+      const before = await program.account.poolState.fetch(source.poolState);
+      const moved = before.totalLiquidity.add(before.totalFeesCollected).add(before.insuranceCollected).toNumber();
+
+      await setPauseLevel(source, { tradingOnly: {} });
+      await migrate(source, successor);
+
+      const sourceState = await program.account.poolState.fetch(source.poolState);
+      const successorState = await program.account.poolState.fetch(successor.poolState);
+      const successorAccount = await spl.getAccount(provider.connection, successor.poolTokenAccount);
+      assert.isTrue(sourceState.retired);
+      assert.equal(sourceState.totalLiquidity.toNumber(), 0);
+      assert.equal(Number(successorAccount.amount), moved);
+      assert.equal(successorState.totalLiquidity.toNumber(), before.totalLiquidity.toNumber());
+      assert.equal(successorState.protection.maxSlippageBps.toNumber(), 300);
+
+      await setPauseLevel(source, { none: {} });
+      await expectError(trade(source, trader, traderTokenAccount, 1_000).rpc(), "PoolRetired");
+    });
+
+    it("rejects migrating a pool that is not paused", async () => {
+      const { source, successor } = await setupPair();
+
+      await expectError(migrate(source, successor), "PoolNotPaused");
+    });
+
+    it("rejects migrating a retired pool again", async () => {
+      const { source, successor } = await setupPair();
+      await setPauseLevel(source, { tradingOnly: {} });
+      await migrate(source, successor);
+
+      await expectError(migrate(source, successor), "PoolRetired");
+    });
+  });
+
+  describe("solvency check", () => {
+    const verifySolvency = (pool: TestPool) =>
+      program.methods
+        .verifySolvency()
+        .accounts({
+          poolState: pool.poolState,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+        })
+        .rpc();
+
+    it("reports a healthy pool to any caller", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+
+      const events = await captureEvents("solvencyChecked", () => verifySolvency(pool));
+
+      assert.equal(events[0].obligations.toNumber(), 1_000_000);
+      assert.equal(events[0].surplus.toNumber(), 0);
+      assert.equal(events[0].deficit.toNumber(), 0);
+    });
+
+    it("reports tokens sent straight to the pool as surplus", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await spl.mintTo(provider.connection, wallet.payer, pool.tokenMint, pool.poolTokenAccount, wallet.payer, 2_500);
+
+      const events = await captureEvents("solvencyChecked", () => verifySolvency(pool));
+
+      assert.equal(events[0].surplus.toNumber(), 2_500);
+      assert.equal(events[0].deficit.toNumber(), 0);
+    });
+  });
+
+  describe("volatility-adaptive price impact", () => {
+    const setVolatilitySettings = (pool: TestPool, enabled: boolean, floorImpactBps: number, maxVolatilityBps: number) =>
+      program.methods
+        .setVolatilitySettings(enabled, new BN(floorImpactBps), new BN(maxVolatilityBps))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const effectiveCap = async (pool: TestPool) =>
+      (await program.methods.getEffectiveMaxPriceImpact().accounts({ poolState: pool.poolState }).view()).toNumber();
+
+    it("tightens the price impact cap as trades move the price", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, maxPriceImpactBps: 1000, maxSlippageBps: 2000 });
+      await setVolatilitySettings(pool, true, 100, 50);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      // Too little history to adapt yet
+      assert.equal(await effectiveCap(pool), 1000);
+
+      for (const amount of [1_000, 60_000, 1_000, 60_000, 1_000]) {
+        await trade(pool, trader, traderTokenAccount, amount).rpc();
+      }
+
+      assert.isBelow(await effectiveCap(pool), 1000);
+      assert.isAtLeast(await effectiveCap(pool), 100);
+    });
+
+    it("keeps the static cap while adaptive mode is off", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, maxPriceImpactBps: 1000, maxSlippageBps: 2000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      for (const amount of [1_000, 60_000, 1_000, 60_000, 1_000]) {
+        await trade(pool, trader, traderTokenAccount, amount).rpc();
+      }
+
+      assert.equal(await effectiveCap(pool), 1000);
+    });
+
+    it("rejects a floor above the static cap", async () => {
+      const pool = await setupPool({ maxPriceImpactBps: 1000 });
+
+      await expectError(setVolatilitySettings(pool, true, 1001, 100), "InvalidVolatilitySettings");
+    });
+
+    it("refuses to loosen an enabled cap without the timelock", async () => {
+      const pool = await setupPool({
+        maxPriceImpactBps: 1000,
+        volatility: { enabled: true, floorImpactBps: 200, maxVolatilityBps: 500 },
+      });
+
+      await expectError(setVolatilitySettings(pool, false, 200, 500), "Unauthorized");
+      await expectError(setVolatilitySettings(pool, true, 300, 500), "Unauthorized");
+      await expectError(setVolatilitySettings(pool, true, 200, 600), "Unauthorized");
+      await setVolatilitySettings(pool, true, 100, 400);
+
+      const { volatility } = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(volatility.floorImpactBps.toNumber(), 100);
+    });
+  });
+
+  describe("post-resume cooldown", () => {
+    const setPostResumeCooldown = (pool: TestPool, seconds: number) =>
+      program.methods
+        .setPostResumeCooldown(new BN(seconds))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("stores the cooldown and emits an event", async () => {
+      const pool = await setupPool();
+
+      const events = await captureEvents("postResumeCooldownUpdated", () => setPostResumeCooldown(pool, 600));
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.postResumeCooldown.toNumber(), 600);
+      assert.equal(events[0].oldCooldown.toNumber(), 0);
+      assert.equal(events[0].newCooldown.toNumber(), 600);
+    });
+
+    it("does not block trades on a pool that was never resumed", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await setPostResumeCooldown(pool, 600);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+    });
+
+    it("rejects a negative or overlong cooldown", async () => {
+      const pool = await setupPool();
+
+      await expectError(setPostResumeCooldown(pool, -1), "InvalidPostResumeCooldown");
+      await expectError(setPostResumeCooldown(pool, 86_401), "InvalidPostResumeCooldown");
+    });
+
+    it("refuses to shorten or disable the cooldown without the timelock", async () => {
+      const pool = await setupPool({ postResumeCooldown: 600 });
+
+      await expectError(setPostResumeCooldown(pool, 599), "Unauthorized");
+      await expectError(setPostResumeCooldown(pool, 0), "Unauthorized");
+      await setPostResumeCooldown(pool, 900);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.postResumeCooldown.toNumber(), 900);
+    });
+  });
+
+  describe("governance token discounts", () => {
+    const discountTiers = [
+      { minBalance: new BN(1_000), discountBps: new BN(1000) },
+      { minBalance: new BN(100_000), discountBps: new BN(5000) },
+    ];
+
+    const setGovernanceDiscount = (pool: TestPool, mint: PublicKey | null, tiers: any[]) =>
+      program.methods
+        .setGovernanceDiscount(mint, tiers)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const governanceAccount = async (mint: PublicKey, owner: PublicKey, balance: number) => {
+      const account = await spl.createAccount(provider.connection, wallet.payer, mint, owner);
+      if (balance > 0) {
+        await spl.mintTo(provider.connection, wallet.payer, mint, account, wallet.payer, balance);
+      }
+      return account;
+    };
+
+    const createGovernanceMint = () =>
+      spl.createMint(provider.connection, wallet.payer, wallet.publicKey, null, 9);
+
+    const pastEarlyWindow = () => new Promise((resolve) => setTimeout(resolve, 1500));
+
+    it("discounts the fee by the holder's tier", async () => {
+      const pool = await setupPool();
+      const governanceMint = await createGovernanceMint();
+      await setGovernanceDiscount(pool, governanceMint, discountTiers);
+      await pastEarlyWindow();
+
+      const cases: [number, number][] = [
+        [0, 300],
+        [999, 300],
+        [1_000, 270],
+        [250_000, 150],
+      ];
+      for (const [balance, expectedFee] of cases) {
+        const { trader, traderTokenAccount } = await fundedTrader(pool);
+        const account = await governanceAccount(governanceMint, trader.publicKey, balance);
+        const events = await captureEvents("tradeExecuted", () =>
+          trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), null, account).rpc()
+        );
+        assert.equal(events[0].feeAmount.toNumber(), expectedFee, `fee at balance ${balance}`);
+      }
+    });
+
+    it("rejects a governance account of another mint or owner", async () => {
+      const pool = await setupPool();
+      const governanceMint = await createGovernanceMint();
+      await setGovernanceDiscount(pool, governanceMint, discountTiers);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const wrongMint = await governanceAccount(await createGovernanceMint(), trader.publicKey, 100_000);
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), null, wrongMint).rpc(),
+        "InvalidGovernanceTokenAccount"
+      );
+
+      const someoneElses = await governanceAccount(governanceMint, wallet.publicKey, 100_000);
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 100_000, 0, new BN(10_000), null, someoneElses).rpc(),
+        "InvalidGovernanceTokenAccount"
+      );
+    });
+
+    it("rejects tiers out of order or above the maximum discount", async () => {
+      const pool = await setupPool();
+      const governanceMint = await createGovernanceMint();
+
+      await expectError(
+        setGovernanceDiscount(pool, governanceMint, [...discountTiers].reverse()),
+        "InvalidGovernanceDiscount"
+      );
+      await expectError(
+        setGovernanceDiscount(pool, governanceMint, [{ minBalance: new BN(1), discountBps: new BN(5001) }]),
+        "InvalidGovernanceDiscount"
+      );
+    });
+
+    it("clears the schedule when the mint is unset", async () => {
+      const pool = await setupPool();
+      await setGovernanceDiscount(pool, await createGovernanceMint(), discountTiers);
+
+      await setGovernanceDiscount(pool, null, discountTiers);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isNull(state.governanceMint);
+      assert.isEmpty(state.governanceDiscountTiers);
+    });
+  });
+
+  describe("hourly volume cap", () => {
+    const setMaxHourlyVolume = (pool: TestPool, maxHourly: number) =>
+      program.methods
+        .setMaxHourlyVolume(new BN(maxHourly))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("stops a burst at the hourly cap well under the daily cap", async () => {
+      const pool = await setupPool({ maxDailyVolume: 100_000_000 });
+      await setMaxHourlyVolume(pool, 150_000);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+      await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+      await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "HourlyVolumeLimitExceeded");
+
+      const { volume } = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(volume.hourlyVolume.toNumber(), 150_000);
+    });
+
+    it("rejects a zero cap", async () => {
+      const pool = await setupPool();
+
+      await expectError(setMaxHourlyVolume(pool, 0), "InvalidMaxHourlyVolume");
+    });
+
+    it("refuses to raise the cap without the timelock", async () => {
+      const pool = await setupPool({ maxHourlyVolume: 150_000 });
+
+      await expectError(setMaxHourlyVolume(pool, 150_001), "Unauthorized");
+
+      const { volume } = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(volume.maxHourly.toNumber(), 150_000);
+    });
+  });
+
+  describe("hourly trade limit", () => {
+    const setMaxHourlyTrades = (pool: TestPool, maxHourlyTrades: number) =>
+      program.methods
+        .setMaxHourlyTrades(maxHourlyTrades)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rejects trades past the hourly count", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await setMaxHourlyTrades(pool, 3);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      for (let i = 0; i < 3; i++) {
+        await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+      }
+      await expectError(trade(pool, trader, traderTokenAccount, 1_000).rpc(), "HourlyTradeLimitExceeded");
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.hourlyTradeCount, 3);
+    });
+
+    it("rejects a zero limit", async () => {
+      const pool = await setupPool();
+
+      await expectError(setMaxHourlyTrades(pool, 0), "InvalidMaxHourlyTrades");
+    });
+
+    it("refuses to raise the cap without the timelock", async () => {
+      const pool = await setupPool({ maxHourlyTrades: 10 });
+
+      await expectError(setMaxHourlyTrades(pool, 11), "Unauthorized");
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.maxHourlyTrades, 10);
+    });
+  });
+
+  describe("token account owner enforcement", () => {
+    const setEnforceOutputOwner = (pool: TestPool, enforce: boolean) =>
+      program.methods
+        .setEnforceOutputOwner(enforce)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    // A delegate signing for a token account it does not own
+    const delegatedTrader = async (pool: TestPool) => {
+      const { trader: owner, traderTokenAccount } = await fundedTrader(pool);
+      const delegate = await fundedKeypair();
+      await spl.approve(provider.connection, wallet.payer, traderTokenAccount, delegate.publicKey, owner, 1_000_000);
+      return { delegate, traderTokenAccount };
+    };
+
+    for (const enforce of [false, true]) {
+      it(`accepts the owner's own account with enforcement ${enforce ? "on" : "off"}`, async () => {
+        const pool = await setupPool({ liquidity: 1_000_000 });
+        await setEnforceOutputOwner(pool, enforce);
+        const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+        await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+      });
+    }
+
+    it("lets a delegate trade through the account while enforcement is off", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      const { delegate, traderTokenAccount } = await delegatedTrader(pool);
+
+      await trade(pool, delegate, traderTokenAccount, 1_000).rpc();
+    });
+
+    it("rejects a delegate's trade while enforcement is on", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await setEnforceOutputOwner(pool, true);
+      const { delegate, traderTokenAccount } = await delegatedTrader(pool);
+
+      await expectError(
+        trade(pool, delegate, traderTokenAccount, 1_000).rpc(),
+        "OutputAccountOwnerMismatch"
+      );
+    });
+  });
+
+  describe("sandwich detection", () => {
+    const setSandwichSettings = (
+      pool: TestPool,
+      enabled: boolean,
+      windowSlots: number,
+      largeTradeBps: number,
+      similarityBps: number,
+      surchargeBps: number
+    ) =>
+      program.methods
+        .setSandwichSettings(enabled, new BN(windowSlots), new BN(largeTradeBps), new BN(similarityBps), new BN(surchargeBps))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const sandwich = { enabled: true, windowSlots: 10, largeTradeBps: 500, similarityBps: 1000, surchargeBps: 100 };
+
+    it("records a large trade as a possible front-run", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, maxSlippageBps: 2000, sandwich });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+
+      const { sandwich } = await program.account.poolState.fetch(pool.poolState);
+      assert.deepEqual(sandwich.lastLargeDirection, { sell: {} });
+      assert.equal(sandwich.lastLargeAmount.toNumber(), 60_000);
+    });
+
+    it("does not flag a same-direction follow-up", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, maxSlippageBps: 2000, sandwich });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const flagged = await captureEvents("sandwichSuspected", async () => {
+        await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+      });
+
+      assert.isEmpty(flagged);
+    });
+
+    it("rejects out-of-range thresholds", async () => {
+      const pool = await setupPool();
+
+      await expectError(setSandwichSettings(pool, true, 0, 500, 1000, 100), "InvalidSandwichSettings");
+      await expectError(setSandwichSettings(pool, true, 10, 500, 1000, 501), "InvalidSandwichSettings");
+    });
+
+    it("refuses weaker detection or a higher surcharge without the timelock", async () => {
+      const pool = await setupPool({ sandwich });
+
+      await expectError(setSandwichSettings(pool, false, 10, 500, 1000, 100), "Unauthorized");
+      await expectError(setSandwichSettings(pool, true, 9, 500, 1000, 100), "Unauthorized");
+      await expectError(setSandwichSettings(pool, true, 10, 500, 1001, 100), "Unauthorized");
+      await expectError(setSandwichSettings(pool, true, 10, 500, 1000, 101), "Unauthorized");
+      await setSandwichSettings(pool, true, 20, 300, 500, 50);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.sandwich.surchargeBps.toNumber(), 50);
+    });
+  });
+
+  describe("accounting periods", () => {
+    const closePeriod = (pool: TestPool) =>
+      program.methods
+        .closeAccountingPeriod()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("reports the period totals and resets them", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const trades = await captureEvents("tradeExecuted", async () => {
+        await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+      });
+      const expectedFees = trades.reduce((total, event) => total + event.feeAmount.toNumber(), 0);
+
+      const closed = await captureEvents("periodClosed", () => closePeriod(pool));
+      assert.equal(closed[0].periodIndex.toNumber(), 0);
+      assert.equal(closed[0].periodVolume.toNumber(), 150_000);
+      assert.equal(closed[0].periodFees.toNumber(), expectedFees);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.accountingPeriod.index.toNumber(), 1);
+      assert.equal(state.accountingPeriod.volume.toNumber(), 0);
+      assert.equal(state.accountingPeriod.fees.toNumber(), 0);
+      // The rolling protection volume is not part of the period reset
+      assert.equal(state.volume.currentVolume.toNumber(), 150_000);
+    });
+
+    it("numbers consecutive periods", async () => {
+      const pool = await setupPool();
+      await closePeriod(pool);
+
+      const closed = await captureEvents("periodClosed", () => closePeriod(pool));
+      assert.equal(closed[0].periodIndex.toNumber(), 1);
+      assert.equal(closed[0].periodVolume.toNumber(), 0);
+    });
+
+    it("rejects a non-admin", async () => {
+      const pool = await setupPool();
+      const stranger = await fundedKeypair();
+
+      await expectError(
+        program.methods
+          .closeAccountingPeriod()
+          .accounts({ poolState: pool.poolState, admin: stranger.publicKey })
+          .signers([stranger])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("launch schedule", () => {
+    const scheduleLaunch = (pool: TestPool, launchTs: number) =>
+      program.methods
+        .scheduleLaunch(new BN(launchTs))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const now = () => Math.floor(Date.now() / 1000);
+
+    it("charges the early fee and reports early mode during the launch phase", async () => {
+      const pool = await setupPool({ earlyTradeFeeBps: 500, earlyTradeWindowSeconds: 3600, maxSlippageBps: 1000 });
+      await scheduleLaunch(pool, now());
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, 10_000, 0, new BN(10_000)).rpc()
+      );
+
+      assert.equal(events[0].feeAmount.toNumber(), 500);
+      assert.equal(events[0].feeMode, 1);
+    });
+
+    it("reverts to tier fees once the phase is over", async () => {
+      const pool = await setupPool({ earlyTradeFeeBps: 500, earlyTradeWindowSeconds: 1 });
+      await scheduleLaunch(pool, now());
+      await new Promise((resolve) => setTimeout(resolve, 2500));
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, 100_000).rpc()
+      );
+
+      assert.equal(events[0].feeAmount.toNumber(), 300);
+      assert.equal(events[0].feeMode, 2);
+    });
+
+    it("rejects rescheduling after the first trade", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 1_000).rpc();
+
+      await expectError(scheduleLaunch(pool, now() + 60), "LaunchAlreadyStarted");
+    });
+
+    it("rejects a launch in the past or beyond the longest timelock", async () => {
+      const pool = await setupPool();
+
+      await expectError(scheduleLaunch(pool, now() - 3600), "InvalidLaunchSchedule");
+      await expectError(scheduleLaunch(pool, now() + 2_592_000 + 60), "InvalidLaunchSchedule");
+    });
+
+    it("leaves the early-trade window to the timelocked trade settings", async () => {
+      const pool = await setupPool({ earlyTradeWindowSeconds: 600 });
+      await scheduleLaunch(pool, now() + 60);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.tradeSettings.earlyTradeWindowSeconds.toNumber(), 600);
+      await expectError(setupPool({ earlyTradeWindowSeconds: 604_801 }), "InvalidTradeSettings");
+    });
+  });
+
+  describe("output drain guard", () => {
+    const setMaxOutputFraction = (pool: TestPool, maxOutputFractionBps: number) =>
+      program.methods
+        .setMaxOutputFraction(new BN(maxOutputFractionBps))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rejects a trade taking more than the allowed share of reserves", async () => {
+      const pool = await setupPool();
+      await setMaxOutputFraction(pool, 10);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(
+        trade(pool, trader, traderTokenAccount, 200_000).rpc(),
+        "OutputExceedsReserveFraction"
+      );
+    });
+
+    it("lets a modest trade through", async () => {
+      const pool = await setupPool();
+      await setMaxOutputFraction(pool, 10);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+    });
+
+    it("rejects a zero or out-of-range fraction", async () => {
+      const pool = await setupPool();
+
+      await expectError(setMaxOutputFraction(pool, 0), "InvalidMaxOutputFraction");
+      await expectError(setMaxOutputFraction(pool, 10_001), "InvalidMaxOutputFraction");
+    });
+
+    it("refuses to raise the fraction without the timelock", async () => {
+      const pool = await setupPool({ maxOutputFractionBps: 10 });
+
+      await expectError(setMaxOutputFraction(pool, 11), "Unauthorized");
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.maxOutputFractionBps.toNumber(), 10);
+    });
+  });
+
+  describe("fee recipients", () => {
+    const setFeeRecipients = (pool: TestPool, recipients: { pubkey: PublicKey; shareBps: number }[]) =>
+      program.methods
+        .setFeeRecipients(recipients.map(({ pubkey, shareBps }) => ({ pubkey, shareBps: new BN(shareBps) })))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const recipientAccount = (pool: TestPool) =>
+      spl.createAccount(
+        provider.connection,
+        wallet.payer,
+        pool.tokenMint,
+        anchor.web3.Keypair.generate().publicKey
+      );
+
+    it("splits the accrued fees by share", async () => {
+      const pool = await setupPool();
+      const treasury = await recipientAccount(pool);
+      const buyback = await recipientAccount(pool);
+      await setFeeRecipients(pool, [
+        { pubkey: treasury, shareBps: 7_000 },
+        { pubkey: buyback, shareBps: 3_000 },
+      ]);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+      const fees = (await program.account.poolState.fetch(pool.poolState)).totalFeesCollected.toNumber();
+
+      await program.methods
+        .distributeFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(
+          [treasury, buyback].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .signers([pool.admin])
+        .rpc();
+
+      const treasuryAccount = await spl.getAccount(provider.connection, treasury);
+      const buybackAccount = await spl.getAccount(provider.connection, buyback);
+      const buybackShare = Math.floor((fees * 3_000) / 10_000);
+      assert.equal(Number(buybackAccount.amount), buybackShare);
+      assert.equal(Number(treasuryAccount.amount), fees - buybackShare);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalFeesCollected.toNumber(), 0);
+    });
+
+    it("rejects shares that do not sum to 10000", async () => {
+      const pool = await setupPool();
+      const treasury = await recipientAccount(pool);
+      const buyback = await recipientAccount(pool);
+
+      await expectError(
+        setFeeRecipients(pool, [
+          { pubkey: treasury, shareBps: 7_000 },
+          { pubkey: buyback, shareBps: 2_000 },
+        ]),
+        "InvalidFeeRecipients"
+      );
+    });
+  });
+
+  describe("state updates before transfers", () => {
+    it("leaves no trace of a trade whose transfer fails", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool, 10_000);
+      const before = await program.account.poolState.fetch(pool.poolState);
+
+      try {
+        await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+        assert.fail("expected the transfer to fail");
+      } catch (err) {
+        assert.match(String(err), /0x1|insufficient funds/);
+      }
+
+      const after = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(after.totalLiquidity.toNumber(), before.totalLiquidity.toNumber());
+      assert.equal(after.totalFeesCollected.toNumber(), before.totalFeesCollected.toNumber());
+      assert.equal(after.volume.volume24h.toNumber(), before.volume.volume24h.toNumber());
+      assert.equal(after.hourlyTradeCount, before.hourlyTradeCount);
+      assert.equal(after.accountingPeriod.volume.toNumber(), 0);
+    });
+  });
+
+  describe("immediate tightening", () => {
+    const tightenVolumeLimit = (pool: TestPool, newMaxDaily: number) =>
+      program.methods
+        .tightenVolumeLimit(new BN(newMaxDaily))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const tightenProtection = (pool: TestPool, changes: object) =>
+      program.methods
+        .tightenProtectionSettings({ ...initialConfig({}).protection, ...changes })
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("lowers the daily volume cap at once", async () => {
+      const pool = await setupPool();
+
+      const events = await captureEvents("volumeLimitTightened", () => tightenVolumeLimit(pool, 1_000_000));
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.maxDaily.toNumber(), 1_000_000);
+      assert.equal(events[0].oldMaxDaily.toNumber(), 100_000_000);
+    });
+
+    it("refuses to raise the daily volume cap", async () => {
+      const pool = await setupPool();
+
+      await expectError(tightenVolumeLimit(pool, 200_000_000), "Unauthorized");
+      await expectError(tightenVolumeLimit(pool, 100_000_000), "Unauthorized");
+    });
+
+    it("applies tighter protection settings at once and refuses looser ones", async () => {
+      const pool = await setupPool();
+
+      await tightenProtection(pool, { maxPriceImpactBps: new BN(500) });
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.protection.maxPriceImpactBps.toNumber(), 500);
+
+      await expectError(tightenProtection(pool, { maxPriceImpactBps: new BN(800) }), "Unauthorized");
+    });
+
+    it("records when and by whom protection settings were last applied", async () => {
+      const pool = await setupPool();
+      const lastUpdate = () =>
+        program.methods.getLastParameterUpdate().accounts({ poolState: pool.poolState }).view();
+
+      assert.isNull(await lastUpdate());
+
+      await tightenProtection(pool, { maxPriceImpactBps: new BN(500) });
+
+      const record = await lastUpdate();
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(record.admin.equals(pool.admin.publicKey));
+      assert.equal(record.ts.toNumber(), state.lastParamUpdateTs.toNumber());
+      assert.isAbove(record.ts.toNumber(), 0);
+    });
+  });
+
+  describe("fee-free threshold", () => {
+    it("charges nothing at or below the threshold", async () => {
+      const pool = await setupPool({ feeFreeThreshold: 1_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, 1_000).rpc()
+      );
+
+      assert.equal(events[0].feeAmount.toNumber(), 0);
+      assert.equal(events[0].feeMode, 0);
+    });
+
+    it("charges the tier fee above the threshold", async () => {
+      const pool = await setupPool({ feeFreeThreshold: 1_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("tradeExecuted", () =>
+        trade(pool, trader, traderTokenAccount, 100_000).rpc()
+      );
+
+      assert.equal(events[0].feeAmount.toNumber(), 300);
+      assert.equal(events[0].feeMode, 2);
+    });
+
+    it("holds a threshold change behind the fee timelock", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [
+        {
+          fee: {
+            0: {
+              feeTiers: [],
+              feeTiersLocked: false,
+              feeRounding: { down: {} },
+              protocolFeeBps: new BN(0),
+              feeFreeThreshold: new BN(1_000_000),
+            },
+          },
+        },
+      ]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.feeFreeThreshold.toNumber(), 0);
+      assert.equal(state.pendingUpdate.updates[0].fee[0].feeFreeThreshold.toNumber(), 1_000_000);
+    });
+  });
+
+  describe("pool health", () => {
+    const getHealth = (pool: TestPool) =>
+      program.methods.getHealth().accounts({ poolState: pool.poolState }).view();
+
+    it("reports a fresh pool as healthy", async () => {
+      const pool = await setupPool();
+
+      const health = await getHealth(pool);
+
+      assert.isTrue(health.healthy);
+      assert.isFalse(health.paused);
+      assert.isFalse(health.circuitBreakerTripped);
+      assert.equal(health.circuitBreakerHeadroomBps.toNumber(), 10_000);
+      assert.equal(health.volumeHeadroomBps.toNumber(), 10_000);
+    });
+
+    it("reports a tripped breaker and volume near the daily cap", async () => {
+      const pool = await setupPool({
+        maxDailyVolume: 100_000,
+        circuitBreakerThreshold: 90_000,
+        autoPauseOnBreaker: true,
+      });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 85_000).rpc();
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const health = await getHealth(pool);
+
+      assert.isFalse(health.healthy);
+      assert.isTrue(health.paused);
+      assert.isTrue(health.circuitBreakerTripped);
+      assert.isTrue(health.volumeNearCap);
+      assert.isAtMost(health.volumeHeadroomBps.toNumber(), 2_000);
+    });
+  });
+
+  describe("slippage tolerance", () => {
+    const tradeWithTolerance = (
+      pool: TestPool,
+      trader: anchor.web3.Keypair,
+      traderTokenAccount: PublicKey,
+      amountIn: number,
+      expectedOut: number,
+      toleranceBps: number
+    ) =>
+      program.methods
+        .executeTradeWithTolerance(new BN(amountIn), new BN(expectedOut), new BN(toleranceBps))
+        .accounts({
+          poolState: pool.poolState,
+          buyer: trader.publicKey,
+          buyerTokenAccount: traderTokenAccount,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          referralState: null,
+          instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+          governanceTokenAccount: null,
+        })
+        .signers([trader])
+        .rpc();
+
+    const outcome = (tx: Promise<string>) =>
+      tx.then(
+        () => "ok",
+        (err) => err.error?.errorCode?.code ?? String(err)
+      );
+
+    // Runs the same trade on two identical fresh pools, one per form
+    const compareForms = async (amountIn: number, expectedOut: number, toleranceBps: number) => {
+      const minimumAmountOut = Math.floor((expectedOut * (10_000 - toleranceBps)) / 10_000);
+      const absolutePool = await setupPool();
+      const tolerancePool = await setupPool();
+      const absoluteTrader = await fundedTrader(absolutePool);
+      const toleranceTrader = await fundedTrader(tolerancePool);
+
+      const absolute = await outcome(
+        trade(
+          absolutePool,
+          absoluteTrader.trader,
+          absoluteTrader.traderTokenAccount,
+          amountIn,
+          minimumAmountOut,
+          new BN(100)
+        ).rpc()
+      );
+      const tolerance = await outcome(
+        tradeWithTolerance(
+          tolerancePool,
+          toleranceTrader.trader,
+          toleranceTrader.traderTokenAccount,
+          amountIn,
+          expectedOut,
+          toleranceBps
+        )
+      );
+      return { absolute, tolerance };
+    };
+
+    it("accepts the same trade as the equivalent absolute minimum", async () => {
+      const { absolute, tolerance } = await compareForms(10_000, 10_000, 100);
+
+      assert.equal(absolute, "ok");
+      assert.equal(tolerance, absolute);
+    });
+
+    it("rejects the same trade as the equivalent absolute minimum", async () => {
+      const { absolute, tolerance } = await compareForms(10_000, 20_000, 50);
+
+      assert.equal(absolute, "SlippageExceeded");
+      assert.equal(tolerance, absolute);
+    });
+
+    it("rejects a tolerance over 10000 bps or a zero expected output", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(
+        tradeWithTolerance(pool, trader, traderTokenAccount, 10_000, 10_000, 10_001),
+        "InvalidSlippageTolerance"
+      );
+      await expectError(tradeWithTolerance(pool, trader, traderTokenAccount, 10_000, 0, 50), "InvalidAmount");
+    });
+  });
+
+  describe("recent trades", () => {
+    const getRecentTrades = (pool: TestPool) =>
+      program.methods.getRecentTrades().accounts({ poolState: pool.poolState }).view();
+
+    it("records each trade with its trader and amounts", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const trades = await getRecentTrades(pool);
+      assert.lengthOf(trades, 1);
+      assert.isTrue(trades[0].trader.equals(trader.publicKey));
+      assert.equal(trades[0].amountIn.toNumber(), 10_000);
+      assert.isAbove(trades[0].amountOut.toNumber(), 0);
+      assert.isAbove(trades[0].ts.toNumber(), 0);
+    });
+
+    it("keeps only the most recent trades, oldest first", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const capacity = 8;
+
+      for (let i = 1; i <= capacity + 2; i++) {
+        await trade(pool, trader, traderTokenAccount, i * 1_000).rpc();
+      }
+
+      const trades = await getRecentTrades(pool);
+      assert.deepEqual(
+        trades.map((record) => record.amountIn.toNumber()),
+        Array.from({ length: capacity }, (_, i) => (i + 3) * 1_000)
+      );
+    });
+  });
+
+  describe("fee overflow behavior", () => {
+    const setFeeOverflowBehavior = (pool: TestPool, highWaterMark: BN, mode: object) =>
+      program.methods
+        .setFeeOverflowBehavior(highWaterMark, mode as any)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const withdrawFees = (pool: TestPool) =>
+      program.methods
+        .withdrawFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          feeAuthorityTokenAccount: pool.adminTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    // Trades once so fees have accrued, then pins the mark at the current total
+    const poolAtMark = async (mode: object) => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+      const { totalFeesCollected } = await program.account.poolState.fetch(pool.poolState);
+      assert.isAbove(totalFeesCollected.toNumber(), 0);
+      await setFeeOverflowBehavior(pool, totalFeesCollected, mode);
+      return { pool, trader, traderTokenAccount, totalFeesCollected };
+    };
+
+    it("requires a withdrawal once accrued fees reach the mark", async () => {
+      const { pool, trader, traderTokenAccount } = await poolAtMark({ requireWithdrawal: {} });
+
+      await expectError(trade(pool, trader, traderTokenAccount, 100_000).rpc(), "FeeWithdrawalRequired");
+
+      await withdrawFees(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+    });
+
+    it("caps accrual at the mark and emits a warning", async () => {
+      const { pool, trader, traderTokenAccount, totalFeesCollected } = await poolAtMark({ capAccrual: {} });
+
+      const events = await captureEvents("feeAccrualCapped", () =>
+        trade(pool, trader, traderTokenAccount, 100_000).rpc()
+      );
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalFeesCollected.toString(), totalFeesCollected.toString());
+      assert.lengthOf(events, 1);
+      assert.isAbove(events[0].droppedAmount.toNumber(), 0);
+    });
+  });
+
+  describe("pool staleness", () => {
+    const setMaxStaleness = (pool: TestPool, maxStaleness: number) =>
+      program.methods
+        .setMaxStaleness(new BN(maxStaleness))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const refreshPool = (pool: TestPool) =>
+      program.methods
+        .refreshPool()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("lets a trade through on a fresh pool", async () => {
+      const pool = await setupPool();
+      await setMaxStaleness(pool, 3600);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+    });
+
+    it("flags a stale pool until the admin refreshes it", async () => {
+      const pool = await setupPool();
+      await setMaxStaleness(pool, 1);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+
+      await expectError(trade(pool, trader, traderTokenAccount, 10_000).rpc(), "PoolStale");
+
+      const events = await captureEvents("poolStale", () => refreshPool(pool));
+      assert.lengthOf(events, 1);
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+    });
+
+    it("rejects a negative max staleness", async () => {
+      const pool = await setupPool();
+
+      await expectError(setMaxStaleness(pool, -1), "InvalidMaxStaleness");
+    });
+  });
+
+  describe("buy and sell volume", () => {
+    it("counts trades paying in the pool token as sells", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const events = await captureEvents("tradeExecuted", async () => {
+        await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+        await trade(pool, trader, traderTokenAccount, 20_000).rpc();
+      });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.sellVolume24H.toNumber(), 30_000);
+      assert.equal(state.volume.buyVolume24H.toNumber(), 0);
+      assert.lengthOf(events, 2);
+      events.forEach((event) => assert.deepEqual(event.direction, { sell: {} }));
+    });
+  });
+
+  describe("force-expiring a pending update", () => {
+    const forceExpire = (pool: TestPool) =>
+      program.methods
+        .forceExpirePendingUpdate()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("keeps the update pending and never delays its schedule", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [{ protection: { 0: protectionUpdate } }]);
+      const before = await program.account.poolState.fetch(pool.poolState);
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      const events = await captureEvents("pendingUpdateForceExpired", () => forceExpire(pool));
+
+      const after = await program.account.poolState.fetch(pool.poolState);
+      assert.isNotNull(after.pendingUpdate);
+      assert.equal(after.pendingUpdate.scheduledTime.toNumber(), before.pendingUpdate.scheduledTime.toNumber());
+      assert.lengthOf(events, 1);
+      assert.equal(events[0].oldScheduledTime.toNumber(), before.pendingUpdate.scheduledTime.toNumber());
+    });
+
+    it("rejects force-expiring with nothing pending", async () => {
+      const pool = await setupPool();
+
+      await expectError(forceExpire(pool), "NoPendingUpdate");
+    });
+  });
+
+  describe("per-trader daily volume", () => {
+    const setMaxDailyVolumePerTrader = (pool: TestPool, maxDailyVolumePerTrader: number) =>
+      program.methods
+        .setMaxDailyVolumePerTrader(new BN(maxDailyVolumePerTrader))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("stops one trader at their cap while others keep trading", async () => {
+      const pool = await setupPool();
+      await setMaxDailyVolumePerTrader(pool, 50_000);
+      const first = await fundedTrader(pool);
+      const second = await fundedTrader(pool);
+
+      await trade(pool, first.trader, first.traderTokenAccount, 30_000).rpc();
+      await trade(pool, first.trader, first.traderTokenAccount, 20_000).rpc();
+      await expectError(
+        trade(pool, first.trader, first.traderTokenAccount, 1_000).rpc(),
+        "TraderDailyVolumeExceeded"
+      );
+
+      await trade(pool, second.trader, second.traderTokenAccount, 30_000).rpc();
+    });
+
+    it("leaves traders uncapped when set to zero", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+      await trade(pool, trader, traderTokenAccount, 60_000).rpc();
+    });
+
+    it("refuses to raise or disable the cap without the timelock", async () => {
+      const pool = await setupPool({ maxDailyVolumePerTrader: 50_000 });
+
+      await expectError(setMaxDailyVolumePerTrader(pool, 50_001), "Unauthorized");
+      await expectError(setMaxDailyVolumePerTrader(pool, 0), "Unauthorized");
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.protection.maxDailyVolumePerTrader.toNumber(), 50_000);
+    });
+  });
+
+  describe("fee tier invariant", () => {
+    it("rejects a pool initialized without fee tiers", async () => {
+      await expectError(setupPool({ feeTiers: [] }), "InvalidFeeTier");
+    });
+
+    it("accepts a fee update without tiers and leaves the current ones in place", async () => {
+      const pool = await setupPool();
+      await scheduleUpdates(pool, [
+        {
+          fee: {
+            0: {
+              feeTiers: [],
+              feeTiersLocked: false,
+              feeRounding: { down: {} },
+              protocolFeeBps: new BN(0),
+              feeFreeThreshold: new BN(0),
+            },
+          },
+        },
+      ]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isNotEmpty(state.feeTiers);
+      assert.isEmpty(state.pendingUpdate.updates[0].fee[0].feeTiers);
+    });
+  });
+
+  describe("zero output guard", () => {
+    it("rejects a trade whose fee would consume the entire input", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 1).rpc(), "ZeroOutput");
+    });
+  });
+
+  describe("keeper tick", () => {
+    const tick = (pool: TestPool, caller: anchor.web3.Keypair) =>
+      program.methods
+        .tick()
+        .accounts({ poolState: pool.poolState, caller: caller.publicKey })
+        .signers([caller])
+        .rpc();
+
+    it("lets anyone tick and reports the counters", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const events = await captureEvents("poolTicked", () => tick(pool, trader));
+
+      assert.lengthOf(events, 1);
+      assert.isTrue(events[0].caller.equals(trader.publicKey));
+      // Nothing has aged out a moment after the trade
+      assert.equal(events[0].volumeAfter.toNumber(), events[0].volumeBefore.toNumber());
+    });
+
+    it("is safe to call repeatedly", async () => {
+      const pool = await setupPool();
+      const keeper = await fundedKeypair();
+
+      await tick(pool, keeper);
+      await tick(pool, keeper);
+    });
+  });
+
+  describe("pool closure", () => {
+    const setPauseLevel = (pool: TestPool, pauseLevel: object) =>
+      program.methods
+        .setPauseLevel(pauseLevel)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const closePool = (pool: TestPool) =>
+      program.methods
+        .closePool()
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("closes an empty paused pool and refunds its rent", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+      await setPauseLevel(pool, { tradingOnly: {} });
+      const before = await provider.connection.getBalance(pool.admin.publicKey);
+
+      await closePool(pool);
+
+      assert.isNull(await provider.connection.getAccountInfo(pool.poolState));
+      assert.isAbove(await provider.connection.getBalance(pool.admin.publicKey), before);
+    });
+
+    it("rejects closing a pool that still holds liquidity", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await setPauseLevel(pool, { tradingOnly: {} });
+
+      await expectError(closePool(pool), "PoolNotEmpty");
+    });
+
+    it("rejects closing a pool whose token account still holds tokens", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+      await spl.mintTo(provider.connection, wallet.payer, pool.tokenMint, pool.poolTokenAccount, wallet.payer, 1);
+      await setPauseLevel(pool, { tradingOnly: {} });
+
+      await expectError(closePool(pool), "PoolNotEmpty");
+      assert.isNotNull(await provider.connection.getAccountInfo(pool.poolState));
+    });
+
+    it("rejects closing a pool that is not paused", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+
+      await expectError(closePool(pool), "PoolNotPaused");
+    });
+  });
+
+  describe("token decimals", () => {
+    const otherDecimalsMint = () =>
+      spl.createMint(provider.connection, wallet.payer, wallet.publicKey, null, 6);
+
+    it("rejects a trade passing a mint with other decimals", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const mint = await otherDecimalsMint();
+
+      await expectError(
+        program.methods
+          .executeTrade(new BN(1_000), new BN(0), new BN(10_000), null, false)
+          .accounts({
+            poolState: pool.poolState,
+            buyer: trader.publicKey,
+            buyerTokenAccount: traderTokenAccount,
+            poolTokenAccount: pool.poolTokenAccount,
+            poolAuthority: pool.poolAuthority,
+            tokenMint: mint,
+            referralState: null,
+            instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+            tokenProgram: spl.TOKEN_PROGRAM_ID,
+            governanceTokenAccount: null,
+          })
+          .signers([trader])
+          .rpc(),
+        "InvalidTokenMint"
+      );
+    });
+
+    it("rejects adding liquidity with a mint of other decimals", async () => {
+      const pool = await setupPool();
+      const mint = await otherDecimalsMint();
+
+      await expectError(
+        program.methods
+          .addLiquidity(new BN(1_000))
+          .accounts({
+            poolState: pool.poolState,
+            admin: pool.admin.publicKey,
+            adminTokenAccount: pool.adminTokenAccount,
+            poolTokenAccount: pool.poolTokenAccount,
+            poolAuthority: pool.poolAuthority,
+            tokenMint: mint,
+            tokenProgram: spl.TOKEN_PROGRAM_ID,
+          })
+          .signers([pool.admin])
+          .rpc(),
+        "InvalidTokenMint"
+      );
+    });
+  });
+
+  describe("fee withdrawal cooldown", () => {
+    const setFeeWithdrawCooldown = (pool: TestPool, cooldown: number) =>
+      program.methods
+        .setFeeWithdrawCooldown(new BN(cooldown))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const withdrawFees = (pool: TestPool) =>
+      program.methods
+        .withdrawFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          feeAuthorityTokenAccount: pool.adminTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    it("rejects a second withdrawal within the cooldown and allows one after it", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await setFeeWithdrawCooldown(pool, 3);
+
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+      await withdrawFees(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+      await expectError(withdrawFees(pool), "FeeWithdrawCooldown");
+
+      await new Promise((resolve) => setTimeout(resolve, 4000));
+      await withdrawFees(pool);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalFeesCollected.toNumber(), 0);
+    });
+
+    it("rejects a cooldown beyond the maximum", async () => {
+      const pool = await setupPool();
+
+      await expectError(setFeeWithdrawCooldown(pool, 604_801), "InvalidFeeWithdrawCooldown");
+    });
+
+    it("refuses to shorten or disable the cooldown without the timelock", async () => {
+      const pool = await setupPool({ feeWithdrawCooldown: 3600 });
+
+      await expectError(setFeeWithdrawCooldown(pool, 3599), "Unauthorized");
+      await expectError(setFeeWithdrawCooldown(pool, 0), "Unauthorized");
+      await setFeeWithdrawCooldown(pool, 7200);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.feeWithdrawCooldown.toNumber(), 7200);
+    });
+  });
+
+  describe("fee authority", () => {
+    const proposeFeeAuthority = (pool: TestPool, feeAuthority: PublicKey) =>
+      program.methods
+        .proposeFeeAuthority(feeAuthority)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const acceptFeeAuthority = (pool: TestPool, signer: anchor.web3.Keypair) =>
+      program.methods
+        .acceptFeeAuthority()
+        .accounts({ poolState: pool.poolState, newFeeAuthority: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    const cancelFeeAuthorityTransfer = (pool: TestPool) =>
+      program.methods
+        .cancelFeeAuthorityTransfer()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const withdrawFees = (pool: TestPool, feeAuthority: anchor.web3.Keypair, destination: PublicKey) =>
+      program.methods
+        .withdrawFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: feeAuthority.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          feeAuthorityTokenAccount: destination,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([feeAuthority])
+        .rpc();
+
+    it("starts as the admin", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(state.feeAuthority.equals(pool.admin.publicKey));
+      assert.isNull(state.pendingFeeAuthority);
+    });
+
+    it("hands fee withdrawals to the proposed key once it accepts", async () => {
+      const pool = await setupPool();
+      const feeAuthority = await fundedKeypair();
+      const destination = await spl.createAccount(provider.connection, wallet.payer, pool.tokenMint, feeAuthority.publicKey);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      await proposeFeeAuthority(pool, feeAuthority.publicKey);
+      const events = await captureEvents("feeAuthorityTransferred", () => acceptFeeAuthority(pool, feeAuthority));
+
+      assert.isTrue(events[0].oldFeeAuthority.equals(pool.admin.publicKey));
+      assert.isTrue(events[0].newFeeAuthority.equals(feeAuthority.publicKey));
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(state.feeAuthority.equals(feeAuthority.publicKey));
+      assert.isNull(state.pendingFeeAuthority);
+
+      await expectError(withdrawFees(pool, pool.admin, pool.adminTokenAccount), "Unauthorized");
+      await withdrawFees(pool, feeAuthority, destination);
+      const account = await spl.getAccount(provider.connection, destination);
+      assert.isAbove(Number(account.amount), 0);
+    });
+
+    it("rejects an accept signed by any other key", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+      const feeAuthority = await fundedKeypair();
+      await proposeFeeAuthority(pool, feeAuthority.publicKey);
+
+      await expectError(acceptFeeAuthority(pool, await fundedKeypair()), "Unauthorized");
+      await expectError(acceptFeeAuthority(pool, pool.admin), "Unauthorized");
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(state.feeAuthority.equals(pool.admin.publicKey));
+    });
+
+    it("lets the admin cancel a pending transfer", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+      const feeAuthority = await fundedKeypair();
+      await proposeFeeAuthority(pool, feeAuthority.publicKey);
+
+      await cancelFeeAuthorityTransfer(pool);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isNull(state.pendingFeeAuthority);
+      await expectError(acceptFeeAuthority(pool, feeAuthority), "NoPendingFeeAuthority");
+      await expectError(cancelFeeAuthorityTransfer(pool), "NoPendingFeeAuthority");
+    });
+
+    it("rejects proposing the default key or the current fee authority", async () => {
+      const pool = await setupPool({ liquidity: 0 });
+
+      await expectError(proposeFeeAuthority(pool, PublicKey.default), "InvalidFeeAuthority");
+      await expectError(proposeFeeAuthority(pool, pool.admin.publicKey), "InvalidFeeAuthority");
+    });
+  });
+
+  describe("reentrancy lock", () => {
+    it("creates the lock PDA on the first trade and releases it afterwards", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      const lockAddress = deriveReentrancyGuard(pool.poolState);
+      assert.isNull(await provider.connection.getAccountInfo(lockAddress));
+
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+
+      const lock = await program.account.reentrancyLock.fetch(lockAddress);
+      assert.isTrue(lock.pool.equals(pool.poolState));
+      assert.isFalse(lock.locked);
+    });
+
+    it("releases the lock after a fee withdrawal", async () => {
+      const pool = await setupPool();
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 100_000).rpc();
+
+      await program.methods
+        .withdrawFees()
+        .accounts({
+          poolState: pool.poolState,
+          feeAuthority: pool.admin.publicKey,
+          poolTokenAccount: pool.poolTokenAccount,
+          feeAuthorityTokenAccount: pool.adminTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+      const lock = await program.account.reentrancyLock.fetch(deriveReentrancyGuard(pool.poolState));
+      assert.isFalse(lock.locked);
+      await trade(pool, trader, traderTokenAccount, 10_000).rpc();
+    });
+  });
+
+  describe("partial fills", () => {
+    const partialTrade = (pool: TestPool, trader: anchor.web3.Keypair, ata: PublicKey, amountIn: number, minimumAmountOut = 0) =>
+      trade(pool, trader, ata, amountIn, minimumAmountOut, new BN(10_000), null, null, true);
+
+    it("fills a capped trade up to the remaining daily volume", async () => {
+      const pool = await setupPool({ maxDailyVolume: 25_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 20_000).rpc();
+      const balanceBefore = await spl.getAccount(provider.connection, traderTokenAccount);
+
+      const fills = await captureEvents("partialFill", () =>
+        partialTrade(pool, trader, traderTokenAccount, 10_000).rpc()
+      );
+
+      assert.lengthOf(fills, 1);
+      assert.equal(fills[0].requestedAmountIn.toNumber(), 10_000);
+      assert.equal(fills[0].filledAmountIn.toNumber(), 5_000);
+      assert.isAbove(fills[0].amountOut.toNumber(), 0);
+      const balanceAfter = await spl.getAccount(provider.connection, traderTokenAccount);
+      assert.equal(Number(balanceBefore.amount - balanceAfter.amount), 5_000);
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.volume.volume24h.toNumber(), 25_000);
+    });
+
+    it("fills up to the circuit breaker headroom", async () => {
+      const pool = await setupPool({ circuitBreakerThreshold: 50_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      const fills = await captureEvents("partialFill", () =>
+        partialTrade(pool, trader, traderTokenAccount, 60_000).rpc()
+      );
+
+      assert.lengthOf(fills, 1);
+      assert.equal(fills[0].filledAmountIn.toNumber(), 50_000);
+    });
+
+    it("still reverts a capped trade without the flag", async () => {
+      const pool = await setupPool({ maxDailyVolume: 25_000 });
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      await trade(pool, trader, traderTokenAccount, 20_000).rpc();
+
+      await expectError(trade(pool, trader, traderTokenAccount, 10_000).rpc(), "VolumeLimitExceeded");
+    });
+  });
+
+  describe("low reserve mode", () => {
+    const setLowReserveSettings = (pool: TestPool, threshold: number, surchargeBps: number, maxTradeSize: number) =>
+      program.methods
+        .setLowReserveSettings(new BN(threshold), new BN(surchargeBps), new BN(maxTradeSize))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const tradeFee = async (pool: TestPool, amountIn: number) => {
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+      let modes: any[] = [];
+      const executed = await captureEvents("tradeExecuted", async () => {
+        modes = await captureEvents("lowReserveMode", () =>
+          trade(pool, trader, traderTokenAccount, amountIn).rpc()
+        );
+      });
+      return { fee: executed[0].feeAmount.toNumber(), modes };
+    };
+
+    it("leaves trades alone while reserves are above the threshold", async () => {
+      const pool = await setupPool({
+        lowReserve: { threshold: 50_000_000, surchargeBps: 100, maxTradeSize: 50_000 },
+      });
+
+      const { fee, modes } = await tradeFee(pool, 100_000);
+
+      assert.equal(fee, 300);
+      assert.isEmpty(modes);
+    });
+
+    it("surcharges trades while reserves are below the threshold", async () => {
+      const pool = await setupPool({ lowReserve: { threshold: 200_000_000, surchargeBps: 100, maxTradeSize: 0 } });
+
+      const { fee, modes } = await tradeFee(pool, 100_000);
+
+      assert.equal(fee, 1_300);
+      assert.lengthOf(modes, 1);
+      assert.equal(modes[0].surcharge.toNumber(), 1_000);
+      assert.equal(modes[0].reserves.toNumber(), 100_000_000);
+    });
+
+    it("rejects trades above the low reserve size", async () => {
+      const pool = await setupPool();
+      await setLowReserveSettings(pool, 200_000_000, 0, 50_000);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 100_000).rpc(), "LowReserveTradeTooLarge");
+      await trade(pool, trader, traderTokenAccount, 50_000).rpc();
+    });
+
+    it("rejects a surcharge above the maximum", async () => {
+      const pool = await setupPool();
+
+      await expectError(setLowReserveSettings(pool, 1, 501, 0), "InvalidLowReserveSettings");
+    });
+
+    it("refuses a higher surcharge or a looser setting without the timelock", async () => {
+      const pool = await setupPool({
+        lowReserve: { threshold: 200_000_000, surchargeBps: 100, maxTradeSize: 50_000 },
+      });
+
+      await expectError(setLowReserveSettings(pool, 200_000_000, 101, 50_000), "Unauthorized");
+      await expectError(setLowReserveSettings(pool, 100_000_000, 100, 50_000), "Unauthorized");
+      await expectError(setLowReserveSettings(pool, 200_000_000, 100, 0), "Unauthorized");
+      await setLowReserveSettings(pool, 300_000_000, 50, 10_000);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.lowReserve.surchargeBps.toNumber(), 50);
+    });
+  });
+
+  describe("locked liquidity", () => {
+    const chainTime = async () =>
+      provider.connection.getBlockTime(await provider.connection.getSlot());
+
+    const addLiquidityLocked = (pool: TestPool, amount: number, unlockTs: number) =>
+      program.methods
+        .addLiquidityLocked(new BN(amount), new BN(unlockTs))
+        .accounts({
+          poolState: pool.poolState,
+          admin: pool.admin.publicKey,
+          adminTokenAccount: pool.adminTokenAccount,
+          poolTokenAccount: pool.poolTokenAccount,
+          poolAuthority: pool.poolAuthority,
+          tokenMint: pool.tokenMint,
+          tokenProgram: spl.TOKEN_PROGRAM_ID,
+        })
+        .signers([pool.admin])
+        .rpc();
+
+    const removeLiquidity = (pool: TestPool, amount: number) =>
+      program.methods
+        .removeLiquidity(new BN(amount))
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("keeps locked liquidity in the pool until it unlocks", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      await addLiquidityLocked(pool, 500_000, (await chainTime()) + 3);
+
+      await expectError(removeLiquidity(pool, 1_000_001), "LiquidityLocked");
+      await removeLiquidity(pool, 1_000_000);
+
+      await new Promise((resolve) => setTimeout(resolve, 5000));
+      await removeLiquidity(pool, 500_000);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.totalLiquidity.toNumber(), 0);
+      assert.isEmpty(state.liquidityLocks);
+    });
+
+    it("records the lock for its provider", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+      const unlockTs = (await chainTime()) + 3600;
+
+      const events = await captureEvents("liquidityLockCreated", () => addLiquidityLocked(pool, 250_000, unlockTs));
+
+      assert.isTrue(events[0].provider.equals(pool.admin.publicKey));
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.equal(state.liquidityLocks[0].amount.toNumber(), 250_000);
+      assert.equal(state.liquidityLocks[0].unlockTs.toNumber(), unlockTs);
+    });
+
+    it("rejects an unlock time in the past", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000 });
+
+      await expectError(addLiquidityLocked(pool, 1_000, (await chainTime()) - 1), "InvalidLiquidityLock");
+    });
+  });
+
+  describe("price impact by trade size", () => {
+    const setPriceImpactTiers = (pool: TestPool, tiers: [number, number][]) =>
+      program.methods
+        .setPriceImpactTiers(
+          tiers.map(([sizeThreshold, maxImpactBps]) => ({
+            sizeThreshold: new BN(sizeThreshold),
+            maxImpactBps: new BN(maxImpactBps),
+          }))
+        )
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    const tiers: [number, number][] = [
+      [5_000, 500],
+      [50_000, 200],
+    ];
+
+    it("accepts about 3% impact from a small trade", async () => {
+      const pool = await setupPool({ liquidity: 100_000, maxSlippageBps: 2000 });
+      await setPriceImpactTiers(pool, tiers);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await trade(pool, trader, traderTokenAccount, 3_000).rpc();
+    });
+
+    it("rejects the same impact from a large trade", async () => {
+      const pool = await setupPool({ liquidity: 1_000_000, maxSlippageBps: 2000 });
+      await setPriceImpactTiers(pool, tiers);
+      const { trader, traderTokenAccount } = await fundedTrader(pool);
+
+      await expectError(trade(pool, trader, traderTokenAccount, 30_000).rpc(), "PriceImpactTooHigh");
+    });
+
+    it("rejects thresholds out of order", async () => {
+      const pool = await setupPool();
+
+      await expectError(
+        setPriceImpactTiers(pool, [
+          [50_000, 500],
+          [5_000, 200],
+        ]),
+        "InvalidPriceImpactTiers"
+      );
+    });
+  });
+
+  describe("emergency blacklist", () => {
+    const emergencyBlacklist = (pool: TestPool, signer: anchor.web3.Keypair, traders: PublicKey[]) =>
+      program.methods
+        .emergencyBlacklist(traders, 1)
+        .accounts({ poolState: pool.poolState, emergencyAdmin: signer.publicKey })
+        .signers([signer])
+        .rpc();
+
+    const withResponder = async () => {
+      const pool = await setupPool({ emergencyPauseSeconds: 0 });
+      const responder = await fundedKeypair();
+      await program.methods
+        .setEmergencyAdmin(responder.publicKey)
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+      return { pool, responder };
+    };
+
+    it("lets the emergency admin blacklist during an emergency pause", async () => {
+      const { pool, responder } = await withResponder();
+      const emergency = { poolState: pool.poolState, emergencyAdmin: responder.publicKey };
+      await program.methods.scheduleEmergencyPause().accounts(emergency).signers([responder]).rpc();
+      await program.methods.applyEmergencyPause().accounts(emergency).signers([responder]).rpc();
+      const attacker = anchor.web3.Keypair.generate().publicKey;
+
+      await emergencyBlacklist(pool, responder, [attacker]);
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(state.isEmergencyPaused);
+      const blacklisted = await program.methods
+        .checkBlacklistStatus(attacker)
+        .accounts({ poolState: pool.poolState })
+        .view();
+      assert.isTrue(blacklisted);
+    });
+
+    it("rejects a signer other than the emergency admin", async () => {
+      const { pool } = await withResponder();
+      const attacker = anchor.web3.Keypair.generate().publicKey;
+
+      await expectError(emergencyBlacklist(pool, pool.admin, [attacker]), "InvalidEmergencyAdmin");
+    });
+  });
+
+  describe("fee tier unlock", () => {
+    const adminCall = (pool: TestPool, method: "lockFeeTiers" | "unlockFeeTiers" | "applyFeeTiersUnlock") =>
+      program.methods[method]()
+        .accounts({ poolState: pool.poolState, admin: pool.admin.publicKey })
+        .signers([pool.admin])
+        .rpc();
+
+    it("keeps the tiers locked until the unlock timelock elapses", async () => {
+      const pool = await setupPool();
+      await adminCall(pool, "lockFeeTiers");
+
+      const scheduled = await captureEvents("feeTiersUnlockScheduled", () => adminCall(pool, "unlockFeeTiers"));
+
+      const state = await program.account.poolState.fetch(pool.poolState);
+      assert.isTrue(state.feeTiersLocked);
+      assert.equal(scheduled[0].scheduledTime.toNumber(), state.feeTiersUnlockTime.toNumber());
+      await expectError(adminCall(pool, "applyFeeTiersUnlock"), "TimelockNotExpired");
+      assert.isTrue((await program.account.poolState.fetch(pool.poolState)).feeTiersLocked);
+    });
+
+    it("rejects scheduling a second unlock", async () => {
+      const pool = await setupPool();
+      await adminCall(pool, "lockFeeTiers");
+      await adminCall(pool, "unlockFeeTiers");
+
+      await expectError(adminCall(pool, "unlockFeeTiers"), "FeeTiersUnlockPending");
+    });
+
+    it("rejects applying an unlock that was never scheduled", async () => {
+      const pool = await setupPool();
+      await adminCall(pool, "lockFeeTiers");
+
+      await expectError(adminCall(pool, "applyFeeTiersUnlock"), "NoPendingFeeTiersUnlock");
+    });
+
+    it("refuses fee updates that change locked tiers or lift the lock", async () => {
+      const pool = await setupPool();
+      await adminCall(pool, "lockFeeTiers");
+      const fee = (feeTiers: any[], feeTiersLocked: boolean) => ({
+        fee: { 0: { feeTiers, feeTiersLocked, feeRounding: { down: {} }, protocolFeeBps: new BN(0), feeFreeThreshold: new BN(0) } },
+      });
+
+      await expectError(scheduleUpdates(pool, [fee(defaultFeeTiers(), true)]), "FeeTiersLocked");
+      await expectError(scheduleUpdates(pool, [fee([], false)]), "FeeTiersLocked");
+      await scheduleUpdates(pool, [fee([], true)]);
+    });
+  });
+});